@@ -0,0 +1,1239 @@
+//! Elementwise Expression Compiler
+//!
+//! Turns a small math expression over named tensors and scalar uniforms, such as
+//! `a * b + sin(c)` or `x > 0 ? x : alpha * x`, into a complete WGSL compute kernel.
+//!
+//! - Operators: `+ - * / %`, `**` (power), comparisons, `&& || !`, `cond ? a : b`
+//! - Functions: WGSL math builtins plus `relu`, `sigmoid`, `silu`, `gelu`, `softplus`,
+//!   `leaky_relu`, `where` and the casts `f32`, `f16`, `i32`, `u32`
+//! - Constants: `pi`, `e`
+//!
+//! Types follow the inputs' `TensorDType`: integers combined with floats become floats,
+//! `f16` stays `f16` unless mixed with `f32`, and 8-bit inputs are read as `i32`/`u32`
+//! from buffers packing four elements per word. Inputs the expression never reads are
+//! not bound.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use super::workgroup::calculate_dispatch_size_1d;
+use crate::tensor::TensorDType;
+
+/// A named tensor input or scalar uniform
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpressionOperand {
+    pub name: String,
+    pub dtype: TensorDType,
+}
+
+/// Expression kernel settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExpressionKernelOptions {
+    /// Tensors read elementwise, bound in this order
+    pub inputs: Vec<ExpressionOperand>,
+    /// Scalars passed in the params uniform (`Float32` or `Int32`)
+    pub uniforms: Vec<ExpressionOperand>,
+    /// Output type; inferred from the expression when unset
+    pub output_dtype: Option<TensorDType>,
+    pub workgroup_size: u32,
+    pub entry_point: String,
+}
+
+impl Default for ExpressionKernelOptions {
+    fn default() -> Self {
+        Self {
+            inputs: Vec::new(),
+            uniforms: Vec::new(),
+            output_dtype: None,
+            workgroup_size: 64,
+            entry_point: "main".to_string(),
+        }
+    }
+}
+
+impl ExpressionKernelOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a tensor input
+    pub fn input(mut self, name: &str, dtype: TensorDType) -> Self {
+        self.inputs.push(ExpressionOperand {
+            name: name.to_string(),
+            dtype,
+        });
+        self
+    }
+
+    /// Add a scalar uniform
+    pub fn uniform(mut self, name: &str, dtype: TensorDType) -> Self {
+        self.uniforms.push(ExpressionOperand {
+            name: name.to_string(),
+            dtype,
+        });
+        self
+    }
+
+    pub fn output(mut self, dtype: TensorDType) -> Self {
+        self.output_dtype = Some(dtype);
+        self
+    }
+
+    pub fn workgroup_size(mut self, size: u32) -> Self {
+        self.workgroup_size = size;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpressionBindingKind {
+    Input,
+    Output,
+    Params,
+}
+
+/// One `@group(0) @binding(n)` resource of the generated kernel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpressionBinding {
+    /// Operand name in the expression (`output` and `params` for the generated buffers)
+    pub name: String,
+    /// Variable name in the WGSL
+    pub variable: String,
+    pub group: u32,
+    pub binding: u32,
+    pub kind: ExpressionBindingKind,
+    pub dtype: Option<TensorDType>,
+    /// 8-bit elements stored four per `u32`
+    pub packed: bool,
+}
+
+/// A member of the params uniform
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpressionUniformField {
+    pub name: String,
+    pub offset: u32,
+    pub dtype: TensorDType,
+}
+
+/// Generated kernel with its binding layout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpressionKernel {
+    pub expression: String,
+    pub wgsl: String,
+    pub entry_point: String,
+    pub workgroup_size: u32,
+    pub output_dtype: TensorDType,
+    pub bindings: Vec<ExpressionBinding>,
+    /// Params uniform members after the leading `len: u32` element count
+    pub uniforms: Vec<ExpressionUniformField>,
+    /// Size of the params uniform buffer in bytes
+    pub params_size: u64,
+    /// Uses `enable f16;` and needs the `shader-f16` feature
+    pub requires_f16: bool,
+}
+
+impl ExpressionKernel {
+    /// Workgroups to dispatch along x for `element_count` elements
+    pub fn dispatch_size(&self, element_count: u32) -> u32 {
+        calculate_dispatch_size_1d(element_count, self.workgroup_size)
+    }
+
+    /// Contents of the params uniform buffer
+    pub fn params_data(
+        &self,
+        element_count: u32,
+        values: &HashMap<String, f64>,
+    ) -> Result<Vec<u8>, ExpressionError> {
+        if let Some(unknown) = values.keys().find(|k| !self.uniforms.iter().any(|u| &u.name == *k)) {
+            return Err(ExpressionError::new(format!("Unknown uniform '{}'", unknown), 0));
+        }
+
+        let mut bytes = vec![0u8; self.params_size as usize];
+        bytes[0..4].copy_from_slice(&element_count.to_le_bytes());
+        for field in &self.uniforms {
+            let value = values
+                .get(&field.name)
+                .ok_or_else(|| ExpressionError::new(format!("Missing value for uniform '{}'", field.name), 0))?;
+            let word = match field.dtype {
+                TensorDType::Int32 => (*value as i32).to_le_bytes(),
+                _ => (*value as f32).to_le_bytes(),
+            };
+            let offset = field.offset as usize;
+            bytes[offset..offset + 4].copy_from_slice(&word);
+        }
+        Ok(bytes)
+    }
+}
+
+/// Expression that cannot be compiled; `position` is a 0-based character offset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpressionError {
+    pub message: String,
+    pub position: usize,
+}
+
+impl ExpressionError {
+    fn new(message: String, position: usize) -> Self {
+        Self { message, position }
+    }
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at column {})", self.message, self.position + 1)
+    }
+}
+
+impl std::error::Error for ExpressionError {}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number { text: String, float: bool },
+    Ident(String),
+    Op(&'static str),
+    End,
+}
+
+const OPERATORS: &[&str] = &[
+    "**", "<=", ">=", "==", "!=", "&&", "||", "+", "-", "*", "/", "%", "<", ">", "!", "?", ":",
+    "(", ")", ",",
+];
+
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, ExpressionError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit())) {
+            let mut float = false;
+            while i < chars.len() {
+                let d = chars[i];
+                if d.is_ascii_digit() {
+                    i += 1;
+                } else if d == '.' && !float {
+                    float = true;
+                    i += 1;
+                } else if (d == 'e' || d == 'E')
+                    && (chars.get(i + 1).is_some_and(|n| n.is_ascii_digit())
+                        || (matches!(chars.get(i + 1), Some('+' | '-'))
+                            && chars.get(i + 2).is_some_and(|n| n.is_ascii_digit())))
+                {
+                    float = true;
+                    i += 2;
+                } else {
+                    break;
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            tokens.push((Token::Number { text, float }, start));
+            continue;
+        }
+
+        if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push((Token::Ident(chars[start..i].iter().collect()), start));
+            continue;
+        }
+
+        let op = OPERATORS.iter().find(|op| {
+            op.chars()
+                .enumerate()
+                .all(|(offset, ch)| chars.get(i + offset) == Some(&ch))
+        });
+        match op {
+            Some(op) => {
+                tokens.push((Token::Op(op), start));
+                i += op.len();
+            }
+            None => return Err(ExpressionError::new(format!("Unexpected character '{}'", c), start)),
+        }
+    }
+
+    tokens.push((Token::End, chars.len()));
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Number { text: String, float: bool },
+    Name(String),
+    Unary { op: &'static str, operand: Box<Node> },
+    Binary { op: &'static str, lhs: Box<Node>, rhs: Box<Node> },
+    Select { condition: Box<Node>, accept: Box<Node>, reject: Box<Node> },
+    Call { function: String, args: Vec<Node> },
+}
+
+/// An expression with the source position it starts at
+#[derive(Debug, Clone)]
+struct Node {
+    expr: Expr,
+    position: usize,
+}
+
+fn binary_precedence(op: &str) -> Option<u8> {
+    match op {
+        "||" => Some(1),
+        "&&" => Some(2),
+        "==" | "!=" => Some(3),
+        "<" | "<=" | ">" | ">=" => Some(4),
+        "+" | "-" => Some(5),
+        "*" | "/" | "%" => Some(6),
+        _ => None,
+    }
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn position(&self) -> usize {
+        self.tokens[self.pos].1
+    }
+
+    fn next(&mut self) -> (Token, usize) {
+        let token = self.tokens[self.pos].clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn eat(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Token::Op(o) if *o == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, op: &str) -> Result<(), ExpressionError> {
+        if self.eat(op) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("expected '{}'", op)))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> ExpressionError {
+        let found = match self.peek() {
+            Token::Number { text, .. } => format!("'{}'", text),
+            Token::Ident(name) => format!("'{}'", name),
+            Token::Op(op) => format!("'{}'", op),
+            Token::End => "end of expression".to_string(),
+        };
+        ExpressionError::new(format!("Unexpected {}, {}", found, expected), self.position())
+    }
+
+    fn parse_ternary(&mut self) -> Result<Node, ExpressionError> {
+        let condition = self.parse_binary(1)?;
+        if !self.eat("?") {
+            return Ok(condition);
+        }
+        let accept = self.parse_ternary()?;
+        self.expect(":")?;
+        let reject = self.parse_ternary()?;
+        Ok(Node {
+            position: condition.position,
+            expr: Expr::Select {
+                condition: Box::new(condition),
+                accept: Box::new(accept),
+                reject: Box::new(reject),
+            },
+        })
+    }
+
+    fn parse_binary(&mut self, min_precedence: u8) -> Result<Node, ExpressionError> {
+        let mut lhs = self.parse_unary()?;
+        while let Token::Op(op) = *self.peek() {
+            let Some(precedence) = binary_precedence(op).filter(|p| *p >= min_precedence) else {
+                break;
+            };
+            self.next();
+            let rhs = self.parse_binary(precedence + 1)?;
+            lhs = Node {
+                position: lhs.position,
+                expr: Expr::Binary {
+                    op,
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                },
+            };
+        }
+        Ok(lhs)
+    }
+
+    fn parse_unary(&mut self) -> Result<Node, ExpressionError> {
+        let position = self.position();
+        for op in ["-", "!", "+"] {
+            if self.eat(op) {
+                let operand = self.parse_unary()?;
+                if op == "+" {
+                    return Ok(operand);
+                }
+                return Ok(Node {
+                    position,
+                    expr: Expr::Unary {
+                        op,
+                        operand: Box::new(operand),
+                    },
+                });
+            }
+        }
+        self.parse_power()
+    }
+
+    /// `**` binds tighter than unary minus on its left and is right associative
+    fn parse_power(&mut self) -> Result<Node, ExpressionError> {
+        let base = self.parse_primary()?;
+        if !self.eat("**") {
+            return Ok(base);
+        }
+        let exponent = self.parse_unary()?;
+        Ok(Node {
+            position: base.position,
+            expr: Expr::Binary {
+                op: "**",
+                lhs: Box::new(base),
+                rhs: Box::new(exponent),
+            },
+        })
+    }
+
+    fn parse_primary(&mut self) -> Result<Node, ExpressionError> {
+        let position = self.position();
+        match self.peek().clone() {
+            Token::Number { text, float } => {
+                self.next();
+                Ok(Node {
+                    position,
+                    expr: Expr::Number { text, float },
+                })
+            }
+            Token::Ident(name) => {
+                self.next();
+                if !self.eat("(") {
+                    return Ok(Node {
+                        position,
+                        expr: Expr::Name(name),
+                    });
+                }
+                let mut args = Vec::new();
+                if !self.eat(")") {
+                    loop {
+                        args.push(self.parse_ternary()?);
+                        if self.eat(")") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+                Ok(Node {
+                    position,
+                    expr: Expr::Call {
+                        function: name,
+                        args,
+                    },
+                })
+            }
+            Token::Op("(") => {
+                self.next();
+                let inner = self.parse_ternary()?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            _ => Err(self.unexpected("expected a value")),
+        }
+    }
+}
+
+fn parse(source: &str) -> Result<Node, ExpressionError> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        pos: 0,
+    };
+    let node = parser.parse_ternary()?;
+    if *parser.peek() != Token::End {
+        return Err(parser.unexpected("expected an operator"));
+    }
+    Ok(node)
+}
+
+// ============================================================================
+// Typing and code generation
+// ============================================================================
+
+/// Value types; abstract types are untyped literals that adapt to the other operand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Ty {
+    F32,
+    F16,
+    I32,
+    U32,
+    Bool,
+    AbstractInt,
+    AbstractFloat,
+}
+
+impl Ty {
+    fn name(self) -> &'static str {
+        match self {
+            Ty::F32 | Ty::AbstractFloat => "f32",
+            Ty::F16 => "f16",
+            Ty::I32 | Ty::AbstractInt => "i32",
+            Ty::U32 => "u32",
+            Ty::Bool => "bool",
+        }
+    }
+
+    fn is_float(self) -> bool {
+        matches!(self, Ty::F32 | Ty::F16 | Ty::AbstractFloat)
+    }
+
+    fn is_abstract(self) -> bool {
+        matches!(self, Ty::AbstractInt | Ty::AbstractFloat)
+    }
+
+    /// Concrete type, with untyped literals defaulting to `i32`/`f32`
+    fn concrete(self) -> Ty {
+        match self {
+            Ty::AbstractInt => Ty::I32,
+            Ty::AbstractFloat => Ty::F32,
+            ty => ty,
+        }
+    }
+
+    /// The float type math builtins compute in
+    fn float(self) -> Ty {
+        match self {
+            Ty::F32 | Ty::F16 | Ty::AbstractFloat => self,
+            Ty::AbstractInt => Ty::AbstractFloat,
+            _ => Ty::F32,
+        }
+    }
+
+    fn from_dtype(dtype: TensorDType) -> Ty {
+        match dtype {
+            TensorDType::Float32 => Ty::F32,
+            TensorDType::Float16 => Ty::F16,
+            TensorDType::Int32 | TensorDType::Int8 => Ty::I32,
+            TensorDType::UInt8 => Ty::U32,
+        }
+    }
+}
+
+/// Common type of two numeric operands
+fn unify(a: Ty, b: Ty) -> Option<Ty> {
+    if a == Ty::Bool || b == Ty::Bool {
+        return if a == b { Some(Ty::Bool) } else { None };
+    }
+    let ty = match (a.is_abstract(), b.is_abstract()) {
+        (true, true) => {
+            if a.is_float() || b.is_float() {
+                Ty::AbstractFloat
+            } else {
+                Ty::AbstractInt
+            }
+        }
+        (true, false) | (false, true) => {
+            let (literal, concrete) = if a.is_abstract() { (a, b) } else { (b, a) };
+            if literal.is_float() && !concrete.is_float() {
+                Ty::F32
+            } else {
+                concrete
+            }
+        }
+        (false, false) => {
+            if a == Ty::F32 || b == Ty::F32 {
+                Ty::F32
+            } else if a == Ty::F16 || b == Ty::F16 {
+                Ty::F16
+            } else if a == Ty::I32 || b == Ty::I32 {
+                Ty::I32
+            } else {
+                Ty::U32
+            }
+        }
+    };
+    Some(ty)
+}
+
+#[derive(Debug, Clone)]
+struct Typed {
+    code: String,
+    ty: Ty,
+}
+
+/// Convert to `target`
+///
+/// Literals get an explicit conversion too: naga does not concretize them in builtin
+/// calls such as `pow(x, 2)`.
+fn cast(value: Typed, target: Ty) -> Typed {
+    if value.ty == target {
+        return value;
+    }
+    let code = if value.ty == Ty::Bool {
+        format!("select({0}(0), {0}(1), {1})", target.name(), value.code)
+    } else if target.is_abstract() {
+        value.code
+    } else {
+        format!("{}({})", target.name(), value.code)
+    };
+    Typed { code, ty: target }
+}
+
+/// Placeholder for the element type in [`HELPERS`]
+const HELPER_TYPE: &str = "{T}";
+
+/// Activation helpers emitted as WGSL functions: (name, helpers it calls, template).
+///
+/// The bodies are the `std::nn` ones, made generic over the element type so they also
+/// serve `f16`; `test_helpers_match_stdlib` keeps them in step.
+const HELPERS: &[(&str, &[&str], &str)] = &[
+    (
+        "sigmoid",
+        &[],
+        "fn ex_sigmoid_{T}(x: {T}) -> {T} {\n    let e = exp(-abs(x));\n    return select(e / (1.0 + e), 1.0 / (1.0 + e), x >= 0.0);\n}",
+    ),
+    ("silu", &["sigmoid"], "fn ex_silu_{T}(x: {T}) -> {T} {\n    return x * ex_sigmoid_{T}(x);\n}"),
+    (
+        "gelu",
+        &[],
+        "fn ex_gelu_{T}(x: {T}) -> {T} {\n    return 0.5 * x * (1.0 + tanh(0.7978845608 * (x + 0.044715 * x * x * x)));\n}",
+    ),
+    (
+        "softplus",
+        &[],
+        "fn ex_softplus_{T}(x: {T}) -> {T} {\n    return max(x, 0.0) + log(1.0 + exp(-abs(x)));\n}",
+    ),
+    (
+        "leaky_relu",
+        &[],
+        "fn ex_leaky_relu_{T}(x: {T}, slope: {T}) -> {T} {\n    return select(x * slope, x, x >= 0.0);\n}",
+    ),
+];
+
+fn instantiate_helper(index: usize, ty: Ty) -> String {
+    HELPERS[index].2.replace(HELPER_TYPE, ty.name())
+}
+
+/// Builtins taking and returning floats: (name, WGSL name, arity)
+const FLOAT_FUNCTIONS: &[(&str, &str, usize)] = &[
+    ("sin", "sin", 1),
+    ("cos", "cos", 1),
+    ("tan", "tan", 1),
+    ("asin", "asin", 1),
+    ("acos", "acos", 1),
+    ("atan", "atan", 1),
+    ("sinh", "sinh", 1),
+    ("cosh", "cosh", 1),
+    ("tanh", "tanh", 1),
+    ("exp", "exp", 1),
+    ("exp2", "exp2", 1),
+    ("log", "log", 1),
+    ("log2", "log2", 1),
+    ("sqrt", "sqrt", 1),
+    ("rsqrt", "inverseSqrt", 1),
+    ("floor", "floor", 1),
+    ("ceil", "ceil", 1),
+    ("round", "round", 1),
+    ("trunc", "trunc", 1),
+    ("fract", "fract", 1),
+    ("atan2", "atan2", 2),
+    ("pow", "pow", 2),
+    ("step", "step", 2),
+    ("mix", "mix", 3),
+    ("smoothstep", "smoothstep", 3),
+    ("fma", "fma", 3),
+];
+
+/// Builtins over any numeric type: (name, arity)
+const NUMERIC_FUNCTIONS: &[(&str, usize)] = &[("abs", 1), ("sign", 1), ("min", 2), ("max", 2), ("clamp", 3)];
+
+const CONSTANTS: &[(&str, &str)] = &[("pi", "3.14159265358979"), ("e", "2.71828182845905")];
+
+struct Lowering<'a> {
+    options: &'a ExpressionKernelOptions,
+    /// Indices into `options.inputs` that the expression reads
+    used_inputs: BTreeSet<usize>,
+    helpers: BTreeSet<(usize, Ty)>,
+    requires_f16: bool,
+}
+
+impl Lowering<'_> {
+    fn note_type(&mut self, ty: Ty) {
+        if ty == Ty::F16 {
+            self.requires_f16 = true;
+        }
+    }
+
+    fn lower(&mut self, node: &Node) -> Result<Typed, ExpressionError> {
+        let error = |message: String| ExpressionError::new(message, node.position);
+
+        match &node.expr {
+            Expr::Number { text, float } => Ok(Typed {
+                code: text.clone(),
+                ty: if *float { Ty::AbstractFloat } else { Ty::AbstractInt },
+            }),
+
+            Expr::Name(name) => {
+                if let Some(index) = self.options.inputs.iter().position(|i| &i.name == name) {
+                    self.used_inputs.insert(index);
+                    let ty = Ty::from_dtype(self.options.inputs[index].dtype);
+                    self.note_type(ty);
+                    return Ok(Typed {
+                        code: format!("v_{}", name),
+                        ty,
+                    });
+                }
+                if let Some(uniform) = self.options.uniforms.iter().find(|u| &u.name == name) {
+                    return Ok(Typed {
+                        code: format!("params.u_{}", name),
+                        ty: Ty::from_dtype(uniform.dtype),
+                    });
+                }
+                if let Some((_, value)) = CONSTANTS.iter().find(|(c, _)| c == name) {
+                    return Ok(Typed {
+                        code: value.to_string(),
+                        ty: Ty::AbstractFloat,
+                    });
+                }
+                Err(error(format!("Unknown name '{}'", name)))
+            }
+
+            Expr::Unary { op, operand } => {
+                let value = self.lower(operand)?;
+                match *op {
+                    "!" => {
+                        if value.ty != Ty::Bool {
+                            return Err(error("'!' needs a boolean operand".to_string()));
+                        }
+                        Ok(Typed {
+                            code: format!("(!{})", value.code),
+                            ty: Ty::Bool,
+                        })
+                    }
+                    _ => {
+                        let value = match value.ty {
+                            Ty::Bool => return Err(error("Cannot negate a boolean".to_string())),
+                            Ty::U32 => cast(value, Ty::I32),
+                            _ => value,
+                        };
+                        Ok(Typed {
+                            code: format!("(-{})", value.code),
+                            ty: value.ty,
+                        })
+                    }
+                }
+            }
+
+            Expr::Binary { op, lhs, rhs } => {
+                let a = self.lower(lhs)?;
+                let b = self.lower(rhs)?;
+                self.binary(op, a, b).map_err(error)
+            }
+
+            Expr::Select {
+                condition,
+                accept,
+                reject,
+            } => {
+                let condition_value = self.lower(condition)?;
+                if condition_value.ty != Ty::Bool {
+                    return Err(ExpressionError::new(
+                        "Condition must be a comparison or boolean".to_string(),
+                        condition.position,
+                    ));
+                }
+                let a = self.lower(accept)?;
+                let b = self.lower(reject)?;
+                let ty = unify(a.ty, b.ty)
+                    .ok_or_else(|| error("Branches of '?:' have incompatible types".to_string()))?;
+                Ok(Typed {
+                    code: format!("select({}, {}, {})", cast(b, ty).code, cast(a, ty).code, condition_value.code),
+                    ty,
+                })
+            }
+
+            Expr::Call { function, args } => {
+                let values = args
+                    .iter()
+                    .map(|arg| self.lower(arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.call(function, values).map_err(error)
+            }
+        }
+    }
+
+    fn binary(&mut self, op: &str, a: Typed, b: Typed) -> Result<Typed, String> {
+        match op {
+            "&&" | "||" => {
+                if a.ty != Ty::Bool || b.ty != Ty::Bool {
+                    return Err(format!("'{}' needs boolean operands", op));
+                }
+                Ok(Typed {
+                    code: format!("({} {} {})", a.code, op, b.code),
+                    ty: Ty::Bool,
+                })
+            }
+            "==" | "!=" | "<" | "<=" | ">" | ">=" => {
+                let ty = unify(a.ty, b.ty).ok_or_else(|| format!("Cannot compare a boolean with a number using '{}'", op))?;
+                if ty == Ty::Bool && !matches!(op, "==" | "!=") {
+                    return Err(format!("Booleans cannot be ordered with '{}'", op));
+                }
+                Ok(Typed {
+                    code: format!("({} {} {})", cast(a, ty).code, op, cast(b, ty).code),
+                    ty: Ty::Bool,
+                })
+            }
+            _ => {
+                let ty = unify(a.ty, b.ty)
+                    .filter(|ty| *ty != Ty::Bool)
+                    .ok_or_else(|| format!("'{}' needs numeric operands", op))?;
+                if op == "**" {
+                    let ty = ty.float();
+                    return Ok(Typed {
+                        code: format!("pow({}, {})", cast(a, ty).code, cast(b, ty).code),
+                        ty,
+                    });
+                }
+                Ok(Typed {
+                    code: format!("({} {} {})", cast(a, ty).code, op, cast(b, ty).code),
+                    ty,
+                })
+            }
+        }
+    }
+
+    fn call(&mut self, function: &str, args: Vec<Typed>) -> Result<Typed, String> {
+        let count = args.len();
+        let arity = |expected: usize| {
+            if count == expected {
+                Ok(())
+            } else {
+                Err(format!("{}() takes {} argument(s), got {}", function, expected, count))
+            }
+        };
+        let common = |args: &[Typed]| {
+            args.iter()
+                .try_fold(None, |acc: Option<Ty>, arg| match acc {
+                    None => Some(Some(arg.ty)),
+                    Some(ty) => unify(ty, arg.ty).map(Some),
+                })
+                .flatten()
+                .filter(|ty| *ty != Ty::Bool)
+                .ok_or_else(|| format!("{}() needs numeric arguments", function))
+        };
+        let join = |args: Vec<Typed>, ty: Ty| {
+            args.into_iter()
+                .map(|arg| cast(arg, ty).code)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        if let Some((_, wgsl, expected)) = FLOAT_FUNCTIONS.iter().find(|(name, _, _)| *name == function) {
+            arity(*expected)?;
+            let ty = common(&args)?.float();
+            return Ok(Typed {
+                code: format!("{}({})", wgsl, join(args, ty)),
+                ty,
+            });
+        }
+
+        if let Some((_, expected)) = NUMERIC_FUNCTIONS.iter().find(|(name, _)| *name == function) {
+            arity(*expected)?;
+            let mut ty = common(&args)?;
+            if function == "sign" && ty == Ty::U32 {
+                ty = Ty::I32;
+            }
+            return Ok(Typed {
+                code: format!("{}({})", function, join(args, ty)),
+                ty,
+            });
+        }
+
+        if let Some(index) = HELPERS.iter().position(|(name, _, _)| *name == function) {
+            let mut args = args;
+            if function == "leaky_relu" {
+                if count == 1 {
+                    args.push(Typed {
+                        code: "0.01".to_string(),
+                        ty: Ty::AbstractFloat,
+                    });
+                } else if count != 2 {
+                    return Err("leaky_relu() takes 1 or 2 arguments".to_string());
+                }
+            } else {
+                arity(1)?;
+            }
+            let ty = common(&args)?.float().concrete();
+            self.helpers.insert((index, ty));
+            for required in HELPERS[index].1 {
+                let required = HELPERS.iter().position(|(name, _, _)| name == required).expect("known helper");
+                self.helpers.insert((required, ty));
+            }
+            return Ok(Typed {
+                code: format!("ex_{}_{}({})", function, ty.name(), join(args, ty)),
+                ty,
+            });
+        }
+
+        match function {
+            "relu" => {
+                arity(1)?;
+                let ty = common(&args)?.concrete();
+                Ok(Typed {
+                    code: format!("max({}, {}(0))", join(args, ty), ty.name()),
+                    ty,
+                })
+            }
+            "where" => {
+                arity(3)?;
+                let mut args = args.into_iter();
+                let (condition, a, b) = (args.next().unwrap(), args.next().unwrap(), args.next().unwrap());
+                if condition.ty != Ty::Bool {
+                    return Err("where() needs a boolean condition".to_string());
+                }
+                let ty = unify(a.ty, b.ty).ok_or_else(|| "where() branches have incompatible types".to_string())?;
+                Ok(Typed {
+                    code: format!("select({}, {}, {})", cast(b, ty).code, cast(a, ty).code, condition.code),
+                    ty,
+                })
+            }
+            "f32" | "f16" | "i32" | "u32" => {
+                arity(1)?;
+                let ty = match function {
+                    "f32" => Ty::F32,
+                    "f16" => Ty::F16,
+                    "i32" => Ty::I32,
+                    _ => Ty::U32,
+                };
+                self.note_type(ty);
+                Ok(cast(args.into_iter().next().unwrap(), ty))
+            }
+            _ => Err(format!("Unknown function '{}'", function)),
+        }
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.starts_with("__")
+}
+
+fn check_options(options: &ExpressionKernelOptions) -> Result<(), ExpressionError> {
+    let invalid = |message: String| Err(ExpressionError::new(message, 0));
+
+    if !is_identifier(&options.entry_point) {
+        return invalid(format!("Invalid entry point name '{}'", options.entry_point));
+    }
+    if options.workgroup_size == 0 {
+        return invalid("Workgroup size must be at least 1".to_string());
+    }
+    let mut seen = BTreeSet::new();
+    for operand in options.inputs.iter().chain(&options.uniforms) {
+        if !is_identifier(&operand.name) {
+            return invalid(format!("Invalid operand name '{}'", operand.name));
+        }
+        if !seen.insert(operand.name.as_str()) {
+            return invalid(format!("Operand '{}' is declared twice", operand.name));
+        }
+    }
+    if let Some(uniform) = options
+        .uniforms
+        .iter()
+        .find(|u| !matches!(u.dtype, TensorDType::Float32 | TensorDType::Int32))
+    {
+        return invalid(format!(
+            "Uniform '{}' must be Float32 or Int32, got {}",
+            uniform.name,
+            uniform.dtype.wgsl_type()
+        ));
+    }
+    if matches!(options.output_dtype, Some(TensorDType::Int8 | TensorDType::UInt8)) {
+        return invalid("8-bit outputs are not supported".to_string());
+    }
+    Ok(())
+}
+
+/// Compile an elementwise expression into a compute kernel
+///
+/// Bindings are `@group(0)`: the inputs the expression reads (in declaration order),
+/// then `output`, then the `params` uniform holding the element count and the scalars.
+pub fn compile_expression(
+    expression: &str,
+    options: &ExpressionKernelOptions,
+) -> Result<ExpressionKernel, ExpressionError> {
+    check_options(options)?;
+    let ast = parse(expression)?;
+
+    let mut lowering = Lowering {
+        options,
+        used_inputs: BTreeSet::new(),
+        helpers: BTreeSet::new(),
+        requires_f16: false,
+    };
+    let result = lowering.lower(&ast)?;
+
+    let output_dtype = options.output_dtype.unwrap_or(match result.ty.concrete() {
+        Ty::F32 => TensorDType::Float32,
+        Ty::F16 => TensorDType::Float16,
+        _ => TensorDType::Int32,
+    });
+    let output_ty = Ty::from_dtype(output_dtype);
+    lowering.note_type(output_ty);
+    let result = cast(result, output_ty);
+
+    let mut wgsl = String::new();
+    if lowering.requires_f16 {
+        wgsl.push_str("enable f16;\n\n");
+    }
+
+    // Params uniform
+    let mut uniforms = Vec::new();
+    wgsl.push_str("struct Params {\n    len: u32,\n");
+    for (index, uniform) in options.uniforms.iter().enumerate() {
+        wgsl.push_str(&format!("    u_{}: {},\n", uniform.name, uniform.dtype.wgsl_type()));
+        uniforms.push(ExpressionUniformField {
+            name: uniform.name.clone(),
+            offset: 4 + 4 * index as u32,
+            dtype: uniform.dtype,
+        });
+    }
+    wgsl.push_str("}\n\n");
+    let params_size = (4 + 4 * options.uniforms.len() as u64).div_ceil(16) * 16;
+
+    // Bindings
+    let mut bindings = Vec::new();
+    let mut loads = String::new();
+    let mut loaders = String::new();
+    for &index in &lowering.used_inputs {
+        let input = &options.inputs[index];
+        let variable = format!("input_{}", input.name);
+        let binding = bindings.len() as u32;
+        let packed = matches!(input.dtype, TensorDType::Int8 | TensorDType::UInt8);
+        let element = if packed { "u32" } else { input.dtype.wgsl_type() };
+        wgsl.push_str(&format!(
+            "@group(0) @binding({}) var<storage, read> {}: array<{}>;\n",
+            binding, variable, element
+        ));
+
+        if packed {
+            let (word, ty) = match input.dtype {
+                TensorDType::Int8 => (format!("bitcast<i32>({}[index / 4u])", variable), "i32"),
+                _ => (format!("{}[index / 4u]", variable), "u32"),
+            };
+            loaders.push_str(&format!(
+                "fn load_{}(index: u32) -> {} {{\n    return extractBits({}, (index % 4u) * 8u, 8u);\n}}\n\n",
+                input.name, ty, word
+            ));
+            loads.push_str(&format!("    let v_{0} = load_{0}(index);\n", input.name));
+        } else {
+            loads.push_str(&format!("    let v_{} = {}[index];\n", input.name, variable));
+        }
+
+        bindings.push(ExpressionBinding {
+            name: input.name.clone(),
+            variable,
+            group: 0,
+            binding,
+            kind: ExpressionBindingKind::Input,
+            dtype: Some(input.dtype),
+            packed,
+        });
+    }
+
+    let output_binding = bindings.len() as u32;
+    wgsl.push_str(&format!(
+        "@group(0) @binding({}) var<storage, read_write> output: array<{}>;\n",
+        output_binding,
+        output_dtype.wgsl_type()
+    ));
+    bindings.push(ExpressionBinding {
+        name: "output".to_string(),
+        variable: "output".to_string(),
+        group: 0,
+        binding: output_binding,
+        kind: ExpressionBindingKind::Output,
+        dtype: Some(output_dtype),
+        packed: false,
+    });
+    wgsl.push_str(&format!(
+        "@group(0) @binding({}) var<uniform> params: Params;\n\n",
+        output_binding + 1
+    ));
+    bindings.push(ExpressionBinding {
+        name: "params".to_string(),
+        variable: "params".to_string(),
+        group: 0,
+        binding: output_binding + 1,
+        kind: ExpressionBindingKind::Params,
+        dtype: None,
+        packed: false,
+    });
+
+    wgsl.push_str(&loaders);
+    for (index, ty) in &lowering.helpers {
+        wgsl.push_str(&instantiate_helper(*index, *ty));
+        wgsl.push_str("\n\n");
+    }
+
+    wgsl.push_str(&format!(
+        "@compute @workgroup_size({})\nfn {}(@builtin(global_invocation_id) global_id: vec3<u32>) {{\n",
+        options.workgroup_size, options.entry_point
+    ));
+    wgsl.push_str("    let index = global_id.x;\n    if (index >= params.len) {\n        return;\n    }\n");
+    wgsl.push_str(&loads);
+    wgsl.push_str(&format!("    output[index] = {};\n}}\n", result.code));
+
+    Ok(ExpressionKernel {
+        expression: expression.to_string(),
+        wgsl,
+        entry_point: options.entry_point.clone(),
+        workgroup_size: options.workgroup_size,
+        output_dtype,
+        bindings,
+        uniforms,
+        params_size,
+        requires_f16: lowering.requires_f16,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shader::validate_wgsl;
+
+    fn float_inputs(names: &[&str]) -> ExpressionKernelOptions {
+        names
+            .iter()
+            .fold(ExpressionKernelOptions::new(), |options, name| options.input(name, TensorDType::Float32))
+    }
+
+    #[test]
+    fn test_compile_float_expression() {
+        let kernel = compile_expression("a * b + sin(c)", &float_inputs(&["a", "b", "c"])).unwrap();
+        validate_wgsl(&kernel.wgsl).unwrap_or_else(|e| panic!("{}\n{}", e, kernel.wgsl));
+        assert!(kernel.wgsl.contains("output[index] = ((v_a * v_b) + sin(v_c));"));
+        assert_eq!(kernel.output_dtype, TensorDType::Float32);
+
+        let kinds: Vec<_> = kernel.bindings.iter().map(|b| (b.name.as_str(), b.binding)).collect();
+        assert_eq!(kinds, [("a", 0), ("b", 1), ("c", 2), ("output", 3), ("params", 4)]);
+        assert_eq!(kernel.dispatch_size(1000), 16);
+    }
+
+    #[test]
+    fn test_type_inference() {
+        let options = ExpressionKernelOptions::new()
+            .input("a", TensorDType::Int8)
+            .input("b", TensorDType::UInt8)
+            .input("unused", TensorDType::Float32);
+        let kernel = compile_expression("a * 2 + b % 3", &options).unwrap();
+        validate_wgsl(&kernel.wgsl).unwrap_or_else(|e| panic!("{}\n{}", e, kernel.wgsl));
+        assert_eq!(kernel.output_dtype, TensorDType::Int32);
+        assert!(kernel.bindings.iter().all(|b| b.name != "unused"));
+        assert!(kernel.bindings[0].packed);
+
+        let kernel = compile_expression("a / 2.0 > 1 && b != 0", &options).unwrap();
+        validate_wgsl(&kernel.wgsl).unwrap_or_else(|e| panic!("{}\n{}", e, kernel.wgsl));
+        assert_eq!(kernel.output_dtype, TensorDType::Int32);
+
+        let options = ExpressionKernelOptions::new()
+            .input("x", TensorDType::Float16)
+            .input("q", TensorDType::UInt8);
+        let kernel = compile_expression("gelu(x) * q + sigmoid(x) ** 2", &options).unwrap();
+        assert!(kernel.requires_f16);
+        assert_eq!(kernel.output_dtype, TensorDType::Float16);
+        assert!(kernel.wgsl.starts_with("enable f16;"));
+        assert!(kernel.wgsl.contains("fn ex_gelu_f16(x: f16) -> f16"));
+        assert!(kernel.wgsl.contains("(ex_gelu_f16(v_x) * f16(v_q))"));
+
+        // The same expression over f32 validates
+        let options = ExpressionKernelOptions::new()
+            .input("x", TensorDType::Float32)
+            .input("q", TensorDType::UInt8);
+        let kernel = compile_expression("gelu(x) * q + sigmoid(x) ** 2", &options).unwrap();
+        validate_wgsl(&kernel.wgsl).unwrap_or_else(|e| panic!("{}\n{}", e, kernel.wgsl));
+    }
+
+    #[test]
+    fn test_helpers_match_stdlib() {
+        let nn = crate::shader::stdlib_source("nn").unwrap();
+        for (index, (name, _, _)) in HELPERS.iter().enumerate() {
+            let helper = instantiate_helper(index, Ty::F32)
+                .replace(&format!("ex_{}_f32(", name), &format!("{}(", name))
+                .replace("ex_sigmoid_f32(", "sigmoid(");
+            let start = nn.find(&format!("fn {}(", name)).unwrap();
+            let end = start + nn[start..].find("\n}").unwrap() + 2;
+            assert_eq!(helper, nn[start..end], "{} differs from std::nn", name);
+        }
+
+        // silu pulls in the sigmoid it calls
+        let kernel = compile_expression("silu(x)", &float_inputs(&["x"])).unwrap();
+        validate_wgsl(&kernel.wgsl).unwrap_or_else(|e| panic!("{}\n{}", e, kernel.wgsl));
+        assert!(kernel.wgsl.contains("fn ex_sigmoid_f32(x: f32) -> f32"));
+    }
+
+    #[test]
+    fn test_uniforms_and_select() {
+        let options = float_inputs(&["x"])
+            .uniform("alpha", TensorDType::Float32)
+            .uniform("offset", TensorDType::Int32)
+            .output(TensorDType::Int32);
+        let kernel = compile_expression("x > 0 ? x : alpha * -x + offset", &options).unwrap();
+        validate_wgsl(&kernel.wgsl).unwrap_or_else(|e| panic!("{}\n{}", e, kernel.wgsl));
+        assert_eq!(kernel.params_size, 16);
+
+        let values = HashMap::from([("alpha".to_string(), 0.5), ("offset".to_string(), -3.0)]);
+        let bytes = kernel.params_data(10, &values).unwrap();
+        assert_eq!(&bytes[0..4], &10u32.to_le_bytes());
+        assert_eq!(&bytes[4..8], &0.5f32.to_le_bytes());
+        assert_eq!(&bytes[8..12], &(-3i32).to_le_bytes());
+        assert!(kernel.params_data(10, &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_errors_report_position() {
+        let options = float_inputs(&["a"]);
+        let err = compile_expression("a + bogus", &options).unwrap_err();
+        assert_eq!(err.position, 4);
+        assert!(err.message.contains("bogus"));
+
+        let err = compile_expression("a + (a > 1)", &options).unwrap_err();
+        assert!(err.message.contains("numeric"));
+
+        let err = compile_expression("frobnicate(a)", &options).unwrap_err();
+        assert!(err.message.contains("Unknown function"));
+
+        let err = compile_expression("a +", &options).unwrap_err();
+        assert!(err.message.contains("end of expression"));
+
+        let err = compile_expression("a", &float_inputs(&["a", "a"])).unwrap_err();
+        assert!(err.message.contains("twice"));
+    }
+}
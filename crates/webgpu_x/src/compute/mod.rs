@@ -1,6 +1,8 @@
 pub mod workgroup;
 pub mod kernel;
 pub mod templates;
+pub mod expression;
+pub mod validation;
 
 pub use workgroup::{
     calculate_dispatch_size, calculate_dispatch_size_1d, calculate_dispatch_size_2d,
@@ -13,3 +15,8 @@ pub use kernel::{
     SimpleKernelBuilder,
 };
 pub use templates::{generate_kernel, KernelOperation};
+pub use expression::{
+    compile_expression, ExpressionBinding, ExpressionBindingKind, ExpressionError, ExpressionKernel,
+    ExpressionKernelOptions, ExpressionOperand, ExpressionUniformField,
+};
+pub use validation::validate_kernel_limits;
@@ -148,6 +148,48 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {{
 "#, workgroup_size.0, workgroup_size.1, workgroup_size.2)
 }
 
+/// Binary element-wise kernel as a single variant source for
+/// [`ShaderCache::load_variant`](crate::shader::compilation::ShaderCache::load_variant)
+///
+/// Features: `OP` (add, sub, mul, div), `WORKGROUP_SIZE` and an optional `BIAS`;
+/// the `alpha` scale and `bias` are override constants.
+pub const ELEMENTWISE_BINARY_VARIANTS: &str = r#"
+#pragma feature OP add sub mul div
+#pragma feature WORKGROUP_SIZE 64 128 256
+#pragma feature BIAS
+
+@group(0) @binding(0) var<storage, read> input_a: array<f32>;
+@group(0) @binding(1) var<storage, read> input_b: array<f32>;
+@group(0) @binding(2) var<storage, read_write> output: array<f32>;
+
+override alpha: f32 = 1.0;
+#ifdef BIAS
+override bias: f32 = 0.0;
+#endif
+
+@compute @workgroup_size(WORKGROUP_SIZE, 1, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let index = global_id.x;
+    if (index >= arrayLength(&input_a)) {
+        return;
+    }
+#if defined(OP_add)
+    var result = input_a[index] + input_b[index];
+#elif defined(OP_sub)
+    var result = input_a[index] - input_b[index];
+#elif defined(OP_mul)
+    var result = input_a[index] * input_b[index];
+#else
+    var result = input_a[index] / input_b[index];
+#endif
+    result *= alpha;
+#ifdef BIAS
+    result += bias;
+#endif
+    output[index] = result;
+}
+"#;
+
 // ============================================================================
 // Matrix Operations
 // ============================================================================
@@ -670,6 +712,21 @@ fn main(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_elementwise_variants_compile() {
+        use crate::shader::compilation::ShaderCache;
+        use crate::shader::{enumerate_variants, variant_space};
+
+        let space = variant_space(ELEMENTWISE_BINARY_VARIANTS).unwrap();
+        let keys = enumerate_variants(&space);
+        assert_eq!(keys.len(), 24);
+
+        let mut cache = ShaderCache::new();
+        let report = cache.prewarm_variants(ELEMENTWISE_BINARY_VARIANTS, &keys);
+        assert!(report.failed.is_empty(), "{:?}", report.failed);
+        assert_eq!(report.compiled.len(), 24);
+    }
+
     #[test]
     fn test_generate_add_kernel() {
         let kernel = generate_kernel(KernelOperation::Add, (64, 1, 1));
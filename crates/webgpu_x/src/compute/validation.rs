@@ -0,0 +1,317 @@
+//! Compute kernel validation against device limits
+//!
+//! Checks what a WGSL kernel asks of the device (workgroup size, workgroup memory,
+//! bind groups and per-stage resources) against a [`LimitsProfile`], so a kernel can
+//! be checked for portability before it is dispatched anywhere. Diagnostics point at
+//! `/entryPoints/<name>` and carry the WGSL location of the offending declaration.
+
+use crate::descriptors::compatibility::{entry_point_location, span_location, ShaderSourceLocation};
+use crate::descriptors::validator::{
+    json_pointer, spec_url, DescriptorDiagnostic, DescriptorValidationResult, Severity,
+};
+use crate::gpu::profiles::LimitsProfile;
+use crate::shader::reflection::validate_wgsl;
+use naga::proc::Layouter;
+
+/// Spec section defining the limits
+const LIMITS_ANCHOR: &str = "limits";
+
+/// Resources one entry point uses, counted the way the per-stage limits count them
+#[derive(Default)]
+struct StageResources {
+    uniform_buffers: u64,
+    storage_buffers: u64,
+    samplers: u64,
+    sampled_textures: u64,
+    storage_textures: u64,
+    /// Each variable rounded up to 16 bytes, as the spec counts it
+    workgroup_storage: u64,
+}
+
+/// Validate every compute entry point of `source` against `profile`
+pub fn validate_kernel_limits(source: &str, profile: &LimitsProfile) -> DescriptorValidationResult {
+    let mut result = DescriptorValidationResult::ok();
+    let shader = match validate_wgsl(source) {
+        Ok(shader) => shader,
+        Err(e) => {
+            let location = e.diagnostics.first().map(|d| ShaderSourceLocation {
+                module: String::new(),
+                name: String::new(),
+                line: d.line,
+                column: d.column,
+            });
+            push(
+                &mut result,
+                String::new(),
+                location,
+                "invalid-shader",
+                e.message,
+                "shader-module-creation",
+            );
+            return result;
+        }
+    };
+    let module = &shader.module;
+    let mut layouter = Layouter::default();
+    if layouter.update(module.to_ctx()).is_err() {
+        return result;
+    }
+    let limits = &profile.limits;
+
+    for (index, ep) in module.entry_points.iter().enumerate() {
+        if ep.stage != naga::ShaderStage::Compute {
+            continue;
+        }
+        let path = json_pointer("/entryPoints", &ep.name);
+        let ep_location = entry_point_location(source, "", &ep.name);
+        let mut exceeded = |location: &ShaderSourceLocation, what: String, value: u64, limit: &str, max: u64| {
+            if value > max {
+                let message = format!(
+                    "{} {} exceeds {} {} of profile '{}'",
+                    what, value, limit, max, profile.name
+                );
+                push(
+                    &mut result,
+                    path.clone(),
+                    Some(location.clone()),
+                    "limit-exceeded",
+                    message,
+                    LIMITS_ANCHOR,
+                );
+            }
+        };
+
+        let [x, y, z] = ep.workgroup_size.map(u64::from);
+        exceeded(
+            &ep_location,
+            "Workgroup size x".to_string(),
+            x,
+            "maxComputeWorkgroupSizeX",
+            limits.max_compute_workgroup_size_x as u64,
+        );
+        exceeded(
+            &ep_location,
+            "Workgroup size y".to_string(),
+            y,
+            "maxComputeWorkgroupSizeY",
+            limits.max_compute_workgroup_size_y as u64,
+        );
+        exceeded(
+            &ep_location,
+            "Workgroup size z".to_string(),
+            z,
+            "maxComputeWorkgroupSizeZ",
+            limits.max_compute_workgroup_size_z as u64,
+        );
+        exceeded(
+            &ep_location,
+            "Workgroup invocation count".to_string(),
+            x * y * z,
+            "maxComputeInvocationsPerWorkgroup",
+            limits.max_compute_invocations_per_workgroup as u64,
+        );
+
+        let usage = shader.info.get_entry_point(index);
+        let mut resources = StageResources::default();
+        for (handle, var) in module.global_variables.iter() {
+            if usage[handle].is_empty() {
+                continue;
+            }
+            let name = var.name.clone().unwrap_or_default();
+            let location = span_location(source, "", &name, module.global_variables.get_span(handle));
+            if let Some(binding) = &var.binding {
+                exceeded(
+                    &location,
+                    format!("Bind group index of '{}'", name),
+                    binding.group as u64 + 1,
+                    "maxBindGroups",
+                    limits.max_bind_groups as u64,
+                );
+                exceeded(
+                    &location,
+                    format!("Binding number of '{}'", name),
+                    binding.binding as u64 + 1,
+                    "maxBindingsPerBindGroup",
+                    limits.max_bindings_per_bind_group as u64,
+                );
+            }
+            count_resource(module, &layouter, var, &mut resources);
+        }
+
+        let counts = [
+            (
+                "uniform buffers",
+                resources.uniform_buffers,
+                "maxUniformBuffersPerShaderStage",
+                limits.max_uniform_buffers_per_shader_stage,
+            ),
+            (
+                "storage buffers",
+                resources.storage_buffers,
+                "maxStorageBuffersPerShaderStage",
+                limits.max_storage_buffers_per_shader_stage,
+            ),
+            (
+                "samplers",
+                resources.samplers,
+                "maxSamplersPerShaderStage",
+                limits.max_samplers_per_shader_stage,
+            ),
+            (
+                "sampled textures",
+                resources.sampled_textures,
+                "maxSampledTexturesPerShaderStage",
+                limits.max_sampled_textures_per_shader_stage,
+            ),
+            (
+                "storage textures",
+                resources.storage_textures,
+                "maxStorageTexturesPerShaderStage",
+                limits.max_storage_textures_per_shader_stage,
+            ),
+        ];
+        for (what, count, limit, max) in counts {
+            exceeded(
+                &ep_location,
+                format!("Number of {} used", what),
+                count,
+                limit,
+                max as u64,
+            );
+        }
+        exceeded(
+            &ep_location,
+            "Workgroup storage size".to_string(),
+            resources.workgroup_storage,
+            "maxComputeWorkgroupStorageSize",
+            limits.max_compute_workgroup_storage_size as u64,
+        );
+    }
+
+    result
+}
+
+fn count_resource(
+    module: &naga::Module,
+    layouter: &Layouter,
+    var: &naga::GlobalVariable,
+    resources: &mut StageResources,
+) {
+    use naga::{AddressSpace, ImageClass, TypeInner};
+
+    match var.space {
+        AddressSpace::Uniform => resources.uniform_buffers += 1,
+        AddressSpace::Storage { .. } => resources.storage_buffers += 1,
+        AddressSpace::WorkGroup => {
+            let size = layouter[var.ty].size as u64;
+            resources.workgroup_storage += size.div_ceil(16) * 16;
+        }
+        AddressSpace::Handle => {
+            // Binding arrays count once per element
+            let (inner, count) = match &module.types[var.ty].inner {
+                TypeInner::BindingArray { base, size } => {
+                    let count = match size {
+                        naga::ArraySize::Constant(n) => n.get() as u64,
+                        _ => 1,
+                    };
+                    (&module.types[*base].inner, count)
+                }
+                inner => (inner, 1),
+            };
+            match inner {
+                TypeInner::Sampler { .. } => resources.samplers += count,
+                TypeInner::Image {
+                    class: ImageClass::Storage { .. },
+                    ..
+                } => resources.storage_textures += count,
+                TypeInner::Image { .. } => resources.sampled_textures += count,
+                _ => {}
+            }
+        }
+        _ => {}
+    }
+}
+
+fn push(
+    result: &mut DescriptorValidationResult,
+    path: String,
+    shader_location: Option<ShaderSourceLocation>,
+    code: &str,
+    message: String,
+    anchor: &str,
+) {
+    result.push_diagnostic(DescriptorDiagnostic {
+        path,
+        code: code.to_string(),
+        severity: Severity::Error,
+        message,
+        spec_url: spec_url(anchor),
+        related_path: None,
+        shader_location,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpu::profiles::{compatibility_profile, webgl2_profile, webgpu_default_profile};
+
+    const KERNEL: &str = r#"
+@group(0) @binding(0) var<storage, read> a: array<f32>;
+@group(0) @binding(1) var<storage, read> b: array<f32>;
+@group(0) @binding(2) var<storage, read> c: array<f32>;
+@group(0) @binding(3) var<storage, read> d: array<f32>;
+@group(0) @binding(4) var<storage, read_write> out: array<f32>;
+
+var<workgroup> tile: array<f32, 2048>;
+
+@compute @workgroup_size(256)
+fn main(@builtin(global_invocation_id) id: vec3<u32>, @builtin(local_invocation_index) i: u32) {
+    tile[i] = a[id.x] + b[id.x] + c[id.x] + d[id.x];
+    workgroupBarrier();
+    out[id.x] = tile[i];
+}
+"#;
+
+    #[test]
+    fn test_kernel_within_default_limits() {
+        let result = validate_kernel_limits(KERNEL, &webgpu_default_profile());
+        assert!(result.valid, "{:?}", result.diagnostics);
+    }
+
+    #[test]
+    fn test_kernel_against_compatibility_profile() {
+        let result = validate_kernel_limits(KERNEL, &compatibility_profile());
+        let messages: Vec<&str> = result.errors().map(|d| d.message.as_str()).collect();
+        assert!(
+            messages.iter().any(|m| m.contains("maxComputeWorkgroupSizeX")),
+            "{:?}",
+            messages
+        );
+        assert!(
+            messages.iter().any(|m| m.contains("maxStorageBuffersPerShaderStage")),
+            "{:?}",
+            messages
+        );
+        // 8 KiB of workgroup memory fits compatibility mode
+        assert!(!messages.iter().any(|m| m.contains("maxComputeWorkgroupStorageSize")));
+
+        let diagnostic = result.errors().next().unwrap();
+        assert_eq!(diagnostic.path, "/entryPoints/main");
+        assert_eq!(diagnostic.shader_location.as_ref().unwrap().line, 11);
+    }
+
+    #[test]
+    fn test_webgl2_has_no_compute() {
+        let result = validate_kernel_limits(KERNEL, &webgl2_profile());
+        assert!(result
+            .errors()
+            .any(|d| d.message.contains("maxComputeInvocationsPerWorkgroup")));
+    }
+
+    #[test]
+    fn test_invalid_kernel() {
+        let result = validate_kernel_limits("@compute fn main( {", &webgpu_default_profile());
+        assert!(result.has_code("invalid-shader"));
+    }
+}
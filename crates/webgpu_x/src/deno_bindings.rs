@@ -15,6 +15,169 @@ use deno_bindgen::deno_bindgen;
 // ============================================================================
 // These functions are NOT exported elsewhere, so we export them here
 
+// ============================================================================
+// GPU ADAPTER ENUMERATION
+// ============================================================================
+
+/// Enumerate adapters on every backend and register them in the GPU registry
+/// replace: 1 clears the registry first, 0 appends after existing entries
+/// Returns JSON array of EnumeratedAdapter: [{"index", "name", "vendor_id", "device_id",
+///   "device_type": "discrete" | "integrated" | "virtual" | "cpu" | "other",
+///   "backend": "vulkan" | "metal" | "dx12" | "gl", "driver", "driver_info",
+///   "is_software", "limits": {"maxTextureDimension2D": ...}, "features": ["shader-f16", ...],
+///   "memory_size": bytes | null, "gpu_info": {"architecture", "cuda_compute_capability",
+///   "gfx_target", "subgroup_size", "optimal_workgroup_size", ...}}]
+#[deno_bindgen]
+pub fn gpu_enumerate_adapters(replace: u8) -> String {
+    let adapters = crate::utilities::enumerate_and_register_gpus(replace != 0);
+    serde_json::to_string(&adapters).unwrap_or_default()
+}
+
+/// Registered GPU by index as JSON FoundGPUDevice (including limits and features)
+#[deno_bindgen]
+pub fn gpu_registry_get(index: u32) -> String {
+    serde_json::to_string(&crate::utilities::get_gpu_info(index)).unwrap_or_default()
+}
+
+// ============================================================================
+// GPU SELECTION
+// ============================================================================
+
+/// Rank every registered GPU under a selection policy
+/// policy_json: {"preset": "balanced" | "compute" | "graphics" | "ml" | "power" | "memory",
+///   "weights": {"device_type", "memory", "vendor", "backend", "power"},
+///   "device_type_scores", "vendor_scores", "backend_scores": {"<name>": 0.0-1.0},
+///   "power_preference": "high-performance" | "low-power" | "none",
+///   "required_features": ["shader-f16"], "min_limits": {"maxBufferSize": ...},
+///   "min_memory_bytes", "exclude": ["llvmpipe"]}; empty string uses "balanced"
+/// Returns JSON GpuSelection: {"policy", "selected": index | null, "candidates": [{"index",
+///   "name", "vendor", "device_type", "backend", "eligible", "rejections", "total_score",
+///   "breakdown": [{"criterion", "weight", "score", "weighted", "reason"}]}]}
+/// or empty string if the policy is invalid
+#[deno_bindgen]
+pub fn gpu_select(policy_json: &str) -> String {
+    match crate::utilities::parse_selection_policy(policy_json) {
+        Ok(policy) => serde_json::to_string(&crate::utilities::rank_gpus(&policy)).unwrap_or_default(),
+        Err(message) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "policy".to_string(),
+                message,
+            });
+            String::new()
+        }
+    }
+}
+
+/// Built-in selection presets as a JSON array of SelectionPolicy
+#[deno_bindgen]
+pub fn gpu_select_presets() -> String {
+    serde_json::to_string(&crate::utilities::list_selection_presets()).unwrap_or_default()
+}
+
+// ============================================================================
+// CAPABILITY REPORT
+// ============================================================================
+
+/// Parse report options, recording the error on failure
+fn report_options(options_json: &str) -> Option<crate::utilities::ReportOptions> {
+    if options_json.trim().is_empty() {
+        return Some(Default::default());
+    }
+    match serde_json::from_str(options_json) {
+        Ok(options) => Some(options),
+        Err(e) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "options".to_string(),
+                message: format!("Invalid report options: {}", e),
+            });
+            None
+        }
+    }
+}
+
+/// Capability report for bug reports as versioned JSON
+/// options_json: {"redact": true} strips PCI addresses, subsystem IDs, device nodes and
+///   build suffixes of driver strings (versions are kept); empty string for defaults
+/// Returns JSON CapabilityReport: {"version", "generator", "redacted", "system": {"os",
+///   "architecture", ...}, "adapters": [{"adapter": EnumeratedAdapter, "driver": {"is_mesa",
+///   "mesa_version", "kernel_driver"}, "texture_formats": [{"format", "usages", "flags"}]}],
+///   "pci_devices": [LinuxGpuDevice]}
+/// or empty string if the options are invalid
+#[deno_bindgen]
+pub fn gpu_capability_report(options_json: &str) -> String {
+    match report_options(options_json) {
+        Some(options) => serde_json::to_string(&crate::utilities::generate_capability_report(&options))
+            .unwrap_or_default(),
+        None => String::new(),
+    }
+}
+
+/// Capability report as Markdown, for pasting into an issue
+/// options_json: same as gpu_capability_report
+#[deno_bindgen]
+pub fn gpu_capability_report_markdown(options_json: &str) -> String {
+    report_options(options_json)
+        .map(|options| crate::utilities::generate_capability_report(&options).to_markdown())
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// PCI DEVICE DATABASE
+// ============================================================================
+
+/// Look up a device in the PCI ID database
+/// Returns JSON PciDeviceRecord: {"vendor_id", "device_id", "name", "architecture",
+///   "cuda_compute_capability"?: "8.9", "gfx_target"?: "gfx1100", "subgroup_size", "memory_type"}
+/// or empty string if the device is unknown
+#[deno_bindgen]
+pub fn gpu_pci_lookup(vendor_id: u32, device_id: u32) -> String {
+    crate::gpu::lookup_pci_device(vendor_id, device_id)
+        .and_then(|record| serde_json::to_string(&record).ok())
+        .unwrap_or_default()
+}
+
+/// ROCm architecture of a device as the code the rocm_* helpers take
+/// (0=GCN, 1=RDNA, 2=RDNA2, 3=RDNA3, 4=CDNA, 5=CDNA2, 6=CDNA3, 7=Unknown)
+#[deno_bindgen]
+pub fn gpu_pci_rocm_architecture(vendor_id: u32, device_id: u32) -> u32 {
+    crate::gpu::lookup_pci_device(vendor_id, device_id)
+        .and_then(|record| record.rocm_architecture())
+        .unwrap_or(crate::gpu::vendors::ROCmArchitecture::Unknown) as u32
+}
+
+/// Add or replace PCI ID records from JSON ({"devices": [...]}, IDs as numbers or hex strings)
+/// Returns the number of records applied, 0 on error
+#[deno_bindgen]
+pub fn gpu_pci_database_update(json: &str) -> u32 {
+    pci_database_result(crate::gpu::update_pci_database(json))
+}
+
+/// Add or replace PCI ID records from a JSON file
+/// Returns the number of records applied, 0 on error
+#[deno_bindgen]
+pub fn gpu_pci_database_load(path: &str) -> u32 {
+    pci_database_result(crate::gpu::load_pci_database_file(std::path::Path::new(path)))
+}
+
+/// Discard runtime updates and restore the embedded PCI ID database
+#[deno_bindgen]
+pub fn gpu_pci_database_reset() {
+    crate::gpu::reset_pci_database();
+}
+
+fn pci_database_result(result: Result<usize, String>) -> u32 {
+    match result {
+        Ok(count) => count as u32,
+        Err(message) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "pci_database".to_string(),
+                message,
+            });
+            0
+        }
+    }
+}
+
 // ============================================================================
 // BUFFER POOL
 // ============================================================================
@@ -353,6 +516,184 @@ pub fn shader_cache_load_from_string(
     }
 }
 
+/// Load a GLSL 450 compute shader into a cache, translated to WGSL
+/// Returns JSON-serialized ShaderSource or empty string on error (see webgpu_x_get_last_error)
+#[deno_bindgen]
+pub fn shader_cache_load_glsl(cache_handle: u64, code: &str, options_json: &str) -> String {
+    let Some(options) = shader_options::<crate::shader::GlslImportOptions>(options_json) else {
+        return String::new();
+    };
+
+    match crate::shader::shader_cache_load_glsl(cache_handle, code.to_string(), &options) {
+        Ok(source) => serde_json::to_string(&source).unwrap_or_default(),
+        Err(e) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "glsl".to_string(),
+                message: e,
+            });
+            String::new()
+        }
+    }
+}
+
+/// Read the `#pragma feature` declarations of a shader
+/// Returns JSON-serialized VariantSpace or empty string on error (see webgpu_x_get_last_error)
+#[deno_bindgen]
+pub fn shader_variant_space(code: &str) -> String {
+    match crate::shader::variant_space(code) {
+        Ok(space) => serde_json::to_string(&space).unwrap_or_default(),
+        Err(e) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "variant".to_string(),
+                message: e.to_string(),
+            });
+            String::new()
+        }
+    }
+}
+
+/// Compile (or fetch from the cache) one shader variant
+/// key_json: {"features": {"WORKGROUP_SIZE": "128", "BIAS": "on"}, "overrides": {"alpha": 2.0}}
+/// Returns JSON-serialized CompiledVariant or empty string on error (see webgpu_x_get_last_error)
+#[deno_bindgen]
+pub fn shader_cache_load_variant(cache_handle: u64, code: &str, key_json: &str) -> String {
+    let key: crate::shader::VariantKey = match serde_json::from_str(key_json) {
+        Ok(key) => key,
+        Err(e) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "variant".to_string(),
+                message: format!("Invalid variant key: {}", e),
+            });
+            return String::new();
+        }
+    };
+
+    match crate::shader::shader_cache_load_variant(cache_handle, code, &key) {
+        Ok(variant) => serde_json::to_string(&variant).unwrap_or_default(),
+        Err(e) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "variant".to_string(),
+                message: e,
+            });
+            String::new()
+        }
+    }
+}
+
+/// List the cached variant keys of a shader
+/// Returns JSON array of canonical variant keys
+#[deno_bindgen]
+pub fn shader_cache_list_variants(cache_handle: u64, code: &str) -> String {
+    serde_json::to_string(&crate::shader::shader_cache_list_variants(cache_handle, code)).unwrap_or_default()
+}
+
+/// Compile the variants a workload will use ahead of time
+/// keys_json: JSON array of variant keys
+/// Returns JSON-serialized VariantPrewarmReport or empty string on error
+#[deno_bindgen]
+pub fn shader_cache_prewarm_variants(cache_handle: u64, code: &str, keys_json: &str) -> String {
+    let keys: Vec<crate::shader::VariantKey> = match serde_json::from_str(keys_json) {
+        Ok(keys) => keys,
+        Err(e) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "variant".to_string(),
+                message: format!("Invalid variant keys: {}", e),
+            });
+            return String::new();
+        }
+    };
+
+    match crate::shader::shader_cache_prewarm_variants(cache_handle, code, &keys) {
+        Ok(report) => serde_json::to_string(&report).unwrap_or_default(),
+        Err(e) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "variant".to_string(),
+                message: e,
+            });
+            String::new()
+        }
+    }
+}
+
+/// Add an `#include` search directory to a shader cache
+/// Returns: 1 on success, 0 if the handle is invalid
+#[deno_bindgen]
+pub fn shader_cache_add_include_path(cache_handle: u64, path: &str) -> u8 {
+    match crate::shader::shader_cache_add_include_path(cache_handle, path.to_string()) {
+        Ok(()) => 1,
+        Err(_) => 0,
+    }
+}
+
+/// Define a preprocessor macro for all shaders loaded by a cache
+/// Returns: 1 on success, 0 if the handle is invalid
+#[deno_bindgen]
+pub fn shader_cache_define(cache_handle: u64, name: &str, value: &str) -> u8 {
+    match crate::shader::shader_cache_define(cache_handle, name.to_string(), value.to_string()) {
+        Ok(()) => 1,
+        Err(_) => 0,
+    }
+}
+
+/// Invalidate a file and every cached shader that includes it
+/// Returns JSON array of removed shader paths
+#[deno_bindgen]
+pub fn shader_cache_invalidate(cache_handle: u64, path: &str) -> String {
+    let removed = crate::shader::shader_cache_invalidate(cache_handle, path.to_string());
+    serde_json::to_string(&removed).unwrap_or_default()
+}
+
+/// Create a file watcher that hot-reloads shaders of a cache
+/// Returns watcher handle; changes are debounced by `debounce_ms`
+#[deno_bindgen]
+pub fn shader_watcher_create(cache_handle: u64, debounce_ms: u32) -> u64 {
+    crate::shader::shader_watcher_create(cache_handle, debounce_ms as u64)
+}
+
+/// Watch a shader directory (recursive: 1 to include subdirectories)
+/// Returns: 1 on success, 0 on error (see webgpu_x_get_last_error)
+#[deno_bindgen]
+pub fn shader_watcher_watch(watcher_handle: u64, path: &str, recursive: u8) -> u8 {
+    match crate::shader::shader_watcher_watch(watcher_handle, path.to_string(), recursive != 0) {
+        Ok(()) => 1,
+        Err(e) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "path".to_string(),
+                message: e,
+            });
+            0
+        }
+    }
+}
+
+/// Stop watching a shader directory
+#[deno_bindgen]
+pub fn shader_watcher_unwatch(watcher_handle: u64, path: &str) {
+    crate::shader::shader_watcher_unwatch(watcher_handle, path.to_string());
+}
+
+/// Process pending file changes, re-validating affected shaders
+/// Returns JSON array of ShaderChangeEvent or empty string on error
+#[deno_bindgen]
+pub fn shader_watcher_poll(watcher_handle: u64) -> String {
+    match crate::shader::shader_watcher_poll(watcher_handle) {
+        Ok(events) => serde_json::to_string(&events).unwrap_or_default(),
+        Err(e) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "watcher".to_string(),
+                message: e,
+            });
+            String::new()
+        }
+    }
+}
+
+/// Destroy shader watcher
+#[deno_bindgen]
+pub fn shader_watcher_destroy(watcher_handle: u64) {
+    crate::shader::shader_watcher_destroy(watcher_handle);
+}
+
 // ============================================================================
 // WGSL CODE GENERATION
 // ============================================================================
@@ -392,6 +733,73 @@ pub fn wgsl_struct(name: &str, fields_json: &str) -> String {
     }
 }
 
+/// Compute memory layout of a WGSL type and check address space constraints
+/// `declarations` holds struct definitions (may be empty); `type_name` is a struct name or type expression
+/// address_space: 0=uniform, 1=storage
+/// Returns JSON-serialized LayoutReport or empty string on error
+#[deno_bindgen]
+pub fn wgsl_layout(declarations: &str, type_name: &str, address_space: u32) -> String {
+    use crate::shader::BufferAddressSpace;
+    let space = match address_space {
+        0 => BufferAddressSpace::Uniform,
+        _ => BufferAddressSpace::Storage,
+    };
+
+    match crate::shader::wgsl_layout_report(declarations, type_name, space) {
+        Ok(report) => serde_json::to_string(&report).unwrap_or_default(),
+        Err(e) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "type_name".to_string(),
+                message: e.to_string(),
+            });
+            String::new()
+        }
+    }
+}
+
+/// Pack a JSON value into padded buffer bytes for a WGSL type
+/// Returns JSON array of bytes or empty string on error
+#[deno_bindgen]
+pub fn wgsl_pack(declarations: &str, type_name: &str, value_json: &str) -> String {
+    let result = serde_json::from_str::<serde_json::Value>(value_json)
+        .map_err(|e| e.to_string())
+        .and_then(|value| {
+            crate::shader::wgsl_type_layout(declarations, type_name)
+                .and_then(|layout| crate::shader::pack_wgsl_value(&layout, &value))
+                .map_err(|e| e.to_string())
+        });
+
+    match result {
+        Ok(bytes) => serde_json::to_string(&bytes).unwrap_or_default(),
+        Err(message) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "value".to_string(),
+                message,
+            });
+            String::new()
+        }
+    }
+}
+
+/// Read buffer bytes back into JSON for a WGSL type
+/// Returns JSON value or empty string on error
+#[deno_bindgen]
+pub fn wgsl_unpack(declarations: &str, type_name: &str, data: &[u8]) -> String {
+    let result = crate::shader::wgsl_type_layout(declarations, type_name)
+        .and_then(|layout| crate::shader::unpack_wgsl_value(&layout, data));
+
+    match result {
+        Ok(value) => serde_json::to_string(&value).unwrap_or_default(),
+        Err(e) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "data".to_string(),
+                message: e.to_string(),
+            });
+            String::new()
+        }
+    }
+}
+
 /// Generate WGSL struct field
 #[deno_bindgen]
 pub fn wgsl_struct_field(name: &str, type_name: &str) -> String {
@@ -458,12 +866,90 @@ pub fn wgsl_function(name: &str, params_json: &str, return_type: &str, body: &st
     crate::shader::wgsl_function(name.to_string(), params, return_type.to_string(), body.to_string())
 }
 
-/// Minify WGSL shader code (remove comments and excess whitespace)
+/// Parse a shader options object, recording the error on failure; empty input gives the defaults
+fn shader_options<T: serde::de::DeserializeOwned + Default>(options_json: &str) -> Option<T> {
+    if options_json.trim().is_empty() {
+        return Some(T::default());
+    }
+    match serde_json::from_str(options_json) {
+        Ok(options) => Some(options),
+        Err(e) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "options".to_string(),
+                message: format!("Invalid options: {}", e),
+            });
+            None
+        }
+    }
+}
+
+/// Minify WGSL shader code (renames locals, drops unused declarations, folds constants;
+/// source that does not validate only loses comments and whitespace)
 #[deno_bindgen]
 pub fn wgsl_minify(shader_code: &str) -> String {
     crate::shader::wgsl_minify(shader_code.to_string())
 }
 
+/// Minify WGSL shader code and produce a Source Map v3 back to the input
+/// Returns JSON-serialized MinifiedShader or empty string on error
+#[deno_bindgen]
+pub fn wgsl_minify_with_source_map(shader_code: &str, options_json: &str) -> String {
+    let Some(options) = shader_options::<crate::shader::MinifyOptions>(options_json) else {
+        return String::new();
+    };
+
+    match crate::shader::minify_wgsl(shader_code, &options) {
+        Ok(minified) => serde_json::to_string(&minified).unwrap_or_default(),
+        Err(e) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "wgsl".to_string(),
+                message: e.to_string(),
+            });
+            String::new()
+        }
+    }
+}
+
+/// Format WGSL in the canonical style (indent width, attribute placement, line width)
+/// Returns formatted code or empty string on error (see webgpu_x_get_last_error)
+#[deno_bindgen]
+pub fn wgsl_format(shader_code: &str, options_json: &str) -> String {
+    let Some(options) = shader_options::<crate::shader::FormatOptions>(options_json) else {
+        return String::new();
+    };
+
+    match crate::shader::format_wgsl(shader_code, &options) {
+        Ok(formatted) => formatted,
+        Err(e) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "wgsl".to_string(),
+                message: e.to_string(),
+            });
+            String::new()
+        }
+    }
+}
+
+/// Translate a GLSL compute shader to WGSL (bindings, unsupported constructs, reflection)
+/// Returns JSON-serialized GlslImport or empty string on parse error
+#[deno_bindgen]
+pub fn glsl_import(code: &str, options_json: &str) -> String {
+    let Some(options) = shader_options::<crate::shader::GlslImportOptions>(options_json) else {
+        return String::new();
+    };
+
+    match crate::shader::import_glsl_compute(code, &options) {
+        Ok(import) => serde_json::to_string(&import).unwrap_or_default(),
+        Err(e) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "glsl".to_string(),
+                message: e.to_string(),
+            });
+            String::new()
+        }
+    }
+}
+
 /// Count lines in shader code
 #[deno_bindgen]
 pub fn wgsl_line_count(shader_code: &str) -> u32 {
@@ -478,6 +964,110 @@ pub fn wgsl_extract_functions(shader_code: &str) -> String {
     serde_json::to_string(&functions).unwrap_or_default()
 }
 
+/// Parse and validate WGSL shader code with naga
+/// Returns JSON-serialized WgslValidationReport with line/column diagnostics
+#[deno_bindgen]
+pub fn wgsl_validate(shader_code: &str) -> String {
+    let report = crate::shader::wgsl_validation_report(shader_code);
+    serde_json::to_string(&report).unwrap_or_default()
+}
+
+/// Reflect entry points, bindings, struct layouts, and overrides of a WGSL shader
+/// Returns JSON-serialized ShaderReflection or empty string on error
+/// (the rendered error is available via webgpu_x_get_last_error)
+#[deno_bindgen]
+pub fn wgsl_reflect(shader_code: &str) -> String {
+    match crate::shader::reflect_wgsl(shader_code) {
+        Ok(reflection) => serde_json::to_string(&reflection).unwrap_or_default(),
+        Err(e) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "wgsl".to_string(),
+                message: e.to_string(),
+            });
+            String::new()
+        }
+    }
+}
+
+/// Cross-compile WGSL to SPIR-V, GLSL (ES 3.10 / 4.50), HLSL, or MSL
+/// Options: {"target": "spir_v" | "glsl_es310" | "glsl450" | "hlsl" | "msl", "entry_point": ..., "overrides": {...}, ...}
+/// Returns JSON-serialized CrossCompiledShader or empty string on error
+#[deno_bindgen]
+pub fn wgsl_cross_compile(shader_code: &str, options_json: &str) -> String {
+    let Some(options) = shader_options::<crate::shader::CrossCompileOptions>(options_json) else {
+        return String::new();
+    };
+
+    match crate::shader::cross_compile_wgsl(shader_code, &options) {
+        Ok(compiled) => serde_json::to_string(&compiled).unwrap_or_default(),
+        Err(e) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "wgsl".to_string(),
+                message: e.to_string(),
+            });
+            String::new()
+        }
+    }
+}
+
+/// Cross-compile WGSL and write the result to a file (binary for SPIR-V)
+/// Returns: 1 on success, 0 on error (see webgpu_x_get_last_error)
+#[deno_bindgen]
+pub fn wgsl_cross_compile_to_file(shader_code: &str, options_json: &str, output_path: &str) -> u8 {
+    let Some(options) = shader_options::<crate::shader::CrossCompileOptions>(options_json) else {
+        return 0;
+    };
+
+    let result = crate::shader::cross_compile_wgsl(shader_code, &options)
+        .map_err(|e| e.to_string())
+        .and_then(|compiled| compiled.save(output_path));
+
+    match result {
+        Ok(()) => 1,
+        Err(message) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "wgsl".to_string(),
+                message,
+            });
+            0
+        }
+    }
+}
+
+/// Expand #include/#define/#ifdef/#import directives in WGSL shader code
+/// Options: {"include_paths": [...], "defines": {"NAME": "value"}, "files": {"name.wgsl": "..."}}
+/// Returns JSON-serialized PreprocessedShader (code, line_map, dependencies, linked) or empty string on error
+#[deno_bindgen]
+pub fn wgsl_preprocess(shader_code: &str, options_json: &str) -> String {
+    let Some(options) = shader_options::<crate::shader::PreprocessOptions>(options_json) else {
+        return String::new();
+    };
+
+    match crate::shader::WgslPreprocessor::from_options(&options).process_str(shader_code, "<input>") {
+        Ok(preprocessed) => serde_json::to_string(&preprocessed).unwrap_or_default(),
+        Err(e) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "wgsl".to_string(),
+                message: e.to_string(),
+            });
+            String::new()
+        }
+    }
+}
+
+/// List the WGSL standard library modules available to `#import std::...`
+/// Returns JSON array of StdModuleInfo (name, version, exports)
+#[deno_bindgen]
+pub fn wgsl_stdlib_modules() -> String {
+    serde_json::to_string(&crate::shader::stdlib_modules()).unwrap_or_default()
+}
+
+/// WGSL source of one standard library module, or empty string if unknown
+#[deno_bindgen]
+pub fn wgsl_stdlib_source(module: &str) -> String {
+    crate::shader::stdlib_source(module).unwrap_or_default().to_string()
+}
+
 // ============================================================================
 // COMPUTE KERNEL TEMPLATES
 // ============================================================================
@@ -522,6 +1112,446 @@ pub fn kernel_generate_from_template(
     crate::compute::generate_kernel(op, (workgroup_x, workgroup_y, workgroup_z))
 }
 
+/// Compile an elementwise expression such as "a * b + sin(c)" into a compute kernel
+/// options_json: {"inputs": [{"name": "a", "dtype": 0}], "uniforms": [...], "output_dtype": null,
+///                "workgroup_size": 64, "entry_point": "main"} (dtype uses TensorDType values)
+/// Returns JSON-serialized ExpressionKernel or empty string on error (see webgpu_x_get_last_error)
+#[deno_bindgen]
+pub fn kernel_compile_expression(expression: &str, options_json: &str) -> String {
+    let Some(options) = shader_options::<crate::compute::ExpressionKernelOptions>(options_json) else {
+        return String::new();
+    };
+
+    match crate::compute::compile_expression(expression, &options) {
+        Ok(kernel) => serde_json::to_string(&kernel).unwrap_or_default(),
+        Err(e) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "expression".to_string(),
+                message: e.to_string(),
+            });
+            String::new()
+        }
+    }
+}
+
+// ============================================================================
+// DESCRIPTOR VALIDATION
+// ============================================================================
+
+/// Resolve a limits profile by name ("" = webgpu-default), recording an error if unknown
+fn resolve_limits_profile(name: &str) -> Option<crate::gpu::profiles::LimitsProfile> {
+    let profile = crate::gpu::profiles::get_limits_profile(name);
+    if profile.is_none() {
+        crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+            field: "profile".to_string(),
+            message: format!("Unknown limits profile '{}'", name),
+        });
+    }
+    profile
+}
+
+/// Validate a WebGPU descriptor and report every problem found
+/// kind: buffer, texture, sampler, bind_group_layout, bind_group, pipeline_layout,
+///       render_pipeline, compute_pipeline, render_pass, query_set
+/// profile: limits profile name ("" = webgpu-default, see limits_profile_list)
+/// Returns JSON DescriptorValidationResult: {"valid": bool, "diagnostics": [
+///   {"path": "/size/width", "code": "zero-value", "severity": "error" | "warning",
+///    "message": "...", "spec_url": "https://www.w3.org/TR/webgpu/#..."}]}
+/// or empty string if the profile is unknown
+#[deno_bindgen]
+pub fn descriptor_validate(kind: &str, descriptor_json: &str, profile: &str) -> String {
+    let Some(profile) = resolve_limits_profile(profile) else {
+        return String::new();
+    };
+    let result = crate::descriptors::validate_descriptor(kind, descriptor_json.to_string(), &profile);
+    serde_json::to_string(&result).unwrap_or_default()
+}
+
+/// Check related descriptors against each other and their shaders
+/// set_json: {"shaders": {"1": "<wgsl>"}, "buffers": {...}, "samplers": {...},
+///            "textureViews": {...}, "bindGroupLayouts": {...}, "pipelineLayouts": {...},
+///            "bindGroups": {...}, "pipelines": {...}} (each map keyed by handle)
+/// profile: limits profile name ("" = webgpu-default)
+/// Returns JSON DescriptorValidationResult with paths into the set; cross-object
+/// diagnostics add "related_path" and "shader_location" ({module, name, line, column})
+#[deno_bindgen]
+pub fn descriptor_validate_related(set_json: &str, profile: &str) -> String {
+    let Some(profile) = resolve_limits_profile(profile) else {
+        return String::new();
+    };
+    match serde_json::from_str::<crate::descriptors::RelatedDescriptors>(set_json) {
+        Ok(set) => serde_json::to_string(&crate::descriptors::validate_related_descriptors(&set, &profile))
+            .unwrap_or_default(),
+        Err(e) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "set".to_string(),
+                message: e.to_string(),
+            });
+            String::new()
+        }
+    }
+}
+
+/// Check a WGSL compute kernel's workgroup size, workgroup memory and resource
+/// counts against a limits profile ("" = webgpu-default)
+/// Returns JSON DescriptorValidationResult with paths like "/entryPoints/main"
+#[deno_bindgen]
+pub fn kernel_validate_limits(source: &str, profile: &str) -> String {
+    let Some(profile) = resolve_limits_profile(profile) else {
+        return String::new();
+    };
+    serde_json::to_string(&crate::compute::validate_kernel_limits(source, &profile)).unwrap_or_default()
+}
+
+// ============================================================================
+// LIMITS PROFILES
+// ============================================================================
+
+/// Names of all limits profiles: built-in (webgpu-default, compatibility, webgl2,
+/// adapter) followed by registered ones. "adapter" only resolves when a GPU is present
+/// Returns JSON array of names
+#[deno_bindgen]
+pub fn limits_profile_list() -> String {
+    serde_json::to_string(&crate::gpu::profiles::list_limits_profiles()).unwrap_or_default()
+}
+
+/// Get a limits profile by name ("" = webgpu-default)
+/// Returns JSON {"name", "description", "limits": {"maxTextureDimension2D": 8192, ...}}
+/// or empty string if unknown
+#[deno_bindgen]
+pub fn limits_profile_get(name: &str) -> String {
+    match resolve_limits_profile(name) {
+        Some(profile) => serde_json::to_string(&profile).unwrap_or_default(),
+        None => String::new(),
+    }
+}
+
+/// Register a user-defined limits profile
+/// profile_json: {"name": "my-target", "description": "...", "extends": "compatibility",
+///                "limits": {"maxTextureDimension2D": 2048}} (unset limits come from "extends")
+/// Returns the resolved profile as JSON, or empty string on error
+#[deno_bindgen]
+pub fn limits_profile_register(profile_json: &str) -> String {
+    match crate::gpu::profiles::register_limits_profile(profile_json) {
+        Ok(profile) => serde_json::to_string(&profile).unwrap_or_default(),
+        Err(message) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "profile".to_string(),
+                message,
+            });
+            String::new()
+        }
+    }
+}
+
+/// Remove a registered limits profile. Returns 1 if it existed
+#[deno_bindgen]
+pub fn limits_profile_unregister(name: &str) -> u8 {
+    crate::gpu::profiles::unregister_limits_profile(name) as u8
+}
+
+/// Compare two limits profiles
+/// Returns JSON LimitsDiff: {"from", "to", "portable": bool, "differences": [
+///   {"limit": "maxTextureDimension2D", "from": 8192, "to": 4096, "tighter": true}]}
+/// "portable" is true when nothing valid under "from" can exceed "to"
+#[deno_bindgen]
+pub fn limits_profile_diff(from: &str, to: &str) -> String {
+    let (Some(from), Some(to)) = (resolve_limits_profile(from), resolve_limits_profile(to)) else {
+        return String::new();
+    };
+    serde_json::to_string(&crate::gpu::profiles::diff_limits_profiles(&from, &to)).unwrap_or_default()
+}
+
+// ============================================================================
+// PIPELINE CACHE
+// ============================================================================
+
+/// Canonical form of a render or compute pipeline descriptor (defaults filled, keys sorted)
+/// Returns canonical JSON or empty string on error
+#[deno_bindgen]
+pub fn pipeline_descriptor_canonicalize(descriptor_json: &str) -> String {
+    match crate::pipeline::canonical_descriptor_json(descriptor_json) {
+        Ok(canonical) => canonical,
+        Err(message) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "descriptor".to_string(),
+                message,
+            });
+            String::new()
+        }
+    }
+}
+
+/// Configure the pipeline cache
+/// config_json: {"render_capacity": 256, "compute_capacity": 256, "policy": "lru" | "lfu",
+///               "ttl_ms": 600000, "record_evictions": true} (0 capacity = unbounded)
+/// Returns 1 on success, 0 on invalid JSON
+#[deno_bindgen]
+pub fn pipeline_cache_configure(config_json: &str) -> u8 {
+    match serde_json::from_str(config_json) {
+        Ok(config) => {
+            crate::pipeline::pipeline_cache_configure(config);
+            1
+        }
+        Err(_) => 0,
+    }
+}
+
+/// Pipelines evicted since the last call, so their GPU objects can be released
+/// Returns JSON array of EvictedPipeline (requires "record_evictions" in the config)
+#[deno_bindgen]
+pub fn pipeline_cache_drain_evictions() -> String {
+    serde_json::to_string(&crate::pipeline::pipeline_cache_drain_evictions()).unwrap_or_default()
+}
+
+/// Stable hash of a pipeline descriptor's canonical form, usable as a persistent cache key
+#[deno_bindgen]
+pub fn pipeline_descriptor_hash(descriptor_json: &str) -> u64 {
+    crate::pipeline::hash_descriptor(descriptor_json.to_string())
+}
+
+fn set_persist_error(field: &str, message: String) {
+    crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+        field: field.to_string(),
+        message,
+    });
+}
+
+/// Open the on-disk pipeline/shader cache for an adapter
+/// identity_json: {"adapter": "...", "driver": "..."} (crate version is filled in)
+/// Returns JSON {"handle": u64, "report": PersistLoadReport} or empty string on error
+#[deno_bindgen]
+pub fn pipeline_persist_open(cache_dir: &str, identity_json: &str) -> String {
+    let identity = match serde_json::from_str(identity_json) {
+        Ok(identity) => identity,
+        Err(e) => {
+            set_persist_error("identity", e.to_string());
+            return String::new();
+        }
+    };
+    match crate::pipeline::pipeline_persist_open(cache_dir, identity) {
+        Ok(result) => serde_json::to_string(&result).unwrap_or_default(),
+        Err(message) => {
+            set_persist_error("cache_dir", message);
+            String::new()
+        }
+    }
+}
+
+/// Record a validated shader (ShaderSource JSON as returned by shader_cache_load*)
+/// Returns the shader's code hash, or 0 if it does not validate
+#[deno_bindgen]
+pub fn pipeline_persist_record_shader(handle: u64, source_json: &str) -> u64 {
+    let source = match serde_json::from_str(source_json) {
+        Ok(source) => source,
+        Err(e) => {
+            set_persist_error("source", e.to_string());
+            return 0;
+        }
+    };
+    let snapshot = crate::shader::ShaderSnapshot::from_source(source);
+    match crate::pipeline::with_persistent_cache(handle, |cache| cache.record_shader(snapshot)) {
+        Ok(Ok(hash)) => hash,
+        Ok(Err(message)) | Err(message) => {
+            set_persist_error("source", message);
+            0
+        }
+    }
+}
+
+/// Record every file-backed shader loaded in a shader cache
+/// Returns the number of shaders recorded
+#[deno_bindgen]
+pub fn pipeline_persist_record_shader_cache(handle: u64, shader_cache_handle: u64) -> u32 {
+    crate::pipeline::with_persistent_cache(handle, |cache| {
+        crate::shader::compilation::with_shader_cache(shader_cache_handle, |shaders| {
+            cache.record_shader_cache(shaders)
+        })
+    })
+    .and_then(|count| count)
+    .unwrap_or(0) as u32
+}
+
+/// Restore recorded shaders into a shader cache, skipping files changed since they were recorded
+/// Returns the number of shaders restored
+#[deno_bindgen]
+pub fn pipeline_persist_restore_shader_cache(handle: u64, shader_cache_handle: u64) -> u32 {
+    crate::pipeline::with_persistent_cache(handle, |cache| {
+        crate::shader::compilation::with_shader_cache(shader_cache_handle, |shaders| {
+            cache.restore_shader_cache(shaders)
+        })
+    })
+    .and_then(|count| count)
+    .unwrap_or(0) as u32
+}
+
+/// Record a pipeline descriptor for warm-up
+/// shader_hashes_json: JSON array of code hashes from pipeline_persist_record_shader
+/// Returns the descriptor hash, or 0 on error
+#[deno_bindgen]
+pub fn pipeline_persist_record_pipeline(handle: u64, descriptor_json: &str, shader_hashes_json: &str) -> u64 {
+    let shaders: Vec<u64> = match serde_json::from_str(shader_hashes_json) {
+        Ok(shaders) => shaders,
+        Err(e) => {
+            set_persist_error("shader_hashes", e.to_string());
+            return 0;
+        }
+    };
+    match crate::pipeline::with_persistent_cache(handle, |cache| cache.record_pipeline(descriptor_json, &shaders)) {
+        Ok(Ok(hash)) => hash,
+        Ok(Err(message)) | Err(message) => {
+            set_persist_error("descriptor", message);
+            0
+        }
+    }
+}
+
+/// Store backend pipeline cache data for a recorded pipeline
+/// Returns 1 on success, 0 on error
+#[deno_bindgen]
+pub fn pipeline_persist_store_blob(handle: u64, pipeline_hash: u64, data: &[u8]) -> u8 {
+    match crate::pipeline::with_persistent_cache(handle, |cache| cache.store_blob(pipeline_hash, data)) {
+        Ok(Ok(())) => 1,
+        Ok(Err(message)) | Err(message) => {
+            set_persist_error("blob", message);
+            0
+        }
+    }
+}
+
+/// Size of the stored blob for a pipeline (0 if none or corrupt)
+#[deno_bindgen]
+pub fn pipeline_persist_blob_size(handle: u64, pipeline_hash: u64) -> u64 {
+    crate::pipeline::with_persistent_cache(handle, |cache| cache.blob(pipeline_hash))
+        .ok()
+        .flatten()
+        .map_or(0, |blob| blob.len() as u64)
+}
+
+/// Copy the stored blob for a pipeline into `out` (sized with pipeline_persist_blob_size)
+/// Returns 1 on success, 0 if missing, corrupt or `out` has the wrong size
+#[deno_bindgen]
+pub fn pipeline_persist_read_blob(handle: u64, pipeline_hash: u64, out: &mut [u8]) -> u8 {
+    match crate::pipeline::with_persistent_cache(handle, |cache| cache.blob(pipeline_hash)) {
+        Ok(Some(blob)) if blob.len() == out.len() => {
+            out.copy_from_slice(&blob);
+            1
+        }
+        _ => 0,
+    }
+}
+
+/// Copy hit counts from the pipeline cache and write everything to disk
+/// Returns 1 on success, 0 on error
+#[deno_bindgen]
+pub fn pipeline_persist_save(handle: u64) -> u8 {
+    let result = crate::pipeline::with_persistent_cache(handle, |cache| {
+        cache.sync_hit_counts();
+        cache.save()
+    });
+    match result {
+        Ok(Ok(())) => 1,
+        Ok(Err(message)) | Err(message) => {
+            set_persist_error("cache_dir", message);
+            0
+        }
+    }
+}
+
+/// Save and close a persistent cache
+/// Returns 1 on success, 0 on error
+#[deno_bindgen]
+pub fn pipeline_persist_close(handle: u64) -> u8 {
+    match crate::pipeline::pipeline_persist_close(handle) {
+        Ok(()) => 1,
+        Err(message) => {
+            set_persist_error("cache_dir", message);
+            0
+        }
+    }
+}
+
+/// Start validating recorded pipelines on a background thread, most used first
+/// Returns a warm-up handle, or 0 on error (see webgpu_x_get_last_error)
+#[deno_bindgen]
+pub fn pipeline_persist_start_warmup(handle: u64) -> u64 {
+    crate::pipeline::pipeline_persist_start_warmup(handle).unwrap_or_else(|e| {
+        set_persist_error("handle", e);
+        0
+    })
+}
+
+/// Pipelines ready to create since the last poll
+/// Returns JSON WarmupProgress {"ready": [...], "failed": [...], "finished": bool}
+#[deno_bindgen]
+pub fn pipeline_persist_poll_warmup(warmup_handle: u64) -> String {
+    match crate::pipeline::pipeline_persist_poll_warmup(warmup_handle) {
+        Ok(progress) => serde_json::to_string(&progress).unwrap_or_default(),
+        Err(e) => {
+            set_persist_error("warmup_handle", e);
+            String::new()
+        }
+    }
+}
+
+/// Queue a pipeline for background compilation
+/// request_json: {"descriptor": {...}, "shaders": ["<wgsl>", ...],
+///                "priority": "low" | "normal" | "high" | "critical", "fallback_handle": 12}
+/// Returns a ticket (identical descriptors share one), or 0 on error
+#[deno_bindgen]
+pub fn pipeline_compile_submit(request_json: &str) -> u64 {
+    let result = serde_json::from_str(request_json)
+        .map_err(|e| e.to_string())
+        .and_then(crate::pipeline::pipeline_compile_submit);
+    match result {
+        Ok(ticket) => ticket,
+        Err(message) => {
+            set_persist_error("request", message);
+            0
+        }
+    }
+}
+
+/// State of a compile ticket
+/// Returns JSON TicketInfo; "pipeline_handle" is the fallback until the real pipeline is complete
+#[deno_bindgen]
+pub fn pipeline_compile_poll(ticket: u64) -> String {
+    crate::pipeline::pipeline_compile_poll(ticket)
+        .and_then(|info| serde_json::to_string(&info).ok())
+        .unwrap_or_default()
+}
+
+/// Report the pipeline created for a ready ticket (also caches it)
+/// Returns 1 on success, 0 if the ticket is not ready
+#[deno_bindgen]
+pub fn pipeline_compile_complete(ticket: u64, pipeline_handle: u64) -> u8 {
+    match crate::pipeline::pipeline_compile_complete(ticket, pipeline_handle) {
+        Ok(()) => 1,
+        Err(message) => {
+            set_persist_error("ticket", message);
+            0
+        }
+    }
+}
+
+/// Cancel a ticket that has not started compiling
+#[deno_bindgen]
+pub fn pipeline_compile_cancel(ticket: u64) -> u8 {
+    crate::pipeline::pipeline_compile_cancel(ticket) as u8
+}
+
+/// Forget a ticket
+#[deno_bindgen]
+pub fn pipeline_compile_release(ticket: u64) -> u8 {
+    crate::pipeline::pipeline_compile_release(ticket) as u8
+}
+
+/// Compilation queue statistics as JSON
+#[deno_bindgen]
+pub fn pipeline_compile_stats() -> String {
+    serde_json::to_string(&crate::pipeline::pipeline_compile_stats()).unwrap_or_default()
+}
+
 // ============================================================================
 // Tensor Operations
 // ============================================================================
@@ -758,6 +1788,76 @@ pub fn tensor_get_strides(tensor_json: &str) -> String {
     serde_json::to_string(&tensor.stride).unwrap_or_default()
 }
 
+/// Plan offsets for a fixed set of tensors inside one shared arena
+///
+/// # Arguments
+/// * `tensors_json` - JSON array of live ranges
+///   (e.g., `[{"dimensions":[64],"dtype":0,"access":2,"first_use":0,"last_use":3}]`)
+/// * `arena_handle` - GPU buffer handle of the arena, stored in every planned tensor
+/// * `strategy` - Placement strategy (0=GreedyBySize, 1=Sequential)
+/// * `storage_alignment` - Device `minStorageBufferOffsetAlignment`
+/// * `uniform_alignment` - Device `minUniformBufferOffsetAlignment`
+///
+/// # Returns
+/// JSON string containing the memory plan or empty string on error
+#[deno_bindgen]
+pub fn tensor_plan_memory(
+    tensors_json: &str,
+    arena_handle: u64,
+    strategy: u32,
+    storage_alignment: u32,
+    uniform_alignment: u32,
+) -> String {
+    use crate::tensor::{PlannerStrategy, TensorLiveRange, TensorMemoryPlanner};
+
+    let tensors: Vec<TensorLiveRange> = match serde_json::from_str(tensors_json) {
+        Ok(t) => t,
+        Err(_) => return String::new(),
+    };
+
+    let strategy = match strategy {
+        0 => PlannerStrategy::GreedyBySize,
+        1 => PlannerStrategy::Sequential,
+        _ => return String::new(),
+    };
+
+    let planner = TensorMemoryPlanner::new(storage_alignment, uniform_alignment);
+    match planner.plan(&tensors, strategy, arena_handle) {
+        Ok(plan) => serde_json::to_string(&plan).unwrap_or_default(),
+        Err(_) => String::new(),
+    }
+}
+
+/// Plan an einsum contraction and generate its kernel
+///
+/// # Arguments
+/// * `spec` - Einsum spec (e.g., "bij,bjk->bik")
+/// * `shapes_json` - JSON array of operand dimensions (e.g., "[[2, 3, 4], [2, 4, 5]]")
+///
+/// # Returns
+/// JSON string containing the einsum plan (WGSL, bindings, dispatch) or empty string on error
+#[deno_bindgen]
+pub fn tensor_einsum_plan(spec: &str, shapes_json: &str) -> String {
+    use crate::tensor::TensorShape;
+
+    let shapes: Vec<Vec<u32>> = match serde_json::from_str(shapes_json) {
+        Ok(shapes) => shapes,
+        Err(_) => return String::new(),
+    };
+    let shapes: Vec<TensorShape> = shapes.into_iter().map(TensorShape::new).collect();
+
+    match crate::tensor::plan_einsum(spec, &shapes) {
+        Ok(plan) => serde_json::to_string(&plan).unwrap_or_default(),
+        Err(message) => {
+            crate::error::set_last_error(&crate::error::WebGPUXError::ValidationError {
+                field: "einsum".to_string(),
+                message,
+            });
+            String::new()
+        }
+    }
+}
+
 // ============================================================================
 // Framework Helpers - Matrix Operations and Device Configuration
 // ============================================================================
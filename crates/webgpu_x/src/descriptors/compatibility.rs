@@ -0,0 +1,1481 @@
+//! Cross-object descriptor validation
+//!
+//! The per-descriptor validators in `validator` check each object alone. This module
+//! takes a set of related objects (shaders, buffers, samplers, texture views, layouts,
+//! bind groups and pipelines, keyed by handle) and checks that they fit together:
+//! - bind groups against their bind group layout (bindings, resource kinds, buffer
+//!   usage, size and alignment, sampler types, view dimensions)
+//! - explicit pipeline layouts against the bindings each entry point actually uses
+//! - vertex buffer layouts against vertex shader inputs
+//! - color targets against fragment shader outputs
+//!
+//! Paths are JSON pointers into the set (e.g. `/bindGroups/3/entries/0/resource`), and
+//! mismatches with a shader carry the line and column of the WGSL declaration.
+
+use super::validator::{
+    json_pointer, json_pointer_index, spec_url, validate_descriptor, vertex_format_size, BindingUsage,
+    DescriptorDiagnostic, DescriptorValidationResult, Severity,
+};
+use crate::gpu::profiles::LimitsProfile;
+use crate::pipeline::canonical::{descriptor_kind, DescriptorKind};
+use crate::shader::reflection::{diagnostic_for_span, validate_wgsl, ValidatedShader};
+use naga::proc::Layouter;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+// GPUBufferUsage
+const BUFFER_USAGE_UNIFORM: u64 = 0x0040;
+const BUFFER_USAGE_STORAGE: u64 = 0x0080;
+
+// GPUShaderStage
+const SHADER_STAGE_VERTEX: u64 = 0x1;
+const SHADER_STAGE_FRAGMENT: u64 = 0x2;
+const SHADER_STAGE_COMPUTE: u64 = 0x4;
+
+/// A set of related descriptors, each map keyed by the handle other descriptors use
+///
+/// References are matched by value: `"layout": 3` and `"layout": "3"` both name key "3".
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RelatedDescriptors {
+    /// WGSL source per shader module handle
+    pub shaders: BTreeMap<String, String>,
+    pub buffers: BTreeMap<String, Value>,
+    pub samplers: BTreeMap<String, Value>,
+    pub texture_views: BTreeMap<String, Value>,
+    pub bind_group_layouts: BTreeMap<String, Value>,
+    pub pipeline_layouts: BTreeMap<String, Value>,
+    pub bind_groups: BTreeMap<String, Value>,
+    /// Render and compute pipelines, told apart by their `vertex`/`compute` key
+    pub pipelines: BTreeMap<String, Value>,
+}
+
+/// Location of a declaration in a shader module
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShaderSourceLocation {
+    /// Shader module handle
+    pub module: String,
+    /// Variable, entry point or struct name
+    pub name: String,
+    /// 1-based line (0 when unknown)
+    pub line: u32,
+    /// 1-based column (0 when unknown)
+    pub column: u32,
+}
+
+/// Key used to look up a referenced object
+fn handle_key(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Resource kind of a bind group layout entry
+#[derive(Debug, Clone)]
+enum LayoutResource {
+    Buffer {
+        ty: String,
+        min_binding_size: u64,
+    },
+    Sampler {
+        ty: String,
+    },
+    Texture {
+        sample_type: String,
+        view_dimension: String,
+        multisampled: bool,
+    },
+    StorageTexture {
+        access: String,
+        format: String,
+        view_dimension: String,
+    },
+    ExternalTexture,
+    Unknown,
+}
+
+impl LayoutResource {
+    fn describe(&self) -> String {
+        match self {
+            LayoutResource::Buffer { ty, .. } => format!("{} buffer", ty),
+            LayoutResource::Sampler { ty } => format!("{} sampler", ty),
+            LayoutResource::Texture { .. } => "texture".to_string(),
+            LayoutResource::StorageTexture { .. } => "storage texture".to_string(),
+            LayoutResource::ExternalTexture => "external texture".to_string(),
+            LayoutResource::Unknown => "unknown resource".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct LayoutEntry {
+    visibility: u64,
+    resource: LayoutResource,
+    path: String,
+}
+
+fn str_or<'a>(obj: &'a Map<String, Value>, key: &str, default: &'a str) -> &'a str {
+    obj.get(key).and_then(Value::as_str).unwrap_or(default)
+}
+
+/// Bind group layout entries by binding number; malformed entries are skipped
+/// (the per-descriptor validator reports them)
+fn parse_layout_entries(layout: &Value, path: &str) -> BTreeMap<u64, LayoutEntry> {
+    let mut entries = BTreeMap::new();
+    let Some(list) = layout.get("entries").and_then(Value::as_array) else {
+        return entries;
+    };
+
+    for (i, entry) in list.iter().enumerate() {
+        let Some(entry) = entry.as_object() else {
+            continue;
+        };
+        let Some(binding) = entry.get("binding").and_then(Value::as_u64) else {
+            continue;
+        };
+        let resource = if let Some(buffer) = entry.get("buffer").and_then(Value::as_object) {
+            LayoutResource::Buffer {
+                ty: str_or(buffer, "type", "uniform").to_string(),
+                min_binding_size: buffer.get("minBindingSize").and_then(Value::as_u64).unwrap_or(0),
+            }
+        } else if let Some(sampler) = entry.get("sampler").and_then(Value::as_object) {
+            LayoutResource::Sampler {
+                ty: str_or(sampler, "type", "filtering").to_string(),
+            }
+        } else if let Some(texture) = entry.get("texture").and_then(Value::as_object) {
+            LayoutResource::Texture {
+                sample_type: str_or(texture, "sampleType", "float").to_string(),
+                view_dimension: str_or(texture, "viewDimension", "2d").to_string(),
+                multisampled: texture.get("multisampled").and_then(Value::as_bool).unwrap_or(false),
+            }
+        } else if let Some(storage) = entry.get("storageTexture").and_then(Value::as_object) {
+            LayoutResource::StorageTexture {
+                access: str_or(storage, "access", "write-only").to_string(),
+                format: str_or(storage, "format", "").to_string(),
+                view_dimension: str_or(storage, "viewDimension", "2d").to_string(),
+            }
+        } else if entry.contains_key("externalTexture") {
+            LayoutResource::ExternalTexture
+        } else {
+            LayoutResource::Unknown
+        };
+
+        entries.entry(binding).or_insert(LayoutEntry {
+            visibility: entry.get("visibility").and_then(Value::as_u64).unwrap_or(0),
+            resource,
+            path: json_pointer_index(&json_pointer(path, "entries"), i),
+        });
+    }
+    entries
+}
+
+/// Builder for cross-object diagnostics
+struct Report<'a> {
+    result: DescriptorValidationResult,
+    profile: &'a LimitsProfile,
+}
+
+impl Report<'_> {
+    #[allow(clippy::too_many_arguments)]
+    fn add(
+        &mut self,
+        severity: Severity,
+        path: String,
+        related_path: Option<String>,
+        shader_location: Option<ShaderSourceLocation>,
+        code: &str,
+        message: String,
+        anchor: &str,
+    ) {
+        self.result.push_diagnostic(DescriptorDiagnostic {
+            path,
+            code: code.to_string(),
+            severity,
+            message,
+            spec_url: spec_url(anchor),
+            related_path,
+            shader_location,
+        });
+    }
+
+    fn error(&mut self, path: String, related: Option<String>, code: &str, message: String, anchor: &str) {
+        self.add(Severity::Error, path, related, None, code, message, anchor);
+    }
+}
+
+/// Validate a set of related descriptors against each other, their shaders and `profile`
+pub fn validate_related_descriptors(
+    set: &RelatedDescriptors,
+    profile: &LimitsProfile,
+) -> DescriptorValidationResult {
+    let mut report = Report {
+        result: DescriptorValidationResult::ok(),
+        profile,
+    };
+
+    // Each object on its own first, with paths rebased into the set
+    let groups: [(&str, &str, &BTreeMap<String, Value>); 6] = [
+        ("buffers", "buffer", &set.buffers),
+        ("samplers", "sampler", &set.samplers),
+        ("bindGroupLayouts", "bind_group_layout", &set.bind_group_layouts),
+        ("pipelineLayouts", "pipeline_layout", &set.pipeline_layouts),
+        ("bindGroups", "bind_group", &set.bind_groups),
+        ("pipelines", "", &set.pipelines),
+    ];
+    for (collection, kind, objects) in groups {
+        for (key, descriptor) in objects {
+            let kind = match (kind, descriptor_kind(descriptor)) {
+                ("", DescriptorKind::Compute) => "compute_pipeline",
+                ("", _) => "render_pipeline",
+                (kind, _) => kind,
+            };
+            let prefix = json_pointer(&json_pointer("", collection), key);
+            let mut single = validate_descriptor(kind, descriptor.to_string(), profile);
+            for diagnostic in &mut single.diagnostics {
+                diagnostic.path = format!("{}{}", prefix, diagnostic.path);
+            }
+            report.result.merge(single);
+        }
+    }
+
+    let layouts: HashMap<&str, BTreeMap<u64, LayoutEntry>> = set
+        .bind_group_layouts
+        .iter()
+        .map(|(key, layout)| {
+            let path = json_pointer("/bindGroupLayouts", key);
+            (key.as_str(), parse_layout_entries(layout, &path))
+        })
+        .collect();
+
+    for (key, group) in &set.bind_groups {
+        check_bind_group(&mut report, set, &layouts, key, group);
+    }
+
+    for (key, layout) in &set.pipeline_layouts {
+        check_pipeline_layout_usage(&mut report, set, key, layout);
+    }
+
+    let mut shaders: HashMap<String, Option<ValidatedShader>> = HashMap::new();
+    for (key, pipeline) in &set.pipelines {
+        let path = json_pointer("/pipelines", key);
+        check_pipeline(&mut report, set, &layouts, &mut shaders, &path, pipeline);
+    }
+
+    report.result
+}
+
+fn check_bind_group(
+    report: &mut Report<'_>,
+    set: &RelatedDescriptors,
+    layouts: &HashMap<&str, BTreeMap<u64, LayoutEntry>>,
+    key: &str,
+    group: &Value,
+) {
+    const ANCHOR: &str = "dom-gpudevice-createbindgroup";
+    let path = json_pointer("/bindGroups", key);
+    let Some(layout_key) = group.get("layout").and_then(handle_key) else {
+        return;
+    };
+    let layout_path = json_pointer("/bindGroupLayouts", &layout_key);
+    let Some(layout) = layouts.get(layout_key.as_str()) else {
+        report.error(
+            json_pointer(&path, "layout"),
+            None,
+            "unknown-object",
+            format!("Bind group layout '{}' is not in the set", layout_key),
+            ANCHOR,
+        );
+        return;
+    };
+
+    let entries = group.get("entries").and_then(Value::as_array).cloned().unwrap_or_default();
+    let mut bound = Vec::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let entry_path = json_pointer_index(&json_pointer(&path, "entries"), i);
+        let Some(binding) = entry.get("binding").and_then(Value::as_u64) else {
+            continue;
+        };
+        bound.push(binding);
+        let Some(layout_entry) = layout.get(&binding) else {
+            report.error(
+                json_pointer(&entry_path, "binding"),
+                Some(layout_path.clone()),
+                "unexpected-binding",
+                format!("Binding {} does not exist in bind group layout '{}'", binding, layout_key),
+                ANCHOR,
+            );
+            continue;
+        };
+        if let Some(resource) = entry.get("resource") {
+            check_bound_resource(report, set, &json_pointer(&entry_path, "resource"), resource, layout_entry);
+        }
+    }
+
+    for (binding, layout_entry) in layout {
+        if !bound.contains(binding) {
+            report.error(
+                json_pointer(&path, "entries"),
+                Some(layout_entry.path.clone()),
+                "missing-binding",
+                format!("Bind group has no resource for layout binding {}", binding),
+                ANCHOR,
+            );
+        }
+    }
+}
+
+/// Per-stage binding limits apply to all bind group layouts of a pipeline layout together
+fn check_pipeline_layout_usage(report: &mut Report<'_>, set: &RelatedDescriptors, key: &str, layout: &Value) {
+    let mut usage = BindingUsage::default();
+    let layout_keys = layout.get("bindGroupLayouts").and_then(Value::as_array).into_iter().flatten();
+    for group_layout in layout_keys.filter_map(handle_key).filter_map(|k| set.bind_group_layouts.get(&k)) {
+        let entries = group_layout.get("entries").and_then(Value::as_array).into_iter().flatten();
+        for entry in entries.filter_map(Value::as_object) {
+            usage.add(entry);
+        }
+    }
+    let path = json_pointer(&json_pointer("/pipelineLayouts", key), "bindGroupLayouts");
+    report.result.merge(usage.validate(report.profile, &path));
+}
+
+fn check_bound_resource(
+    report: &mut Report<'_>,
+    set: &RelatedDescriptors,
+    path: &str,
+    resource: &Value,
+    layout_entry: &LayoutEntry,
+) {
+    const ANCHOR: &str = "dom-gpudevice-createbindgroup";
+    let related = Some(layout_entry.path.clone());
+
+    // Buffers are bound as {buffer, offset, size}; everything else by handle
+    let bound_kind = match resource.as_object() {
+        Some(obj) if obj.contains_key("buffer") => "buffer",
+        Some(_) => "unknown",
+        None => match handle_key(resource) {
+            Some(k) if set.samplers.contains_key(&k) => "sampler",
+            Some(k) if set.texture_views.contains_key(&k) => "texture view",
+            Some(k) if set.buffers.contains_key(&k) => "buffer",
+            _ => "unknown",
+        },
+    };
+
+    let expected_kind = match layout_entry.resource {
+        LayoutResource::Buffer { .. } => "buffer",
+        LayoutResource::Sampler { .. } => "sampler",
+        LayoutResource::Texture { .. } | LayoutResource::StorageTexture { .. } => "texture view",
+        LayoutResource::ExternalTexture | LayoutResource::Unknown => return,
+    };
+    if bound_kind != "unknown" && bound_kind != expected_kind {
+        report.error(
+            path.to_string(),
+            related,
+            "resource-kind-mismatch",
+            format!("Layout expects a {} but a {} is bound", layout_entry.resource.describe(), bound_kind),
+            ANCHOR,
+        );
+        return;
+    }
+
+    match &layout_entry.resource {
+        LayoutResource::Buffer { ty, min_binding_size } => {
+            let Some(binding) = resource.as_object() else {
+                report.error(
+                    path.to_string(),
+                    related,
+                    "resource-kind-mismatch",
+                    "Buffer bindings must be {buffer, offset, size} objects".to_string(),
+                    ANCHOR,
+                );
+                return;
+            };
+            let limits = &report.profile.limits;
+            let (alignment, max_binding_size) = if ty == "uniform" {
+                (limits.min_uniform_buffer_offset_alignment as u64, limits.max_uniform_buffer_binding_size)
+            } else {
+                (limits.min_storage_buffer_offset_alignment as u64, limits.max_storage_buffer_binding_size)
+            };
+            let offset = binding.get("offset").and_then(Value::as_u64).unwrap_or(0);
+            if offset % alignment.max(1) != 0 {
+                report.error(
+                    json_pointer(path, "offset"),
+                    related.clone(),
+                    "offset-alignment",
+                    format!("Buffer offset {} must be a multiple of {}", offset, alignment),
+                    ANCHOR,
+                );
+            }
+
+            let buffer_key = binding.get("buffer").and_then(handle_key);
+            let buffer = buffer_key.as_ref().and_then(|k| set.buffers.get(k));
+            let buffer_size = buffer.and_then(|b| b.get("size")).and_then(Value::as_u64);
+            let size = binding
+                .get("size")
+                .and_then(Value::as_u64)
+                .or_else(|| buffer_size.map(|s| s.saturating_sub(offset)));
+
+            if let (Some(buffer), Some(buffer_key)) = (buffer, &buffer_key) {
+                let buffer_path = json_pointer("/buffers", buffer_key);
+                let usage = buffer.get("usage").and_then(Value::as_u64).unwrap_or(0);
+                let (needed, name) = if ty == "uniform" {
+                    (BUFFER_USAGE_UNIFORM, "UNIFORM")
+                } else {
+                    (BUFFER_USAGE_STORAGE, "STORAGE")
+                };
+                if usage & needed == 0 {
+                    report.error(
+                        json_pointer(path, "buffer"),
+                        Some(buffer_path.clone()),
+                        "missing-usage",
+                        format!("Buffer '{}' is bound as a {} buffer but lacks {} usage", buffer_key, ty, name),
+                        ANCHOR,
+                    );
+                }
+                if let (Some(buffer_size), Some(size)) = (buffer_size, size) {
+                    if offset + size > buffer_size {
+                        report.error(
+                            path.to_string(),
+                            Some(buffer_path),
+                            "binding-out-of-bounds",
+                            format!(
+                                "Binding range {}..{} exceeds buffer '{}' of size {}",
+                                offset,
+                                offset + size,
+                                buffer_key,
+                                buffer_size
+                            ),
+                            ANCHOR,
+                        );
+                    }
+                }
+            }
+
+            if let Some(size) = size {
+                if *min_binding_size > 0 && size < *min_binding_size {
+                    report.error(
+                        path.to_string(),
+                        related.clone(),
+                        "binding-too-small",
+                        format!("Bound size {} is smaller than the layout's minBindingSize {}", size, min_binding_size),
+                        ANCHOR,
+                    );
+                }
+                if size > max_binding_size {
+                    report.error(
+                        path.to_string(),
+                        related,
+                        "binding-too-large",
+                        format!(
+                            "Bound size {} exceeds the {} buffer binding limit {} of profile '{}'",
+                            size, ty, max_binding_size, report.profile.name
+                        ),
+                        ANCHOR,
+                    );
+                }
+            }
+        }
+        LayoutResource::Sampler { ty } => {
+            let Some(sampler) = handle_key(resource).and_then(|k| set.samplers.get(&k)) else {
+                return;
+            };
+            let is_comparison = sampler.get("compare").is_some();
+            let filters = ["magFilter", "minFilter", "mipmapFilter"];
+            let is_filtering = filters
+                .iter()
+                .any(|f| sampler.get(*f).and_then(Value::as_str) == Some("linear"));
+            let problem = match ty.as_str() {
+                "comparison" if !is_comparison => Some("a comparison sampler (with 'compare')"),
+                "filtering" | "non-filtering" if is_comparison => Some("a non-comparison sampler"),
+                "non-filtering" if is_filtering => Some("a sampler with only 'nearest' filters"),
+                _ => None,
+            };
+            if let Some(expected) = problem {
+                report.error(
+                    path.to_string(),
+                    related,
+                    "sampler-type-mismatch",
+                    format!("Layout binding of type '{}' needs {}", ty, expected),
+                    ANCHOR,
+                );
+            }
+        }
+        LayoutResource::Texture { view_dimension, .. } | LayoutResource::StorageTexture { view_dimension, .. } => {
+            let Some(view) = handle_key(resource).and_then(|k| set.texture_views.get(&k)) else {
+                return;
+            };
+            if let Some(dimension) = view.get("dimension").and_then(Value::as_str) {
+                if dimension != view_dimension {
+                    report.error(
+                        path.to_string(),
+                        related.clone(),
+                        "view-dimension-mismatch",
+                        format!("Texture view dimension '{}' does not match layout viewDimension '{}'", dimension, view_dimension),
+                        ANCHOR,
+                    );
+                }
+            }
+            if let LayoutResource::StorageTexture { format, .. } = &layout_entry.resource {
+                if let Some(view_format) = view.get("format").and_then(Value::as_str) {
+                    if !format.is_empty() && view_format != format {
+                        report.error(
+                            path.to_string(),
+                            related,
+                            "format-mismatch",
+                            format!("Texture view format '{}' does not match storage texture format '{}'", view_format, format),
+                            ANCHOR,
+                        );
+                    }
+                }
+            }
+        }
+        LayoutResource::ExternalTexture | LayoutResource::Unknown => {}
+    }
+}
+
+/// A programmable stage of a pipeline
+struct StageRef<'a> {
+    key: &'a str,
+    stage: naga::ShaderStage,
+    stage_bit: u64,
+    path: String,
+    module: String,
+    entry_point: Option<&'a str>,
+}
+
+fn stage_name(stage: naga::ShaderStage) -> &'static str {
+    match stage {
+        naga::ShaderStage::Vertex => "vertex",
+        naga::ShaderStage::Fragment => "fragment",
+        naga::ShaderStage::Compute => "compute",
+    }
+}
+
+fn check_pipeline(
+    report: &mut Report<'_>,
+    set: &RelatedDescriptors,
+    layouts: &HashMap<&str, BTreeMap<u64, LayoutEntry>>,
+    shaders: &mut HashMap<String, Option<ValidatedShader>>,
+    path: &str,
+    pipeline: &Value,
+) {
+    let stage_refs: Vec<StageRef> = [
+        ("vertex", naga::ShaderStage::Vertex, SHADER_STAGE_VERTEX),
+        ("fragment", naga::ShaderStage::Fragment, SHADER_STAGE_FRAGMENT),
+        ("compute", naga::ShaderStage::Compute, SHADER_STAGE_COMPUTE),
+    ]
+    .into_iter()
+    .filter_map(|(key, stage, stage_bit)| {
+        let obj = pipeline.get(key)?.as_object()?;
+        Some(StageRef {
+            key,
+            stage,
+            stage_bit,
+            path: json_pointer(path, key),
+            module: obj.get("module").and_then(handle_key)?,
+            entry_point: obj.get("entryPoint").and_then(Value::as_str),
+        })
+    })
+    .collect();
+
+    // Explicit layout: group index -> (layout key, entries)
+    let mut pipeline_layout: Option<(String, Vec<Option<String>>)> = None;
+    if let Some(layout) = pipeline.get("layout").filter(|l| l.as_str() != Some("auto")) {
+        let layout_key = handle_key(layout).unwrap_or_default();
+        match set.pipeline_layouts.get(&layout_key) {
+            Some(descriptor) => {
+                let groups = descriptor
+                    .get("bindGroupLayouts")
+                    .and_then(Value::as_array)
+                    .map(|list| list.iter().map(handle_key).collect())
+                    .unwrap_or_default();
+                pipeline_layout = Some((layout_key, groups));
+            }
+            None => report.error(
+                json_pointer(path, "layout"),
+                None,
+                "unknown-object",
+                format!("Pipeline layout '{}' is not in the set", layout_key),
+                "dom-gpudevice-createpipelinelayout",
+            ),
+        }
+    }
+
+    if let (Some((layout_key, groups)), Some(vertex)) = (&pipeline_layout, pipeline.get("vertex")) {
+        let buffers = vertex.get("buffers").and_then(Value::as_array).map_or(0, Vec::len);
+        let max = report.profile.limits.max_bind_groups_plus_vertex_buffers as usize;
+        if groups.len() + buffers > max {
+            report.error(
+                json_pointer(path, "layout"),
+                Some(json_pointer("/pipelineLayouts", layout_key)),
+                "limit-exceeded",
+                format!(
+                    "{} bind groups plus {} vertex buffers exceed maxBindGroupsPlusVertexBuffers {} of profile '{}'",
+                    groups.len(),
+                    buffers,
+                    max,
+                    report.profile.name
+                ),
+                "limits",
+            );
+        }
+    }
+
+    for stage in &stage_refs {
+        let shader = shaders
+            .entry(stage.module.clone())
+            .or_insert_with(|| set.shaders.get(&stage.module).and_then(|source| validate_wgsl(source).ok()));
+        let Some(source) = set.shaders.get(&stage.module) else {
+            report.error(
+                json_pointer(&stage.path, "module"),
+                None,
+                "unknown-object",
+                format!("Shader module '{}' is not in the set", stage.module),
+                "abstract-opdef-validating-gpuprogrammablestage",
+            );
+            continue;
+        };
+        let Some(shader) = shader.as_ref() else {
+            report_invalid_shader(report, &stage.path, &stage.module, source);
+            continue;
+        };
+
+        let candidates: Vec<usize> = shader
+            .module
+            .entry_points
+            .iter()
+            .enumerate()
+            .filter(|(_, ep)| ep.stage == stage.stage && stage.entry_point.is_none_or(|name| ep.name == name))
+            .map(|(i, _)| i)
+            .collect();
+        let index = match candidates.as_slice() {
+            [index] => *index,
+            _ => {
+                let message = match stage.entry_point {
+                    Some(name) => format!("Module '{}' has no {} entry point '{}'", stage.module, stage_name(stage.stage), name),
+                    None => format!(
+                        "Module '{}' has {} {} entry points; entryPoint must name one",
+                        stage.module,
+                        candidates.len(),
+                        stage_name(stage.stage)
+                    ),
+                };
+                report.error(
+                    json_pointer(&stage.path, "entryPoint"),
+                    None,
+                    "unknown-entry-point",
+                    message,
+                    "abstract-opdef-validating-gpuprogrammablestage",
+                );
+                continue;
+            }
+        };
+
+        if let Some((layout_key, groups)) = &pipeline_layout {
+            check_stage_bindings(report, layouts, stage, shader, source, index, layout_key, groups);
+        }
+        match stage.stage {
+            naga::ShaderStage::Vertex => check_vertex_inputs(report, pipeline, stage, shader, source, index),
+            naga::ShaderStage::Fragment => check_fragment_outputs(report, pipeline, stage, shader, source, index),
+            naga::ShaderStage::Compute => {}
+        }
+    }
+}
+
+fn report_invalid_shader(report: &mut Report<'_>, stage_path: &str, module: &str, source: &str) {
+    let location = validate_wgsl(source).err().and_then(|e| {
+        e.diagnostics.first().map(|d| ShaderSourceLocation {
+            module: module.to_string(),
+            name: String::new(),
+            line: d.line,
+            column: d.column,
+        })
+    });
+    report.add(
+        Severity::Error,
+        json_pointer(stage_path, "module"),
+        Some(json_pointer("/shaders", module)),
+        location,
+        "invalid-shader",
+        format!("Shader module '{}' does not validate", module),
+        "abstract-opdef-validating-gpuprogrammablestage",
+    );
+}
+
+pub(crate) fn span_location(source: &str, module: &str, name: &str, span: naga::Span) -> ShaderSourceLocation {
+    let diagnostic = diagnostic_for_span(source, span, "");
+    ShaderSourceLocation {
+        module: module.to_string(),
+        name: name.to_string(),
+        line: diagnostic.line,
+        column: diagnostic.column,
+    }
+}
+
+/// Location of an entry point's `fn` declaration (naga keeps no span for it)
+pub(crate) fn entry_point_location(source: &str, module: &str, name: &str) -> ShaderSourceLocation {
+    let needle = format!("fn {}", name);
+    let found = source.lines().enumerate().find_map(|(i, line)| {
+        let column = line.find(&needle)?;
+        let rest = &line[column + needle.len()..];
+        rest.trim_start().starts_with('(').then(|| (i as u32 + 1, line[..column].chars().count() as u32 + 1))
+    });
+    let (line, column) = found.unwrap_or((0, 0));
+    ShaderSourceLocation {
+        module: module.to_string(),
+        name: name.to_string(),
+        line,
+        column,
+    }
+}
+
+/// How the shader declares a binding
+enum ShaderResource {
+    Uniform { size: u64 },
+    Storage { writable: bool, size: u64 },
+    Sampler { comparison: bool },
+    Texture { sample_kind: &'static str, view_dimension: &'static str, multisampled: bool },
+    StorageTexture { access: &'static str, format: String, view_dimension: &'static str },
+    Other,
+}
+
+fn view_dimension(dim: naga::ImageDimension, arrayed: bool) -> &'static str {
+    match (dim, arrayed) {
+        (naga::ImageDimension::D1, _) => "1d",
+        (naga::ImageDimension::D2, false) => "2d",
+        (naga::ImageDimension::D2, true) => "2d-array",
+        (naga::ImageDimension::D3, _) => "3d",
+        (naga::ImageDimension::Cube, false) => "cube",
+        (naga::ImageDimension::Cube, true) => "cube-array",
+    }
+}
+
+fn storage_access(access: naga::StorageAccess) -> &'static str {
+    if access.contains(naga::StorageAccess::LOAD | naga::StorageAccess::STORE) {
+        "read-write"
+    } else if access.contains(naga::StorageAccess::STORE) {
+        "write-only"
+    } else {
+        "read-only"
+    }
+}
+
+fn classify(module: &naga::Module, layouter: &Layouter, var: &naga::GlobalVariable) -> ShaderResource {
+    use naga::{AddressSpace, ImageClass, ScalarKind, StorageAccess, TypeInner};
+
+    match var.space {
+        AddressSpace::Uniform => return ShaderResource::Uniform { size: layouter[var.ty].size as u64 },
+        AddressSpace::Storage { access } => {
+            return ShaderResource::Storage {
+                writable: access.contains(StorageAccess::STORE),
+                size: layouter[var.ty].size as u64,
+            }
+        }
+        _ => {}
+    }
+
+    let mut ty = var.ty;
+    if let TypeInner::BindingArray { base, .. } = module.types[ty].inner {
+        ty = base;
+    }
+    match module.types[ty].inner {
+        TypeInner::Sampler { comparison } => ShaderResource::Sampler { comparison },
+        TypeInner::Image { dim, arrayed, class } => match class {
+            ImageClass::Sampled { kind, multi } => ShaderResource::Texture {
+                sample_kind: match kind {
+                    ScalarKind::Sint => "sint",
+                    ScalarKind::Uint => "uint",
+                    _ => "float",
+                },
+                view_dimension: view_dimension(dim, arrayed),
+                multisampled: multi,
+            },
+            ImageClass::Depth { multi } => ShaderResource::Texture {
+                sample_kind: "depth",
+                view_dimension: view_dimension(dim, arrayed),
+                multisampled: multi,
+            },
+            ImageClass::Storage { access, .. } => {
+                // The WGSL spelling is `texture_storage_2d<format, access>`
+                let type_name = ty.to_wgsl(&module.to_ctx());
+                let format = type_name
+                    .split_once('<')
+                    .map(|(_, args)| args.split([',', '>']).next().unwrap_or("").trim().to_string())
+                    .unwrap_or_default();
+                ShaderResource::StorageTexture {
+                    access: storage_access(access),
+                    format,
+                    view_dimension: view_dimension(dim, arrayed),
+                }
+            }
+        },
+        _ => ShaderResource::Other,
+    }
+}
+
+/// Reason a layout entry cannot serve a shader binding, if any
+fn binding_mismatch(shader: &ShaderResource, layout: &LayoutResource) -> Option<(&'static str, String)> {
+    let kind_error = |expected: &str| {
+        Some((
+            "binding-type-mismatch",
+            format!("Shader declares a {} but the layout has a {}", expected, layout.describe()),
+        ))
+    };
+
+    match (shader, layout) {
+        (ShaderResource::Uniform { size }, LayoutResource::Buffer { ty, min_binding_size }) => {
+            if ty != "uniform" {
+                return kind_error("uniform buffer");
+            }
+            min_size_mismatch(*size, *min_binding_size)
+        }
+        (ShaderResource::Storage { writable, size }, LayoutResource::Buffer { ty, min_binding_size }) => {
+            match ty.as_str() {
+                "uniform" => return kind_error("storage buffer"),
+                "read-only-storage" if *writable => {
+                    return Some((
+                        "binding-access-mismatch",
+                        "Shader writes the buffer but the layout type is 'read-only-storage'".to_string(),
+                    ))
+                }
+                _ => {}
+            }
+            min_size_mismatch(*size, *min_binding_size)
+        }
+        (ShaderResource::Sampler { comparison }, LayoutResource::Sampler { ty }) => {
+            if *comparison != (ty == "comparison") {
+                return Some((
+                    "sampler-type-mismatch",
+                    format!(
+                        "Shader declares a {} but the layout sampler type is '{}'",
+                        if *comparison { "sampler_comparison" } else { "sampler" },
+                        ty
+                    ),
+                ));
+            }
+            None
+        }
+        (
+            ShaderResource::Texture { sample_kind, view_dimension, multisampled },
+            LayoutResource::Texture { sample_type, view_dimension: layout_dimension, multisampled: layout_multisampled },
+        ) => {
+            let sample_ok = match *sample_kind {
+                "float" => matches!(sample_type.as_str(), "float" | "unfilterable-float" | "depth"),
+                "depth" => sample_type == "depth",
+                kind => sample_type == kind,
+            };
+            if !sample_ok {
+                return Some((
+                    "sample-type-mismatch",
+                    format!("Shader samples {} texels but the layout sampleType is '{}'", sample_kind, sample_type),
+                ));
+            }
+            if view_dimension != layout_dimension {
+                return Some((
+                    "view-dimension-mismatch",
+                    format!("Shader texture is '{}' but the layout viewDimension is '{}'", view_dimension, layout_dimension),
+                ));
+            }
+            if multisampled != layout_multisampled {
+                return Some((
+                    "multisample-mismatch",
+                    format!(
+                        "Shader texture is {}multisampled but the layout entry is {}",
+                        if *multisampled { "" } else { "not " },
+                        if *layout_multisampled { "multisampled" } else { "not multisampled" }
+                    ),
+                ));
+            }
+            None
+        }
+        (
+            ShaderResource::StorageTexture { access, format, view_dimension },
+            LayoutResource::StorageTexture { access: layout_access, format: layout_format, view_dimension: layout_dimension },
+        ) => {
+            if access != layout_access {
+                return Some((
+                    "binding-access-mismatch",
+                    format!("Shader storage texture is {} but the layout access is '{}'", access, layout_access),
+                ));
+            }
+            if !format.is_empty() && format != layout_format {
+                return Some((
+                    "format-mismatch",
+                    format!("Shader storage texture format is '{}' but the layout format is '{}'", format, layout_format),
+                ));
+            }
+            if view_dimension != layout_dimension {
+                return Some((
+                    "view-dimension-mismatch",
+                    format!("Shader texture is '{}' but the layout viewDimension is '{}'", view_dimension, layout_dimension),
+                ));
+            }
+            None
+        }
+        (ShaderResource::Other, _) | (_, LayoutResource::Unknown) => None,
+        (ShaderResource::Uniform { .. }, _) => kind_error("uniform buffer"),
+        (ShaderResource::Storage { .. }, _) => kind_error("storage buffer"),
+        (ShaderResource::Sampler { .. }, _) => kind_error("sampler"),
+        (ShaderResource::Texture { .. }, _) => kind_error("sampled texture"),
+        (ShaderResource::StorageTexture { .. }, _) => kind_error("storage texture"),
+    }
+}
+
+fn min_size_mismatch(shader_size: u64, min_binding_size: u64) -> Option<(&'static str, String)> {
+    if min_binding_size > 0 && min_binding_size < shader_size {
+        return Some((
+            "min-binding-size",
+            format!(
+                "Layout minBindingSize {} is smaller than the {} bytes the shader reads",
+                min_binding_size, shader_size
+            ),
+        ));
+    }
+    None
+}
+
+#[allow(clippy::too_many_arguments)]
+fn check_stage_bindings(
+    report: &mut Report<'_>,
+    layouts: &HashMap<&str, BTreeMap<u64, LayoutEntry>>,
+    stage: &StageRef,
+    shader: &ValidatedShader,
+    source: &str,
+    entry_index: usize,
+    layout_key: &str,
+    groups: &[Option<String>],
+) {
+    const ANCHOR: &str = "abstract-opdef-validating-shader-binding";
+    let module = &shader.module;
+    let mut layouter = Layouter::default();
+    if layouter.update(module.to_ctx()).is_err() {
+        return;
+    }
+    let usage = shader.info.get_entry_point(entry_index);
+    let layout_path = json_pointer("/pipelineLayouts", layout_key);
+
+    for (handle, var) in module.global_variables.iter() {
+        let Some(binding) = var.binding.as_ref() else {
+            continue;
+        };
+        if usage[handle].is_empty() {
+            continue;
+        }
+
+        let name = var.name.clone().unwrap_or_default();
+        let location = span_location(source, &stage.module, &name, module.global_variables.get_span(handle));
+        let mut fail = |related: String, code: &str, message: String| {
+            report.add(
+                Severity::Error,
+                json_pointer(&stage.path, "entryPoint"),
+                Some(related),
+                Some(location.clone()),
+                code,
+                message,
+                ANCHOR,
+            );
+        };
+
+        let group_layout = groups.get(binding.group as usize).cloned().flatten();
+        let Some(group_key) = group_layout else {
+            fail(
+                json_pointer(&layout_path, "bindGroupLayouts"),
+                "missing-bind-group-layout",
+                format!(
+                    "'{}' uses @group({}) but pipeline layout '{}' has no bind group layout there",
+                    name, binding.group, layout_key
+                ),
+            );
+            continue;
+        };
+        let Some(entries) = layouts.get(group_key.as_str()) else {
+            fail(
+                json_pointer_index(&json_pointer(&layout_path, "bindGroupLayouts"), binding.group as usize),
+                "unknown-object",
+                format!("Bind group layout '{}' is not in the set", group_key),
+            );
+            continue;
+        };
+        let group_path = json_pointer("/bindGroupLayouts", &group_key);
+        let Some(entry) = entries.get(&(binding.binding as u64)) else {
+            fail(
+                json_pointer(&group_path, "entries"),
+                "missing-binding",
+                format!(
+                    "'{}' uses @group({}) @binding({}) which bind group layout '{}' does not declare",
+                    name, binding.group, binding.binding, group_key
+                ),
+            );
+            continue;
+        };
+
+        if entry.visibility & stage.stage_bit == 0 {
+            fail(
+                json_pointer(&entry.path, "visibility"),
+                "binding-not-visible",
+                format!("Binding for '{}' is not visible to the {} stage", name, stage_name(stage.stage)),
+            );
+        }
+        if let Some((code, message)) = binding_mismatch(&classify(module, &layouter, var), &entry.resource) {
+            fail(entry.path.clone(), code, format!("'{}': {}", name, message));
+        }
+    }
+}
+
+/// Shader inputs or outputs by location: (scalar kind, component count, declaration name)
+fn located_values(
+    module: &naga::Module,
+    items: Vec<(Option<String>, naga::Handle<naga::Type>, Option<naga::Binding>)>,
+) -> BTreeMap<u32, (naga::ScalarKind, u32, String)> {
+    let mut out = BTreeMap::new();
+    for (name, ty, binding) in items {
+        match (binding, &module.types[ty].inner) {
+            (Some(naga::Binding::Location { location, .. }), inner) => {
+                let (kind, components) = match *inner {
+                    naga::TypeInner::Scalar(scalar) => (scalar.kind, 1),
+                    naga::TypeInner::Vector { size, scalar } => (scalar.kind, size as u32),
+                    _ => continue,
+                };
+                out.insert(location, (kind, components, name.unwrap_or_default()));
+            }
+            (None, naga::TypeInner::Struct { members, .. }) => {
+                let members = members.iter().map(|m| (m.name.clone(), m.ty, m.binding.clone())).collect();
+                out.extend(located_values(module, members));
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Scalar kind and component count a vertex format feeds to the shader
+fn vertex_format_kind(format: &str) -> (naga::ScalarKind, u32) {
+    let kind = if format.starts_with("uint") {
+        naga::ScalarKind::Uint
+    } else if format.starts_with("sint") {
+        naga::ScalarKind::Sint
+    } else {
+        naga::ScalarKind::Float
+    };
+    let components = if format == "unorm10-10-10-2" || format.contains("x4") {
+        4
+    } else if format.contains("x3") {
+        3
+    } else if format.contains("x2") {
+        2
+    } else {
+        1
+    };
+    (kind, components)
+}
+
+fn kind_name(kind: naga::ScalarKind) -> &'static str {
+    match kind {
+        naga::ScalarKind::Sint => "i32",
+        naga::ScalarKind::Uint => "u32",
+        naga::ScalarKind::Bool => "bool",
+        _ => "f32",
+    }
+}
+
+fn check_vertex_inputs(
+    report: &mut Report<'_>,
+    pipeline: &Value,
+    stage: &StageRef,
+    shader: &ValidatedShader,
+    source: &str,
+    entry_index: usize,
+) {
+    const ANCHOR: &str = "abstract-opdef-validating-gpuvertexstate";
+    let module = &shader.module;
+    let entry_point = &module.entry_points[entry_index];
+    let inputs = located_values(
+        module,
+        entry_point
+            .function
+            .arguments
+            .iter()
+            .map(|arg| (arg.name.clone(), arg.ty, arg.binding.clone()))
+            .collect(),
+    );
+    let ep_location = entry_point_location(source, &stage.module, &entry_point.name);
+
+    // shaderLocation -> (attribute path, format)
+    let mut attributes: BTreeMap<u32, (String, String)> = BTreeMap::new();
+    let buffers = pipeline
+        .get(stage.key)
+        .and_then(|v| v.get("buffers"))
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    for (i, buffer) in buffers.iter().enumerate() {
+        let buffer_path = json_pointer_index(&json_pointer(&stage.path, "buffers"), i);
+        let list = buffer.get("attributes").and_then(Value::as_array).cloned().unwrap_or_default();
+        for (j, attribute) in list.iter().enumerate() {
+            let (Some(location), Some(format)) = (
+                attribute.get("shaderLocation").and_then(Value::as_u64),
+                attribute.get("format").and_then(Value::as_str),
+            ) else {
+                continue;
+            };
+            let path = json_pointer_index(&json_pointer(&buffer_path, "attributes"), j);
+            attributes.entry(location as u32).or_insert((path, format.to_string()));
+        }
+    }
+
+    for (location, (kind, components, name)) in &inputs {
+        let input_location = ShaderSourceLocation {
+            name: name.clone(),
+            ..ep_location.clone()
+        };
+        let Some((path, format)) = attributes.get(location) else {
+            report.add(
+                Severity::Error,
+                json_pointer(&stage.path, "buffers"),
+                None,
+                Some(input_location),
+                "missing-vertex-attribute",
+                format!("Vertex input '{}' at @location({}) has no vertex attribute", name, location),
+                ANCHOR,
+            );
+            continue;
+        };
+        if vertex_format_size(format).is_none() {
+            continue;
+        }
+        let (format_kind, format_components) = vertex_format_kind(format);
+        if format_kind != *kind {
+            report.add(
+                Severity::Error,
+                json_pointer(path, "format"),
+                None,
+                Some(input_location),
+                "vertex-format-type",
+                format!(
+                    "Vertex format '{}' provides {} but input '{}' is {}",
+                    format,
+                    kind_name(format_kind),
+                    name,
+                    kind_name(*kind)
+                ),
+                ANCHOR,
+            );
+        } else if format_components < *components {
+            report.add(
+                Severity::Warning,
+                json_pointer(path, "format"),
+                None,
+                Some(input_location),
+                "vertex-component-count",
+                format!(
+                    "Vertex format '{}' has {} components but input '{}' reads {}; the rest are filled with defaults",
+                    format, format_components, name, components
+                ),
+                ANCHOR,
+            );
+        }
+    }
+
+    for (location, (path, _)) in &attributes {
+        if !inputs.contains_key(location) {
+            report.add(
+                Severity::Warning,
+                json_pointer(path, "shaderLocation"),
+                None,
+                Some(ep_location.clone()),
+                "unused-vertex-attribute",
+                format!("Vertex shader '{}' does not read @location({})", entry_point.name, location),
+                ANCHOR,
+            );
+        }
+    }
+}
+
+fn check_fragment_outputs(
+    report: &mut Report<'_>,
+    pipeline: &Value,
+    stage: &StageRef,
+    shader: &ValidatedShader,
+    source: &str,
+    entry_index: usize,
+) {
+    const ANCHOR: &str = "abstract-opdef-validating-gpufragmentstate";
+    let module = &shader.module;
+    let entry_point = &module.entry_points[entry_index];
+    let outputs = located_values(
+        module,
+        entry_point
+            .function
+            .result
+            .iter()
+            .map(|result| (None, result.ty, result.binding.clone()))
+            .collect(),
+    );
+    let ep_location = entry_point_location(source, &stage.module, &entry_point.name);
+
+    let targets = pipeline
+        .get(stage.key)
+        .and_then(|v| v.get("targets"))
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    for (i, target) in targets.iter().enumerate() {
+        if target.is_null() {
+            continue;
+        }
+        let write_mask = target.get("writeMask").and_then(Value::as_u64).unwrap_or(0xF);
+        if write_mask != 0 && !outputs.contains_key(&(i as u32)) {
+            report.add(
+                Severity::Error,
+                json_pointer_index(&json_pointer(&stage.path, "targets"), i),
+                None,
+                Some(ep_location.clone()),
+                "missing-fragment-output",
+                format!("Color target {} is written but '{}' has no @location({}) output", i, entry_point.name, i),
+                ANCHOR,
+            );
+        }
+    }
+
+    for (location, (_, _, name)) in &outputs {
+        let has_target = targets.get(*location as usize).is_some_and(|t| !t.is_null());
+        if !has_target {
+            report.add(
+                Severity::Warning,
+                json_pointer(&stage.path, "targets"),
+                None,
+                Some(ShaderSourceLocation {
+                    name: name.clone(),
+                    ..ep_location.clone()
+                }),
+                "unused-fragment-output",
+                format!("Fragment output @location({}) has no color target and is discarded", location),
+                ANCHOR,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpu::profiles::{compatibility_profile, webgpu_default_profile};
+
+    const SHADER: &str = r#"
+struct Params {
+    scale: vec4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read_write> data: array<f32>;
+@group(1) @binding(0) var tex: texture_2d<f32>;
+@group(1) @binding(1) var samp: sampler;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) id: u32,
+}
+
+@vertex
+fn vs(input: VertexInput) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(input.position, f32(input.id)) * params.scale;
+}
+
+@fragment
+fn fs(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+    return textureSample(tex, samp, pos.xy);
+}
+
+@compute @workgroup_size(64)
+fn cs(@builtin(global_invocation_id) id: vec3<u32>) {
+    data[id.x] = data[id.x] * params.scale.x;
+}
+"#;
+
+    fn related(json: &str) -> RelatedDescriptors {
+        let mut set: RelatedDescriptors = serde_json::from_str(json).unwrap();
+        set.shaders.insert("1".to_string(), SHADER.to_string());
+        set
+    }
+
+    #[test]
+    fn test_matching_objects_are_valid() {
+        let set = related(
+            r#"{
+                "buffers": {"5": {"size": 1024, "usage": 200}},
+                "bindGroupLayouts": {
+                    "10": {"entries": [
+                        {"binding": 0, "visibility": 5, "buffer": {"type": "uniform"}},
+                        {"binding": 1, "visibility": 4, "buffer": {"type": "storage"}}
+                    ]}
+                },
+                "pipelineLayouts": {"20": {"bindGroupLayouts": [10]}},
+                "bindGroups": {"30": {"layout": 10, "entries": [
+                    {"binding": 0, "resource": {"buffer": 5, "size": 16}},
+                    {"binding": 1, "resource": {"buffer": 5, "offset": 256}}
+                ]}},
+                "pipelines": {"40": {"layout": 20, "compute": {"module": 1, "entryPoint": "cs"}}}
+            }"#,
+        );
+        let result = validate_related_descriptors(&set, &webgpu_default_profile());
+        assert!(result.valid, "{:?}", result.diagnostics);
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+    }
+
+    #[test]
+    fn test_bind_group_against_layout() {
+        let set = related(
+            r#"{
+                "buffers": {"5": {"size": 64, "usage": 8}},
+                "samplers": {"6": {"compare": "less"}},
+                "bindGroupLayouts": {
+                    "10": {"entries": [
+                        {"binding": 0, "visibility": 1, "buffer": {"type": "uniform", "minBindingSize": 32}},
+                        {"binding": 1, "visibility": 2, "sampler": {}},
+                        {"binding": 2, "visibility": 2, "texture": {}}
+                    ]}
+                },
+                "bindGroups": {"30": {"layout": 10, "entries": [
+                    {"binding": 0, "resource": {"buffer": 5, "offset": 4, "size": 16}},
+                    {"binding": 1, "resource": 6},
+                    {"binding": 7, "resource": 6}
+                ]}}
+            }"#,
+        );
+        let result = validate_related_descriptors(&set, &webgpu_default_profile());
+        for code in [
+            "offset-alignment",
+            "missing-usage",
+            "binding-too-small",
+            "sampler-type-mismatch",
+            "unexpected-binding",
+            "missing-binding",
+        ] {
+            assert!(result.has_code(code), "missing {}: {:?}", code, result.diagnostics);
+        }
+        let usage = result.diagnostics.iter().find(|d| d.code == "missing-usage").unwrap();
+        assert_eq!(usage.path, "/bindGroups/30/entries/0/resource/buffer");
+        assert_eq!(usage.related_path.as_deref(), Some("/buffers/5"));
+        let missing = result.diagnostics.iter().find(|d| d.code == "missing-binding").unwrap();
+        assert_eq!(missing.related_path.as_deref(), Some("/bindGroupLayouts/10/entries/2"));
+    }
+
+    #[test]
+    fn test_pipeline_layout_against_shader() {
+        let set = related(
+            r#"{
+                "bindGroupLayouts": {
+                    "10": {"entries": [
+                        {"binding": 0, "visibility": 2, "buffer": {"type": "uniform", "minBindingSize": 4}}
+                    ]},
+                    "11": {"entries": [
+                        {"binding": 0, "visibility": 2, "texture": {"sampleType": "uint"}},
+                        {"binding": 1, "visibility": 2, "sampler": {"type": "comparison"}}
+                    ]}
+                },
+                "pipelineLayouts": {"20": {"bindGroupLayouts": [10, 11]}},
+                "pipelines": {"40": {
+                    "layout": 20,
+                    "vertex": {"module": 1, "entryPoint": "vs", "buffers": [{"arrayStride": 16, "attributes": [
+                        {"format": "float32x3", "offset": 0, "shaderLocation": 0},
+                        {"format": "uint32", "offset": 12, "shaderLocation": 1}
+                    ]}]},
+                    "fragment": {"module": 1, "entryPoint": "fs", "targets": [{"format": "rgba8unorm"}]}
+                }}
+            }"#,
+        );
+        let result = validate_related_descriptors(&set, &webgpu_default_profile());
+        assert!(result.has_code("binding-not-visible"));
+        assert!(result.has_code("min-binding-size"));
+        assert!(result.has_code("sample-type-mismatch"));
+        assert!(result.has_code("sampler-type-mismatch"));
+        // `data` is only used by the compute entry point
+        assert!(!result.has_code("missing-binding"));
+
+        let visibility = result.diagnostics.iter().find(|d| d.code == "binding-not-visible").unwrap();
+        assert_eq!(visibility.path, "/pipelines/40/vertex/entryPoint");
+        assert_eq!(visibility.related_path.as_deref(), Some("/bindGroupLayouts/10/entries/0/visibility"));
+        let location = visibility.shader_location.as_ref().unwrap();
+        assert_eq!(location.name, "params");
+        assert_eq!(location.line, 6);
+    }
+
+    #[test]
+    fn test_vertex_and_fragment_interfaces() {
+        let set = related(
+            r#"{"pipelines": {"40": {
+                "layout": "auto",
+                "vertex": {"module": 1, "entryPoint": "vs", "buffers": [{"arrayStride": 24, "attributes": [
+                    {"format": "float32x2", "offset": 0, "shaderLocation": 0},
+                    {"format": "float32", "offset": 8, "shaderLocation": 1},
+                    {"format": "float32", "offset": 12, "shaderLocation": 4}
+                ]}]},
+                "fragment": {"module": 1, "entryPoint": "fs", "targets": [{"format": "rgba8unorm"}, {"format": "r8unorm"}]}
+            }}}"#,
+        );
+        let result = validate_related_descriptors(&set, &webgpu_default_profile());
+
+        let type_error = result.diagnostics.iter().find(|d| d.code == "vertex-format-type").unwrap();
+        assert_eq!(type_error.path, "/pipelines/40/vertex/buffers/0/attributes/1/format");
+        assert_eq!(type_error.shader_location.as_ref().unwrap().name, "id");
+        assert!(result.has_code("vertex-component-count"));
+        assert!(result.has_code("unused-vertex-attribute"));
+
+        let output = result.diagnostics.iter().find(|d| d.code == "missing-fragment-output").unwrap();
+        assert_eq!(output.path, "/pipelines/40/fragment/targets/1");
+        assert_eq!(output.shader_location.as_ref().unwrap().line, 22);
+    }
+
+    #[test]
+    fn test_unknown_references() {
+        let set = related(
+            r#"{
+                "bindGroups": {"30": {"layout": 99, "entries": []}},
+                "pipelines": {"40": {"layout": 98, "compute": {"module": 2, "entryPoint": "cs"}}}
+            }"#,
+        );
+        let result = validate_related_descriptors(&set, &webgpu_default_profile());
+        let unknown: Vec<&str> = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.code == "unknown-object")
+            .map(|d| d.path.as_str())
+            .collect();
+        assert_eq!(
+            unknown,
+            vec!["/bindGroups/30/layout", "/pipelines/40/layout", "/pipelines/40/compute/module"]
+        );
+    }
+
+    #[test]
+    fn test_limits_follow_profile() {
+        let set = related(
+            r#"{
+                "buffers": {"5": {"size": 65536, "usage": 64}},
+                "bindGroupLayouts": {
+                    "10": {"entries": [{"binding": 0, "visibility": 4, "buffer": {"type": "uniform"}}]},
+                    "11": {"entries": [
+                        {"binding": 0, "visibility": 4, "buffer": {"type": "storage"}},
+                        {"binding": 1, "visibility": 4, "buffer": {"type": "storage"}},
+                        {"binding": 2, "visibility": 4, "buffer": {"type": "read-only-storage"}}
+                    ]}
+                },
+                "pipelineLayouts": {"20": {"bindGroupLayouts": [11, 11]}},
+                "bindGroups": {"30": {"layout": 10, "entries": [
+                    {"binding": 0, "resource": {"buffer": 5, "size": 32768}}
+                ]}}
+            }"#,
+        );
+        let result = validate_related_descriptors(&set, &webgpu_default_profile());
+        assert!(result.valid, "{:?}", result.diagnostics);
+
+        let result = validate_related_descriptors(&set, &compatibility_profile());
+        let too_large = result.diagnostics.iter().find(|d| d.code == "binding-too-large").unwrap();
+        assert!(too_large.message.contains("16384"));
+        let exceeded = result.diagnostics.iter().find(|d| d.code == "limit-exceeded").unwrap();
+        assert_eq!(exceeded.path, "/pipelineLayouts/20/bindGroupLayouts");
+        assert!(exceeded.message.contains("maxStorageBuffersPerShaderStage"));
+    }
+}
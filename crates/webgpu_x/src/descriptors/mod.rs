@@ -1,7 +1,13 @@
+pub mod compatibility;
 pub mod validator;
 
+pub use compatibility::{validate_related_descriptors, RelatedDescriptors, ShaderSourceLocation};
+
 pub use validator::{
-    validate_bind_group_layout_descriptor, validate_buffer_descriptor,
-    validate_compute_pipeline_descriptor, validate_render_pipeline_descriptor,
-    validate_texture_descriptor, DescriptorValidationResult, ValidationRule,
+    validate_bind_group_descriptor, validate_bind_group_layout_descriptor,
+    validate_buffer_descriptor, validate_compute_pipeline_descriptor, validate_descriptor,
+    validate_pipeline_layout_descriptor, validate_query_set_descriptor,
+    validate_render_pass_descriptor, validate_render_pipeline_descriptor,
+    validate_sampler_descriptor, validate_texture_descriptor, DescriptorDiagnostic,
+    DescriptorValidationResult, Severity, ValidationRule,
 };
@@ -1,5 +1,23 @@
-use deno_bindgen::deno_bindgen;
-use serde_json::Value;
+//! WebGPU descriptor validation
+//!
+//! Descriptors arrive as JSON and are checked before they reach the driver. Every
+//! problem found is reported as a [`DescriptorDiagnostic`] carrying:
+//! - a JSON pointer (RFC 6901) to the offending value, e.g. `/vertex/buffers/0/arrayStride`
+//! - a stable kebab-case error code
+//! - a severity; only errors make the descriptor invalid
+//! - a link to the WebGPU spec section that defines the rule
+//!
+//! Validation never stops at the first problem, so one pass reports all of them.
+
+use super::compatibility::ShaderSourceLocation;
+use crate::gpu::limits::DeviceLimits;
+use crate::gpu::profiles::LimitsProfile;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// WebGPU specification all `spec_url`s point into
+pub const WEBGPU_SPEC_URL: &str = "https://www.w3.org/TR/webgpu/";
 
 /// Field validation rule
 pub enum ValidationRule {
@@ -11,66 +29,131 @@ pub enum ValidationRule {
     NonZero,
 }
 
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The descriptor would be rejected by WebGPU
+    Error,
+    /// Valid, but likely a mistake or not portable
+    Warning,
+}
+
+/// A single validation finding
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DescriptorDiagnostic {
+    /// JSON pointer to the offending value ("" for the whole descriptor)
+    pub path: String,
+    /// Stable machine-readable code, e.g. "missing-field" or "duplicate-binding"
+    pub code: String,
+    pub severity: Severity,
+    pub message: String,
+    /// Link to the WebGPU spec section defining the rule
+    pub spec_url: String,
+    /// The other object involved in a cross-object mismatch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub related_path: Option<String>,
+    /// Shader declaration involved in a cross-object mismatch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shader_location: Option<ShaderSourceLocation>,
+}
+
 /// Validation result
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DescriptorValidationResult {
-    pub valid: u8,
-    pub error_count: u32,
-    pub error_0: String,
-    pub error_1: String,
-    pub error_2: String,
-    pub error_3: String,
-    pub error_4: String,
+    /// False when at least one diagnostic is an error
+    pub valid: bool,
+    pub diagnostics: Vec<DescriptorDiagnostic>,
 }
 
 impl DescriptorValidationResult {
-    fn ok() -> Self {
+    pub(crate) fn ok() -> Self {
         Self {
-            valid: 1,
-            error_count: 0,
-            error_0: String::new(),
-            error_1: String::new(),
-            error_2: String::new(),
-            error_3: String::new(),
-            error_4: String::new(),
+            valid: true,
+            diagnostics: Vec::new(),
         }
     }
 
-    fn with_error(error: String) -> Self {
-        Self {
-            valid: 0,
-            error_count: 1,
-            error_0: error,
-            error_1: String::new(),
-            error_2: String::new(),
-            error_3: String::new(),
-            error_4: String::new(),
-        }
+    fn with_error(code: &str, message: String, anchor: &str) -> Self {
+        let mut result = Self::ok();
+        result.push(String::new(), code, Severity::Error, message, anchor);
+        result
     }
 
-    fn add_error(&mut self, error: String) {
-        self.valid = 0;
-        match self.error_count {
-            0 => self.error_0 = error,
-            1 => self.error_1 = error,
-            2 => self.error_2 = error,
-            3 => self.error_3 = error,
-            4 => self.error_4 = error,
-            _ => return, // Max 5 errors
+    pub(crate) fn push(&mut self, path: String, code: &str, severity: Severity, message: String, anchor: &str) {
+        self.push_diagnostic(DescriptorDiagnostic {
+            path,
+            code: code.to_string(),
+            severity,
+            message,
+            spec_url: spec_url(anchor),
+            related_path: None,
+            shader_location: None,
+        });
+    }
+
+    pub(crate) fn push_diagnostic(&mut self, diagnostic: DescriptorDiagnostic) {
+        if diagnostic.severity == Severity::Error {
+            self.valid = false;
         }
-        self.error_count += 1;
+        self.diagnostics.push(diagnostic);
     }
+
+    /// Append the diagnostics of another result
+    pub fn merge(&mut self, other: DescriptorValidationResult) {
+        self.valid &= other.valid;
+        self.diagnostics.extend(other.diagnostics);
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &DescriptorDiagnostic> {
+        self.diagnostics.iter().filter(|d| d.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &DescriptorDiagnostic> {
+        self.diagnostics.iter().filter(|d| d.severity == Severity::Warning)
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.errors().count()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.warnings().count()
+    }
+
+    /// Whether any diagnostic has the given code
+    pub fn has_code(&self, code: &str) -> bool {
+        self.diagnostics.iter().any(|d| d.code == code)
+    }
+}
+
+/// Full spec URL for a section anchor
+pub fn spec_url(anchor: &str) -> String {
+    format!("{}#{}", WEBGPU_SPEC_URL, anchor)
+}
+
+/// Append a key to a JSON pointer, escaping `~` and `/`
+pub fn json_pointer(parent: &str, key: &str) -> String {
+    format!("{}/{}", parent, key.replace('~', "~0").replace('/', "~1"))
 }
 
-/// Validate descriptor field
+pub(crate) fn json_pointer_index(parent: &str, index: usize) -> String {
+    format!("{}/{}", parent, index)
+}
+
+/// Validate descriptor field, returning an error code and message
 fn validate_field(
     field_name: &str,
     value: Option<&Value>,
     rule: &ValidationRule,
-) -> Result<(), String> {
+) -> Result<(), (&'static str, String)> {
     match rule {
         ValidationRule::Required => {
             if value.is_none() {
-                return Err(format!("Required field '{}' is missing", field_name));
+                return Err((
+                    "missing-field",
+                    format!("Required field '{}' is missing", field_name),
+                ));
             }
         }
         ValidationRule::Optional => {
@@ -80,27 +163,36 @@ fn validate_field(
             if let Some(value) = value {
                 if let Some(num) = value.as_u64() {
                     if num < *min || num > *max {
-                        return Err(format!(
-                            "Field '{}' value {} out of range [{}, {}]",
-                            field_name, num, min, max
+                        return Err((
+                            "out-of-range",
+                            format!(
+                                "Field '{}' value {} out of range [{}, {}]",
+                                field_name, num, min, max
+                            ),
                         ));
                     }
                 } else {
-                    return Err(format!("Field '{}' must be a number", field_name));
+                    return Err(not_a_number(field_name));
                 }
             }
         }
         ValidationRule::Enum { values } => {
             if let Some(value) = value {
                 if let Some(string) = value.as_str() {
-                    if !values.contains(&string.to_string()) {
-                        return Err(format!(
-                            "Field '{}' value '{}' not in allowed values: {:?}",
-                            field_name, string, values
+                    if !values.iter().any(|v| v == string) {
+                        return Err((
+                            "invalid-enum",
+                            format!(
+                                "Field '{}' value '{}' not in allowed values: {:?}",
+                                field_name, string, values
+                            ),
                         ));
                     }
                 } else {
-                    return Err(format!("Field '{}' must be a string", field_name));
+                    return Err((
+                        "type-mismatch",
+                        format!("Field '{}' must be a string", field_name),
+                    ));
                 }
             }
         }
@@ -108,13 +200,13 @@ fn validate_field(
             if let Some(value) = value {
                 if let Some(num) = value.as_u64() {
                     if num == 0 || !num.is_power_of_two() {
-                        return Err(format!(
-                            "Field '{}' value {} must be power of 2",
-                            field_name, num
+                        return Err((
+                            "not-power-of-two",
+                            format!("Field '{}' value {} must be power of 2", field_name, num),
                         ));
                     }
                 } else {
-                    return Err(format!("Field '{}' must be a number", field_name));
+                    return Err(not_a_number(field_name));
                 }
             }
         }
@@ -122,10 +214,13 @@ fn validate_field(
             if let Some(value) = value {
                 if let Some(num) = value.as_u64() {
                     if num == 0 {
-                        return Err(format!("Field '{}' must be non-zero", field_name));
+                        return Err((
+                            "zero-value",
+                            format!("Field '{}' must be non-zero", field_name),
+                        ));
                     }
                 } else {
-                    return Err(format!("Field '{}' must be a number", field_name));
+                    return Err(not_a_number(field_name));
                 }
             }
         }
@@ -133,173 +228,1575 @@ fn validate_field(
     Ok(())
 }
 
-/// Validate buffer descriptor
-pub fn validate_buffer_descriptor(descriptor_json: String) -> DescriptorValidationResult {
-    let descriptor: Value = match serde_json::from_str(&descriptor_json) {
-        Ok(d) => d,
-        Err(e) => return DescriptorValidationResult::with_error(format!("Invalid JSON: {}", e)),
-    };
+fn not_a_number(field_name: &str) -> (&'static str, String) {
+    (
+        "type-mismatch",
+        format!("Field '{}' must be a non-negative integer", field_name),
+    )
+}
 
-    let obj = match descriptor.as_object() {
-        Some(o) => o,
-        None => return DescriptorValidationResult::with_error("Descriptor must be an object".to_string()),
-    };
+fn enum_rule(values: &[&str]) -> ValidationRule {
+    ValidationRule::Enum {
+        values: values.iter().map(|v| v.to_string()).collect(),
+    }
+}
+
+const COMPARE_FUNCTIONS: &[&str] = &[
+    "never", "less", "equal", "less-equal", "greater", "not-equal", "greater-equal", "always",
+];
+const TEXTURE_VIEW_DIMENSIONS: &[&str] = &["1d", "2d", "2d-array", "cube", "cube-array", "3d"];
+
+// GPUBufferUsage
+const BUFFER_USAGE_MAP_READ: u64 = 0x0001;
+const BUFFER_USAGE_MAP_WRITE: u64 = 0x0002;
+const BUFFER_USAGE_COPY_SRC: u64 = 0x0004;
+const BUFFER_USAGE_COPY_DST: u64 = 0x0008;
+const BUFFER_USAGE_ALL: u64 = 0x03FF;
+
+// GPUTextureUsage
+const TEXTURE_USAGE_STORAGE_BINDING: u64 = 0x08;
+const TEXTURE_USAGE_ALL: u64 = 0x1F;
+
+// GPUShaderStage
+const SHADER_STAGE_VERTEX: u64 = 0x1;
+const SHADER_STAGE_FRAGMENT: u64 = 0x2;
+const SHADER_STAGE_COMPUTE: u64 = 0x4;
+const SHADER_STAGE_ALL: u64 = 0x7;
+
+/// Maximum `count` of a query set, fixed by the spec
+const MAX_QUERY_SET_COUNT: u64 = 4096;
+/// Collects diagnostics for one descriptor, tagging them with a default spec section
+struct Checker<'a> {
+    result: DescriptorValidationResult,
+    anchor: &'static str,
+    profile: &'a LimitsProfile,
+}
+
+impl<'a> Checker<'a> {
+    fn new(anchor: &'static str, profile: &'a LimitsProfile) -> Self {
+        Self {
+            result: DescriptorValidationResult::ok(),
+            anchor,
+            profile,
+        }
+    }
+
+    fn limits(&self) -> &'a DeviceLimits {
+        &self.profile.limits
+    }
+
+    /// Report `value` above the named limit of the active profile
+    fn limit(&mut self, path: String, what: &str, value: u64, limit: &str, max: u64) {
+        if value > max {
+            let message = format!(
+                "{} {} exceeds {} {} of profile '{}'",
+                what, value, limit, max, self.profile.name
+            );
+            self.error_at(path, "limit-exceeded", message, "limits");
+        }
+    }
+
+    fn error(&mut self, path: String, code: &str, message: String) {
+        let anchor = self.anchor;
+        self.result.push(path, code, Severity::Error, message, anchor);
+    }
+
+    fn error_at(&mut self, path: String, code: &str, message: String, anchor: &str) {
+        self.result.push(path, code, Severity::Error, message, anchor);
+    }
+
+    fn warning(&mut self, path: String, code: &str, message: String) {
+        let anchor = self.anchor;
+        self.result.push(path, code, Severity::Warning, message, anchor);
+    }
+
+    /// Apply a rule to `obj[key]`; returns whether the rule held
+    fn rule(&mut self, obj: &Map<String, Value>, path: &str, key: &str, rule: &ValidationRule) -> bool {
+        match validate_field(key, obj.get(key), rule) {
+            Ok(()) => true,
+            Err((code, message)) => {
+                self.error(json_pointer(path, key), code, message);
+                false
+            }
+        }
+    }
+
+    fn required(&mut self, obj: &Map<String, Value>, path: &str, key: &str) -> bool {
+        self.rule(obj, path, key, &ValidationRule::Required)
+    }
+
+    fn one_of(&mut self, obj: &Map<String, Value>, path: &str, key: &str, values: &[&str]) {
+        self.rule(obj, path, key, &enum_rule(values));
+    }
+
+    /// `obj[key]` as an unsigned integer; reports a type error when it is something else
+    fn uint(&mut self, obj: &Map<String, Value>, path: &str, key: &str) -> Option<u64> {
+        let value = obj.get(key)?;
+        match value.as_u64() {
+            Some(num) => Some(num),
+            None => {
+                let (code, message) = not_a_number(key);
+                self.error(json_pointer(path, key), code, message);
+                None
+            }
+        }
+    }
+
+    /// `obj[key]` as a number of any sign
+    fn number(&mut self, obj: &Map<String, Value>, path: &str, key: &str) -> Option<f64> {
+        let value = obj.get(key)?;
+        match value.as_f64() {
+            Some(num) => Some(num),
+            None => {
+                self.error(
+                    json_pointer(path, key),
+                    "type-mismatch",
+                    format!("Field '{}' must be a number", key),
+                );
+                None
+            }
+        }
+    }
+
+    fn boolean(&mut self, obj: &Map<String, Value>, path: &str, key: &str) -> Option<bool> {
+        let value = obj.get(key)?;
+        match value.as_bool() {
+            Some(flag) => Some(flag),
+            None => {
+                self.error(
+                    json_pointer(path, key),
+                    "type-mismatch",
+                    format!("Field '{}' must be a boolean", key),
+                );
+                None
+            }
+        }
+    }
+
+    /// `obj[key]` as an object; `required` adds a missing-field error
+    fn object<'v>(
+        &mut self,
+        obj: &'v Map<String, Value>,
+        path: &str,
+        key: &str,
+        required: bool,
+    ) -> Option<&'v Map<String, Value>> {
+        match obj.get(key) {
+            None => {
+                if required {
+                    self.required(obj, path, key);
+                }
+                None
+            }
+            Some(value) => {
+                let inner = value.as_object();
+                if inner.is_none() {
+                    self.error(
+                        json_pointer(path, key),
+                        "type-mismatch",
+                        format!("Field '{}' must be an object", key),
+                    );
+                }
+                inner
+            }
+        }
+    }
 
-    let mut result = DescriptorValidationResult::ok();
+    /// `obj[key]` as an array; `required` adds a missing-field error
+    fn array<'v>(
+        &mut self,
+        obj: &'v Map<String, Value>,
+        path: &str,
+        key: &str,
+        required: bool,
+    ) -> Option<&'v Vec<Value>> {
+        match obj.get(key) {
+            None => {
+                if required {
+                    self.required(obj, path, key);
+                }
+                None
+            }
+            Some(value) => {
+                let inner = value.as_array();
+                if inner.is_none() {
+                    self.error(
+                        json_pointer(path, key),
+                        "type-mismatch",
+                        format!("Field '{}' must be an array", key),
+                    );
+                }
+                inner
+            }
+        }
+    }
 
-    // Validate size field
-    if let Err(e) = validate_field("size", obj.get("size"), &ValidationRule::Required) {
-        result.add_error(e);
+    /// Check that a bit-flag field only uses known bits
+    fn flags(&mut self, obj: &Map<String, Value>, path: &str, key: &str, known: u64, what: &str) -> Option<u64> {
+        let bits = self.uint(obj, path, key)?;
+        if bits & !known != 0 {
+            self.error(
+                json_pointer(path, key),
+                "unknown-flags",
+                format!("Field '{}' has bits 0x{:x} that are not valid {} flags", key, bits & !known, what),
+            );
+        }
+        Some(bits)
     }
-    if let Err(e) = validate_field("size", obj.get("size"), &ValidationRule::NonZero) {
-        result.add_error(e);
+
+    fn finish(self) -> DescriptorValidationResult {
+        self.result
     }
+}
+
+/// Parse a descriptor that must be a JSON object
+fn parse_descriptor(descriptor_json: &str, anchor: &str) -> Result<Map<String, Value>, DescriptorValidationResult> {
+    let descriptor: Value = serde_json::from_str(descriptor_json).map_err(|e| {
+        DescriptorValidationResult::with_error("invalid-json", format!("Invalid JSON: {}", e), anchor)
+    })?;
 
-    // Validate usage field
-    if let Err(e) = validate_field("usage", obj.get("usage"), &ValidationRule::Required) {
-        result.add_error(e);
+    match descriptor {
+        Value::Object(obj) => Ok(obj),
+        _ => Err(DescriptorValidationResult::with_error(
+            "not-an-object",
+            "Descriptor must be an object".to_string(),
+            anchor,
+        )),
     }
+}
+
+/// Run a descriptor check over parsed JSON
+fn run(
+    descriptor_json: &str,
+    anchor: &'static str,
+    profile: &LimitsProfile,
+    check: impl FnOnce(&mut Checker, &Map<String, Value>),
+) -> DescriptorValidationResult {
+    match parse_descriptor(descriptor_json, anchor) {
+        Ok(obj) => {
+            let mut checker = Checker::new(anchor, profile);
+            check(&mut checker, &obj);
+            checker.finish()
+        }
+        Err(result) => result,
+    }
+}
 
-    result
+/// Validate buffer descriptor
+pub fn validate_buffer_descriptor(
+    descriptor_json: String,
+    profile: &LimitsProfile,
+) -> DescriptorValidationResult {
+    run(&descriptor_json, "dom-gpudevice-createbuffer", profile, check_buffer)
+}
+
+fn check_buffer(c: &mut Checker, obj: &Map<String, Value>) {
+    c.required(obj, "", "size");
+    let size = c.uint(obj, "", "size");
+    if size == Some(0) {
+        c.warning(
+            "/size".to_string(),
+            "zero-size",
+            "Buffer size is 0; the buffer cannot hold any data".to_string(),
+        );
+    }
+    if let Some(size) = size {
+        let max = c.limits().max_buffer_size;
+        c.limit("/size".to_string(), "Buffer size", size, "maxBufferSize", max);
+    }
+
+    c.required(obj, "", "usage");
+    if let Some(usage) = c.flags(obj, "", "usage", BUFFER_USAGE_ALL, "GPUBufferUsage") {
+        if usage == 0 {
+            c.error("/usage".to_string(), "zero-value", "Field 'usage' must be non-zero".to_string());
+        }
+        if usage & BUFFER_USAGE_MAP_READ != 0 && usage & !(BUFFER_USAGE_MAP_READ | BUFFER_USAGE_COPY_DST) != 0 {
+            c.error(
+                "/usage".to_string(),
+                "invalid-usage",
+                "MAP_READ may only be combined with COPY_DST".to_string(),
+            );
+        }
+        if usage & BUFFER_USAGE_MAP_WRITE != 0 && usage & !(BUFFER_USAGE_MAP_WRITE | BUFFER_USAGE_COPY_SRC) != 0 {
+            c.error(
+                "/usage".to_string(),
+                "invalid-usage",
+                "MAP_WRITE may only be combined with COPY_SRC".to_string(),
+            );
+        }
+    }
+
+    if c.boolean(obj, "", "mappedAtCreation") == Some(true) {
+        if let Some(size) = size {
+            if size % 4 != 0 {
+                c.error_at(
+                    "/size".to_string(),
+                    "size-alignment",
+                    format!("Buffers mapped at creation need a size that is a multiple of 4, got {}", size),
+                    "dom-gpubufferdescriptor-mappedatcreation",
+                );
+            }
+        }
+    }
 }
 
 /// Validate texture descriptor
-pub fn validate_texture_descriptor(descriptor_json: String) -> DescriptorValidationResult {
-    let descriptor: Value = match serde_json::from_str(&descriptor_json) {
-        Ok(d) => d,
-        Err(e) => return DescriptorValidationResult::with_error(format!("Invalid JSON: {}", e)),
-    };
+pub fn validate_texture_descriptor(
+    descriptor_json: String,
+    profile: &LimitsProfile,
+) -> DescriptorValidationResult {
+    run(&descriptor_json, "abstract-opdef-validating-gputexturedescriptor", profile, check_texture)
+}
 
-    let obj = match descriptor.as_object() {
-        Some(o) => o,
-        None => return DescriptorValidationResult::with_error("Descriptor must be an object".to_string()),
-    };
+/// Texture extent in either sequence (`[w, h, d]`) or dictionary form
+fn check_extent(c: &mut Checker, value: &Value, path: &str) -> Option<[u64; 3]> {
+    let mut extent = [0u64, 1, 1];
+    match value {
+        Value::Array(items) => {
+            if items.is_empty() || items.len() > 3 {
+                c.error(
+                    path.to_string(),
+                    "invalid-extent",
+                    format!("Extent sequence must have 1 to 3 elements, got {}", items.len()),
+                );
+                return None;
+            }
+            for (i, item) in items.iter().enumerate() {
+                match item.as_u64() {
+                    Some(v) => extent[i] = v,
+                    None => {
+                        c.error(
+                            json_pointer_index(path, i),
+                            "type-mismatch",
+                            "Extent elements must be non-negative integers".to_string(),
+                        );
+                        return None;
+                    }
+                }
+            }
+            for (i, v) in extent.iter().enumerate().take(items.len()) {
+                if *v == 0 {
+                    c.error(json_pointer_index(path, i), "zero-value", "Extent elements must be non-zero".to_string());
+                }
+            }
+        }
+        Value::Object(size) => {
+            c.required(size, path, "width");
+            for (i, key) in ["width", "height", "depthOrArrayLayers"].iter().enumerate() {
+                if let Some(v) = c.uint(size, path, key) {
+                    extent[i] = v;
+                    c.rule(size, path, key, &ValidationRule::NonZero);
+                }
+            }
+        }
+        _ => {
+            c.error(
+                path.to_string(),
+                "type-mismatch",
+                "Extent must be an array or an object".to_string(),
+            );
+            return None;
+        }
+    }
+    Some(extent)
+}
+
+fn check_texture(c: &mut Checker, obj: &Map<String, Value>) {
+    c.required(obj, "", "size");
+    c.required(obj, "", "format");
+    c.required(obj, "", "usage");
 
-    let mut result = DescriptorValidationResult::ok();
+    let extent = obj.get("size").and_then(|size| check_extent(c, size, "/size"));
 
-    // Validate size field
-    if let Err(e) = validate_field("size", obj.get("size"), &ValidationRule::Required) {
-        result.add_error(e);
+    if let Some(format) = obj.get("format") {
+        if !format.is_string() {
+            c.error("/format".to_string(), "type-mismatch", "Field 'format' must be a string".to_string());
+        }
     }
 
-    // Validate format field
-    if let Err(e) = validate_field("format", obj.get("format"), &ValidationRule::Required) {
-        result.add_error(e);
+    let usage = c.flags(obj, "", "usage", TEXTURE_USAGE_ALL, "GPUTextureUsage");
+    if usage == Some(0) {
+        c.error("/usage".to_string(), "zero-value", "Field 'usage' must be non-zero".to_string());
     }
 
-    // Validate usage field
-    if let Err(e) = validate_field("usage", obj.get("usage"), &ValidationRule::Required) {
-        result.add_error(e);
+    c.one_of(obj, "", "dimension", &["1d", "2d", "3d"]);
+    let dimension = obj.get("dimension").and_then(Value::as_str).unwrap_or("2d");
+
+    let mip_level_count = c.uint(obj, "", "mipLevelCount").unwrap_or(1);
+    if mip_level_count == 0 {
+        c.error("/mipLevelCount".to_string(), "zero-value", "Field 'mipLevelCount' must be non-zero".to_string());
     }
 
-    // Validate size.width and size.height if size is present
-    if let Some(size) = obj.get("size") {
-        if let Some(size_obj) = size.as_object() {
-            if let Err(e) = validate_field("size.width", size_obj.get("width"), &ValidationRule::NonZero) {
-                result.add_error(e);
+    let sample_count = c.uint(obj, "", "sampleCount").unwrap_or(1);
+    if sample_count != 1 && sample_count != 4 {
+        c.error(
+            "/sampleCount".to_string(),
+            "invalid-sample-count",
+            format!("Field 'sampleCount' must be 1 or 4, got {}", sample_count),
+        );
+    }
+
+    if let Some([width, height, depth]) = extent {
+        let limits = c.limits();
+        let (max_dimension, limit_name) = match dimension {
+            "1d" => (limits.max_texture_dimension_1d, "maxTextureDimension1D"),
+            "3d" => (limits.max_texture_dimension_3d, "maxTextureDimension3D"),
+            _ => (limits.max_texture_dimension_2d, "maxTextureDimension2D"),
+        };
+        let max_layers = limits.max_texture_array_layers as u64;
+        c.limit("/size".to_string(), "Texture width", width, limit_name, max_dimension as u64);
+        if dimension != "1d" {
+            c.limit("/size".to_string(), "Texture height", height, limit_name, max_dimension as u64);
+        }
+        if dimension == "3d" {
+            c.limit("/size".to_string(), "Texture depth", depth, limit_name, max_dimension as u64);
+        } else if dimension == "2d" {
+            c.limit("/size".to_string(), "Texture array layer count", depth, "maxTextureArrayLayers", max_layers);
+        }
+
+        match dimension {
+            "1d" => {
+                if height != 1 || depth != 1 {
+                    c.error(
+                        "/size".to_string(),
+                        "invalid-extent",
+                        "1D textures must have height and depthOrArrayLayers of 1".to_string(),
+                    );
+                }
+                if mip_level_count > 1 {
+                    c.error(
+                        "/mipLevelCount".to_string(),
+                        "mip-level-count",
+                        "1D textures cannot have mipmaps".to_string(),
+                    );
+                }
+            }
+            _ => {
+                let largest = if dimension == "3d" {
+                    width.max(height).max(depth)
+                } else {
+                    width.max(height)
+                };
+                let max_mips = 64 - largest.max(1).leading_zeros() as u64;
+                if mip_level_count > max_mips {
+                    c.error(
+                        "/mipLevelCount".to_string(),
+                        "mip-level-count",
+                        format!(
+                            "mipLevelCount {} exceeds the maximum of {} for this size",
+                            mip_level_count, max_mips
+                        ),
+                    );
+                }
+            }
+        }
+
+        if sample_count > 1 {
+            if dimension != "2d" {
+                c.error(
+                    "/sampleCount".to_string(),
+                    "multisample-dimension",
+                    "Multisampled textures must be 2D".to_string(),
+                );
             }
-            if let Err(e) = validate_field("size.height", size_obj.get("height"), &ValidationRule::NonZero) {
-                result.add_error(e);
+            if depth != 1 {
+                c.error(
+                    "/size".to_string(),
+                    "multisample-array",
+                    "Multisampled textures cannot have array layers".to_string(),
+                );
             }
         }
     }
 
-    result
+    if sample_count > 1 {
+        if mip_level_count != 1 {
+            c.error(
+                "/mipLevelCount".to_string(),
+                "mip-level-count",
+                "Multisampled textures must have a single mip level".to_string(),
+            );
+        }
+        if usage.map(|u| u & TEXTURE_USAGE_STORAGE_BINDING != 0).unwrap_or(false) {
+            c.error(
+                "/usage".to_string(),
+                "invalid-usage",
+                "Multisampled textures cannot have STORAGE_BINDING usage".to_string(),
+            );
+        }
+    }
+
+    if let Some(view_formats) = c.array(obj, "", "viewFormats", false) {
+        for (i, format) in view_formats.iter().enumerate() {
+            if !format.is_string() {
+                c.error(
+                    json_pointer_index("/viewFormats", i),
+                    "type-mismatch",
+                    "View formats must be strings".to_string(),
+                );
+            }
+        }
+    }
 }
 
-/// Validate render pipeline descriptor
-pub fn validate_render_pipeline_descriptor(descriptor_json: String) -> DescriptorValidationResult {
-    let descriptor: Value = match serde_json::from_str(&descriptor_json) {
-        Ok(d) => d,
-        Err(e) => return DescriptorValidationResult::with_error(format!("Invalid JSON: {}", e)),
-    };
+/// Validate sampler descriptor
+pub fn validate_sampler_descriptor(
+    descriptor_json: String,
+    profile: &LimitsProfile,
+) -> DescriptorValidationResult {
+    run(&descriptor_json, "dom-gpudevice-createsampler", profile, check_sampler)
+}
+
+fn check_sampler(c: &mut Checker, obj: &Map<String, Value>) {
+    for key in ["addressModeU", "addressModeV", "addressModeW"] {
+        c.one_of(obj, "", key, &["clamp-to-edge", "repeat", "mirror-repeat"]);
+    }
+    for key in ["magFilter", "minFilter"] {
+        c.one_of(obj, "", key, &["nearest", "linear"]);
+    }
+    c.one_of(obj, "", "mipmapFilter", &["nearest", "linear"]);
+    c.one_of(obj, "", "compare", COMPARE_FUNCTIONS);
+
+    let lod_min = c.number(obj, "", "lodMinClamp").unwrap_or(0.0);
+    let lod_max = c.number(obj, "", "lodMaxClamp").unwrap_or(32.0);
+    if lod_min < 0.0 {
+        c.error(
+            "/lodMinClamp".to_string(),
+            "out-of-range",
+            format!("lodMinClamp must be non-negative, got {}", lod_min),
+        );
+    }
+    if lod_max < lod_min {
+        c.error(
+            "/lodMaxClamp".to_string(),
+            "out-of-range",
+            format!("lodMaxClamp {} is less than lodMinClamp {}", lod_max, lod_min),
+        );
+    }
+
+    if let Some(anisotropy) = c.uint(obj, "", "maxAnisotropy") {
+        c.rule(obj, "", "maxAnisotropy", &ValidationRule::Range { min: 1, max: 65535 });
+        if anisotropy > 1 {
+            let all_linear = ["magFilter", "minFilter", "mipmapFilter"]
+                .iter()
+                .all(|key| obj.get(*key).and_then(Value::as_str) == Some("linear"));
+            if !all_linear {
+                c.error(
+                    "/maxAnisotropy".to_string(),
+                    "anisotropy-filter",
+                    "maxAnisotropy > 1 requires magFilter, minFilter and mipmapFilter to be 'linear'".to_string(),
+                );
+            }
+            if anisotropy > 16 {
+                c.warning(
+                    "/maxAnisotropy".to_string(),
+                    "anisotropy-clamped",
+                    format!("maxAnisotropy {} is clamped to 16 by all implementations", anisotropy),
+                );
+            }
+        }
+    }
+}
+
+/// Validate bind group layout descriptor
+pub fn validate_bind_group_layout_descriptor(
+    descriptor_json: String,
+    profile: &LimitsProfile,
+) -> DescriptorValidationResult {
+    run(&descriptor_json, "dom-gpudevice-createbindgrouplayout", profile, check_bind_group_layout)
+}
+
+const BINDING_RESOURCE_KINDS: &[&str] = &["buffer", "sampler", "texture", "storageTexture", "externalTexture"];
 
-    let obj = match descriptor.as_object() {
-        Some(o) => o,
-        None => return DescriptorValidationResult::with_error("Descriptor must be an object".to_string()),
+fn check_bind_group_layout(c: &mut Checker, obj: &Map<String, Value>) {
+    let Some(entries) = c.array(obj, "", "entries", true) else {
+        return;
     };
 
-    let mut result = DescriptorValidationResult::ok();
+    let mut seen: HashMap<u64, usize> = HashMap::new();
+    let mut usage = BindingUsage::default();
+    for (i, entry) in entries.iter().enumerate() {
+        let path = json_pointer_index("/entries", i);
+        let Some(entry) = entry.as_object() else {
+            c.error(path, "type-mismatch", "Bind group layout entries must be objects".to_string());
+            continue;
+        };
+
+        c.required(entry, &path, "binding");
+        if let Some(binding) = c.uint(entry, &path, "binding") {
+            if let Some(first) = seen.insert(binding, i) {
+                c.error(
+                    json_pointer(&path, "binding"),
+                    "duplicate-binding",
+                    format!("Binding {} is already used by /entries/{}", binding, first),
+                );
+            }
+            // Binding numbers are indices, so the limit is exclusive
+            let max = c.limits().max_bindings_per_bind_group as u64;
+            c.limit(json_pointer(&path, "binding"), "Binding number", binding + 1, "maxBindingsPerBindGroup", max);
+        }
+        usage.add(entry);
+
+        c.required(entry, &path, "visibility");
+        let visibility = c.flags(entry, &path, "visibility", SHADER_STAGE_ALL, "GPUShaderStage");
+        if visibility == Some(0) {
+            c.warning(
+                json_pointer(&path, "visibility"),
+                "unused-binding",
+                "Binding is not visible to any shader stage".to_string(),
+            );
+        }
+        let vertex_visible = visibility.map(|v| v & SHADER_STAGE_VERTEX != 0).unwrap_or(false);
 
-    // Validate vertex field
-    if let Err(e) = validate_field("vertex", obj.get("vertex"), &ValidationRule::Required) {
-        result.add_error(e);
+        let kinds: Vec<&str> = BINDING_RESOURCE_KINDS
+            .iter()
+            .copied()
+            .filter(|kind| entry.contains_key(*kind))
+            .collect();
+        if kinds.len() != 1 {
+            c.error(
+                path.clone(),
+                "binding-resource-kind",
+                format!(
+                    "Entry must set exactly one of {:?}, found {}",
+                    BINDING_RESOURCE_KINDS,
+                    if kinds.is_empty() { "none".to_string() } else { kinds.join(", ") }
+                ),
+            );
+        }
+
+        if let Some(buffer) = c.object(entry, &path, "buffer", false) {
+            let buffer_path = json_pointer(&path, "buffer");
+            c.one_of(buffer, &buffer_path, "type", &["uniform", "storage", "read-only-storage"]);
+            c.boolean(buffer, &buffer_path, "hasDynamicOffset");
+            c.uint(buffer, &buffer_path, "minBindingSize");
+            if vertex_visible && buffer.get("type").and_then(Value::as_str) == Some("storage") {
+                c.error(
+                    json_pointer(&buffer_path, "type"),
+                    "vertex-writable-storage",
+                    "Writable storage buffers cannot be visible to the vertex stage".to_string(),
+                );
+            }
+        }
+
+        if let Some(sampler) = c.object(entry, &path, "sampler", false) {
+            c.one_of(sampler, &json_pointer(&path, "sampler"), "type", &["filtering", "non-filtering", "comparison"]);
+        }
+
+        if let Some(texture) = c.object(entry, &path, "texture", false) {
+            let texture_path = json_pointer(&path, "texture");
+            c.one_of(
+                texture,
+                &texture_path,
+                "sampleType",
+                &["float", "unfilterable-float", "depth", "sint", "uint"],
+            );
+            c.one_of(texture, &texture_path, "viewDimension", TEXTURE_VIEW_DIMENSIONS);
+            if c.boolean(texture, &texture_path, "multisampled") == Some(true) {
+                let view_dimension = texture.get("viewDimension").and_then(Value::as_str).unwrap_or("2d");
+                if view_dimension != "2d" {
+                    c.error(
+                        json_pointer(&texture_path, "viewDimension"),
+                        "multisample-dimension",
+                        "Multisampled texture bindings must use viewDimension '2d'".to_string(),
+                    );
+                }
+                if texture.get("sampleType").and_then(Value::as_str) == Some("float") {
+                    c.error(
+                        json_pointer(&texture_path, "sampleType"),
+                        "multisample-filterable",
+                        "Multisampled texture bindings cannot use sampleType 'float'".to_string(),
+                    );
+                }
+            }
+        }
+
+        if let Some(storage) = c.object(entry, &path, "storageTexture", false) {
+            let storage_path = json_pointer(&path, "storageTexture");
+            c.required(storage, &storage_path, "format");
+            c.one_of(storage, &storage_path, "access", &["write-only", "read-only", "read-write"]);
+            c.one_of(storage, &storage_path, "viewDimension", TEXTURE_VIEW_DIMENSIONS);
+            if let Some(dimension @ ("cube" | "cube-array")) = storage.get("viewDimension").and_then(Value::as_str) {
+                c.error(
+                    json_pointer(&storage_path, "viewDimension"),
+                    "invalid-view-dimension",
+                    format!("Storage textures cannot use viewDimension '{}'", dimension),
+                );
+            }
+            let access = storage.get("access").and_then(Value::as_str).unwrap_or("write-only");
+            if vertex_visible && access != "read-only" {
+                c.error(
+                    json_pointer(&storage_path, "access"),
+                    "vertex-writable-storage",
+                    "Writable storage textures cannot be visible to the vertex stage".to_string(),
+                );
+            }
+        }
     }
 
-    // Validate vertex.module if vertex is present
-    if let Some(vertex) = obj.get("vertex") {
-        if let Some(vertex_obj) = vertex.as_object() {
-            if let Err(e) = validate_field("vertex.module", vertex_obj.get("module"), &ValidationRule::Required) {
-                result.add_error(e);
+    usage.check(c, "/entries");
+}
+
+/// Per-stage binding counts of one or more bind group layouts
+#[derive(Default)]
+pub(crate) struct BindingUsage {
+    /// Indexed by stage: vertex, fragment, compute
+    uniform_buffers: [u64; 3],
+    storage_buffers: [u64; 3],
+    samplers: [u64; 3],
+    sampled_textures: [u64; 3],
+    storage_textures: [u64; 3],
+    dynamic_uniform_buffers: u64,
+    dynamic_storage_buffers: u64,
+}
+
+const STAGE_NAMES: [&str; 3] = ["vertex", "fragment", "compute"];
+
+impl BindingUsage {
+    /// Count one bind group layout entry; malformed entries are ignored
+    pub(crate) fn add(&mut self, entry: &Map<String, Value>) {
+        let visibility = entry.get("visibility").and_then(Value::as_u64).unwrap_or(0);
+        let counter = if let Some(buffer) = entry.get("buffer").and_then(Value::as_object) {
+            let uniform = buffer.get("type").and_then(Value::as_str).unwrap_or("uniform") == "uniform";
+            if buffer.get("hasDynamicOffset").and_then(Value::as_bool) == Some(true) {
+                if uniform {
+                    self.dynamic_uniform_buffers += 1;
+                } else {
+                    self.dynamic_storage_buffers += 1;
+                }
+            }
+            if uniform {
+                &mut self.uniform_buffers
+            } else {
+                &mut self.storage_buffers
             }
-            if let Err(e) = validate_field("vertex.entryPoint", vertex_obj.get("entryPoint"), &ValidationRule::Required) {
-                result.add_error(e);
+        } else if entry.contains_key("sampler") {
+            &mut self.samplers
+        } else if entry.contains_key("texture") || entry.contains_key("externalTexture") {
+            &mut self.sampled_textures
+        } else if entry.contains_key("storageTexture") {
+            &mut self.storage_textures
+        } else {
+            return;
+        };
+        for (stage, flag) in [SHADER_STAGE_VERTEX, SHADER_STAGE_FRAGMENT, SHADER_STAGE_COMPUTE].iter().enumerate() {
+            if visibility & flag != 0 {
+                counter[stage] += 1;
             }
         }
     }
 
-    result
+    /// Check the combined counts of a pipeline layout against `profile`
+    pub(crate) fn validate(&self, profile: &LimitsProfile, path: &str) -> DescriptorValidationResult {
+        let mut checker = Checker::new("limits", profile);
+        self.check(&mut checker, path);
+        checker.finish()
+    }
+
+    /// Report every count above the profile's per-stage and dynamic-offset limits
+    fn check(&self, c: &mut Checker, path: &str) {
+        let limits = c.limits();
+        let per_stage = [
+            ("uniform buffers", "maxUniformBuffersPerShaderStage", &self.uniform_buffers, limits.max_uniform_buffers_per_shader_stage),
+            ("storage buffers", "maxStorageBuffersPerShaderStage", &self.storage_buffers, limits.max_storage_buffers_per_shader_stage),
+            ("samplers", "maxSamplersPerShaderStage", &self.samplers, limits.max_samplers_per_shader_stage),
+            ("sampled textures", "maxSampledTexturesPerShaderStage", &self.sampled_textures, limits.max_sampled_textures_per_shader_stage),
+            ("storage textures", "maxStorageTexturesPerShaderStage", &self.storage_textures, limits.max_storage_textures_per_shader_stage),
+        ];
+        for (what, limit_name, counts, max) in per_stage {
+            for (stage, count) in counts.iter().enumerate() {
+                let what = format!("Number of {} visible to the {} stage", what, STAGE_NAMES[stage]);
+                c.limit(path.to_string(), &what, *count, limit_name, max as u64);
+            }
+        }
+        c.limit(
+            path.to_string(),
+            "Number of dynamic uniform buffers",
+            self.dynamic_uniform_buffers,
+            "maxDynamicUniformBuffersPerPipelineLayout",
+            limits.max_dynamic_uniform_buffers_per_pipeline_layout as u64,
+        );
+        c.limit(
+            path.to_string(),
+            "Number of dynamic storage buffers",
+            self.dynamic_storage_buffers,
+            "maxDynamicStorageBuffersPerPipelineLayout",
+            limits.max_dynamic_storage_buffers_per_pipeline_layout as u64,
+        );
+    }
 }
 
-/// Validate compute pipeline descriptor
-pub fn validate_compute_pipeline_descriptor(descriptor_json: String) -> DescriptorValidationResult {
-    let descriptor: Value = match serde_json::from_str(&descriptor_json) {
-        Ok(d) => d,
-        Err(e) => return DescriptorValidationResult::with_error(format!("Invalid JSON: {}", e)),
-    };
+/// Validate bind group descriptor
+pub fn validate_bind_group_descriptor(
+    descriptor_json: String,
+    profile: &LimitsProfile,
+) -> DescriptorValidationResult {
+    run(&descriptor_json, "dom-gpudevice-createbindgroup", profile, check_bind_group)
+}
 
-    let obj = match descriptor.as_object() {
-        Some(o) => o,
-        None => return DescriptorValidationResult::with_error("Descriptor must be an object".to_string()),
+fn check_bind_group(c: &mut Checker, obj: &Map<String, Value>) {
+    c.required(obj, "", "layout");
+    let Some(entries) = c.array(obj, "", "entries", true) else {
+        return;
     };
 
-    let mut result = DescriptorValidationResult::ok();
+    let mut seen: HashMap<u64, usize> = HashMap::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let path = json_pointer_index("/entries", i);
+        let Some(entry) = entry.as_object() else {
+            c.error(path, "type-mismatch", "Bind group entries must be objects".to_string());
+            continue;
+        };
+
+        c.required(entry, &path, "binding");
+        if let Some(binding) = c.uint(entry, &path, "binding") {
+            if let Some(first) = seen.insert(binding, i) {
+                c.error(
+                    json_pointer(&path, "binding"),
+                    "duplicate-binding",
+                    format!("Binding {} is already used by /entries/{}", binding, first),
+                );
+            }
+        }
 
-    // Validate compute field
-    if let Err(e) = validate_field("compute", obj.get("compute"), &ValidationRule::Required) {
-        result.add_error(e);
+        c.required(entry, &path, "resource");
+        // Buffer bindings are the only resources described inline
+        if let Some(resource) = entry.get("resource").and_then(Value::as_object) {
+            if resource.contains_key("buffer") {
+                let resource_path = json_pointer(&path, "resource");
+                c.uint(resource, &resource_path, "offset");
+                c.uint(resource, &resource_path, "size");
+                c.rule(resource, &resource_path, "size", &ValidationRule::NonZero);
+            }
+        }
     }
+}
+
+/// Validate pipeline layout descriptor
+pub fn validate_pipeline_layout_descriptor(
+    descriptor_json: String,
+    profile: &LimitsProfile,
+) -> DescriptorValidationResult {
+    run(&descriptor_json, "dom-gpudevice-createpipelinelayout", profile, check_pipeline_layout)
+}
+
+fn check_pipeline_layout(c: &mut Checker, obj: &Map<String, Value>) {
+    let Some(layouts) = c.array(obj, "", "bindGroupLayouts", true) else {
+        return;
+    };
+    let max = c.limits().max_bind_groups as u64;
+    c.limit("/bindGroupLayouts".to_string(), "Number of bind group layouts", layouts.len() as u64, "maxBindGroups", max);
+    if layouts.is_empty() {
+        c.warning(
+            "/bindGroupLayouts".to_string(),
+            "empty-layout",
+            "Pipeline layout has no bind group layouts".to_string(),
+        );
+    }
+    for (i, layout) in layouts.iter().enumerate() {
+        if layout.is_null() {
+            c.warning(
+                json_pointer_index("/bindGroupLayouts", i),
+                "null-bind-group-layout",
+                format!("Bind group {} has no layout and is treated as empty", i),
+            );
+        }
+    }
+}
+
+/// Validate render pipeline descriptor
+pub fn validate_render_pipeline_descriptor(
+    descriptor_json: String,
+    profile: &LimitsProfile,
+) -> DescriptorValidationResult {
+    run(&descriptor_json, "dom-gpudevice-createrenderpipeline", profile, check_render_pipeline)
+}
+
+/// Size in bytes of a GPUVertexFormat
+pub fn vertex_format_size(format: &str) -> Option<u64> {
+    let size = match format {
+        "uint8" | "sint8" | "unorm8" | "snorm8" => 1,
+        "uint8x2" | "sint8x2" | "unorm8x2" | "snorm8x2" | "uint16" | "sint16" | "unorm16"
+        | "snorm16" | "float16" => 2,
+        "uint8x4" | "sint8x4" | "unorm8x4" | "snorm8x4" | "unorm8x4-bgra" | "uint16x2"
+        | "sint16x2" | "unorm16x2" | "snorm16x2" | "float16x2" | "float32" | "uint32"
+        | "sint32" | "unorm10-10-10-2" => 4,
+        "uint16x4" | "sint16x4" | "unorm16x4" | "snorm16x4" | "float16x4" | "float32x2"
+        | "uint32x2" | "sint32x2" => 8,
+        "float32x3" | "uint32x3" | "sint32x3" => 12,
+        "float32x4" | "uint32x4" | "sint32x4" => 16,
+        _ => return None,
+    };
+    Some(size)
+}
 
-    // Validate compute.module if compute is present
-    if let Some(compute) = obj.get("compute") {
-        if let Some(compute_obj) = compute.as_object() {
-            if let Err(e) = validate_field("compute.module", compute_obj.get("module"), &ValidationRule::Required) {
-                result.add_error(e);
+/// Entry point and module of a programmable stage
+fn check_stage(c: &mut Checker, obj: &Map<String, Value>, key: &str) -> Option<String> {
+    let stage = c.object(obj, "", key, true)?;
+    let path = json_pointer("", key);
+    c.required(stage, &path, "module");
+    c.required(stage, &path, "entryPoint");
+    if let Some(constants) = stage.get("constants") {
+        match constants.as_object() {
+            Some(constants) => {
+                for (name, value) in constants {
+                    if !value.is_number() && !value.is_boolean() {
+                        c.error_at(
+                            json_pointer(&json_pointer(&path, "constants"), name),
+                            "type-mismatch",
+                            format!("Override constant '{}' must be a number or boolean", name),
+                            "abstract-opdef-validating-gpuprogrammablestage",
+                        );
+                    }
+                }
             }
-            if let Err(e) = validate_field("compute.entryPoint", compute_obj.get("entryPoint"), &ValidationRule::Required) {
-                result.add_error(e);
+            None => c.error(
+                json_pointer(&path, "constants"),
+                "type-mismatch",
+                "Field 'constants' must be an object".to_string(),
+            ),
+        }
+    }
+    Some(path)
+}
+
+fn check_render_pipeline(c: &mut Checker, obj: &Map<String, Value>) {
+    if check_stage(c, obj, "vertex").is_some() {
+        if let Some(vertex) = obj.get("vertex").and_then(Value::as_object) {
+            check_vertex_buffers(c, vertex);
+        }
+    }
+
+    if let Some(primitive) = c.object(obj, "", "primitive", false) {
+        const ANCHOR: &str = "abstract-opdef-validating-gpuprimitivestate";
+        c.one_of(
+            primitive,
+            "/primitive",
+            "topology",
+            &["point-list", "line-list", "line-strip", "triangle-list", "triangle-strip"],
+        );
+        c.one_of(primitive, "/primitive", "stripIndexFormat", &["uint16", "uint32"]);
+        c.one_of(primitive, "/primitive", "frontFace", &["ccw", "cw"]);
+        c.one_of(primitive, "/primitive", "cullMode", &["none", "front", "back"]);
+        let topology = primitive.get("topology").and_then(Value::as_str).unwrap_or("triangle-list");
+        if primitive.contains_key("stripIndexFormat") && !topology.ends_with("-strip") {
+            c.error_at(
+                "/primitive/stripIndexFormat".to_string(),
+                "strip-index-format",
+                format!("stripIndexFormat is only allowed with strip topologies, not '{}'", topology),
+                ANCHOR,
+            );
+        }
+    }
+
+    if let Some(multisample) = c.object(obj, "", "multisample", false) {
+        const ANCHOR: &str = "abstract-opdef-validating-gpumultisamplestate";
+        let sample_count = c.uint(multisample, "/multisample", "count").unwrap_or(1);
+        if sample_count != 1 && sample_count != 4 {
+            c.error_at(
+                "/multisample/count".to_string(),
+                "invalid-sample-count",
+                format!("Multisample count must be 1 or 4, got {}", sample_count),
+                ANCHOR,
+            );
+        }
+        c.uint(multisample, "/multisample", "mask");
+        if c.boolean(multisample, "/multisample", "alphaToCoverageEnabled") == Some(true) && sample_count == 1 {
+            c.error_at(
+                "/multisample/alphaToCoverageEnabled".to_string(),
+                "alpha-to-coverage",
+                "alphaToCoverageEnabled requires a multisample count greater than 1".to_string(),
+                ANCHOR,
+            );
+        }
+    }
+
+    let has_fragment = obj.contains_key("fragment");
+    if has_fragment {
+        if let Some(path) = check_stage(c, obj, "fragment") {
+            if let Some(fragment) = obj.get("fragment").and_then(Value::as_object) {
+                check_color_targets(c, fragment, &path);
             }
         }
     }
 
-    result
+    if let Some(depth_stencil) = c.object(obj, "", "depthStencil", false) {
+        const ANCHOR: &str = "abstract-opdef-validating-gpudepthstencilstate";
+        c.required(depth_stencil, "/depthStencil", "format");
+        c.boolean(depth_stencil, "/depthStencil", "depthWriteEnabled");
+        c.one_of(depth_stencil, "/depthStencil", "depthCompare", COMPARE_FUNCTIONS);
+        if let Some(format) = depth_stencil.get("format").and_then(Value::as_str) {
+            if !format.starts_with("depth") && format != "stencil8" {
+                c.error_at(
+                    "/depthStencil/format".to_string(),
+                    "invalid-format",
+                    format!("'{}' is not a depth or stencil format", format),
+                    ANCHOR,
+                );
+            }
+        }
+    } else if !has_fragment {
+        c.warning(
+            String::new(),
+            "no-outputs",
+            "Pipeline has neither a fragment stage nor a depth/stencil state and writes nothing".to_string(),
+        );
+    }
 }
 
-/// Validate bind group layout descriptor
-pub fn validate_bind_group_layout_descriptor(descriptor_json: String) -> DescriptorValidationResult {
-    let descriptor: Value = match serde_json::from_str(&descriptor_json) {
-        Ok(d) => d,
-        Err(e) => return DescriptorValidationResult::with_error(format!("Invalid JSON: {}", e)),
+fn check_vertex_buffers(c: &mut Checker, vertex: &Map<String, Value>) {
+    const ANCHOR: &str = "abstract-opdef-validating-gpuvertexbufferlayout";
+    let Some(buffers) = c.array(vertex, "/vertex", "buffers", false) else {
+        return;
     };
+    let limits = c.limits();
+    let max_attributes = limits.max_vertex_attributes as u64;
+    c.limit(
+        "/vertex/buffers".to_string(),
+        "Number of vertex buffers",
+        buffers.len() as u64,
+        "maxVertexBuffers",
+        limits.max_vertex_buffers as u64,
+    );
 
-    let obj = match descriptor.as_object() {
-        Some(o) => o,
-        None => return DescriptorValidationResult::with_error("Descriptor must be an object".to_string()),
+    let mut attribute_count = 0;
+    let mut locations: HashMap<u64, String> = HashMap::new();
+    for (i, buffer) in buffers.iter().enumerate() {
+        let path = json_pointer_index("/vertex/buffers", i);
+        // Null entries are holes in the buffer slot list
+        let Some(buffer) = buffer.as_object() else {
+            if !buffer.is_null() {
+                c.error(path, "type-mismatch", "Vertex buffer layouts must be objects or null".to_string());
+            }
+            continue;
+        };
+
+        c.required(buffer, &path, "arrayStride");
+        let stride = c.uint(buffer, &path, "arrayStride");
+        if let Some(stride) = stride {
+            if stride % 4 != 0 {
+                c.error_at(
+                    json_pointer(&path, "arrayStride"),
+                    "stride-alignment",
+                    format!("arrayStride {} must be a multiple of 4", stride),
+                    ANCHOR,
+                );
+            }
+            let max_stride = c.limits().max_vertex_buffer_array_stride as u64;
+            c.limit(json_pointer(&path, "arrayStride"), "arrayStride", stride, "maxVertexBufferArrayStride", max_stride);
+        }
+        c.one_of(buffer, &path, "stepMode", &["vertex", "instance"]);
+
+        let Some(attributes) = c.array(buffer, &path, "attributes", true) else {
+            continue;
+        };
+        let attributes_path = json_pointer(&path, "attributes");
+        attribute_count += attributes.len() as u64;
+        for (j, attribute) in attributes.iter().enumerate() {
+            let attribute_path = json_pointer_index(&attributes_path, j);
+            let Some(attribute) = attribute.as_object() else {
+                c.error(attribute_path, "type-mismatch", "Vertex attributes must be objects".to_string());
+                continue;
+            };
+
+            c.required(attribute, &attribute_path, "format");
+            c.required(attribute, &attribute_path, "offset");
+            c.required(attribute, &attribute_path, "shaderLocation");
+
+            let format_size = match attribute.get("format").and_then(Value::as_str) {
+                Some(format) => {
+                    let size = vertex_format_size(format);
+                    if size.is_none() {
+                        c.error_at(
+                            json_pointer(&attribute_path, "format"),
+                            "invalid-enum",
+                            format!("'{}' is not a vertex format", format),
+                            ANCHOR,
+                        );
+                    }
+                    size
+                }
+                None => None,
+            };
+
+            if let Some(offset) = c.uint(attribute, &attribute_path, "offset") {
+                if let Some(size) = format_size {
+                    if offset % size.min(4) != 0 {
+                        c.error_at(
+                            json_pointer(&attribute_path, "offset"),
+                            "attribute-alignment",
+                            format!("Attribute offset {} must be a multiple of {}", offset, size.min(4)),
+                            ANCHOR,
+                        );
+                    }
+                    if let Some(stride) = stride.filter(|s| *s != 0) {
+                        if offset + size > stride {
+                            c.error_at(
+                                json_pointer(&attribute_path, "offset"),
+                                "attribute-out-of-bounds",
+                                format!(
+                                    "Attribute ends at byte {} which is past arrayStride {}",
+                                    offset + size,
+                                    stride
+                                ),
+                                ANCHOR,
+                            );
+                        }
+                    }
+                }
+            }
+
+            if let Some(location) = c.uint(attribute, &attribute_path, "shaderLocation") {
+                let location_path = json_pointer(&attribute_path, "shaderLocation");
+                c.limit(location_path.clone(), "Shader location", location + 1, "maxVertexAttributes", max_attributes);
+                if let Some(first) = locations.get(&location) {
+                    c.error_at(
+                        location_path,
+                        "duplicate-location",
+                        format!("Shader location {} is already used by {}", location, first),
+                        "abstract-opdef-validating-gpuvertexstate",
+                    );
+                } else {
+                    locations.insert(location, attribute_path.clone());
+                }
+            }
+        }
+    }
+    c.limit(
+        "/vertex/buffers".to_string(),
+        "Number of vertex attributes",
+        attribute_count,
+        "maxVertexAttributes",
+        max_attributes,
+    );
+}
+
+fn check_color_targets(c: &mut Checker, fragment: &Map<String, Value>, path: &str) {
+    const ANCHOR: &str = "abstract-opdef-validating-gpufragmentstate";
+    let Some(targets) = c.array(fragment, path, "targets", true) else {
+        return;
     };
+    let targets_path = json_pointer(path, "targets");
+    let max = c.limits().max_color_attachments as u64;
+    c.limit(targets_path.clone(), "Number of color targets", targets.len() as u64, "maxColorAttachments", max);
+    for (i, target) in targets.iter().enumerate() {
+        let target_path = json_pointer_index(&targets_path, i);
+        let Some(target) = target.as_object() else {
+            if !target.is_null() {
+                c.error(target_path, "type-mismatch", "Color targets must be objects or null".to_string());
+            }
+            continue;
+        };
+        c.required(target, &target_path, "format");
+        if let Some(mask) = c.uint(target, &target_path, "writeMask") {
+            if mask > 0xF {
+                c.error_at(
+                    json_pointer(&target_path, "writeMask"),
+                    "unknown-flags",
+                    format!("writeMask 0x{:x} has bits outside GPUColorWrite", mask),
+                    ANCHOR,
+                );
+            }
+        }
+        if let Some(blend) = c.object(target, &target_path, "blend", false) {
+            let blend_path = json_pointer(&target_path, "blend");
+            for component in ["color", "alpha"] {
+                c.required(blend, &blend_path, component);
+                if let Some(component_obj) = c.object(blend, &blend_path, component, false) {
+                    let component_path = json_pointer(&blend_path, component);
+                    c.one_of(
+                        component_obj,
+                        &component_path,
+                        "operation",
+                        &["add", "subtract", "reverse-subtract", "min", "max"],
+                    );
+                    let operation = component_obj.get("operation").and_then(Value::as_str).unwrap_or("add");
+                    if operation == "min" || operation == "max" {
+                        for factor in ["srcFactor", "dstFactor"] {
+                            let value = component_obj.get(factor).and_then(Value::as_str);
+                            if value.is_some_and(|v| v != "one") {
+                                c.error_at(
+                                    json_pointer(&component_path, factor),
+                                    "blend-factor",
+                                    format!("Blend operation '{}' requires {} 'one'", operation, factor),
+                                    ANCHOR,
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Validate compute pipeline descriptor
+pub fn validate_compute_pipeline_descriptor(
+    descriptor_json: String,
+    profile: &LimitsProfile,
+) -> DescriptorValidationResult {
+    run(&descriptor_json, "dom-gpudevice-createcomputepipeline", profile, |c, obj| {
+        check_stage(c, obj, "compute");
+        if !obj.contains_key("layout") {
+            c.warning(
+                "/layout".to_string(),
+                "missing-field",
+                "Field 'layout' is required by the spec; use \"auto\" for an implicit layout".to_string(),
+            );
+        }
+    })
+}
+
+/// Validate render pass descriptor
+pub fn validate_render_pass_descriptor(
+    descriptor_json: String,
+    profile: &LimitsProfile,
+) -> DescriptorValidationResult {
+    run(&descriptor_json, "abstract-opdef-validating-gpurenderpassdescriptor", profile, check_render_pass)
+}
+
+fn check_render_pass(c: &mut Checker, obj: &Map<String, Value>) {
+    let mut has_attachment = false;
+
+    if let Some(colors) = c.array(obj, "", "colorAttachments", true) {
+        const ANCHOR: &str = "abstract-opdef-validating-gpurenderpasscolorattachment";
+        let max = c.limits().max_color_attachments as u64;
+        c.limit("/colorAttachments".to_string(), "Number of color attachments", colors.len() as u64, "maxColorAttachments", max);
+        for (i, attachment) in colors.iter().enumerate() {
+            let path = json_pointer_index("/colorAttachments", i);
+            let Some(attachment) = attachment.as_object() else {
+                if !attachment.is_null() {
+                    c.error(path, "type-mismatch", "Color attachments must be objects or null".to_string());
+                }
+                continue;
+            };
+            has_attachment = true;
+            c.required(attachment, &path, "view");
+            c.required(attachment, &path, "loadOp");
+            c.required(attachment, &path, "storeOp");
+            c.one_of(attachment, &path, "loadOp", &["load", "clear"]);
+            c.one_of(attachment, &path, "storeOp", &["store", "discard"]);
+            c.uint(attachment, &path, "depthSlice");
+            if let Some(clear) = attachment.get("clearValue") {
+                let well_formed = match clear {
+                    Value::Array(items) => items.len() == 4 && items.iter().all(Value::is_number),
+                    Value::Object(color) => ["r", "g", "b", "a"]
+                        .iter()
+                        .all(|k| color.get(*k).map(Value::is_number).unwrap_or(false)),
+                    _ => false,
+                };
+                if !well_formed {
+                    c.error_at(
+                        json_pointer(&path, "clearValue"),
+                        "type-mismatch",
+                        "clearValue must be [r, g, b, a] or {r, g, b, a}".to_string(),
+                        ANCHOR,
+                    );
+                }
+            }
+        }
+    }
 
-    let mut result = DescriptorValidationResult::ok();
+    if let Some(depth_stencil) = c.object(obj, "", "depthStencilAttachment", false) {
+        const ANCHOR: &str = "abstract-opdef-validating-gpurenderpassdepthstencilattachment";
+        let path = "/depthStencilAttachment";
+        has_attachment = true;
+        c.required(depth_stencil, path, "view");
+        for aspect in ["depth", "stencil"] {
+            let load_key = format!("{}LoadOp", aspect);
+            let store_key = format!("{}StoreOp", aspect);
+            c.one_of(depth_stencil, path, &load_key, &["load", "clear"]);
+            c.one_of(depth_stencil, path, &store_key, &["store", "discard"]);
+            let has_load = depth_stencil.contains_key(&load_key);
+            let has_store = depth_stencil.contains_key(&store_key);
+            let read_only = c.boolean(depth_stencil, path, &format!("{}ReadOnly", aspect)) == Some(true);
+            if read_only && (has_load || has_store) {
+                c.error_at(
+                    json_pointer(path, &format!("{}ReadOnly", aspect)),
+                    "read-only-ops",
+                    format!("{}ReadOnly attachments must not set {} or {}", aspect, load_key, store_key),
+                    ANCHOR,
+                );
+            } else if has_load != has_store {
+                c.error_at(
+                    json_pointer(path, if has_load { &store_key } else { &load_key }),
+                    "missing-field",
+                    format!("{} and {} must be set together", load_key, store_key),
+                    ANCHOR,
+                );
+            }
+        }
+        if let Some(depth_clear) = c.number(depth_stencil, path, "depthClearValue") {
+            if !(0.0..=1.0).contains(&depth_clear) {
+                c.error_at(
+                    json_pointer(path, "depthClearValue"),
+                    "out-of-range",
+                    format!("depthClearValue {} must be between 0 and 1", depth_clear),
+                    ANCHOR,
+                );
+            }
+        }
+    }
 
-    // Validate entries field
-    if let Err(e) = validate_field("entries", obj.get("entries"), &ValidationRule::Required) {
-        result.add_error(e);
+    if !has_attachment {
+        c.error(
+            "/colorAttachments".to_string(),
+            "no-attachments",
+            "Render pass needs at least one color attachment or a depth/stencil attachment".to_string(),
+        );
     }
 
-    // Validate entries is an array
-    if let Some(entries) = obj.get("entries") {
-        if !entries.is_array() {
-            result.add_error("Field 'entries' must be an array".to_string());
+    if let Some(timestamps) = c.object(obj, "", "timestampWrites", false) {
+        const ANCHOR: &str = "abstract-opdef-validate-timestampwrites";
+        let path = "/timestampWrites";
+        c.required(timestamps, path, "querySet");
+        let beginning = c.uint(timestamps, path, "beginningOfPassWriteIndex");
+        let end = c.uint(timestamps, path, "endOfPassWriteIndex");
+        if beginning.is_none() && end.is_none() {
+            c.error_at(
+                path.to_string(),
+                "missing-field",
+                "timestampWrites needs beginningOfPassWriteIndex or endOfPassWriteIndex".to_string(),
+                ANCHOR,
+            );
+        }
+        if beginning.is_some() && beginning == end {
+            c.error_at(
+                json_pointer(path, "endOfPassWriteIndex"),
+                "duplicate-query-index",
+                "Beginning and end of pass timestamps must use different query indices".to_string(),
+                ANCHOR,
+            );
         }
     }
 
-    result
+    c.uint(obj, "", "maxDrawCount");
+}
+
+/// Validate query set descriptor
+pub fn validate_query_set_descriptor(
+    descriptor_json: String,
+    profile: &LimitsProfile,
+) -> DescriptorValidationResult {
+    run(&descriptor_json, "dom-gpudevice-createqueryset", profile, |c, obj| {
+        c.required(obj, "", "type");
+        c.one_of(obj, "", "type", &["occlusion", "timestamp"]);
+        c.required(obj, "", "count");
+        c.rule(
+            obj,
+            "",
+            "count",
+            &ValidationRule::Range { min: 0, max: MAX_QUERY_SET_COUNT },
+        );
+        if obj.get("type").and_then(Value::as_str) == Some("timestamp") {
+            c.warning(
+                "/type".to_string(),
+                "requires-feature",
+                "Timestamp queries require the 'timestamp-query' feature".to_string(),
+            );
+        }
+    })
+}
+
+/// Validate a descriptor by kind name
+///
+/// Kinds: buffer, texture, sampler, bind_group_layout, bind_group, pipeline_layout,
+/// render_pipeline, compute_pipeline, render_pass, query_set
+pub fn validate_descriptor(
+    kind: &str,
+    descriptor_json: String,
+    profile: &LimitsProfile,
+) -> DescriptorValidationResult {
+    match kind {
+        "buffer" => validate_buffer_descriptor(descriptor_json, profile),
+        "texture" => validate_texture_descriptor(descriptor_json, profile),
+        "sampler" => validate_sampler_descriptor(descriptor_json, profile),
+        "bind_group_layout" => validate_bind_group_layout_descriptor(descriptor_json, profile),
+        "bind_group" => validate_bind_group_descriptor(descriptor_json, profile),
+        "pipeline_layout" => validate_pipeline_layout_descriptor(descriptor_json, profile),
+        "render_pipeline" => validate_render_pipeline_descriptor(descriptor_json, profile),
+        "compute_pipeline" => validate_compute_pipeline_descriptor(descriptor_json, profile),
+        "render_pass" => validate_render_pass_descriptor(descriptor_json, profile),
+        "query_set" => validate_query_set_descriptor(descriptor_json, profile),
+        _ => DescriptorValidationResult::with_error(
+            "unknown-descriptor-kind",
+            format!("Unknown descriptor kind '{}'", kind),
+            "gpudevice",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpu::profiles::{get_limits_profile, webgpu_default_profile, WEBGL2_PROFILE};
+
+    #[test]
+    fn test_reports_every_error_with_paths() {
+        let result = validate_render_pipeline_descriptor(
+            r#"{
+                "vertex": {"module": 1, "entryPoint": "vs", "buffers": [{
+                    "arrayStride": 6,
+                    "attributes": [
+                        {"format": "float32x3", "offset": 0, "shaderLocation": 0},
+                        {"format": "float32x2", "offset": 2, "shaderLocation": 0},
+                        {"format": "bogus", "offset": 0, "shaderLocation": 1},
+                        {"format": "float32", "offset": 0, "shaderLocation": 2},
+                        {"format": "float32", "offset": 0, "shaderLocation": 3},
+                        {"format": "float32", "offset": 0}
+                    ]
+                }]},
+                "primitive": {"topology": "triangle-list", "stripIndexFormat": "uint16"},
+                "multisample": {"count": 2, "alphaToCoverageEnabled": true},
+                "fragment": {"module": 1, "entryPoint": "fs", "targets": [{"format": "rgba8unorm", "writeMask": 99}]}
+            }"#
+            .to_string(),
+            &webgpu_default_profile(),
+        );
+
+        assert!(!result.valid);
+        assert!(result.error_count() > 5);
+        let paths: Vec<&str> = result.diagnostics.iter().map(|d| d.path.as_str()).collect();
+        assert!(paths.contains(&"/vertex/buffers/0/arrayStride"));
+        assert!(paths.contains(&"/vertex/buffers/0/attributes/1/shaderLocation"));
+        assert!(paths.contains(&"/vertex/buffers/0/attributes/2/format"));
+        assert!(paths.contains(&"/vertex/buffers/0/attributes/5/shaderLocation"));
+        assert!(paths.contains(&"/primitive/stripIndexFormat"));
+        assert!(paths.contains(&"/fragment/targets/0/writeMask"));
+        assert!(result.has_code("duplicate-location"));
+        assert!(result.has_code("attribute-out-of-bounds"));
+        assert!(result
+            .diagnostics
+            .iter()
+            .all(|d| d.spec_url.starts_with(WEBGPU_SPEC_URL)));
+    }
+
+    #[test]
+    fn test_valid_descriptors_pass() {
+        let cases = [
+            ("buffer", r#"{"size": 256, "usage": 72}"#),
+            ("texture", r#"{"size": [256, 256], "format": "rgba8unorm", "usage": 6, "mipLevelCount": 9}"#),
+            ("sampler", r#"{"magFilter": "linear", "minFilter": "linear", "mipmapFilter": "linear", "maxAnisotropy": 8}"#),
+            ("query_set", r#"{"type": "occlusion", "count": 32}"#),
+            ("pipeline_layout", r#"{"bindGroupLayouts": [1, 2]}"#),
+            ("bind_group", r#"{"layout": 1, "entries": [{"binding": 0, "resource": {"buffer": 3, "size": 64}}]}"#),
+            (
+                "render_pass",
+                r#"{"colorAttachments": [{"view": 1, "loadOp": "clear", "storeOp": "store", "clearValue": [0, 0, 0, 1]}]}"#,
+            ),
+        ];
+        for (kind, json) in cases {
+            let result = validate_descriptor(kind, json.to_string(), &webgpu_default_profile());
+            assert!(result.valid, "{}: {:?}", kind, result.diagnostics);
+            assert_eq!(result.warning_count(), 0, "{}: {:?}", kind, result.diagnostics);
+        }
+    }
+
+    #[test]
+    fn test_buffer_usage_rules() {
+        // MAP_READ | STORAGE
+        let result = validate_buffer_descriptor(r#"{"size": 6, "usage": 129, "mappedAtCreation": true}"#.to_string(), &webgpu_default_profile());
+        assert!(result.has_code("invalid-usage"));
+        assert!(result.has_code("size-alignment"));
+        assert_eq!(result.error_count(), 2);
+
+        let result = validate_buffer_descriptor(r#"{"size": 0, "usage": 9}"#.to_string(), &webgpu_default_profile());
+        assert!(result.valid);
+        assert!(result.has_code("zero-size"));
+    }
+
+    #[test]
+    fn test_texture_rules() {
+        let result = validate_texture_descriptor(
+            r#"{"size": {"width": 64, "height": 64}, "format": "rgba8unorm", "usage": 8, "sampleCount": 4, "mipLevelCount": 8}"#
+                .to_string(),
+            &webgpu_default_profile(),
+        );
+        assert!(result.has_code("invalid-usage"));
+        let mip_errors = result.diagnostics.iter().filter(|d| d.code == "mip-level-count").count();
+        // Too many mips for 64x64, and multisampled textures have only one
+        assert_eq!(mip_errors, 2);
+    }
+
+    #[test]
+    fn test_sampler_and_query_set() {
+        let result = validate_sampler_descriptor(r#"{"maxAnisotropy": 4, "lodMinClamp": 5, "lodMaxClamp": 1}"#.to_string(), &webgpu_default_profile());
+        assert!(result.has_code("anisotropy-filter"));
+        assert_eq!(result.diagnostics.iter().find(|d| d.code == "out-of-range").unwrap().path, "/lodMaxClamp");
+
+        let result = validate_query_set_descriptor(r#"{"type": "timestamp", "count": 5000}"#.to_string(), &webgpu_default_profile());
+        assert_eq!(result.error_count(), 1);
+        assert!(result.has_code("requires-feature"));
+    }
+
+    #[test]
+    fn test_bind_group_layout_rules() {
+        let result = validate_bind_group_layout_descriptor(
+            r#"{"entries": [
+                {"binding": 0, "visibility": 1, "buffer": {"type": "storage"}},
+                {"binding": 0, "visibility": 4, "sampler": {}, "texture": {}},
+                {"binding": 2, "visibility": 2, "texture": {"multisampled": true, "viewDimension": "cube"}},
+                {"binding": 3, "visibility": 4, "storageTexture": {"format": "r32float", "viewDimension": "cube"}}
+            ]}"#
+            .to_string(),
+            &webgpu_default_profile(),
+        );
+        assert!(result.has_code("vertex-writable-storage"));
+        assert!(result.has_code("duplicate-binding"));
+        assert!(result.has_code("binding-resource-kind"));
+        assert!(result.has_code("multisample-dimension"));
+        assert!(result.has_code("invalid-view-dimension"));
+    }
+
+    #[test]
+    fn test_render_pass_rules() {
+        let result = validate_render_pass_descriptor(
+            r#"{
+                "colorAttachments": [null],
+                "timestampWrites": {"querySet": 1, "beginningOfPassWriteIndex": 0, "endOfPassWriteIndex": 0}
+            }"#
+            .to_string(),
+            &webgpu_default_profile(),
+        );
+        assert!(result.has_code("no-attachments"));
+        assert!(result.has_code("duplicate-query-index"));
+
+        let result = validate_render_pass_descriptor(
+            r#"{"colorAttachments": [], "depthStencilAttachment": {"view": 1, "depthReadOnly": true, "depthLoadOp": "load", "stencilLoadOp": "clear"}}"#
+                .to_string(),
+            &webgpu_default_profile(),
+        );
+        assert!(result.has_code("read-only-ops"));
+        let missing = result.diagnostics.iter().find(|d| d.code == "missing-field").unwrap();
+        assert_eq!(missing.path, "/depthStencilAttachment/stencilStoreOp");
+    }
+
+    #[test]
+    fn test_json_pointer_escaping() {
+        assert_eq!(json_pointer("/constants", "a/b~c"), "/constants/a~1b~0c");
+        let result = validate_descriptor("nope", "{}".to_string(), &webgpu_default_profile());
+        assert!(result.has_code("unknown-descriptor-kind"));
+        let result = validate_buffer_descriptor("[1]".to_string(), &webgpu_default_profile());
+        assert!(result.has_code("not-an-object"));
+    }
+
+    #[test]
+    fn test_limits_follow_profile() {
+        let texture = r#"{"size": [4096, 4096], "format": "rgba8unorm", "usage": 4}"#;
+        let webgl2 = get_limits_profile(WEBGL2_PROFILE).unwrap();
+        assert!(validate_texture_descriptor(texture.to_string(), &webgpu_default_profile()).valid);
+        let result = validate_texture_descriptor(texture.to_string(), &webgl2);
+        assert!(result.has_code("limit-exceeded"));
+        assert!(result.errors().any(|d| d.message.contains("maxTextureDimension2D")));
+
+        let layout = r#"{"entries": [
+            {"binding": 0, "visibility": 4, "buffer": {"type": "storage"}},
+            {"binding": 1, "visibility": 4, "buffer": {"type": "storage"}}
+        ]}"#;
+        assert!(validate_bind_group_layout_descriptor(layout.to_string(), &webgpu_default_profile()).valid);
+        let result = validate_bind_group_layout_descriptor(layout.to_string(), &webgl2);
+        let exceeded = result.errors().find(|d| d.code == "limit-exceeded").unwrap();
+        assert_eq!(exceeded.path, "/entries");
+        assert!(exceeded.message.contains("compute stage"));
+    }
 }
@@ -1,5 +1,7 @@
 use deno_bindgen::deno_bindgen;
 use serde::{Deserialize, Serialize};
+use super::pci_ids::lookup_pci_device;
+use super::vendors::{cuda_optimal_workgroup_size, rocm_optimal_workgroup_size, rocm_wavefront_size, ROCmArchitecture};
 
 /// GPU vendor types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -14,7 +16,7 @@ pub enum GPUVendor {
 }
 
 /// GPU information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GPUInfo {
     pub vendor: GPUVendor,
     pub device_name: String,
@@ -22,6 +24,61 @@ pub struct GPUInfo {
     pub driver_version: String,
     pub vendor_id: u32,
     pub device_id: u32,
+    // Filled from the PCI ID database when the device is known
+    pub architecture: Option<String>,
+    pub cuda_compute_capability: Option<(u32, u32)>,
+    pub gfx_target: Option<String>,
+    pub rocm_architecture: Option<ROCmArchitecture>,
+    pub subgroup_size: Option<u32>,
+    pub memory_type: Option<String>,
+    // From the CUDA/ROCm helpers, fed with the database's compute capability or gfx target
+    pub optimal_workgroup_size: Option<u32>,
+}
+
+impl GPUInfo {
+    /// Describe a device by PCI IDs, filling name and architecture from the PCI ID database
+    pub fn from_pci_ids(vendor_id: u32, device_id: u32, backend: &str, driver_version: &str) -> Self {
+        let record = lookup_pci_device(vendor_id, device_id);
+        let cuda_compute_capability = record.as_ref().and_then(|r| r.cuda_compute_capability());
+        let rocm_architecture = record
+            .as_ref()
+            .and_then(|r| r.rocm_architecture())
+            .filter(|arch| *arch != ROCmArchitecture::Unknown);
+        // ROCmArchitecture is declared in the order of the helpers' numbering
+        let rocm_index = rocm_architecture.map(|arch| arch as u32);
+        Self {
+            vendor: detect_gpu_vendor_enum(vendor_id),
+            device_name: record
+                .as_ref()
+                .map(|r| r.name.clone())
+                .unwrap_or_else(|| format!("Unknown device {:04x}:{:04x}", vendor_id, device_id)),
+            backend: backend.to_string(),
+            driver_version: driver_version.to_string(),
+            vendor_id,
+            device_id,
+            cuda_compute_capability,
+            architecture: record.as_ref().map(|r| r.architecture.clone()),
+            gfx_target: record.as_ref().and_then(|r| r.gfx_target.clone()),
+            rocm_architecture,
+            // The ROCm helpers are FFI exports (extern "C"), so they are called through closures
+            subgroup_size: rocm_index
+                .map(|arch| rocm_wavefront_size(arch))
+                .or_else(|| record.as_ref().map(|r| r.subgroup_size)),
+            memory_type: record.map(|r| r.memory_type),
+            optimal_workgroup_size: cuda_compute_capability
+                .map(|(major, minor)| cuda_optimal_workgroup_size(major, minor))
+                .or_else(|| rocm_index.map(|arch| rocm_optimal_workgroup_size(arch))),
+        }
+    }
+
+    /// Describe a wgpu adapter; the adapter's own name is kept for unknown devices
+    pub fn from_adapter_info(info: &wgpu::AdapterInfo) -> Self {
+        let mut gpu = Self::from_pci_ids(info.vendor, info.device, info.backend.to_str(), &info.driver_info);
+        if gpu.architecture.is_none() && !info.name.is_empty() {
+            gpu.device_name = info.name.clone();
+        }
+        gpu
+    }
 }
 
 /// Platform-specific GPU capabilities
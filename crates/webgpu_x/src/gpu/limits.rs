@@ -1,14 +1,24 @@
-use deno_bindgen::deno_bindgen;
+use super::profiles::LimitsProfile;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Complete device limits (including missing ones in Deno)
-#[derive(Debug, Clone)]
+///
+/// Serialized with WebGPU limit names (`maxTextureDimension2D`, ...); limits missing
+/// from JSON take their WebGPU default.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct DeviceLimits {
     // Standard limits
+    #[serde(rename = "maxTextureDimension1D")]
     pub max_texture_dimension_1d: u32,
+    #[serde(rename = "maxTextureDimension2D")]
     pub max_texture_dimension_2d: u32,
+    #[serde(rename = "maxTextureDimension3D")]
     pub max_texture_dimension_3d: u32,
     pub max_texture_array_layers: u32,
     pub max_bind_groups: u32,
+    pub max_bindings_per_bind_group: u32,
     pub max_dynamic_uniform_buffers_per_pipeline_layout: u32,
     pub max_dynamic_storage_buffers_per_pipeline_layout: u32,
     pub max_sampled_textures_per_shader_stage: u32,
@@ -21,9 +31,12 @@ pub struct DeviceLimits {
     pub min_uniform_buffer_offset_alignment: u32,
     pub min_storage_buffer_offset_alignment: u32,
     pub max_vertex_buffers: u32,
+    pub max_buffer_size: u64,
     pub max_vertex_attributes: u32,
     pub max_vertex_buffer_array_stride: u32,
     pub max_inter_stage_shader_components: u32,
+    pub max_color_attachments: u32,
+    pub max_color_attachment_bytes_per_sample: u32,
 
     // MISSING in Deno (added here)
     pub max_bind_groups_plus_vertex_buffers: u32,
@@ -38,6 +51,109 @@ pub struct DeviceLimits {
     pub max_compute_workgroups_per_dimension: u32,
 }
 
+impl Default for DeviceLimits {
+    /// Limits every WebGPU adapter supports
+    fn default() -> Self {
+        Self {
+            max_texture_dimension_1d: 8192,
+            max_texture_dimension_2d: 8192,
+            max_texture_dimension_3d: 2048,
+            max_texture_array_layers: 256,
+            max_bind_groups: 4,
+            max_bindings_per_bind_group: 1000,
+            max_dynamic_uniform_buffers_per_pipeline_layout: 8,
+            max_dynamic_storage_buffers_per_pipeline_layout: 4,
+            max_sampled_textures_per_shader_stage: 16,
+            max_samplers_per_shader_stage: 16,
+            max_storage_buffers_per_shader_stage: 8,
+            max_storage_textures_per_shader_stage: 4,
+            max_uniform_buffers_per_shader_stage: 12,
+            max_uniform_buffer_binding_size: 65536,
+            max_storage_buffer_binding_size: 134_217_728,
+            min_uniform_buffer_offset_alignment: 256,
+            min_storage_buffer_offset_alignment: 256,
+            max_vertex_buffers: 8,
+            max_buffer_size: 268_435_456,
+            max_vertex_attributes: 16,
+            max_vertex_buffer_array_stride: 2048,
+            max_inter_stage_shader_components: 60,
+            max_color_attachments: 8,
+            max_color_attachment_bytes_per_sample: 32,
+            max_bind_groups_plus_vertex_buffers: 24,
+            max_inter_stage_shader_variables: 16,
+            max_compute_workgroup_storage_size: 16384,
+            max_compute_invocations_per_workgroup: 256,
+            max_compute_workgroup_size_x: 256,
+            max_compute_workgroup_size_y: 256,
+            max_compute_workgroup_size_z: 64,
+            max_compute_workgroups_per_dimension: 65535,
+        }
+    }
+}
+
+impl From<&wgpu::Limits> for DeviceLimits {
+    fn from(limits: &wgpu::Limits) -> Self {
+        Self {
+            max_texture_dimension_1d: limits.max_texture_dimension_1d,
+            max_texture_dimension_2d: limits.max_texture_dimension_2d,
+            max_texture_dimension_3d: limits.max_texture_dimension_3d,
+            max_texture_array_layers: limits.max_texture_array_layers,
+            max_bind_groups: limits.max_bind_groups,
+            max_bindings_per_bind_group: limits.max_bindings_per_bind_group,
+            max_dynamic_uniform_buffers_per_pipeline_layout: limits
+                .max_dynamic_uniform_buffers_per_pipeline_layout,
+            max_dynamic_storage_buffers_per_pipeline_layout: limits
+                .max_dynamic_storage_buffers_per_pipeline_layout,
+            max_sampled_textures_per_shader_stage: limits.max_sampled_textures_per_shader_stage,
+            max_samplers_per_shader_stage: limits.max_samplers_per_shader_stage,
+            max_storage_buffers_per_shader_stage: limits.max_storage_buffers_per_shader_stage,
+            max_storage_textures_per_shader_stage: limits.max_storage_textures_per_shader_stage,
+            max_uniform_buffers_per_shader_stage: limits.max_uniform_buffers_per_shader_stage,
+            max_uniform_buffer_binding_size: limits.max_uniform_buffer_binding_size as u64,
+            max_storage_buffer_binding_size: limits.max_storage_buffer_binding_size as u64,
+            min_uniform_buffer_offset_alignment: limits.min_uniform_buffer_offset_alignment,
+            min_storage_buffer_offset_alignment: limits.min_storage_buffer_offset_alignment,
+            max_vertex_buffers: limits.max_vertex_buffers,
+            max_buffer_size: limits.max_buffer_size,
+            max_vertex_attributes: limits.max_vertex_attributes,
+            max_vertex_buffer_array_stride: limits.max_vertex_buffer_array_stride,
+            max_inter_stage_shader_components: limits.max_inter_stage_shader_components,
+            max_color_attachments: limits.max_color_attachments,
+            max_color_attachment_bytes_per_sample: limits.max_color_attachment_bytes_per_sample,
+            // wgpu does not report these; derive them the way the spec defines their defaults
+            max_bind_groups_plus_vertex_buffers: (limits.max_bind_groups + limits.max_vertex_buffers)
+                .min(24),
+            // Four components per variable, plus the four of the position builtin: the spec's
+            // 60 components are its 16 variables, so no conforming adapter reports fewer
+            max_inter_stage_shader_variables: limits.max_inter_stage_shader_components.saturating_add(4) / 4,
+            max_compute_workgroup_storage_size: limits.max_compute_workgroup_storage_size,
+            max_compute_invocations_per_workgroup: limits.max_compute_invocations_per_workgroup,
+            max_compute_workgroup_size_x: limits.max_compute_workgroup_size_x,
+            max_compute_workgroup_size_y: limits.max_compute_workgroup_size_y,
+            max_compute_workgroup_size_z: limits.max_compute_workgroup_size_z,
+            max_compute_workgroups_per_dimension: limits.max_compute_workgroups_per_dimension,
+        }
+    }
+}
+
+impl DeviceLimits {
+    /// Limits by WebGPU name
+    pub fn to_map(&self) -> BTreeMap<String, u64> {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::Object(map)) => map
+                .into_iter()
+                .filter_map(|(name, value)| Some((name, value.as_u64()?)))
+                .collect(),
+            _ => BTreeMap::new(),
+        }
+    }
+
+    /// Whether a smaller value of the named limit is more permissive (`min*` alignments)
+    pub fn lower_is_better(name: &str) -> bool {
+        name.starts_with("min")
+    }
+}
+
 /// Validation result
 #[derive(Debug, Clone)]
 pub struct ValidationResult {
@@ -59,18 +175,23 @@ impl ValidationResult {
             error_message: message,
         }
     }
+
+    fn exceeded(profile: &LimitsProfile, message: String) -> Self {
+        Self::err(format!("{} (profile '{}')", message, profile.name))
+    }
 }
 
 /// Validate bind group count against limits
 pub fn validate_bind_group_count(
     bind_groups: u32,
     vertex_buffers: u32,
-    limits: DeviceLimits,
+    profile: &LimitsProfile,
 ) -> ValidationResult {
+    let limits = &profile.limits;
     // Check missing limit: maxBindGroupsPlusVertexBuffers
     let total = bind_groups + vertex_buffers;
     if total > limits.max_bind_groups_plus_vertex_buffers {
-        return ValidationResult::err(format!(
+        return ValidationResult::exceeded(profile, format!(
             "Total bind groups ({}) + vertex buffers ({}) = {} exceeds limit {}",
             bind_groups, vertex_buffers, total, limits.max_bind_groups_plus_vertex_buffers
         ));
@@ -78,14 +199,14 @@ pub fn validate_bind_group_count(
 
     // Check individual limits
     if bind_groups > limits.max_bind_groups {
-        return ValidationResult::err(format!(
+        return ValidationResult::exceeded(profile, format!(
             "Bind group count {} exceeds limit {}",
             bind_groups, limits.max_bind_groups
         ));
     }
 
     if vertex_buffers > limits.max_vertex_buffers {
-        return ValidationResult::err(format!(
+        return ValidationResult::exceeded(profile, format!(
             "Vertex buffer count {} exceeds limit {}",
             vertex_buffers, limits.max_vertex_buffers
         ));
@@ -97,11 +218,12 @@ pub fn validate_bind_group_count(
 /// Validate inter-stage shader variables
 pub fn validate_inter_stage_variables(
     variable_count: u32,
-    limits: DeviceLimits,
+    profile: &LimitsProfile,
 ) -> ValidationResult {
+    let limits = &profile.limits;
     // Check missing limit: maxInterStageShaderVariables
     if variable_count > limits.max_inter_stage_shader_variables {
-        return ValidationResult::err(format!(
+        return ValidationResult::exceeded(profile, format!(
             "Inter-stage shader variable count {} exceeds limit {}",
             variable_count, limits.max_inter_stage_shader_variables
         ));
@@ -115,24 +237,25 @@ pub fn validate_workgroup_size(
     size_x: u32,
     size_y: u32,
     size_z: u32,
-    limits: DeviceLimits,
+    profile: &LimitsProfile,
 ) -> ValidationResult {
+    let limits = &profile.limits;
     if size_x > limits.max_compute_workgroup_size_x {
-        return ValidationResult::err(format!(
+        return ValidationResult::exceeded(profile, format!(
             "Workgroup size X {} exceeds limit {}",
             size_x, limits.max_compute_workgroup_size_x
         ));
     }
 
     if size_y > limits.max_compute_workgroup_size_y {
-        return ValidationResult::err(format!(
+        return ValidationResult::exceeded(profile, format!(
             "Workgroup size Y {} exceeds limit {}",
             size_y, limits.max_compute_workgroup_size_y
         ));
     }
 
     if size_z > limits.max_compute_workgroup_size_z {
-        return ValidationResult::err(format!(
+        return ValidationResult::exceeded(profile, format!(
             "Workgroup size Z {} exceeds limit {}",
             size_z, limits.max_compute_workgroup_size_z
         ));
@@ -140,7 +263,7 @@ pub fn validate_workgroup_size(
 
     let total = size_x * size_y * size_z;
     if total > limits.max_compute_invocations_per_workgroup {
-        return ValidationResult::err(format!(
+        return ValidationResult::exceeded(profile, format!(
             "Total workgroup invocations {} exceeds limit {}",
             total, limits.max_compute_invocations_per_workgroup
         ));
@@ -153,8 +276,9 @@ pub fn validate_workgroup_size(
 pub fn validate_buffer_size(
     size: u64,
     is_uniform: u8,
-    limits: DeviceLimits,
+    profile: &LimitsProfile,
 ) -> ValidationResult {
+    let limits = &profile.limits;
     let max_size = if is_uniform != 0 {
         limits.max_uniform_buffer_binding_size
     } else {
@@ -162,7 +286,7 @@ pub fn validate_buffer_size(
     };
 
     if size > max_size {
-        return ValidationResult::err(format!(
+        return ValidationResult::exceeded(profile, format!(
             "{} buffer size {} exceeds limit {}",
             if is_uniform != 0 { "Uniform" } else { "Storage" },
             size,
@@ -179,12 +303,13 @@ pub fn validate_texture_dimensions(
     width: u32,
     height: u32,
     depth: u32,
-    limits: DeviceLimits,
+    profile: &LimitsProfile,
 ) -> ValidationResult {
+    let limits = &profile.limits;
     match dimension.as_str() {
         "1d" => {
             if width > limits.max_texture_dimension_1d {
-                return ValidationResult::err(format!(
+                return ValidationResult::exceeded(profile, format!(
                     "1D texture width {} exceeds limit {}",
                     width, limits.max_texture_dimension_1d
                 ));
@@ -192,13 +317,13 @@ pub fn validate_texture_dimensions(
         }
         "2d" => {
             if width > limits.max_texture_dimension_2d {
-                return ValidationResult::err(format!(
+                return ValidationResult::exceeded(profile, format!(
                     "2D texture width {} exceeds limit {}",
                     width, limits.max_texture_dimension_2d
                 ));
             }
             if height > limits.max_texture_dimension_2d {
-                return ValidationResult::err(format!(
+                return ValidationResult::exceeded(profile, format!(
                     "2D texture height {} exceeds limit {}",
                     height, limits.max_texture_dimension_2d
                 ));
@@ -206,19 +331,19 @@ pub fn validate_texture_dimensions(
         }
         "3d" => {
             if width > limits.max_texture_dimension_3d {
-                return ValidationResult::err(format!(
+                return ValidationResult::exceeded(profile, format!(
                     "3D texture width {} exceeds limit {}",
                     width, limits.max_texture_dimension_3d
                 ));
             }
             if height > limits.max_texture_dimension_3d {
-                return ValidationResult::err(format!(
+                return ValidationResult::exceeded(profile, format!(
                     "3D texture height {} exceeds limit {}",
                     height, limits.max_texture_dimension_3d
                 ));
             }
             if depth > limits.max_texture_dimension_3d {
-                return ValidationResult::err(format!(
+                return ValidationResult::exceeded(profile, format!(
                     "3D texture depth {} exceeds limit {}",
                     depth, limits.max_texture_dimension_3d
                 ));
@@ -1,5 +1,7 @@
 pub mod detection;
 pub mod limits;
+pub mod pci_ids;
+pub mod profiles;
 pub mod vendors;
 pub mod non_vendor;
 
@@ -8,3 +10,11 @@ pub use limits::{
     validate_bind_group_count, validate_buffer_size, validate_inter_stage_variables,
     validate_texture_dimensions, validate_workgroup_size, DeviceLimits, ValidationResult,
 };
+pub use pci_ids::{
+    load_pci_database_file, lookup_pci_device, pci_database_len, reset_pci_database,
+    update_pci_database, PciDeviceRecord,
+};
+pub use profiles::{
+    diff_limits_profiles, get_limits_profile, list_limits_profiles, parse_limits_profile,
+    register_limits_profile, unregister_limits_profile, LimitDifference, LimitsDiff, LimitsProfile,
+};
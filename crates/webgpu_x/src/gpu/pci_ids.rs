@@ -0,0 +1,212 @@
+//! PCI device ID database
+//!
+//! Maps (vendor_id, device_id) to the facts the vendor tuning helpers take as raw
+//! arguments: marketing name, architecture generation, CUDA compute capability or
+//! ROCm gfx target, native subgroup size and memory type. The data lives in
+//! `data/pci_ids.json`, is embedded at build time, and can be extended or corrected at
+//! runtime from JSON in the same format:
+//!
+//! `{"devices": [{"vendor_id": "0x10DE", "device_id": "0x2684", "name": "...",
+//!   "architecture": "Ada Lovelace", "cuda_compute_capability": "8.9",
+//!   "subgroup_size": 32, "memory_type": "GDDR6X"}]}`
+//!
+//! IDs may be JSON numbers or hex strings.
+
+use super::vendors::ROCmArchitecture;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Database shipped with the crate
+const EMBEDDED_PCI_IDS: &str = include_str!("data/pci_ids.json");
+
+lazy_static! {
+    static ref PCI_DATABASE: Mutex<HashMap<(u32, u32), PciDeviceRecord>> =
+        Mutex::new(embedded_records());
+}
+
+/// What the database knows about one device
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PciDeviceRecord {
+    #[serde(deserialize_with = "deserialize_id")]
+    pub vendor_id: u32,
+    #[serde(deserialize_with = "deserialize_id")]
+    pub device_id: u32,
+    /// Marketing name, e.g. "NVIDIA GeForce RTX 4090"
+    pub name: String,
+    /// Architecture generation, e.g. "Ada Lovelace", "RDNA3", "Xe-HPG"
+    pub architecture: String,
+    /// NVIDIA only, "major.minor"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cuda_compute_capability: Option<String>,
+    /// AMD only, e.g. "gfx1100"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gfx_target: Option<String>,
+    /// Native subgroup (warp/wavefront/SIMD) width
+    pub subgroup_size: u32,
+    /// "GDDR6X", "HBM3", ... or "shared" for integrated GPUs
+    pub memory_type: String,
+}
+
+impl PciDeviceRecord {
+    /// CUDA compute capability as (major, minor)
+    pub fn cuda_compute_capability(&self) -> Option<(u32, u32)> {
+        let (major, minor) = self.cuda_compute_capability.as_deref()?.split_once('.')?;
+        Some((major.parse().ok()?, minor.parse().ok()?))
+    }
+
+    /// ROCm architecture family derived from the gfx target
+    pub fn rocm_architecture(&self) -> Option<ROCmArchitecture> {
+        let target = self.gfx_target.as_deref()?.strip_prefix("gfx")?;
+        Some(match target {
+            "908" => ROCmArchitecture::CDNA,
+            "90a" => ROCmArchitecture::CDNA2,
+            "940" | "941" | "942" => ROCmArchitecture::CDNA3,
+            t if t.starts_with("101") => ROCmArchitecture::RDNA,
+            t if t.starts_with("103") => ROCmArchitecture::RDNA2,
+            t if t.starts_with("110") || t.starts_with("115") => ROCmArchitecture::RDNA3,
+            t if t.len() == 3 && (t.starts_with('8') || t.starts_with('9')) => ROCmArchitecture::GCN,
+            _ => ROCmArchitecture::Unknown,
+        })
+    }
+}
+
+/// Top-level layout of a database file
+#[derive(Debug, Deserialize)]
+struct PciDatabaseFile {
+    devices: Vec<PciDeviceRecord>,
+}
+
+/// Accept `4318` or a hex string (`"0x10DE"`, `"10de"`); strings are always hex
+fn deserialize_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Number(u32),
+        Text(String),
+    }
+    match Id::deserialize(deserializer)? {
+        Id::Number(n) => Ok(n),
+        Id::Text(text) => {
+            let text = text.trim();
+            let hex = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")).unwrap_or(text);
+            u32::from_str_radix(hex, 16)
+                .map_err(|_| serde::de::Error::custom(format!("invalid PCI ID '{}'", text)))
+        }
+    }
+}
+
+fn parse_records(json: &str) -> Result<Vec<PciDeviceRecord>, String> {
+    serde_json::from_str::<PciDatabaseFile>(json)
+        .map(|file| file.devices)
+        .map_err(|e| format!("Invalid PCI ID database: {}", e))
+}
+
+fn embedded_records() -> HashMap<(u32, u32), PciDeviceRecord> {
+    parse_records(EMBEDDED_PCI_IDS)
+        .expect("embedded PCI ID database is valid")
+        .into_iter()
+        .map(|record| ((record.vendor_id, record.device_id), record))
+        .collect()
+}
+
+/// Look up a device
+pub fn lookup_pci_device(vendor_id: u32, device_id: u32) -> Option<PciDeviceRecord> {
+    PCI_DATABASE.lock().get(&(vendor_id, device_id)).cloned()
+}
+
+/// Add or replace records from JSON; returns how many were applied
+pub fn update_pci_database(json: &str) -> Result<usize, String> {
+    let records = parse_records(json)?;
+    let count = records.len();
+    let mut database = PCI_DATABASE.lock();
+    for record in records {
+        database.insert((record.vendor_id, record.device_id), record);
+    }
+    Ok(count)
+}
+
+/// Add or replace records from a JSON file
+pub fn load_pci_database_file(path: &Path) -> Result<usize, String> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    update_pci_database(&json)
+}
+
+/// Drop runtime updates and go back to the embedded records
+pub fn reset_pci_database() {
+    *PCI_DATABASE.lock() = embedded_records();
+}
+
+/// Number of known devices
+pub fn pci_database_len() -> usize {
+    PCI_DATABASE.lock().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpu::detection::{GPUInfo, GPUVendor};
+
+    #[test]
+    fn test_embedded_database() {
+        assert!(pci_database_len() >= 40);
+        let rtx = lookup_pci_device(0x10DE, 0x2684).unwrap();
+        assert_eq!(rtx.architecture, "Ada Lovelace");
+        assert_eq!(rtx.cuda_compute_capability(), Some((8, 9)));
+        assert_eq!(rtx.rocm_architecture(), None);
+
+        let navi = lookup_pci_device(0x1002, 0x744C).unwrap();
+        assert_eq!(navi.rocm_architecture(), Some(ROCmArchitecture::RDNA3));
+        assert_eq!(navi.subgroup_size, 32);
+        let mi300 = lookup_pci_device(0x1002, 0x74A1).unwrap();
+        assert_eq!(mi300.rocm_architecture(), Some(ROCmArchitecture::CDNA3));
+        assert_eq!(mi300.memory_type, "HBM3");
+
+        assert!(lookup_pci_device(0x10DE, 0xFFFF).is_none());
+    }
+
+    #[test]
+    fn test_update_from_json() {
+        let json = r#"{"devices": [
+            {"vendor_id": 65535, "device_id": "beef", "name": "Test GPU", "architecture": "Test",
+             "gfx_target": "gfx906", "subgroup_size": 64, "memory_type": "HBM2"}
+        ]}"#;
+        assert_eq!(update_pci_database(json), Ok(1));
+        let record = lookup_pci_device(0xFFFF, 0xBEEF).unwrap();
+        assert_eq!(record.rocm_architecture(), Some(ROCmArchitecture::GCN));
+
+        let path = std::env::temp_dir().join(format!("webgpu_x_pci_ids_{}.json", std::process::id()));
+        std::fs::write(&path, json.replace("Test GPU", "Renamed GPU")).unwrap();
+        assert_eq!(load_pci_database_file(&path), Ok(1));
+        assert_eq!(lookup_pci_device(0xFFFF, 0xBEEF).unwrap().name, "Renamed GPU");
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(update_pci_database(r#"{"devices": [{"vendor_id": "0xZZ"}]}"#).is_err());
+    }
+
+    #[test]
+    fn test_gpu_info_is_filled() {
+        let info = GPUInfo::from_pci_ids(0x10DE, 0x2330, "Vulkan", "550.54");
+        assert_eq!(info.vendor, GPUVendor::NVIDIA);
+        assert_eq!(info.device_name, "NVIDIA H100 SXM5 80GB");
+        assert_eq!(info.architecture.as_deref(), Some("Hopper"));
+        assert_eq!(info.cuda_compute_capability, Some((9, 0)));
+        assert_eq!(info.subgroup_size, Some(32));
+        assert_eq!(info.optimal_workgroup_size, Some(256));
+
+        // Wavefront width comes from the ROCm helper for the database's gfx target
+        let mi300 = GPUInfo::from_pci_ids(0x1002, 0x74A1, "Vulkan", "");
+        assert_eq!(mi300.rocm_architecture, Some(ROCmArchitecture::CDNA3));
+        assert_eq!(mi300.subgroup_size, Some(64));
+        assert_eq!(mi300.optimal_workgroup_size, Some(256));
+
+        let unknown = GPUInfo::from_pci_ids(0x8086, 0x0001, "Vulkan", "");
+        assert_eq!(unknown.vendor, GPUVendor::Intel);
+        assert_eq!(unknown.device_name, "Unknown device 8086:0001");
+        assert_eq!(unknown.architecture, None);
+        assert_eq!(unknown.optimal_workgroup_size, None);
+    }
+}
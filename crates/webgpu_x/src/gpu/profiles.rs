@@ -0,0 +1,337 @@
+//! Named device-limits profiles
+//!
+//! Validators check descriptors and kernels against a profile rather than against
+//! whatever device happens to be attached, so portability can be checked up front.
+//!
+//! Built-in profiles:
+//! - `webgpu-default`: limits every WebGPU adapter guarantees
+//! - `compatibility`: WebGPU compatibility mode (OpenGL ES 3.1 / D3D11 class hardware)
+//! - `webgl2`: wgpu's downlevel WebGL2 limits (no compute, no storage buffers)
+//! - `adapter`: snapshot of the best adapter on this machine, taken on first use
+//!
+//! User profiles are JSON and may `extends` another profile, listing only the limits
+//! that differ:
+//! `{"name": "mobile", "extends": "compatibility", "limits": {"maxTextureDimension2D": 4096}}`
+
+use super::limits::DeviceLimits;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+pub const WEBGPU_DEFAULT_PROFILE: &str = "webgpu-default";
+pub const COMPATIBILITY_PROFILE: &str = "compatibility";
+pub const WEBGL2_PROFILE: &str = "webgl2";
+pub const ADAPTER_PROFILE: &str = "adapter";
+
+/// Names of the built-in profiles
+pub const BUILTIN_PROFILES: &[&str] = &[
+    WEBGPU_DEFAULT_PROFILE,
+    COMPATIBILITY_PROFILE,
+    WEBGL2_PROFILE,
+    ADAPTER_PROFILE,
+];
+
+/// A named set of device limits
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LimitsProfile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub limits: DeviceLimits,
+}
+
+impl Default for LimitsProfile {
+    fn default() -> Self {
+        webgpu_default_profile()
+    }
+}
+
+/// User profile as written in JSON
+#[derive(Debug, Deserialize)]
+struct ProfileSource {
+    name: String,
+    #[serde(default)]
+    description: String,
+    /// Profile the limits start from (defaults to `webgpu-default`)
+    #[serde(default)]
+    extends: Option<String>,
+    #[serde(default)]
+    limits: Map<String, Value>,
+}
+
+lazy_static! {
+    static ref USER_PROFILES: Mutex<BTreeMap<String, LimitsProfile>> = Mutex::new(BTreeMap::new());
+    /// `None` until the first snapshot; `Some(None)` when no adapter was found
+    static ref ADAPTER_SNAPSHOT: Mutex<Option<Option<LimitsProfile>>> = Mutex::new(None);
+}
+
+pub fn webgpu_default_profile() -> LimitsProfile {
+    LimitsProfile {
+        name: WEBGPU_DEFAULT_PROFILE.to_string(),
+        description: "WebGPU default limits, supported by every adapter".to_string(),
+        limits: DeviceLimits::default(),
+    }
+}
+
+/// Limits of the WebGPU compatibility-mode proposal
+pub fn compatibility_profile() -> LimitsProfile {
+    LimitsProfile {
+        name: COMPATIBILITY_PROFILE.to_string(),
+        description: "WebGPU compatibility mode (OpenGL ES 3.1 / Direct3D 11 class devices)"
+            .to_string(),
+        limits: DeviceLimits {
+            max_texture_dimension_1d: 4096,
+            max_texture_dimension_2d: 4096,
+            max_uniform_buffer_binding_size: 16384,
+            max_storage_buffers_per_shader_stage: 4,
+            max_vertex_attributes: 16,
+            max_inter_stage_shader_components: 60,
+            max_inter_stage_shader_variables: 15,
+            max_color_attachments: 4,
+            max_compute_workgroup_storage_size: 16384,
+            max_compute_invocations_per_workgroup: 128,
+            max_compute_workgroup_size_x: 128,
+            max_compute_workgroup_size_y: 128,
+            max_compute_workgroup_size_z: 64,
+            ..DeviceLimits::default()
+        },
+    }
+}
+
+pub fn webgl2_profile() -> LimitsProfile {
+    LimitsProfile {
+        name: WEBGL2_PROFILE.to_string(),
+        description: "Downlevel WebGL2 limits (no compute shaders or storage buffers)".to_string(),
+        limits: DeviceLimits::from(&wgpu::Limits::downlevel_webgl2_defaults()),
+    }
+}
+
+/// Rank adapter types so a discrete GPU wins over integrated, virtual and CPU adapters
+fn adapter_rank(device_type: wgpu::DeviceType) -> u8 {
+    match device_type {
+        wgpu::DeviceType::DiscreteGpu => 0,
+        wgpu::DeviceType::IntegratedGpu => 1,
+        wgpu::DeviceType::VirtualGpu => 2,
+        wgpu::DeviceType::Cpu => 3,
+        wgpu::DeviceType::Other => 4,
+    }
+}
+
+/// Snapshot of the best adapter's limits; enumerates adapters once per process
+pub fn adapter_profile() -> Option<LimitsProfile> {
+    let mut snapshot = ADAPTER_SNAPSHOT.lock();
+    if snapshot.is_none() {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let best = instance
+            .enumerate_adapters(wgpu::Backends::all())
+            .into_iter()
+            .min_by_key(|adapter| adapter_rank(adapter.get_info().device_type));
+        *snapshot = Some(best.map(|adapter| {
+            let info = adapter.get_info();
+            LimitsProfile {
+                name: ADAPTER_PROFILE.to_string(),
+                description: format!("{} ({:?}, {:?})", info.name, info.backend, info.device_type),
+                limits: DeviceLimits::from(&adapter.limits()),
+            }
+        }));
+    }
+    snapshot.clone().flatten()
+}
+
+/// Look up a built-in or registered profile; an empty name means `webgpu-default`
+pub fn get_limits_profile(name: &str) -> Option<LimitsProfile> {
+    match name {
+        "" | WEBGPU_DEFAULT_PROFILE => Some(webgpu_default_profile()),
+        COMPATIBILITY_PROFILE => Some(compatibility_profile()),
+        WEBGL2_PROFILE => Some(webgl2_profile()),
+        ADAPTER_PROFILE => adapter_profile(),
+        _ => USER_PROFILES.lock().get(name).cloned(),
+    }
+}
+
+/// Names of all profiles, built-in first
+pub fn list_limits_profiles() -> Vec<String> {
+    BUILTIN_PROFILES
+        .iter()
+        .map(|name| name.to_string())
+        .chain(USER_PROFILES.lock().keys().cloned())
+        .collect()
+}
+
+/// Resolve a JSON profile against the profile it extends
+pub fn parse_limits_profile(profile_json: &str) -> Result<LimitsProfile, String> {
+    let source: ProfileSource =
+        serde_json::from_str(profile_json).map_err(|e| format!("Invalid profile JSON: {}", e))?;
+    if source.name.is_empty() {
+        return Err("Profile name must not be empty".to_string());
+    }
+
+    let base_name = source.extends.as_deref().unwrap_or(WEBGPU_DEFAULT_PROFILE);
+    let base = get_limits_profile(base_name)
+        .ok_or_else(|| format!("Profile '{}' extends unknown profile '{}'", source.name, base_name))?;
+
+    let mut limits = match serde_json::to_value(&base.limits) {
+        Ok(Value::Object(map)) => map,
+        _ => return Err("Failed to serialize base limits".to_string()),
+    };
+    for (name, value) in source.limits {
+        if !limits.contains_key(&name) {
+            return Err(format!("Unknown limit '{}' in profile '{}'", name, source.name));
+        }
+        if value.as_u64().is_none() {
+            return Err(format!("Limit '{}' must be a non-negative integer", name));
+        }
+        limits.insert(name, value);
+    }
+
+    let limits: DeviceLimits = serde_json::from_value(Value::Object(limits))
+        .map_err(|e| format!("Invalid limits in profile '{}': {}", source.name, e))?;
+
+    Ok(LimitsProfile {
+        name: source.name,
+        description: source.description,
+        limits,
+    })
+}
+
+/// Parse and register a user profile, replacing any earlier one with the same name
+pub fn register_limits_profile(profile_json: &str) -> Result<LimitsProfile, String> {
+    let profile = parse_limits_profile(profile_json)?;
+    if BUILTIN_PROFILES.contains(&profile.name.as_str()) {
+        return Err(format!("Cannot replace built-in profile '{}'", profile.name));
+    }
+    USER_PROFILES.lock().insert(profile.name.clone(), profile.clone());
+    Ok(profile)
+}
+
+/// Remove a user profile; returns whether it existed
+pub fn unregister_limits_profile(name: &str) -> bool {
+    USER_PROFILES.lock().remove(name).is_some()
+}
+
+/// One limit that differs between two profiles
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LimitDifference {
+    /// WebGPU limit name
+    pub limit: String,
+    pub from: u64,
+    pub to: u64,
+    /// True when `to` is more restrictive than `from`
+    pub tighter: bool,
+}
+
+/// Limits that differ between two profiles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LimitsDiff {
+    pub from: String,
+    pub to: String,
+    pub differences: Vec<LimitDifference>,
+    /// True when no limit of `to` is tighter, so anything valid on `from` is valid on `to`
+    pub portable: bool,
+}
+
+/// Compare two profiles, e.g. a development machine (`from`) against a deployment target (`to`)
+pub fn diff_limits_profiles(from: &LimitsProfile, to: &LimitsProfile) -> LimitsDiff {
+    let from_limits = from.limits.to_map();
+    let to_limits = to.limits.to_map();
+
+    let differences: Vec<LimitDifference> = from_limits
+        .iter()
+        .filter_map(|(limit, &from_value)| {
+            let to_value = *to_limits.get(limit)?;
+            if from_value == to_value {
+                return None;
+            }
+            let tighter = if DeviceLimits::lower_is_better(limit) {
+                to_value > from_value
+            } else {
+                to_value < from_value
+            };
+            Some(LimitDifference {
+                limit: limit.clone(),
+                from: from_value,
+                to: to_value,
+                tighter,
+            })
+        })
+        .collect();
+
+    LimitsDiff {
+        from: from.name.clone(),
+        to: to.name.clone(),
+        portable: !differences.iter().any(|d| d.tighter),
+        differences,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_profiles() {
+        for name in [WEBGPU_DEFAULT_PROFILE, COMPATIBILITY_PROFILE, WEBGL2_PROFILE] {
+            assert_eq!(get_limits_profile(name).unwrap().name, name);
+        }
+        assert_eq!(get_limits_profile("").unwrap().name, WEBGPU_DEFAULT_PROFILE);
+        assert_eq!(webgl2_profile().limits.max_compute_invocations_per_workgroup, 0);
+        assert_eq!(webgl2_profile().limits.max_storage_buffers_per_shader_stage, 0);
+
+        // wgpu's default 60 components are the spec's default 16 variables
+        let adapter = DeviceLimits::from(&wgpu::Limits::default());
+        assert_eq!(adapter.max_inter_stage_shader_variables, 16);
+        assert!(get_limits_profile("missing").is_none());
+    }
+
+    #[test]
+    fn test_limits_use_webgpu_names() {
+        let json = serde_json::to_value(DeviceLimits::default()).unwrap();
+        assert_eq!(json["maxTextureDimension2D"], 8192);
+        assert_eq!(json["minUniformBufferOffsetAlignment"], 256);
+        assert_eq!(json["maxBindGroupsPlusVertexBuffers"], 24);
+
+        let partial: DeviceLimits = serde_json::from_str(r#"{"maxBindGroups": 8}"#).unwrap();
+        assert_eq!(partial.max_bind_groups, 8);
+        assert_eq!(partial.max_vertex_buffers, 8);
+    }
+
+    #[test]
+    fn test_user_profile_extends_builtin() {
+        let profile = register_limits_profile(
+            r#"{"name": "test-mobile", "extends": "compatibility", "limits": {"maxTextureDimension2D": 2048}}"#,
+        )
+        .unwrap();
+        assert_eq!(profile.limits.max_texture_dimension_2d, 2048);
+        assert_eq!(profile.limits.max_color_attachments, 4);
+        assert_eq!(get_limits_profile("test-mobile").unwrap(), profile);
+        assert!(list_limits_profiles().contains(&"test-mobile".to_string()));
+        assert!(unregister_limits_profile("test-mobile"));
+
+        let unknown = parse_limits_profile(r#"{"name": "x", "limits": {"maxWidgets": 1}}"#);
+        assert!(unknown.unwrap_err().contains("maxWidgets"));
+        let builtin = register_limits_profile(r#"{"name": "webgl2"}"#);
+        assert!(builtin.is_err());
+    }
+
+    #[test]
+    fn test_diff_reports_tighter_limits() {
+        let diff = diff_limits_profiles(&webgpu_default_profile(), &compatibility_profile());
+        assert!(!diff.portable);
+        let colors = diff
+            .differences
+            .iter()
+            .find(|d| d.limit == "maxColorAttachments")
+            .unwrap();
+        assert_eq!((colors.from, colors.to, colors.tighter), (8, 4, true));
+
+        let looser = parse_limits_profile(
+            r#"{"name": "big", "limits": {"maxBindGroups": 8, "minUniformBufferOffsetAlignment": 64}}"#,
+        )
+        .unwrap();
+        let diff = diff_limits_profiles(&webgpu_default_profile(), &looser);
+        assert!(diff.portable);
+        assert_eq!(diff.differences.len(), 2);
+    }
+}
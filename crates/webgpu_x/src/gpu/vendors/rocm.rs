@@ -1,7 +1,8 @@
 use deno_bindgen::deno_bindgen;
+use serde::{Deserialize, Serialize};
 
 /// ROCm/AMD GPU architecture
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ROCmArchitecture {
     GCN,      // Graphics Core Next
     RDNA,     // RDNA 1.0
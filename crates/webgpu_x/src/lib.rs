@@ -33,6 +33,16 @@ pub use gpu::limits::{
     validate_texture_dimensions, validate_workgroup_size, DeviceLimits, ValidationResult,
 };
 
+pub use gpu::pci_ids::{
+    load_pci_database_file, lookup_pci_device, pci_database_len, reset_pci_database,
+    update_pci_database, PciDeviceRecord,
+};
+
+pub use gpu::profiles::{
+    diff_limits_profiles, get_limits_profile, list_limits_profiles, parse_limits_profile,
+    register_limits_profile, unregister_limits_profile, LimitDifference, LimitsDiff, LimitsProfile,
+};
+
 pub use memory::buffer_pool::{
     buffer_pool_acquire, buffer_pool_add, buffer_pool_clear, buffer_pool_configure,
     buffer_pool_evict, buffer_pool_release, buffer_pool_remove, buffer_pool_stats,
@@ -45,9 +55,16 @@ pub use memory::buddy_allocator::{
 };
 
 pub use descriptors::validator::{
-    validate_bind_group_layout_descriptor, validate_buffer_descriptor,
-    validate_compute_pipeline_descriptor, validate_render_pipeline_descriptor,
-    validate_texture_descriptor, DescriptorValidationResult, ValidationRule,
+    validate_bind_group_descriptor, validate_bind_group_layout_descriptor,
+    validate_buffer_descriptor, validate_compute_pipeline_descriptor, validate_descriptor,
+    validate_pipeline_layout_descriptor, validate_query_set_descriptor,
+    validate_render_pass_descriptor, validate_render_pipeline_descriptor,
+    validate_sampler_descriptor, validate_texture_descriptor, DescriptorDiagnostic,
+    DescriptorValidationResult, Severity, ValidationRule,
+};
+
+pub use descriptors::compatibility::{
+    validate_related_descriptors, RelatedDescriptors, ShaderSourceLocation,
 };
 
 pub use compute::workgroup::{
@@ -62,11 +79,16 @@ pub use compute::kernel::{
     SimpleKernelBuilder,
 };
 
+pub use compute::validation::validate_kernel_limits;
+
 pub use pipeline::cache::{
-    hash_descriptor, pipeline_cache_clear, pipeline_cache_insert_compute,
-    pipeline_cache_insert_render, pipeline_cache_lookup_compute, pipeline_cache_lookup_render,
-    pipeline_cache_remove_compute, pipeline_cache_remove_render, pipeline_cache_stats,
-    pipeline_cache_top_hits, PipelineCacheStats, PipelineHitInfo,
+    hash_descriptor, pipeline_cache_clear, pipeline_cache_clear_eviction_callback,
+    pipeline_cache_config, pipeline_cache_configure, pipeline_cache_drain_evictions,
+    pipeline_cache_insert_compute, pipeline_cache_insert_render, pipeline_cache_lookup_compute,
+    pipeline_cache_lookup_render, pipeline_cache_purge_expired, pipeline_cache_remove_compute,
+    pipeline_cache_remove_render, pipeline_cache_set_eviction_callback, pipeline_cache_stats,
+    pipeline_cache_top_hits, EvictedPipeline, EvictionPolicy, EvictionReason, PipelineCacheConfig,
+    PipelineCacheStats, PipelineHitInfo,
 };
 
 pub use framework::{
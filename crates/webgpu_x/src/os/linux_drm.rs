@@ -0,0 +1,291 @@
+//! Linux GPU discovery through DRM and PCI sysfs
+//!
+//! Scans `<root>/bus/pci/devices` for display controllers (PCI class 0x03) and
+//! `<root>/class/drm` for card and render nodes, then merges both views by PCI address.
+//! Devices without PCI (e.g. SoC GPUs driven by vc4 or panfrost) come from DRM alone.
+//!
+//! Every path is relative to a sysfs root (normally `/sys`), so the scan can run
+//! against a fake tree in tests. Nothing here is Linux-specific at compile time; on
+//! other systems the default root simply does not exist and the scan finds nothing.
+
+use deno_bindgen::deno_bindgen;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default sysfs mount point
+pub const DEFAULT_SYSFS_ROOT: &str = "/sys";
+
+/// Where DRM device nodes live
+const DEV_DRI: &str = "/dev/dri";
+
+/// PCI base class of display controllers (VGA, 3D, display)
+const PCI_CLASS_DISPLAY: u32 = 0x03;
+
+/// A GPU found in sysfs
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinuxGpuDevice {
+    /// PCI address such as "0000:03:00.0"; empty for non-PCI devices
+    pub pci_address: String,
+    pub vendor_id: u32,
+    pub device_id: u32,
+    pub subsystem_vendor_id: u32,
+    pub subsystem_device_id: u32,
+    /// 24-bit PCI class code, e.g. 0x030000 for a VGA controller
+    pub class_code: u32,
+    /// Bound kernel driver ("amdgpu", "i915", "xe", "nouveau", "nvidia", ...)
+    pub driver: Option<String>,
+    /// Total VRAM in bytes where the driver reports it (amdgpu `mem_info_vram_total`)
+    pub vram_total: Option<u64>,
+    /// CPU-visible VRAM in bytes (amdgpu `mem_info_vis_vram_total`)
+    pub vram_visible: Option<u64>,
+    /// Primary node, e.g. "/dev/dri/card0"
+    pub card_node: Option<String>,
+    /// Render node, e.g. "/dev/dri/renderD128"
+    pub render_node: Option<String>,
+    /// Whether the firmware used this device for the boot console
+    pub boot_vga: bool,
+    pub numa_node: Option<i32>,
+}
+
+/// Read a sysfs attribute, trimmed
+fn read_attr(dir: &Path, name: &str) -> Option<String> {
+    fs::read_to_string(dir.join(name)).ok().map(|s| s.trim().to_string())
+}
+
+/// Parse "0x1002" or "1002" as hex
+fn parse_hex(value: &str) -> Option<u32> {
+    let digits = value.trim().trim_start_matches("0x").trim_start_matches("0X");
+    u32::from_str_radix(digits, 16).ok()
+}
+
+fn read_hex(dir: &Path, name: &str) -> Option<u32> {
+    read_attr(dir, name).and_then(|v| parse_hex(&v))
+}
+
+/// `KEY=value` lines of a `uevent` file
+fn read_uevent(dir: &Path) -> BTreeMap<String, String> {
+    read_attr(dir, "uevent")
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+/// Driver bound to a device: `uevent` DRIVER, else the `driver` symlink target
+fn read_driver(dir: &Path, uevent: &BTreeMap<String, String>) -> Option<String> {
+    uevent.get("DRIVER").cloned().or_else(|| {
+        fs::read_link(dir.join("driver"))
+            .ok()
+            .and_then(|target| target.file_name().map(|name| name.to_string_lossy().into_owned()))
+    })
+}
+
+/// Read everything a device directory says about the GPU
+fn read_device(dir: &Path, pci_address: &str) -> LinuxGpuDevice {
+    let uevent = read_uevent(dir);
+    // PCI_ID=1002:744C covers devices whose vendor/device files are missing
+    let pci_id = uevent.get("PCI_ID").and_then(|id| id.split_once(':'));
+    LinuxGpuDevice {
+        pci_address: pci_address.to_string(),
+        vendor_id: read_hex(dir, "vendor").or_else(|| pci_id.and_then(|(v, _)| parse_hex(v))).unwrap_or(0),
+        device_id: read_hex(dir, "device").or_else(|| pci_id.and_then(|(_, d)| parse_hex(d))).unwrap_or(0),
+        subsystem_vendor_id: read_hex(dir, "subsystem_vendor").unwrap_or(0),
+        subsystem_device_id: read_hex(dir, "subsystem_device").unwrap_or(0),
+        class_code: read_hex(dir, "class")
+            .or_else(|| uevent.get("PCI_CLASS").and_then(|c| parse_hex(c)))
+            .unwrap_or(0),
+        driver: read_driver(dir, &uevent),
+        vram_total: read_attr(dir, "mem_info_vram_total").and_then(|v| v.parse().ok()),
+        vram_visible: read_attr(dir, "mem_info_vis_vram_total").and_then(|v| v.parse().ok()),
+        card_node: None,
+        render_node: None,
+        boot_vga: read_attr(dir, "boot_vga").as_deref() == Some("1"),
+        numa_node: read_attr(dir, "numa_node").and_then(|v| v.parse().ok()).filter(|n: &i32| *n >= 0),
+    }
+}
+
+fn is_display_class(class_code: u32) -> bool {
+    class_code >> 16 == PCI_CLASS_DISPLAY
+}
+
+/// PCI address of a DRM node's parent device
+fn drm_pci_address(device_dir: &Path) -> Option<String> {
+    read_uevent(device_dir).get("PCI_SLOT_NAME").cloned().or_else(|| {
+        let resolved = fs::canonicalize(device_dir).ok()?;
+        let name = resolved.file_name()?.to_string_lossy().into_owned();
+        // PCI addresses look like 0000:03:00.0
+        (name.len() == 12 && name.as_bytes()[4] == b':').then_some(name)
+    })
+}
+
+/// Sorted entries of a directory, empty if it cannot be read
+fn list_dir(dir: &Path) -> Vec<(String, PathBuf)> {
+    let mut entries: Vec<(String, PathBuf)> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| (entry.file_name().to_string_lossy().into_owned(), entry.path()))
+                .collect()
+        })
+        .unwrap_or_default();
+    entries.sort();
+    entries
+}
+
+/// Discover GPUs under `sysfs_root`, ordered by PCI address (non-PCI devices last)
+pub fn discover_linux_gpus(sysfs_root: &Path) -> Vec<LinuxGpuDevice> {
+    let mut devices: BTreeMap<String, LinuxGpuDevice> = BTreeMap::new();
+
+    for (address, dir) in list_dir(&sysfs_root.join("bus/pci/devices")) {
+        let device = read_device(&dir, &address);
+        if is_display_class(device.class_code) {
+            devices.insert(address, device);
+        }
+    }
+
+    // Connectors such as card0-DP-1 also live in class/drm and are skipped
+    for (name, dir) in list_dir(&sysfs_root.join("class/drm")) {
+        let is_card = name.strip_prefix("card").is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+        let is_render =
+            name.strip_prefix("renderD").is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+        if !is_card && !is_render {
+            continue;
+        }
+        let device_dir = dir.join("device");
+        let address = drm_pci_address(&device_dir);
+        let key = address.clone().unwrap_or_else(|| {
+            // Non-PCI: key by the parent device so card and render node still pair up
+            let parent = fs::canonicalize(&device_dir).unwrap_or(device_dir.clone());
+            format!("~{}", parent.display())
+        });
+        let device = devices
+            .entry(key)
+            .or_insert_with(|| read_device(&device_dir, address.as_deref().unwrap_or("")));
+        let node = format!("{}/{}", DEV_DRI, name);
+        if is_card {
+            device.card_node = Some(node);
+        } else {
+            device.render_node = Some(node);
+        }
+    }
+
+    devices.into_values().collect()
+}
+
+/// Discover GPUs under `/sys`
+pub fn discover_system_gpus() -> Vec<LinuxGpuDevice> {
+    discover_linux_gpus(Path::new(DEFAULT_SYSFS_ROOT))
+}
+
+/// Discover GPUs from DRM and PCI sysfs
+/// sysfs_root: sysfs mount point ("" = /sys)
+/// Returns JSON array of LinuxGpuDevice: [{"pci_address", "vendor_id", "device_id",
+///   "driver", "vram_total", "card_node", "render_node", "boot_vga", ...}]
+#[deno_bindgen]
+pub fn linux_discover_gpus(sysfs_root: &str) -> String {
+    let root = if sysfs_root.is_empty() { DEFAULT_SYSFS_ROOT } else { sysfs_root };
+    serde_json::to_string(&discover_linux_gpus(Path::new(root))).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("webgpu_x_sysfs_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        root
+    }
+
+    fn write_attrs(dir: &Path, attrs: &[(&str, &str)]) {
+        fs::create_dir_all(dir).unwrap();
+        for (name, value) in attrs {
+            fs::write(dir.join(name), format!("{}\n", value)).unwrap();
+        }
+    }
+
+    /// Fake tree with an AMD dGPU, an Intel iGPU and a non-GPU PCI device. DRM device
+    /// directories are copies rather than symlinks so the tree works on any filesystem.
+    fn fake_sysfs(root: &Path) {
+        let amd = [
+            ("vendor", "0x1002"),
+            ("device", "0x744c"),
+            ("subsystem_vendor", "0x1da2"),
+            ("subsystem_device", "0x471e"),
+            ("class", "0x030000"),
+            ("boot_vga", "0"),
+            ("numa_node", "-1"),
+            ("mem_info_vram_total", "25753026560"),
+            ("mem_info_vis_vram_total", "268435456"),
+            ("uevent", "DRIVER=amdgpu\nPCI_CLASS=30000\nPCI_ID=1002:744C\nPCI_SLOT_NAME=0000:03:00.0"),
+        ];
+        let intel = [
+            ("vendor", "0x8086"),
+            ("device", "0xa780"),
+            ("class", "0x030000"),
+            ("boot_vga", "1"),
+            ("uevent", "DRIVER=i915\nPCI_SLOT_NAME=0000:00:02.0"),
+        ];
+        write_attrs(&root.join("bus/pci/devices/0000:03:00.0"), &amd);
+        write_attrs(&root.join("bus/pci/devices/0000:00:02.0"), &intel);
+        write_attrs(
+            &root.join("bus/pci/devices/0000:00:14.0"),
+            &[("vendor", "0x8086"), ("device", "0x7ae0"), ("class", "0x0c0330")],
+        );
+
+        write_attrs(&root.join("class/drm/card0/device"), &intel);
+        write_attrs(&root.join("class/drm/renderD128/device"), &intel);
+        write_attrs(&root.join("class/drm/card1/device"), &amd);
+        write_attrs(&root.join("class/drm/renderD129/device"), &amd);
+        write_attrs(&root.join("class/drm/card1-DP-1"), &[("status", "connected")]);
+        write_attrs(&root.join("class/drm/version"), &[]);
+    }
+
+    #[test]
+    fn test_discovers_pci_gpus() {
+        let root = temp_root("pci");
+        fake_sysfs(&root);
+
+        let gpus = discover_linux_gpus(&root);
+        assert_eq!(gpus.len(), 2, "{:?}", gpus);
+
+        let intel = &gpus[0];
+        assert_eq!(intel.pci_address, "0000:00:02.0");
+        assert_eq!((intel.vendor_id, intel.device_id), (0x8086, 0xa780));
+        assert_eq!(intel.driver.as_deref(), Some("i915"));
+        assert!(intel.boot_vga);
+        assert_eq!(intel.vram_total, None);
+        assert_eq!(intel.card_node.as_deref(), Some("/dev/dri/card0"));
+        assert_eq!(intel.render_node.as_deref(), Some("/dev/dri/renderD128"));
+
+        let amd = &gpus[1];
+        assert_eq!(amd.driver.as_deref(), Some("amdgpu"));
+        assert_eq!(amd.vram_total, Some(25_753_026_560));
+        assert_eq!(amd.vram_visible, Some(268_435_456));
+        assert_eq!(amd.subsystem_vendor_id, 0x1da2);
+        assert_eq!(amd.numa_node, None);
+        assert!(!amd.boot_vga);
+        assert_eq!(amd.render_node.as_deref(), Some("/dev/dri/renderD129"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_drm_only_devices() {
+        let root = temp_root("soc");
+        let vc4 = [("uevent", "DRIVER=vc4\nOF_NAME=gpu")];
+        write_attrs(&root.join("class/drm/card0/device"), &vc4);
+
+        let gpus = discover_linux_gpus(&root);
+        assert_eq!(gpus.len(), 1);
+        assert_eq!(gpus[0].pci_address, "");
+        assert_eq!(gpus[0].driver.as_deref(), Some("vc4"));
+        assert_eq!(gpus[0].card_node.as_deref(), Some("/dev/dri/card0"));
+
+        assert!(discover_linux_gpus(&root.join("missing")).is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,5 +1,6 @@
 pub mod darwin;
 pub mod linux;
+pub mod linux_drm;
 pub mod windows;
 
 pub use darwin::{
@@ -13,6 +14,11 @@ pub use linux::{
     linux_recommended_memory_strategy, LinuxSystemInfo,
 };
 
+pub use linux_drm::{
+    discover_linux_gpus, discover_system_gpus, linux_discover_gpus, LinuxGpuDevice,
+    DEFAULT_SYSFS_ROOT,
+};
+
 pub use windows::{
     windows_get_logical_processor_count, windows_get_page_size, windows_has_amd_driver,
     windows_has_dx12, windows_has_intel_driver, windows_has_nvidia_driver, windows_is_arm,
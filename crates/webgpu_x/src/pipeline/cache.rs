@@ -1,9 +1,83 @@
 use deno_bindgen::deno_bindgen;
-use std::collections::HashMap;
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
 use parking_lot::Mutex;
 use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+/// Which entry to drop when a map is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvictionPolicy {
+    /// Least recently looked up or inserted
+    Lru,
+    /// Fewest hits, least recently used among equals
+    Lfu,
+}
+
+/// Pipeline cache limits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PipelineCacheConfig {
+    /// Maximum render pipelines (0 = unbounded)
+    pub render_capacity: usize,
+    /// Maximum compute pipelines (0 = unbounded)
+    pub compute_capacity: usize,
+    pub policy: EvictionPolicy,
+    /// Entries older than this many milliseconds are dropped (measured from insertion)
+    pub ttl_ms: Option<u64>,
+    /// Queue evicted pipelines for `pipeline_cache_drain_evictions`; the queue keeps the
+    /// most recent `MAX_RECORDED_EVICTIONS`
+    pub record_evictions: bool,
+}
+
+impl Default for PipelineCacheConfig {
+    fn default() -> Self {
+        Self {
+            render_capacity: 0,
+            compute_capacity: 0,
+            policy: EvictionPolicy::Lru,
+            ttl_ms: None,
+            record_evictions: false,
+        }
+    }
+}
+
+/// Why a pipeline left the cache without an explicit remove
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvictionReason {
+    Capacity,
+    Expired,
+    /// Another handle was inserted under the same hash
+    Replaced,
+    Cleared,
+}
+
+/// A pipeline dropped by the cache; its GPU object can be released
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvictedPipeline {
+    pub hash: u64,
+    pub handle: u64,
+    pub pipeline_type: String, // "render" or "compute"
+    pub reason: EvictionReason,
+    pub hit_count: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PipelineKind {
+    Render,
+    Compute,
+}
+
+impl PipelineKind {
+    fn name(self) -> &'static str {
+        match self {
+            PipelineKind::Render => "render",
+            PipelineKind::Compute => "compute",
+        }
+    }
+}
 
 /// Pipeline cache entry
 #[derive(Clone)]
@@ -12,18 +86,31 @@ struct CachedPipeline {
     hash: u64,
     created_at: u64,
     hit_count: u64,
+    /// Access counter value at the last lookup or insert
+    last_used: u64,
 }
 
 /// Pipeline cache
 pub struct PipelineCache {
     render_pipelines: HashMap<u64, CachedPipeline>,
     compute_pipelines: HashMap<u64, CachedPipeline>,
+    config: PipelineCacheConfig,
     total_hits: u64,
     total_misses: u64,
+    evictions: u64,
+    expirations: u64,
+    access_counter: u64,
+    evicted: VecDeque<EvictedPipeline>,
 }
 
+/// Recorded evictions kept when nobody drains them; the oldest are dropped first
+pub const MAX_RECORDED_EVICTIONS: usize = 4096;
+
+type EvictionCallback = Arc<dyn Fn(&EvictedPipeline) + Send + Sync>;
+
 lazy_static! {
     static ref PIPELINE_CACHE: Mutex<PipelineCache> = Mutex::new(PipelineCache::new());
+    static ref EVICTION_CALLBACK: Mutex<Option<EvictionCallback>> = Mutex::new(None);
 }
 
 impl PipelineCache {
@@ -31,53 +118,186 @@ impl PipelineCache {
         Self {
             render_pipelines: HashMap::new(),
             compute_pipelines: HashMap::new(),
+            config: PipelineCacheConfig::default(),
             total_hits: 0,
             total_misses: 0,
+            evictions: 0,
+            expirations: 0,
+            access_counter: 0,
+            evicted: VecDeque::new(),
         }
     }
 
-    /// Lookup render pipeline by descriptor hash
-    fn lookup_render_pipeline(&mut self, hash: u64) -> Option<u64> {
-        if let Some(cached) = self.render_pipelines.get_mut(&hash) {
-            cached.hit_count += 1;
-            self.total_hits += 1;
-            Some(cached.handle)
-        } else {
-            self.total_misses += 1;
-            None
+    fn pipelines(&self, kind: PipelineKind) -> &HashMap<u64, CachedPipeline> {
+        match kind {
+            PipelineKind::Render => &self.render_pipelines,
+            PipelineKind::Compute => &self.compute_pipelines,
         }
     }
 
-    /// Cache render pipeline
-    fn cache_render_pipeline(&mut self, hash: u64, handle: u64) {
-        self.render_pipelines.insert(hash, CachedPipeline {
-            handle,
-            hash,
-            created_at: Self::timestamp(),
-            hit_count: 0,
-        });
+    fn pipelines_mut(&mut self, kind: PipelineKind) -> &mut HashMap<u64, CachedPipeline> {
+        match kind {
+            PipelineKind::Render => &mut self.render_pipelines,
+            PipelineKind::Compute => &mut self.compute_pipelines,
+        }
+    }
+
+    fn capacity(&self, kind: PipelineKind) -> usize {
+        match kind {
+            PipelineKind::Render => self.config.render_capacity,
+            PipelineKind::Compute => self.config.compute_capacity,
+        }
     }
 
-    /// Lookup compute pipeline by descriptor hash
-    fn lookup_compute_pipeline(&mut self, hash: u64) -> Option<u64> {
-        if let Some(cached) = self.compute_pipelines.get_mut(&hash) {
+    fn is_expired(&self, pipeline: &CachedPipeline, now: u64) -> bool {
+        self.config
+            .ttl_ms
+            .is_some_and(|ttl| now.saturating_sub(pipeline.created_at) >= ttl)
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.access_counter += 1;
+        self.access_counter
+    }
+
+    /// Lookup pipeline by descriptor hash
+    fn lookup(&mut self, kind: PipelineKind, hash: u64, now: u64) -> (Option<u64>, Vec<EvictedPipeline>) {
+        let mut evicted = Vec::new();
+        let expired = self
+            .pipelines(kind)
+            .get(&hash)
+            .is_some_and(|p| self.is_expired(p, now));
+        if expired {
+            evicted.extend(self.evict(kind, hash, EvictionReason::Expired));
+        }
+
+        let tick = self.tick();
+        if let Some(cached) = self.pipelines_mut(kind).get_mut(&hash) {
             cached.hit_count += 1;
+            cached.last_used = tick;
+            let handle = cached.handle;
             self.total_hits += 1;
-            Some(cached.handle)
+            (Some(handle), evicted)
         } else {
             self.total_misses += 1;
-            None
+            (None, evicted)
         }
     }
 
-    /// Cache compute pipeline
-    fn cache_compute_pipeline(&mut self, hash: u64, handle: u64) {
-        self.compute_pipelines.insert(hash, CachedPipeline {
+    /// Cache pipeline, evicting to stay within capacity
+    fn insert(&mut self, kind: PipelineKind, hash: u64, handle: u64, now: u64) -> Vec<EvictedPipeline> {
+        let mut evicted = self.purge_expired(now);
+
+        let tick = self.tick();
+        let previous = self.pipelines_mut(kind).insert(hash, CachedPipeline {
             handle,
             hash,
-            created_at: Self::timestamp(),
+            created_at: now,
             hit_count: 0,
+            last_used: tick,
         });
+        if let Some(previous) = previous.filter(|p| p.handle != handle) {
+            evicted.push(self.record(kind, &previous, EvictionReason::Replaced));
+        }
+
+        evicted.extend(self.enforce_capacity(kind, Some(hash)));
+        evicted
+    }
+
+    /// Evict entries until `kind` fits its capacity, never evicting `keep`
+    fn enforce_capacity(&mut self, kind: PipelineKind, keep: Option<u64>) -> Vec<EvictedPipeline> {
+        let capacity = self.capacity(kind);
+        let policy = self.config.policy;
+        let mut evicted = Vec::new();
+        if capacity == 0 {
+            return evicted;
+        }
+
+        while self.pipelines(kind).len() > capacity {
+            let victim = self
+                .pipelines(kind)
+                .values()
+                .filter(|p| Some(p.hash) != keep)
+                .min_by_key(|p| match policy {
+                    EvictionPolicy::Lru => (0, p.last_used),
+                    EvictionPolicy::Lfu => (p.hit_count, p.last_used),
+                })
+                .map(|p| p.hash);
+            let Some(victim) = victim else {
+                break;
+            };
+            evicted.extend(self.evict(kind, victim, EvictionReason::Capacity));
+        }
+        evicted
+    }
+
+    /// Drop every expired entry
+    fn purge_expired(&mut self, now: u64) -> Vec<EvictedPipeline> {
+        if self.config.ttl_ms.is_none() {
+            return Vec::new();
+        }
+        let mut evicted = Vec::new();
+        for kind in [PipelineKind::Render, PipelineKind::Compute] {
+            let expired: Vec<u64> = self
+                .pipelines(kind)
+                .values()
+                .filter(|p| self.is_expired(p, now))
+                .map(|p| p.hash)
+                .collect();
+            for hash in expired {
+                evicted.extend(self.evict(kind, hash, EvictionReason::Expired));
+            }
+        }
+        evicted
+    }
+
+    fn evict(&mut self, kind: PipelineKind, hash: u64, reason: EvictionReason) -> Option<EvictedPipeline> {
+        let pipeline = self.pipelines_mut(kind).remove(&hash)?;
+        Some(self.record(kind, &pipeline, reason))
+    }
+
+    fn record(&mut self, kind: PipelineKind, pipeline: &CachedPipeline, reason: EvictionReason) -> EvictedPipeline {
+        match reason {
+            EvictionReason::Capacity => self.evictions += 1,
+            EvictionReason::Expired => self.expirations += 1,
+            EvictionReason::Replaced | EvictionReason::Cleared => {}
+        }
+        let evicted = EvictedPipeline {
+            hash: pipeline.hash,
+            handle: pipeline.handle,
+            pipeline_type: kind.name().to_string(),
+            reason,
+            hit_count: pipeline.hit_count,
+        };
+        if self.config.record_evictions {
+            if self.evicted.len() == MAX_RECORDED_EVICTIONS {
+                self.evicted.pop_front();
+            }
+            self.evicted.push_back(evicted.clone());
+        }
+        evicted
+    }
+
+    fn clear(&mut self) -> Vec<EvictedPipeline> {
+        let mut evicted = Vec::new();
+        for kind in [PipelineKind::Render, PipelineKind::Compute] {
+            let pipelines: Vec<CachedPipeline> = self.pipelines_mut(kind).drain().map(|(_, p)| p).collect();
+            for pipeline in pipelines {
+                evicted.push(self.record(kind, &pipeline, EvictionReason::Cleared));
+            }
+        }
+        evicted
+    }
+
+    fn configure(&mut self, config: PipelineCacheConfig, now: u64) -> Vec<EvictedPipeline> {
+        if !config.record_evictions {
+            self.evicted.clear();
+        }
+        self.config = config;
+        let mut evicted = self.purge_expired(now);
+        evicted.extend(self.enforce_capacity(PipelineKind::Render, None));
+        evicted.extend(self.enforce_capacity(PipelineKind::Compute, None));
+        evicted
     }
 
     fn timestamp() -> u64 {
@@ -88,31 +308,102 @@ impl PipelineCache {
     }
 }
 
+/// Run the eviction callback outside the cache and callback locks, so it may call back
+/// into the cache or replace the callback
+fn notify(evicted: Vec<EvictedPipeline>) {
+    if evicted.is_empty() {
+        return;
+    }
+    let callback = EVICTION_CALLBACK.lock().clone();
+    if let Some(callback) = callback {
+        for pipeline in &evicted {
+            callback(pipeline);
+        }
+    }
+}
+
 /// Compute hash of descriptor
+///
+/// Hashes the canonical form (see `pipeline::canonical`), so key order, omitted
+/// defaults and labels do not matter. Text that is not JSON is hashed as-is.
 pub fn hash_descriptor(descriptor_json: String) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    descriptor_json.hash(&mut hasher);
-    hasher.finish()
+    super::canonical::canonical_descriptor_hash(&descriptor_json).unwrap_or_else(|_| {
+        super::canonical::fnv1a_64(descriptor_json.as_bytes())
+    })
 }
 
 /// Lookup render pipeline in cache
 pub fn pipeline_cache_lookup_render(descriptor_hash: u64) -> u64 {
-    PIPELINE_CACHE.lock().lookup_render_pipeline(descriptor_hash).unwrap_or(0)
+    let (handle, evicted) = PIPELINE_CACHE
+        .lock()
+        .lookup(PipelineKind::Render, descriptor_hash, PipelineCache::timestamp());
+    notify(evicted);
+    handle.unwrap_or(0)
 }
 
 /// Cache render pipeline
 pub fn pipeline_cache_insert_render(descriptor_hash: u64, pipeline_handle: u64) {
-    PIPELINE_CACHE.lock().cache_render_pipeline(descriptor_hash, pipeline_handle);
+    let evicted = PIPELINE_CACHE.lock().insert(
+        PipelineKind::Render,
+        descriptor_hash,
+        pipeline_handle,
+        PipelineCache::timestamp(),
+    );
+    notify(evicted);
 }
 
 /// Lookup compute pipeline in cache
 pub fn pipeline_cache_lookup_compute(descriptor_hash: u64) -> u64 {
-    PIPELINE_CACHE.lock().lookup_compute_pipeline(descriptor_hash).unwrap_or(0)
+    let (handle, evicted) = PIPELINE_CACHE
+        .lock()
+        .lookup(PipelineKind::Compute, descriptor_hash, PipelineCache::timestamp());
+    notify(evicted);
+    handle.unwrap_or(0)
 }
 
 /// Cache compute pipeline
 pub fn pipeline_cache_insert_compute(descriptor_hash: u64, pipeline_handle: u64) {
-    PIPELINE_CACHE.lock().cache_compute_pipeline(descriptor_hash, pipeline_handle);
+    let evicted = PIPELINE_CACHE.lock().insert(
+        PipelineKind::Compute,
+        descriptor_hash,
+        pipeline_handle,
+        PipelineCache::timestamp(),
+    );
+    notify(evicted);
+}
+
+/// Set capacities, eviction policy and TTL; entries beyond the new limits are evicted
+pub fn pipeline_cache_configure(config: PipelineCacheConfig) {
+    let evicted = PIPELINE_CACHE.lock().configure(config, PipelineCache::timestamp());
+    notify(evicted);
+}
+
+/// Current pipeline cache configuration
+pub fn pipeline_cache_config() -> PipelineCacheConfig {
+    PIPELINE_CACHE.lock().config.clone()
+}
+
+/// Call `callback` for every pipeline the cache drops (capacity, expiry, replacement, clear)
+pub fn pipeline_cache_set_eviction_callback(callback: impl Fn(&EvictedPipeline) + Send + Sync + 'static) {
+    *EVICTION_CALLBACK.lock() = Some(Arc::new(callback));
+}
+
+/// Remove the eviction callback
+pub fn pipeline_cache_clear_eviction_callback() {
+    *EVICTION_CALLBACK.lock() = None;
+}
+
+/// Take the queued evictions (requires `record_evictions`)
+pub fn pipeline_cache_drain_evictions() -> Vec<EvictedPipeline> {
+    PIPELINE_CACHE.lock().evicted.drain(..).collect()
+}
+
+/// Drop expired pipelines now instead of on the next lookup or insert
+pub fn pipeline_cache_purge_expired() -> usize {
+    let evicted = PIPELINE_CACHE.lock().purge_expired(PipelineCache::timestamp());
+    let count = evicted.len();
+    notify(evicted);
+    count
 }
 
 /// Pipeline cache statistics
@@ -122,6 +413,10 @@ pub struct PipelineCacheStats {
     pub total_hits: u64,
     pub total_misses: u64,
     pub hit_rate: f64,
+    /// Pipelines dropped to stay within capacity
+    pub evictions: u64,
+    /// Pipelines dropped because their TTL ran out
+    pub expirations: u64,
 }
 
 /// Get pipeline cache statistics
@@ -140,14 +435,15 @@ pub fn pipeline_cache_stats() -> PipelineCacheStats {
         total_hits: cache.total_hits,
         total_misses: cache.total_misses,
         hit_rate,
+        evictions: cache.evictions,
+        expirations: cache.expirations,
     }
 }
 
 /// Clear pipeline cache
 pub fn pipeline_cache_clear() {
-    let mut cache = PIPELINE_CACHE.lock();
-    cache.render_pipelines.clear();
-    cache.compute_pipelines.clear();
+    let evicted = PIPELINE_CACHE.lock().clear();
+    notify(evicted);
 }
 
 /// Remove specific render pipeline from cache
@@ -200,3 +496,124 @@ pub fn pipeline_cache_top_hits(top_n: u32) -> Vec<PipelineHitInfo> {
 
     hits
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(config: PipelineCacheConfig) -> PipelineCache {
+        let mut cache = PipelineCache::new();
+        cache.configure(config, 0);
+        cache
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let mut cache = cache(PipelineCacheConfig {
+            compute_capacity: 2,
+            ..Default::default()
+        });
+        cache.insert(PipelineKind::Compute, 1, 10, 0);
+        cache.insert(PipelineKind::Compute, 2, 20, 0);
+        cache.lookup(PipelineKind::Compute, 1, 0);
+
+        let evicted = cache.insert(PipelineKind::Compute, 3, 30, 0);
+        assert_eq!(evicted.len(), 1);
+        assert_eq!((evicted[0].handle, evicted[0].reason), (20, EvictionReason::Capacity));
+        assert_eq!(cache.lookup(PipelineKind::Compute, 1, 0).0, Some(10));
+        assert_eq!(cache.evictions, 1);
+
+        // Render capacity is separate and unbounded here
+        for hash in 0..10 {
+            assert!(cache.insert(PipelineKind::Render, hash, hash, 0).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_lfu_eviction() {
+        let mut cache = cache(PipelineCacheConfig {
+            render_capacity: 2,
+            policy: EvictionPolicy::Lfu,
+            ..Default::default()
+        });
+        cache.insert(PipelineKind::Render, 1, 10, 0);
+        cache.insert(PipelineKind::Render, 2, 20, 0);
+        cache.lookup(PipelineKind::Render, 1, 0);
+        cache.lookup(PipelineKind::Render, 1, 0);
+        cache.lookup(PipelineKind::Render, 2, 0);
+
+        // 2 was used more recently but 1 has more hits
+        let evicted = cache.insert(PipelineKind::Render, 3, 30, 0);
+        assert_eq!(evicted[0].hash, 2);
+    }
+
+    #[test]
+    fn test_ttl_expiry() {
+        let mut cache = cache(PipelineCacheConfig {
+            ttl_ms: Some(1000),
+            record_evictions: true,
+            ..Default::default()
+        });
+        cache.insert(PipelineKind::Compute, 1, 10, 0);
+        assert_eq!(cache.lookup(PipelineKind::Compute, 1, 999).0, Some(10));
+
+        let (handle, evicted) = cache.lookup(PipelineKind::Compute, 1, 1000);
+        assert_eq!(handle, None);
+        assert_eq!(evicted[0].reason, EvictionReason::Expired);
+        assert_eq!(cache.expirations, 1);
+        assert_eq!(cache.evicted.len(), 1);
+    }
+
+    #[test]
+    fn test_recorded_evictions_are_capped() {
+        let mut cache = cache(PipelineCacheConfig {
+            record_evictions: true,
+            ..Default::default()
+        });
+        for handle in 0..MAX_RECORDED_EVICTIONS as u64 + 11 {
+            cache.insert(PipelineKind::Compute, 1, handle, 0);
+        }
+        assert_eq!(cache.evicted.len(), MAX_RECORDED_EVICTIONS);
+        assert_eq!(cache.evicted.front().unwrap().handle, 10);
+    }
+
+    #[test]
+    fn test_eviction_callback_may_replace_itself() {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let seen = Arc::clone(&calls);
+        pipeline_cache_set_eviction_callback(move |_| {
+            seen.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            pipeline_cache_clear_eviction_callback();
+        });
+        notify(vec![EvictedPipeline {
+            hash: 1,
+            handle: 10,
+            pipeline_type: "compute".to_string(),
+            reason: EvictionReason::Cleared,
+            hit_count: 0,
+        }]);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert!(EVICTION_CALLBACK.lock().is_none());
+    }
+
+    #[test]
+    fn test_replace_and_shrink() {
+        let mut cache = cache(PipelineCacheConfig::default());
+        cache.insert(PipelineKind::Render, 1, 10, 0);
+        let evicted = cache.insert(PipelineKind::Render, 1, 11, 0);
+        assert_eq!((evicted[0].handle, evicted[0].reason), (10, EvictionReason::Replaced));
+
+        cache.insert(PipelineKind::Render, 2, 20, 0);
+        cache.insert(PipelineKind::Render, 3, 30, 0);
+        let evicted = cache.configure(
+            PipelineCacheConfig {
+                render_capacity: 1,
+                ..Default::default()
+            },
+            0,
+        );
+        assert_eq!(evicted.len(), 2);
+        assert_eq!(cache.render_pipelines.len(), 1);
+        assert!(cache.render_pipelines.contains_key(&3));
+    }
+}
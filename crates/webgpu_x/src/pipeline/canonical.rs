@@ -0,0 +1,326 @@
+//! Canonical pipeline descriptors
+//!
+//! Equivalent descriptors must map to the same cache entry, so before hashing a
+//! descriptor is rewritten into one canonical form:
+//! - WebGPU defaults are filled in (`primitive`, `multisample`, blend and stencil state,
+//!   vertex buffer step modes, `constants`, `layout: "auto"`), so omitting a default and
+//!   spelling it out give the same result
+//! - `label`s are dropped; they do not change the pipeline
+//! - Numbers are normalized: `1.0` becomes `1`, `-0` becomes `0`, and constant values
+//!   that are booleans become `0`/`1`
+//! - Object keys are sorted and the JSON is written without whitespace
+//!
+//! The hash is 64-bit FNV-1a over `DESCRIPTOR_HASH_DOMAIN` followed by the canonical
+//! JSON bytes. It depends on nothing but those bytes, so hashes are stable across
+//! processes, platforms and compiler versions and can be stored on disk.
+
+use serde_json::{Map, Number, Value};
+
+/// Prefix mixed into every descriptor hash; bump it when the canonical form changes
+pub const DESCRIPTOR_HASH_DOMAIN: &str = "webgpu_x/pipeline-descriptor/v1\0";
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// 64-bit FNV-1a
+pub fn fnv1a_64(bytes: &[u8]) -> u64 {
+    extend_fnv1a_64(FNV_OFFSET_BASIS, bytes)
+}
+
+fn extend_fnv1a_64(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// Kind of descriptor, detected from its top-level keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DescriptorKind {
+    Render,
+    Compute,
+    /// Anything else; only labels, numbers and key order are normalized
+    Other,
+}
+
+pub fn descriptor_kind(descriptor: &Value) -> DescriptorKind {
+    match descriptor.as_object() {
+        Some(obj) if obj.contains_key("compute") => DescriptorKind::Compute,
+        Some(obj) if obj.contains_key("vertex") => DescriptorKind::Render,
+        _ => DescriptorKind::Other,
+    }
+}
+
+/// Rewrite a parsed descriptor into canonical form
+pub fn canonicalize_descriptor(descriptor: &Value) -> Value {
+    let mut value = normalize(descriptor);
+    if let Some(obj) = value.as_object_mut() {
+        match descriptor_kind(descriptor) {
+            DescriptorKind::Compute => fill_compute_defaults(obj),
+            DescriptorKind::Render => fill_render_defaults(obj),
+            DescriptorKind::Other => {}
+        }
+    }
+    value
+}
+
+/// Canonical JSON text of a descriptor
+pub fn canonical_descriptor_json(descriptor_json: &str) -> Result<String, String> {
+    let descriptor: Value =
+        serde_json::from_str(descriptor_json).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let mut out = String::new();
+    write_sorted(&canonicalize_descriptor(&descriptor), &mut out);
+    Ok(out)
+}
+
+/// Stable hash of a descriptor's canonical form
+pub fn canonical_descriptor_hash(descriptor_json: &str) -> Result<u64, String> {
+    let canonical = canonical_descriptor_json(descriptor_json)?;
+    let hash = fnv1a_64(DESCRIPTOR_HASH_DOMAIN.as_bytes());
+    Ok(extend_fnv1a_64(hash, canonical.as_bytes()))
+}
+
+/// Drop labels and normalize numbers, recursively
+fn normalize(value: &Value) -> Value {
+    match value {
+        Value::Number(n) => Value::Number(normalize_number(n)),
+        Value::Array(items) => Value::Array(items.iter().map(normalize).collect()),
+        Value::Object(obj) => {
+            let mut out = Map::new();
+            for (key, item) in obj {
+                match key.as_str() {
+                    "label" => {}
+                    "constants" => {
+                        out.insert(key.clone(), normalize_constants(item));
+                    }
+                    _ => {
+                        out.insert(key.clone(), normalize(item));
+                    }
+                }
+            }
+            Value::Object(out)
+        }
+        _ => value.clone(),
+    }
+}
+
+/// Pipeline-overridable constants are doubles; any key is a constant name
+fn normalize_constants(value: &Value) -> Value {
+    match value {
+        Value::Object(obj) => Value::Object(
+            obj.iter()
+                .map(|(key, item)| {
+                    let item = match item {
+                        Value::Bool(b) => Value::Number(Number::from(*b as u64)),
+                        Value::Number(n) => Value::Number(normalize_number(n)),
+                        other => other.clone(),
+                    };
+                    (key.clone(), item)
+                })
+                .collect(),
+        ),
+        other => normalize(other),
+    }
+}
+
+fn normalize_number(n: &Number) -> Number {
+    if n.is_u64() || n.is_i64() {
+        return n.clone();
+    }
+    let Some(f) = n.as_f64() else {
+        return n.clone();
+    };
+    if f == 0.0 {
+        return Number::from(0u64);
+    }
+    if f.fract() == 0.0 && f.abs() < 9.007_199_254_740_992e15 {
+        return if f > 0.0 {
+            Number::from(f as u64)
+        } else {
+            Number::from(f as i64)
+        };
+    }
+    Number::from_f64(f).unwrap_or_else(|| n.clone())
+}
+
+fn set_default(obj: &mut Map<String, Value>, key: &str, default: Value) {
+    if obj.get(key).is_none_or(Value::is_null) {
+        obj.insert(key.to_string(), default);
+    }
+}
+
+/// Object stored under `key`, created when missing
+fn object_entry<'a>(obj: &'a mut Map<String, Value>, key: &str) -> Option<&'a mut Map<String, Value>> {
+    set_default(obj, key, Value::Object(Map::new()));
+    obj.get_mut(key).and_then(Value::as_object_mut)
+}
+
+fn fill_stage_defaults(stage: &mut Map<String, Value>) {
+    set_default(stage, "constants", Value::Object(Map::new()));
+}
+
+fn fill_compute_defaults(obj: &mut Map<String, Value>) {
+    set_default(obj, "layout", Value::from("auto"));
+    if let Some(compute) = obj.get_mut("compute").and_then(Value::as_object_mut) {
+        fill_stage_defaults(compute);
+    }
+}
+
+fn fill_render_defaults(obj: &mut Map<String, Value>) {
+    set_default(obj, "layout", Value::from("auto"));
+
+    if let Some(vertex) = obj.get_mut("vertex").and_then(Value::as_object_mut) {
+        fill_stage_defaults(vertex);
+        set_default(vertex, "buffers", Value::Array(Vec::new()));
+        if let Some(buffers) = vertex.get_mut("buffers").and_then(Value::as_array_mut) {
+            for buffer in buffers.iter_mut().filter_map(Value::as_object_mut) {
+                set_default(buffer, "stepMode", Value::from("vertex"));
+                set_default(buffer, "attributes", Value::Array(Vec::new()));
+            }
+        }
+    }
+
+    if let Some(primitive) = object_entry(obj, "primitive") {
+        set_default(primitive, "topology", Value::from("triangle-list"));
+        set_default(primitive, "frontFace", Value::from("ccw"));
+        set_default(primitive, "cullMode", Value::from("none"));
+        set_default(primitive, "unclippedDepth", Value::from(false));
+    }
+
+    if let Some(multisample) = object_entry(obj, "multisample") {
+        set_default(multisample, "count", Value::from(1u32));
+        set_default(multisample, "mask", Value::from(0xFFFF_FFFFu32));
+        set_default(multisample, "alphaToCoverageEnabled", Value::from(false));
+    }
+
+    if let Some(depth_stencil) = obj.get_mut("depthStencil").and_then(Value::as_object_mut) {
+        for face in ["stencilFront", "stencilBack"] {
+            if let Some(face) = object_entry(depth_stencil, face) {
+                set_default(face, "compare", Value::from("always"));
+                set_default(face, "failOp", Value::from("keep"));
+                set_default(face, "depthFailOp", Value::from("keep"));
+                set_default(face, "passOp", Value::from("keep"));
+            }
+        }
+        set_default(depth_stencil, "stencilReadMask", Value::from(0xFFFF_FFFFu32));
+        set_default(depth_stencil, "stencilWriteMask", Value::from(0xFFFF_FFFFu32));
+        set_default(depth_stencil, "depthBias", Value::from(0u32));
+        set_default(depth_stencil, "depthBiasSlopeScale", Value::from(0u32));
+        set_default(depth_stencil, "depthBiasClamp", Value::from(0u32));
+    }
+
+    if let Some(fragment) = obj.get_mut("fragment").and_then(Value::as_object_mut) {
+        fill_stage_defaults(fragment);
+        if let Some(targets) = fragment.get_mut("targets").and_then(Value::as_array_mut) {
+            for target in targets.iter_mut().filter_map(Value::as_object_mut) {
+                set_default(target, "writeMask", Value::from(0xFu32));
+                if let Some(blend) = target.get_mut("blend").and_then(Value::as_object_mut) {
+                    for component in ["color", "alpha"] {
+                        if let Some(component) = object_entry(blend, component) {
+                            set_default(component, "operation", Value::from("add"));
+                            set_default(component, "srcFactor", Value::from("one"));
+                            set_default(component, "dstFactor", Value::from("zero"));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Compact JSON with object keys in byte order, independent of serde_json's map type
+fn write_sorted(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(obj) => {
+            let mut keys: Vec<&String> = obj.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_sorted(&obj[key], out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_sorted(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equivalent_compute_descriptors_hash_equal() {
+        let a = r#"{"label": "a", "compute": {"module": 7, "entryPoint": "main"}}"#;
+        let b = r#"{
+            "layout": "auto",
+            "compute": {"entryPoint": "main", "constants": {}, "module": 7.0}
+        }"#;
+        assert_eq!(canonical_descriptor_json(a).unwrap(), canonical_descriptor_json(b).unwrap());
+        assert_eq!(canonical_descriptor_hash(a).unwrap(), canonical_descriptor_hash(b).unwrap());
+
+        let c = r#"{"compute": {"module": 7, "entryPoint": "other"}}"#;
+        assert_ne!(canonical_descriptor_hash(a).unwrap(), canonical_descriptor_hash(c).unwrap());
+    }
+
+    #[test]
+    fn test_render_defaults_are_filled() {
+        let short = r#"{
+            "vertex": {"module": 1, "entryPoint": "vs", "buffers": [{"arrayStride": 12, "attributes": []}]},
+            "fragment": {"module": 1, "entryPoint": "fs", "targets": [{"format": "bgra8unorm", "blend": {"color": {}}}]}
+        }"#;
+        let long = r#"{
+            "layout": "auto",
+            "vertex": {"module": 1, "entryPoint": "vs", "constants": {},
+                       "buffers": [{"arrayStride": 12, "stepMode": "vertex", "attributes": []}]},
+            "primitive": {"topology": "triangle-list", "frontFace": "ccw", "cullMode": "none", "unclippedDepth": false},
+            "multisample": {"count": 1, "mask": 4294967295, "alphaToCoverageEnabled": false},
+            "fragment": {"module": 1, "entryPoint": "fs", "constants": {}, "targets": [{
+                "format": "bgra8unorm", "writeMask": 15,
+                "blend": {
+                    "alpha": {"operation": "add", "srcFactor": "one", "dstFactor": "zero"},
+                    "color": {"dstFactor": "zero", "srcFactor": "one", "operation": "add"}
+                }
+            }]}
+        }"#;
+        assert_eq!(canonical_descriptor_json(short).unwrap(), canonical_descriptor_json(long).unwrap());
+
+        let culled = short.replace(r#""vertex": {"#, r#""primitive": {"cullMode": "back"}, "vertex": {"#);
+        assert_ne!(canonical_descriptor_hash(short).unwrap(), canonical_descriptor_hash(&culled).unwrap());
+    }
+
+    #[test]
+    fn test_constants_keep_label_keys_and_normalize_values() {
+        let a = r#"{"compute": {"module": 1, "constants": {"label": 1.0, "flag": true, "zero": -0.0}}}"#;
+        let canonical = canonical_descriptor_json(a).unwrap();
+        assert!(canonical.contains(r#""constants":{"flag":1,"label":1,"zero":0}"#), "{}", canonical);
+    }
+
+    #[test]
+    fn test_hash_is_stable() {
+        // FNV-1a test vectors
+        assert_eq!(fnv1a_64(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a_64(b"a"), 0xaf63dc4c8601ec8c);
+
+        let canonical = canonical_descriptor_json(r#"{"compute": {"module": 1}}"#).unwrap();
+        assert_eq!(canonical, r#"{"compute":{"constants":{},"module":1},"layout":"auto"}"#);
+        let mut bytes = DESCRIPTOR_HASH_DOMAIN.as_bytes().to_vec();
+        bytes.extend_from_slice(canonical.as_bytes());
+        assert_eq!(canonical_descriptor_hash(r#"{"compute": {"module": 1}}"#).unwrap(), fnv1a_64(&bytes));
+    }
+}
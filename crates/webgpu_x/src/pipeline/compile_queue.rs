@@ -0,0 +1,571 @@
+//! Asynchronous, prioritized pipeline compilation
+//!
+//! Requests are queued with a priority and compiled on worker threads: shaders are
+//! parsed and validated with naga and the descriptor's entry points are checked, so
+//! that by the time a request resolves, creating the GPU pipeline will not fail or
+//! stall on shader errors. Identical requests (same canonical descriptor hash) share
+//! one ticket, and a resubmission at a higher priority moves the request up.
+//!
+//! A ticket can carry a fallback pipeline handle; [`TicketInfo::pipeline_handle`]
+//! returns it until the real pipeline is created and reported with
+//! [`CompileQueue::complete`], which also adds it to the `PipelineCache`.
+
+use super::cache::{
+    pipeline_cache_insert_compute, pipeline_cache_insert_render, pipeline_cache_lookup_compute,
+    pipeline_cache_lookup_render,
+};
+use super::canonical::{canonicalize_descriptor, descriptor_kind, DescriptorKind};
+use crate::shader::reflection::validate_wgsl;
+use lazy_static::lazy_static;
+use parking_lot::{Condvar, Mutex};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// How urgently a pipeline is needed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompilePriority {
+    /// Speculative warm-up
+    Low,
+    #[default]
+    Normal,
+    /// Needed for content that is about to be shown
+    High,
+    /// Needed for the current frame
+    Critical,
+}
+
+/// A pipeline to compile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompileRequest {
+    /// Render or compute pipeline descriptor
+    pub descriptor: Value,
+    /// WGSL of the modules the descriptor references
+    #[serde(default)]
+    pub shaders: Vec<String>,
+    #[serde(default)]
+    pub priority: CompilePriority,
+    /// Pipeline to use until this one is ready
+    #[serde(default)]
+    pub fallback_handle: Option<u64>,
+}
+
+/// Where a ticket is in its life
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum CompileStatus {
+    Queued,
+    Compiling,
+    /// Shaders validated; create the pipeline and report it with `complete`
+    Ready,
+    /// The GPU pipeline exists (created by the caller or found in the cache)
+    Complete,
+    Failed { message: String },
+    Cancelled,
+}
+
+impl CompileStatus {
+    fn is_live(&self) -> bool {
+        !matches!(self, CompileStatus::Failed { .. } | CompileStatus::Cancelled)
+    }
+}
+
+/// Output of a successful compilation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompiledPipeline {
+    pub pipeline_type: String, // "render" or "compute"
+    /// Canonical descriptor
+    pub descriptor: Value,
+    /// Entry points declared by the validated shaders
+    pub entry_points: Vec<String>,
+    pub compile_ms: f64,
+}
+
+/// Snapshot of a ticket for polling
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketInfo {
+    pub ticket: u64,
+    pub hash: u64,
+    pub priority: CompilePriority,
+    pub status: CompileStatus,
+    /// Real pipeline once complete, otherwise the fallback (0 if neither)
+    pub pipeline_handle: u64,
+    pub using_fallback: bool,
+    pub compiled: Option<CompiledPipeline>,
+}
+
+/// Queue counters
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct CompileQueueStats {
+    pub workers: usize,
+    pub queued: usize,
+    pub compiling: usize,
+    pub submitted: u64,
+    /// Submissions answered by an existing ticket or a cached pipeline
+    pub deduplicated: u64,
+    pub compiled: u64,
+    pub failed: u64,
+}
+
+struct Ticket {
+    hash: u64,
+    priority: CompilePriority,
+    pipeline_type: &'static str,
+    descriptor: Value,
+    shaders: Arc<Vec<String>>,
+    status: CompileStatus,
+    fallback: Option<u64>,
+    pipeline: Option<u64>,
+    compiled: Option<CompiledPipeline>,
+}
+
+/// Heap entry; stale once the ticket's priority changes or it leaves `Queued`
+#[derive(PartialEq, Eq)]
+struct QueuedJob {
+    priority: CompilePriority,
+    seq: u64,
+    ticket: u64,
+}
+
+impl Ord for QueuedJob {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // Highest priority first, then first come first served
+        self.priority
+            .cmp(&other.priority)
+            .then(other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for QueuedJob {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Default)]
+struct QueueState {
+    heap: BinaryHeap<QueuedJob>,
+    tickets: HashMap<u64, Ticket>,
+    by_hash: HashMap<u64, u64>,
+    next_ticket: u64,
+    next_seq: u64,
+    shutdown: bool,
+    stats: CompileQueueStats,
+}
+
+impl QueueState {
+    fn push(&mut self, ticket: u64, priority: CompilePriority) {
+        self.next_seq += 1;
+        self.heap.push(QueuedJob {
+            priority,
+            seq: self.next_seq,
+            ticket,
+        });
+    }
+
+    fn info(&self, id: u64) -> Option<TicketInfo> {
+        let ticket = self.tickets.get(&id)?;
+        let pipeline_handle = ticket.pipeline.or(ticket.fallback).unwrap_or(0);
+        Some(TicketInfo {
+            ticket: id,
+            hash: ticket.hash,
+            priority: ticket.priority,
+            status: ticket.status.clone(),
+            pipeline_handle,
+            using_fallback: ticket.pipeline.is_none() && ticket.fallback.is_some(),
+            compiled: ticket.compiled.clone(),
+        })
+    }
+}
+
+struct Shared {
+    state: Mutex<QueueState>,
+    /// Signalled when work is queued or on shutdown
+    work: Condvar,
+    /// Signalled when a ticket leaves `Compiling`
+    done: Condvar,
+}
+
+/// Pipeline compilation service with its own worker threads
+pub struct CompileQueue {
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl CompileQueue {
+    pub fn new(workers: usize) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(QueueState {
+                next_ticket: 1,
+                ..Default::default()
+            }),
+            work: Condvar::new(),
+            done: Condvar::new(),
+        });
+        shared.state.lock().stats.workers = workers.max(1);
+
+        let workers = (0..workers.max(1))
+            .map(|_| {
+                let shared = Arc::clone(&shared);
+                std::thread::spawn(move || worker_loop(&shared))
+            })
+            .collect();
+
+        Self { shared, workers }
+    }
+
+    /// Queue a request, returning its ticket
+    ///
+    /// A request whose descriptor is already queued, compiled or cached resolves to the
+    /// existing ticket; its priority is raised if the new request is more urgent.
+    pub fn submit(&self, request: CompileRequest) -> Result<u64, String> {
+        let pipeline_type = match descriptor_kind(&request.descriptor) {
+            DescriptorKind::Render => "render",
+            DescriptorKind::Compute => "compute",
+            DescriptorKind::Other => {
+                return Err("Descriptor is neither a render nor a compute pipeline".to_string())
+            }
+        };
+        let descriptor = canonicalize_descriptor(&request.descriptor);
+        let hash = super::cache::hash_descriptor(descriptor.to_string());
+
+        // Looked up before taking `state`: a lookup can run the cache's eviction callback,
+        // which may call back into this queue
+        let cached = match pipeline_type {
+            "render" => pipeline_cache_lookup_render(hash),
+            _ => pipeline_cache_lookup_compute(hash),
+        };
+
+        let mut state = self.shared.state.lock();
+        state.stats.submitted += 1;
+
+        if let Some(&id) = state.by_hash.get(&hash) {
+            let ticket = state.tickets.get_mut(&id).expect("by_hash points at a live ticket");
+            if ticket.status.is_live() {
+                ticket.fallback = ticket.fallback.or(request.fallback_handle);
+                let raise = ticket.status == CompileStatus::Queued && request.priority > ticket.priority;
+                if raise {
+                    ticket.priority = request.priority;
+                    state.push(id, request.priority);
+                    self.shared.work.notify_one();
+                }
+                state.stats.deduplicated += 1;
+                return Ok(id);
+            }
+        }
+
+        let id = state.next_ticket;
+        state.next_ticket += 1;
+
+        // Already created by someone else: nothing to compile
+        let status = if cached != 0 {
+            state.stats.deduplicated += 1;
+            CompileStatus::Complete
+        } else {
+            CompileStatus::Queued
+        };
+
+        state.tickets.insert(id, Ticket {
+            hash,
+            priority: request.priority,
+            pipeline_type,
+            descriptor,
+            shaders: Arc::new(request.shaders),
+            status: status.clone(),
+            fallback: request.fallback_handle,
+            pipeline: (cached != 0).then_some(cached),
+            compiled: None,
+        });
+        state.by_hash.insert(hash, id);
+        if status == CompileStatus::Queued {
+            state.push(id, request.priority);
+            self.shared.work.notify_one();
+        }
+        Ok(id)
+    }
+
+    /// Current state of a ticket
+    pub fn poll(&self, ticket: u64) -> Option<TicketInfo> {
+        self.shared.state.lock().info(ticket)
+    }
+
+    /// Block until a ticket is no longer queued or compiling, or the timeout passes
+    pub fn wait(&self, ticket: u64, timeout: Duration) -> Option<TicketInfo> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.shared.state.lock();
+        loop {
+            let status = &state.tickets.get(&ticket)?.status;
+            if !matches!(status, CompileStatus::Queued | CompileStatus::Compiling) {
+                break;
+            }
+            if self.shared.done.wait_until(&mut state, deadline).timed_out() {
+                break;
+            }
+        }
+        state.info(ticket)
+    }
+
+    /// Report the GPU pipeline created for a ready ticket and add it to the pipeline cache
+    pub fn complete(&self, ticket: u64, pipeline_handle: u64) -> Result<(), String> {
+        let mut state = self.shared.state.lock();
+        let entry = state
+            .tickets
+            .get_mut(&ticket)
+            .ok_or_else(|| "Invalid compile ticket".to_string())?;
+        if entry.status != CompileStatus::Ready {
+            return Err(format!("Ticket {} is not ready: {:?}", ticket, entry.status));
+        }
+        entry.status = CompileStatus::Complete;
+        entry.pipeline = Some(pipeline_handle);
+        let (hash, pipeline_type) = (entry.hash, entry.pipeline_type);
+        drop(state);
+
+        match pipeline_type {
+            "render" => pipeline_cache_insert_render(hash, pipeline_handle),
+            _ => pipeline_cache_insert_compute(hash, pipeline_handle),
+        }
+        Ok(())
+    }
+
+    /// Cancel a ticket that has not started compiling
+    pub fn cancel(&self, ticket: u64) -> bool {
+        let mut state = self.shared.state.lock();
+        match state.tickets.get_mut(&ticket) {
+            Some(entry) if entry.status == CompileStatus::Queued => {
+                entry.status = CompileStatus::Cancelled;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Forget a ticket; later submissions of the same descriptor start over
+    pub fn release(&self, ticket: u64) -> bool {
+        let mut state = self.shared.state.lock();
+        let Some(entry) = state.tickets.remove(&ticket) else {
+            return false;
+        };
+        if state.by_hash.get(&entry.hash) == Some(&ticket) {
+            state.by_hash.remove(&entry.hash);
+        }
+        true
+    }
+
+    pub fn stats(&self) -> CompileQueueStats {
+        let state = self.shared.state.lock();
+        let mut stats = state.stats;
+        for ticket in state.tickets.values() {
+            match ticket.status {
+                CompileStatus::Queued => stats.queued += 1,
+                CompileStatus::Compiling => stats.compiling += 1,
+                _ => {}
+            }
+        }
+        stats
+    }
+}
+
+impl Drop for CompileQueue {
+    fn drop(&mut self) {
+        self.shared.state.lock().shutdown = true;
+        self.shared.work.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn worker_loop(shared: &Shared) {
+    loop {
+        let mut state = shared.state.lock();
+        let (id, descriptor, shaders) = loop {
+            if state.shutdown {
+                return;
+            }
+            let Some(job) = state.heap.pop() else {
+                shared.work.wait(&mut state);
+                continue;
+            };
+            match state.tickets.get_mut(&job.ticket) {
+                Some(ticket) if ticket.status == CompileStatus::Queued && ticket.priority == job.priority => {
+                    ticket.status = CompileStatus::Compiling;
+                    break (job.ticket, ticket.descriptor.clone(), Arc::clone(&ticket.shaders));
+                }
+                _ => continue,
+            }
+        };
+        drop(state);
+
+        let result = compile(&descriptor, &shaders);
+
+        let mut state = shared.state.lock();
+        match &result {
+            Ok(_) => state.stats.compiled += 1,
+            Err(_) => state.stats.failed += 1,
+        }
+        if let Some(ticket) = state.tickets.get_mut(&id) {
+            match result {
+                Ok(compiled) => {
+                    ticket.status = CompileStatus::Ready;
+                    ticket.compiled = Some(compiled);
+                }
+                Err(message) => ticket.status = CompileStatus::Failed { message },
+            }
+        }
+        drop(state);
+        shared.done.notify_all();
+    }
+}
+
+fn compile(descriptor: &Value, shaders: &[String]) -> Result<CompiledPipeline, String> {
+    let start = Instant::now();
+    let mut entry_points = Vec::new();
+    for (index, code) in shaders.iter().enumerate() {
+        let shader = validate_wgsl(code).map_err(|e| format!("shader {}: {}", index, e))?;
+        entry_points.extend(shader.module.entry_points.iter().map(|ep| ep.name.clone()));
+    }
+    if !shaders.is_empty() {
+        if let Some(message) = missing_entry_point(descriptor, &entry_points) {
+            return Err(message);
+        }
+    }
+
+    Ok(CompiledPipeline {
+        pipeline_type: match descriptor_kind(descriptor) {
+            DescriptorKind::Render => "render",
+            _ => "compute",
+        }
+        .to_string(),
+        descriptor: descriptor.clone(),
+        entry_points,
+        compile_ms: start.elapsed().as_secs_f64() * 1000.0,
+    })
+}
+
+/// The first stage whose `entryPoint` none of `entry_points` provides
+pub(crate) fn missing_entry_point(descriptor: &Value, entry_points: &[String]) -> Option<String> {
+    ["vertex", "fragment", "compute"].iter().find_map(|stage| {
+        let name = descriptor[stage]["entryPoint"].as_str()?;
+        (!entry_points.iter().any(|ep| ep == name))
+            .then(|| format!("{} entry point '{}' not found in its shaders", stage, name))
+    })
+}
+
+lazy_static! {
+    static ref COMPILE_QUEUE: CompileQueue = CompileQueue::new(
+        std::thread::available_parallelism().map_or(1, |n| n.get().saturating_sub(1).max(1))
+    );
+}
+
+/// Queue a request on the shared compilation service
+pub fn pipeline_compile_submit(request: CompileRequest) -> Result<u64, String> {
+    COMPILE_QUEUE.submit(request)
+}
+
+pub fn pipeline_compile_poll(ticket: u64) -> Option<TicketInfo> {
+    COMPILE_QUEUE.poll(ticket)
+}
+
+pub fn pipeline_compile_complete(ticket: u64, pipeline_handle: u64) -> Result<(), String> {
+    COMPILE_QUEUE.complete(ticket, pipeline_handle)
+}
+
+pub fn pipeline_compile_cancel(ticket: u64) -> bool {
+    COMPILE_QUEUE.cancel(ticket)
+}
+
+pub fn pipeline_compile_release(ticket: u64) -> bool {
+    COMPILE_QUEUE.release(ticket)
+}
+
+pub fn pipeline_compile_stats() -> CompileQueueStats {
+    COMPILE_QUEUE.stats()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SHADER: &str = "@compute @workgroup_size(64) fn main() {}";
+
+    fn request(entry_point: &str, priority: CompilePriority) -> CompileRequest {
+        CompileRequest {
+            descriptor: json!({"compute": {"module": 42_001, "entryPoint": entry_point}}),
+            shaders: vec![SHADER.to_string()],
+            priority,
+            fallback_handle: None,
+        }
+    }
+
+    #[test]
+    fn test_compile_dedup_and_complete() {
+        let queue = CompileQueue::new(2);
+        let mut first = request("main", CompilePriority::Normal);
+        first.fallback_handle = Some(7);
+        let ticket = queue.submit(first).unwrap();
+
+        // Same descriptor spelled differently shares the ticket
+        let mut again = request("main", CompilePriority::High);
+        again.descriptor = json!({"label": "x", "compute": {"entryPoint": "main", "module": 42_001.0, "constants": {}}});
+        assert_eq!(queue.submit(again).unwrap(), ticket);
+
+        let info = queue.wait(ticket, Duration::from_secs(10)).unwrap();
+        assert_eq!(info.status, CompileStatus::Ready);
+        assert_eq!(info.pipeline_handle, 7);
+        assert!(info.using_fallback);
+        assert_eq!(info.compiled.unwrap().entry_points, vec!["main".to_string()]);
+
+        queue.complete(ticket, 99).unwrap();
+        let info = queue.poll(ticket).unwrap();
+        assert_eq!((info.status, info.pipeline_handle, info.using_fallback), (CompileStatus::Complete, 99, false));
+        assert_eq!(pipeline_cache_lookup_compute(info.hash), 99);
+        assert!(queue.complete(ticket, 100).is_err());
+
+        let stats = queue.stats();
+        assert_eq!((stats.submitted, stats.deduplicated, stats.compiled), (2, 1, 1));
+    }
+
+    #[test]
+    fn test_failures_are_reported() {
+        let queue = CompileQueue::new(1);
+        let missing = queue.submit(request("other", CompilePriority::Normal)).unwrap();
+        let mut broken = request("main", CompilePriority::Normal);
+        broken.shaders = vec!["fn main( {".to_string()];
+        broken.descriptor["compute"]["module"] = json!(42_002);
+        let broken = queue.submit(broken).unwrap();
+
+        for ticket in [missing, broken] {
+            let info = queue.wait(ticket, Duration::from_secs(10)).unwrap();
+            assert!(matches!(info.status, CompileStatus::Failed { .. }), "{:?}", info.status);
+        }
+        assert!(queue.submit(CompileRequest {
+            descriptor: json!({"entries": []}),
+            shaders: Vec::new(),
+            priority: CompilePriority::Low,
+            fallback_handle: None,
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_priority_order() {
+        let mut heap = BinaryHeap::new();
+        for (seq, priority) in [CompilePriority::Low, CompilePriority::Critical, CompilePriority::Normal, CompilePriority::Critical]
+            .into_iter()
+            .enumerate()
+        {
+            heap.push(QueuedJob {
+                priority,
+                seq: seq as u64,
+                ticket: seq as u64,
+            });
+        }
+        let order: Vec<u64> = std::iter::from_fn(|| heap.pop().map(|job| job.ticket)).collect();
+        assert_eq!(order, vec![1, 3, 2, 0]);
+    }
+}
@@ -1,8 +1,29 @@
 pub mod cache;
+pub mod canonical;
+pub mod compile_queue;
+pub mod persist;
 
 pub use cache::{
-    hash_descriptor, pipeline_cache_clear, pipeline_cache_insert_compute,
-    pipeline_cache_insert_render, pipeline_cache_lookup_compute, pipeline_cache_lookup_render,
-    pipeline_cache_remove_compute, pipeline_cache_remove_render, pipeline_cache_stats,
-    pipeline_cache_top_hits, PipelineCacheStats, PipelineHitInfo,
+    hash_descriptor, pipeline_cache_clear, pipeline_cache_clear_eviction_callback,
+    pipeline_cache_config, pipeline_cache_configure, pipeline_cache_drain_evictions,
+    pipeline_cache_insert_compute, pipeline_cache_insert_render, pipeline_cache_lookup_compute,
+    pipeline_cache_lookup_render, pipeline_cache_purge_expired, pipeline_cache_remove_compute,
+    pipeline_cache_remove_render, pipeline_cache_set_eviction_callback, pipeline_cache_stats,
+    pipeline_cache_top_hits, EvictedPipeline, EvictionPolicy, EvictionReason, PipelineCacheConfig,
+    PipelineCacheStats, PipelineHitInfo,
+};
+pub use canonical::{
+    canonical_descriptor_hash, canonical_descriptor_json, canonicalize_descriptor, descriptor_kind,
+    fnv1a_64, DescriptorKind, DESCRIPTOR_HASH_DOMAIN,
+};
+pub use compile_queue::{
+    pipeline_compile_cancel, pipeline_compile_complete, pipeline_compile_poll,
+    pipeline_compile_release, pipeline_compile_stats, pipeline_compile_submit, CompilePriority,
+    CompileQueue, CompileQueueStats, CompileRequest, CompileStatus, CompiledPipeline, TicketInfo,
+};
+pub use persist::{
+    pipeline_persist_close, pipeline_persist_open, pipeline_persist_poll_warmup,
+    pipeline_persist_start_warmup, with_persistent_cache, CacheIdentity, PersistLoadReport,
+    PersistOpenResult, PersistedPipeline, PersistentCache, Warmup, WarmupFailure, WarmupItem,
+    WarmupProgress, PERSIST_FORMAT_VERSION,
 };
@@ -0,0 +1,746 @@
+//! On-disk persistence for the pipeline and shader caches
+//!
+//! Everything recorded for one adapter/driver/crate version lives in its own
+//! directory under the cache root, named after the identity fingerprint:
+//!
+//! ```text
+//! <root>/<fingerprint>/manifest.json    identity, format version, file checksums
+//!                     /pipelines.json   canonical descriptors with hit counts
+//!                     /shaders.json     validated shader sources
+//!                     /blobs/<hash>.bin backend pipeline cache data (wgpu `PipelineCache`)
+//! ```
+//!
+//! Files are written to a temporary name and renamed into place, and every file is
+//! checked against the FNV-1a checksum in the manifest when the cache is opened;
+//! corrupt files are dropped instead of trusted. Opening with a different driver or
+//! crate version for the same adapter deletes the stale directory.
+//!
+//! Pipeline handles only mean something to the process that created them, so the
+//! cache stores descriptors rather than pipelines. At startup [`PersistentCache::start_warmup`]
+//! re-validates the recorded shaders on a background thread and hands back the most
+//! used pipelines first, ready to be created with `create*PipelineAsync`.
+
+use super::cache::pipeline_cache_top_hits;
+use super::compile_queue::missing_entry_point;
+use super::canonical::{canonicalize_descriptor, descriptor_kind, fnv1a_64, DescriptorKind};
+use crate::shader::compilation::{ShaderCache, ShaderSnapshot};
+use crate::shader::reflection::validate_wgsl;
+use crate::shader::ShaderSource;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+
+/// Bump when the on-disk layout changes; older directories are discarded
+pub const PERSIST_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const PIPELINES_FILE: &str = "pipelines.json";
+const SHADERS_FILE: &str = "shaders.json";
+const BLOB_DIR: &str = "blobs";
+
+/// What cached data is valid for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheIdentity {
+    /// Adapter name plus vendor/device IDs, e.g. "NVIDIA GeForce RTX 4090 (10de:2684)"
+    pub adapter: String,
+    /// Driver name and version as reported by the adapter info
+    pub driver: String,
+    #[serde(default = "crate_version")]
+    pub crate_version: String,
+}
+
+fn crate_version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
+
+impl CacheIdentity {
+    pub fn new(adapter: impl Into<String>, driver: impl Into<String>) -> Self {
+        Self {
+            adapter: adapter.into(),
+            driver: driver.into(),
+            crate_version: crate_version(),
+        }
+    }
+
+    /// Stable hash of the identity, used as the directory name
+    pub fn fingerprint(&self) -> u64 {
+        let mut bytes = format!("webgpu_x/cache/v{}\0", PERSIST_FORMAT_VERSION).into_bytes();
+        for part in [&self.adapter, &self.driver, &self.crate_version] {
+            bytes.extend_from_slice(part.as_bytes());
+            bytes.push(0);
+        }
+        fnv1a_64(&bytes)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    format_version: u32,
+    identity: CacheIdentity,
+    /// Path relative to the cache directory -> FNV-1a checksum
+    files: BTreeMap<String, u64>,
+}
+
+/// A pipeline recorded for warm-up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedPipeline {
+    /// Canonical descriptor hash, the same key `PipelineCache` uses
+    pub hash: u64,
+    pub pipeline_type: String, // "render" or "compute"
+    /// Canonical descriptor
+    pub descriptor: Value,
+    /// `code_hash`es of the shaders the descriptor's modules were created from
+    #[serde(default)]
+    pub shaders: Vec<u64>,
+    pub hit_count: u64,
+    #[serde(default)]
+    pub has_blob: bool,
+}
+
+/// What `PersistentCache::open` found on disk
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PersistLoadReport {
+    pub directory: String,
+    pub pipelines: usize,
+    pub shaders: usize,
+    pub blobs: usize,
+    /// Why the existing directory was discarded, if it was
+    pub invalidated: Option<String>,
+    /// Files that failed their integrity check and were dropped
+    pub corrupt: Vec<String>,
+    /// Directories removed because they belong to an older driver or crate version
+    pub pruned: Vec<String>,
+}
+
+/// Pipeline and shader cache backed by a directory
+pub struct PersistentCache {
+    dir: PathBuf,
+    identity: CacheIdentity,
+    pipelines: BTreeMap<u64, PersistedPipeline>,
+    shaders: BTreeMap<u64, ShaderSnapshot>,
+    /// Blob file name -> checksum
+    blobs: BTreeMap<String, u64>,
+}
+
+impl PersistentCache {
+    /// Open (or create) the cache for `identity` under `root`
+    pub fn open(root: impl AsRef<Path>, identity: CacheIdentity) -> Result<(Self, PersistLoadReport), String> {
+        let root = root.as_ref();
+        let dir = root.join(format!("{:016x}", identity.fingerprint()));
+        std::fs::create_dir_all(dir.join(BLOB_DIR))
+            .map_err(|e| format!("Failed to create cache directory {}: {}", dir.display(), e))?;
+
+        let mut cache = Self {
+            dir,
+            identity,
+            pipelines: BTreeMap::new(),
+            shaders: BTreeMap::new(),
+            blobs: BTreeMap::new(),
+        };
+        let mut report = PersistLoadReport {
+            directory: cache.dir.to_string_lossy().into_owned(),
+            pruned: cache.prune_stale(root),
+            ..Default::default()
+        };
+
+        match cache.read_manifest() {
+            Ok(Some(manifest)) => cache.load(&manifest, &mut report),
+            Ok(None) => {}
+            Err(reason) => {
+                cache.wipe()?;
+                report.invalidated = Some(reason);
+            }
+        }
+
+        report.pipelines = cache.pipelines.len();
+        report.shaders = cache.shaders.len();
+        report.blobs = cache.blobs.len();
+        Ok((cache, report))
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn identity(&self) -> &CacheIdentity {
+        &self.identity
+    }
+
+    /// Record a pipeline descriptor and the shaders its modules came from, returning its hash
+    pub fn record_pipeline(&mut self, descriptor_json: &str, shaders: &[u64]) -> Result<u64, String> {
+        let descriptor: Value = serde_json::from_str(descriptor_json)
+            .map_err(|e| format!("Invalid descriptor JSON: {}", e))?;
+        let pipeline_type = match descriptor_kind(&descriptor) {
+            DescriptorKind::Render => "render",
+            DescriptorKind::Compute => "compute",
+            DescriptorKind::Other => {
+                return Err("Descriptor is neither a render nor a compute pipeline".to_string())
+            }
+        };
+        if let Some(missing) = shaders.iter().find(|hash| !self.shaders.contains_key(hash)) {
+            return Err(format!("Shader {:016x} has not been recorded", missing));
+        }
+
+        let hash = super::cache::hash_descriptor(descriptor_json.to_string());
+        let entry = self.pipelines.entry(hash).or_insert_with(|| PersistedPipeline {
+            hash,
+            pipeline_type: pipeline_type.to_string(),
+            descriptor: canonicalize_descriptor(&descriptor),
+            shaders: Vec::new(),
+            hit_count: 0,
+            has_blob: false,
+        });
+        for shader in shaders {
+            if !entry.shaders.contains(shader) {
+                entry.shaders.push(*shader);
+            }
+        }
+        Ok(hash)
+    }
+
+    /// Record a shader after validating it, returning its `code_hash`
+    pub fn record_shader(&mut self, snapshot: ShaderSnapshot) -> Result<u64, String> {
+        validate_wgsl(&snapshot.source.code).map_err(|e| e.to_string())?;
+        let hash = snapshot.code_hash;
+        self.shaders.insert(hash, snapshot);
+        Ok(hash)
+    }
+
+    /// Record every file-backed shader in `cache`, returning how many validated
+    pub fn record_shader_cache(&mut self, cache: &ShaderCache) -> usize {
+        cache
+            .snapshot()
+            .into_iter()
+            .filter(|snapshot| self.record_shader(snapshot.clone()).is_ok())
+            .count()
+    }
+
+    /// Put recorded file-backed shaders back into `cache`, skipping ones whose files changed
+    pub fn restore_shader_cache(&self, cache: &mut ShaderCache) -> usize {
+        let snapshots: Vec<ShaderSnapshot> = self.shaders.values().cloned().collect();
+        cache.restore(&snapshots)
+    }
+
+    /// Copy hit counts from the in-memory `PipelineCache` so warm-up favours hot pipelines
+    pub fn sync_hit_counts(&mut self) {
+        for hit in pipeline_cache_top_hits(u32::MAX) {
+            if let Some(pipeline) = self.pipelines.get_mut(&hit.hash) {
+                pipeline.hit_count = pipeline.hit_count.max(hit.hit_count);
+            }
+        }
+    }
+
+    /// Store backend pipeline cache data (e.g. `wgpu::PipelineCache::get_data`) for a recorded pipeline
+    pub fn store_blob(&mut self, pipeline_hash: u64, data: &[u8]) -> Result<(), String> {
+        let Some(pipeline) = self.pipelines.get_mut(&pipeline_hash) else {
+            return Err(format!("Pipeline {:016x} has not been recorded", pipeline_hash));
+        };
+        let name = blob_name(pipeline_hash);
+        write_atomic(&self.dir.join(&name), data)?;
+        pipeline.has_blob = true;
+        self.blobs.insert(name, fnv1a_64(data));
+        Ok(())
+    }
+
+    /// Backend pipeline cache data for a pipeline, if stored and intact
+    pub fn blob(&self, pipeline_hash: u64) -> Option<Vec<u8>> {
+        let name = blob_name(pipeline_hash);
+        let checksum = *self.blobs.get(&name)?;
+        std::fs::read(self.dir.join(&name))
+            .ok()
+            .filter(|data| fnv1a_64(data) == checksum)
+    }
+
+    /// Recorded pipelines, most used first
+    pub fn pipelines(&self) -> Vec<PersistedPipeline> {
+        let mut pipelines: Vec<PersistedPipeline> = self.pipelines.values().cloned().collect();
+        pipelines.sort_by(|a, b| b.hit_count.cmp(&a.hit_count).then(a.hash.cmp(&b.hash)));
+        pipelines
+    }
+
+    pub fn shader(&self, code_hash: u64) -> Option<&ShaderSnapshot> {
+        self.shaders.get(&code_hash)
+    }
+
+    /// Write pipelines, shaders and the manifest
+    pub fn save(&self) -> Result<(), String> {
+        let mut files = self.blobs.clone();
+        let pipelines: Vec<&PersistedPipeline> = self.pipelines.values().collect();
+        let shaders: Vec<&ShaderSnapshot> = self.shaders.values().collect();
+
+        for (name, json) in [
+            (PIPELINES_FILE, serde_json::to_vec(&pipelines)),
+            (SHADERS_FILE, serde_json::to_vec(&shaders)),
+        ] {
+            let json = json.map_err(|e| e.to_string())?;
+            write_atomic(&self.dir.join(name), &json)?;
+            files.insert(name.to_string(), fnv1a_64(&json));
+        }
+
+        let manifest = Manifest {
+            format_version: PERSIST_FORMAT_VERSION,
+            identity: self.identity.clone(),
+            files,
+        };
+        let json = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+        write_atomic(&self.dir.join(MANIFEST_FILE), &json)
+    }
+
+    /// Forget everything recorded and delete the files
+    pub fn clear(&mut self) -> Result<(), String> {
+        self.pipelines.clear();
+        self.shaders.clear();
+        self.blobs.clear();
+        self.wipe()
+    }
+
+    /// Re-validate recorded shaders on a background thread, most used pipelines first
+    pub fn start_warmup(&self) -> Warmup {
+        let (sender, receiver) = mpsc::channel();
+        let pipelines = self.pipelines();
+        let shaders = self.shaders.clone();
+
+        let worker = std::thread::spawn(move || {
+            let mut validated: HashMap<u64, Result<Vec<String>, String>> = HashMap::new();
+            for pipeline in pipelines {
+                let event = warm_pipeline(pipeline, &shaders, &mut validated);
+                if sender.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Warmup {
+            receiver,
+            worker: Some(worker),
+        }
+    }
+
+    fn read_manifest(&self) -> Result<Option<Manifest>, String> {
+        let bytes = match std::fs::read(self.dir.join(MANIFEST_FILE)) {
+            Ok(bytes) => bytes,
+            Err(_) => return Ok(None),
+        };
+        let manifest: Manifest =
+            serde_json::from_slice(&bytes).map_err(|e| format!("unreadable manifest: {}", e))?;
+        if manifest.format_version != PERSIST_FORMAT_VERSION {
+            return Err(format!("format version {} is not {}", manifest.format_version, PERSIST_FORMAT_VERSION));
+        }
+        if manifest.identity != self.identity {
+            return Err("manifest belongs to a different adapter, driver or crate version".to_string());
+        }
+        Ok(Some(manifest))
+    }
+
+    fn load(&mut self, manifest: &Manifest, report: &mut PersistLoadReport) {
+        for (name, checksum) in &manifest.files {
+            let path = self.dir.join(name);
+            let data = match std::fs::read(&path) {
+                Ok(data) if fnv1a_64(&data) == *checksum => data,
+                _ => {
+                    let _ = std::fs::remove_file(&path);
+                    report.corrupt.push(name.clone());
+                    continue;
+                }
+            };
+
+            match name.as_str() {
+                PIPELINES_FILE => match serde_json::from_slice::<Vec<PersistedPipeline>>(&data) {
+                    Ok(pipelines) => self.pipelines = pipelines.into_iter().map(|p| (p.hash, p)).collect(),
+                    Err(_) => report.corrupt.push(name.clone()),
+                },
+                SHADERS_FILE => match serde_json::from_slice::<Vec<ShaderSnapshot>>(&data) {
+                    Ok(shaders) => self.shaders = shaders.into_iter().map(|s| (s.code_hash, s)).collect(),
+                    Err(_) => report.corrupt.push(name.clone()),
+                },
+                _ => {
+                    self.blobs.insert(name.clone(), *checksum);
+                }
+            }
+        }
+
+        // Blobs that failed their check no longer back their pipeline
+        for pipeline in self.pipelines.values_mut() {
+            pipeline.has_blob = self.blobs.contains_key(&blob_name(pipeline.hash));
+        }
+    }
+
+    /// Remove sibling directories recorded for this adapter with another driver or crate version.
+    /// Only directories this cache provably wrote are touched: the name must be a fingerprint
+    /// and the manifest must parse and name the same adapter. Anything else under `root` is
+    /// left alone.
+    fn prune_stale(&self, root: &Path) -> Vec<String> {
+        let mut pruned = Vec::new();
+        let Ok(entries) = std::fs::read_dir(root) else {
+            return pruned;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if path == self.dir || !is_fingerprint_name(&name) {
+                continue;
+            }
+            let ours = std::fs::read(path.join(MANIFEST_FILE))
+                .ok()
+                .and_then(|bytes| serde_json::from_slice::<Manifest>(&bytes).ok())
+                .is_some_and(|manifest| manifest.identity.adapter == self.identity.adapter);
+            if ours && std::fs::remove_dir_all(&path).is_ok() {
+                pruned.push(name);
+            }
+        }
+        pruned.sort();
+        pruned
+    }
+
+    fn wipe(&self) -> Result<(), String> {
+        let _ = std::fs::remove_dir_all(&self.dir);
+        std::fs::create_dir_all(self.dir.join(BLOB_DIR))
+            .map_err(|e| format!("Failed to recreate cache directory {}: {}", self.dir.display(), e))
+    }
+}
+
+/// Whether a directory name looks like one `open` creates
+fn is_fingerprint_name(name: &str) -> bool {
+    name.len() == 16 && name.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn blob_name(pipeline_hash: u64) -> String {
+    format!("{}/{:016x}.bin", BLOB_DIR, pipeline_hash)
+}
+
+/// Write through a temporary file so a crash never leaves a half-written file behind
+fn write_atomic(path: &Path, data: &[u8]) -> Result<(), String> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, data)
+        .and_then(|_| std::fs::rename(&tmp, path))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// A pipeline whose shaders validated and can be created now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarmupItem {
+    pub hash: u64,
+    pub pipeline_type: String,
+    pub descriptor: Value,
+    pub shaders: Vec<ShaderSource>,
+    pub has_blob: bool,
+}
+
+/// A recorded pipeline that can no longer be built
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarmupFailure {
+    pub hash: u64,
+    pub message: String,
+}
+
+enum WarmupEvent {
+    Ready(WarmupItem),
+    Failed(WarmupFailure),
+}
+
+/// Items produced by a warm-up since the last poll
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WarmupProgress {
+    pub ready: Vec<WarmupItem>,
+    pub failed: Vec<WarmupFailure>,
+    /// The worker has checked every recorded pipeline
+    pub finished: bool,
+}
+
+/// Background warm-up started by `PersistentCache::start_warmup`
+pub struct Warmup {
+    receiver: mpsc::Receiver<WarmupEvent>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Warmup {
+    /// Collect what is ready without blocking
+    pub fn poll(&mut self) -> WarmupProgress {
+        let mut progress = WarmupProgress::default();
+        loop {
+            match self.receiver.try_recv() {
+                Ok(event) => progress.push(event),
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    progress.finished = true;
+                    break;
+                }
+            }
+        }
+        if progress.finished {
+            if let Some(worker) = self.worker.take() {
+                let _ = worker.join();
+            }
+        }
+        progress
+    }
+
+    /// Block until every recorded pipeline has been checked
+    pub fn wait(mut self) -> WarmupProgress {
+        let mut progress = WarmupProgress::default();
+        for event in self.receiver.iter() {
+            progress.push(event);
+        }
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        progress.finished = true;
+        progress
+    }
+}
+
+impl WarmupProgress {
+    fn push(&mut self, event: WarmupEvent) {
+        match event {
+            WarmupEvent::Ready(item) => self.ready.push(item),
+            WarmupEvent::Failed(failure) => self.failed.push(failure),
+        }
+    }
+}
+
+/// Validate a pipeline's shaders (memoized by code hash) and check its entry points exist
+fn warm_pipeline(
+    pipeline: PersistedPipeline,
+    shaders: &BTreeMap<u64, ShaderSnapshot>,
+    validated: &mut HashMap<u64, Result<Vec<String>, String>>,
+) -> WarmupEvent {
+    let fail = |message: String| {
+        WarmupEvent::Failed(WarmupFailure {
+            hash: pipeline.hash,
+            message,
+        })
+    };
+
+    let mut sources = Vec::new();
+    let mut entry_points = Vec::new();
+    for code_hash in &pipeline.shaders {
+        let Some(snapshot) = shaders.get(code_hash) else {
+            return fail(format!("shader {:016x} is missing from the cache", code_hash));
+        };
+        let result = validated.entry(*code_hash).or_insert_with(|| {
+            validate_wgsl(&snapshot.source.code)
+                .map(|shader| shader.module.entry_points.iter().map(|ep| ep.name.clone()).collect())
+                .map_err(|e| e.to_string())
+        });
+        match result {
+            Ok(names) => entry_points.extend(names.iter().cloned()),
+            Err(message) => return fail(format!("shader {:016x}: {}", code_hash, message)),
+        }
+        sources.push(snapshot.source.clone());
+    }
+
+    if !sources.is_empty() {
+        if let Some(message) = missing_entry_point(&pipeline.descriptor, &entry_points) {
+            return fail(message);
+        }
+    }
+
+    WarmupEvent::Ready(WarmupItem {
+        hash: pipeline.hash,
+        pipeline_type: pipeline.pipeline_type,
+        descriptor: pipeline.descriptor,
+        shaders: sources,
+        has_blob: pipeline.has_blob,
+    })
+}
+
+// Registries for FFI handles
+lazy_static! {
+    static ref PERSISTENT_CACHES: Mutex<HashMap<u64, PersistentCache>> = Mutex::new(HashMap::new());
+    static ref WARMUPS: Mutex<HashMap<u64, Warmup>> = Mutex::new(HashMap::new());
+    static ref NEXT_HANDLE: Mutex<u64> = Mutex::new(1);
+}
+
+fn next_handle() -> u64 {
+    let mut next = NEXT_HANDLE.lock();
+    let handle = *next;
+    *next += 1;
+    handle
+}
+
+/// Result of `pipeline_persist_open`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistOpenResult {
+    pub handle: u64,
+    pub report: PersistLoadReport,
+}
+
+/// Open a persistent cache and register it under a handle
+pub fn pipeline_persist_open(root: &str, identity: CacheIdentity) -> Result<PersistOpenResult, String> {
+    let (cache, report) = PersistentCache::open(root, identity)?;
+    let handle = next_handle();
+    PERSISTENT_CACHES.lock().insert(handle, cache);
+    Ok(PersistOpenResult { handle, report })
+}
+
+/// Run a closure against a registered persistent cache
+pub fn with_persistent_cache<R>(handle: u64, f: impl FnOnce(&mut PersistentCache) -> R) -> Result<R, String> {
+    PERSISTENT_CACHES
+        .lock()
+        .get_mut(&handle)
+        .map(f)
+        .ok_or_else(|| "Invalid persistent cache handle".to_string())
+}
+
+/// Save and unregister a persistent cache
+pub fn pipeline_persist_close(handle: u64) -> Result<(), String> {
+    let cache = PERSISTENT_CACHES
+        .lock()
+        .remove(&handle)
+        .ok_or_else(|| "Invalid persistent cache handle".to_string())?;
+    cache.save()
+}
+
+/// Start warming up a registered cache, returning the warm-up handle
+pub fn pipeline_persist_start_warmup(handle: u64) -> Result<u64, String> {
+    let warmup = with_persistent_cache(handle, |cache| cache.start_warmup())?;
+    let warmup_handle = next_handle();
+    WARMUPS.lock().insert(warmup_handle, warmup);
+    Ok(warmup_handle)
+}
+
+/// Poll a warm-up; finished warm-ups are unregistered
+pub fn pipeline_persist_poll_warmup(warmup_handle: u64) -> Result<WarmupProgress, String> {
+    let mut warmups = WARMUPS.lock();
+    let warmup = warmups
+        .get_mut(&warmup_handle)
+        .ok_or_else(|| "Invalid warm-up handle".to_string())?;
+    let progress = warmup.poll();
+    if progress.finished {
+        warmups.remove(&warmup_handle);
+    }
+    Ok(progress)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMPUTE: &str = "@compute @workgroup_size(64) fn main() {}";
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("webgpu_x_persist_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        root
+    }
+
+    fn shader(code: &str) -> ShaderSnapshot {
+        ShaderSnapshot::from_source(ShaderSource {
+            code: code.to_string(),
+            stage: crate::shader::ShaderStage::Compute,
+            entry_point: "main".to_string(),
+            file_path: None,
+            last_modified: 0,
+            last_modified_ms: 0,
+            dependencies: Vec::new(),
+        })
+    }
+
+    fn identity() -> CacheIdentity {
+        CacheIdentity::new("Test Adapter (1234:5678)", "test-driver 1.0")
+    }
+
+    #[test]
+    fn test_round_trip_and_warmup() {
+        let root = temp_root("round_trip");
+        let (mut cache, report) = PersistentCache::open(&root, identity()).unwrap();
+        assert_eq!(report.pipelines, 0);
+
+        let shader_hash = cache.record_shader(shader(COMPUTE)).unwrap();
+        assert!(cache.record_shader(shader("fn broken( {")).is_err());
+        let hot = cache
+            .record_pipeline(r#"{"label": "hot", "compute": {"module": 1, "entryPoint": "main"}}"#, &[shader_hash])
+            .unwrap();
+        let missing = cache
+            .record_pipeline(r#"{"compute": {"module": 1, "entryPoint": "other"}}"#, &[shader_hash])
+            .unwrap();
+        cache.pipelines.get_mut(&hot).unwrap().hit_count = 5;
+        cache.store_blob(hot, &[1, 2, 3]).unwrap();
+        cache.save().unwrap();
+
+        let (cache, report) = PersistentCache::open(&root, identity()).unwrap();
+        assert_eq!((report.pipelines, report.shaders, report.blobs), (2, 1, 1));
+        assert!(report.corrupt.is_empty() && report.invalidated.is_none());
+        assert_eq!(cache.blob(hot), Some(vec![1, 2, 3]));
+
+        let progress = cache.start_warmup().wait();
+        assert!(progress.finished);
+        assert_eq!(progress.ready.len(), 1);
+        assert_eq!(progress.ready[0].hash, hot);
+        assert!(progress.ready[0].has_blob);
+        assert_eq!(progress.ready[0].shaders[0].code, COMPUTE);
+        assert_eq!(progress.failed[0].hash, missing);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_corrupt_files_are_dropped() {
+        let root = temp_root("corrupt");
+        let (mut cache, _) = PersistentCache::open(&root, identity()).unwrap();
+        let shader_hash = cache.record_shader(shader(COMPUTE)).unwrap();
+        let hash = cache.record_pipeline(r#"{"compute": {"module": 1}}"#, &[shader_hash]).unwrap();
+        cache.store_blob(hash, b"blob").unwrap();
+        cache.save().unwrap();
+
+        std::fs::write(cache.directory().join(blob_name(hash)), b"tampered").unwrap();
+        let (cache, report) = PersistentCache::open(&root, identity()).unwrap();
+        assert_eq!(report.corrupt, vec![blob_name(hash)]);
+        assert_eq!(report.pipelines, 1);
+        assert!(cache.blob(hash).is_none());
+        assert!(!cache.pipelines()[0].has_blob);
+
+        std::fs::write(cache.directory().join(MANIFEST_FILE), b"{").unwrap();
+        let (_, report) = PersistentCache::open(&root, identity()).unwrap();
+        assert!(report.invalidated.is_some());
+        assert_eq!(report.pipelines, 0);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_driver_change_prunes_stale_directory() {
+        let root = temp_root("prune");
+        let (old, _) = PersistentCache::open(&root, identity()).unwrap();
+        old.save().unwrap();
+        let old_dir = old.directory().to_path_buf();
+
+        let other_adapter = CacheIdentity::new("Other Adapter", "test-driver 1.0");
+        let (other, _) = PersistentCache::open(&root, other_adapter).unwrap();
+        other.save().unwrap();
+
+        let updated = CacheIdentity::new("Test Adapter (1234:5678)", "test-driver 2.0");
+        assert_ne!(updated.fingerprint(), identity().fingerprint());
+        let (_, report) = PersistentCache::open(&root, updated).unwrap();
+        assert_eq!(report.pruned.len(), 1);
+        assert!(!old_dir.exists());
+        assert!(other.directory().exists());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_prune_leaves_unrelated_directories() {
+        let root = temp_root("prune_unrelated");
+        let unrelated = root.join("some-other-app");
+        std::fs::create_dir_all(&unrelated).unwrap();
+        std::fs::write(unrelated.join("data.bin"), b"keep me").unwrap();
+        std::fs::write(unrelated.join(MANIFEST_FILE), b"{\"name\": \"other\"}").unwrap();
+        // Fingerprint-shaped, but the manifest is not ours
+        let lookalike = root.join("0123456789abcdef");
+        std::fs::create_dir_all(&lookalike).unwrap();
+        std::fs::write(lookalike.join(MANIFEST_FILE), b"not json").unwrap();
+
+        let (_, report) = PersistentCache::open(&root, identity()).unwrap();
+        assert!(report.pruned.is_empty());
+        assert!(unrelated.join("data.bin").exists());
+        assert!(lookalike.exists());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
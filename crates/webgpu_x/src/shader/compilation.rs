@@ -5,7 +5,15 @@
 /// - File-based hot-reload with change detection
 /// - Source code hashing for cache invalidation
 /// - Multiple entry point support
-
+/// - `#include`/`#define` preprocessing with include dependency tracking
+
+use super::glsl_import::{import_glsl_compute, GlslImportOptions};
+use super::preprocessor::{PreprocessedShader, WgslPreprocessor};
+use super::reflection::WgslDiagnostic;
+use super::variants::{
+    canonical_variant_key, compile_variant, variant_space, CompiledVariant, VariantError,
+    VariantKey, VariantPrewarmReport,
+};
 use super::ShaderStage; // Import from parent module
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -14,13 +22,93 @@ use std::sync::Mutex;
 use lazy_static::lazy_static;
 
 /// Shader source with metadata
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ShaderSource {
     pub code: String,
     pub stage: ShaderStage,
     pub entry_point: String,
     pub file_path: Option<String>,
+    /// Modification time in seconds since the Unix epoch (0 if unknown)
     pub last_modified: u64,
+    /// Modification time in milliseconds since the Unix epoch (0 if unknown)
+    #[serde(default)]
+    pub last_modified_ms: u64,
+    /// Canonical paths of files pulled in via `#include`
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+/// Snapshot of a file used for change detection
+///
+/// Compares full-resolution mtime and size. Filesystems that do not report
+/// mtime fall back to hashing the file contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct FileStamp {
+    modified_nanos: Option<u128>,
+    len: u64,
+    content_hash: Option<u64>,
+}
+
+impl FileStamp {
+    pub(crate) fn of(path: &str) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        let modified_nanos = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos());
+
+        let content_hash = match modified_nanos {
+            Some(_) => None,
+            None => Some(ShaderCache::hash_source(&std::fs::read_to_string(path).ok()?)),
+        };
+
+        Some(Self {
+            modified_nanos,
+            len: metadata.len(),
+            content_hash,
+        })
+    }
+
+    fn modified_secs(&self) -> u64 {
+        self.modified_nanos.map_or(0, |n| (n / 1_000_000_000) as u64)
+    }
+
+    fn modified_millis(&self) -> u64 {
+        self.modified_nanos.map_or(0, |n| (n / 1_000_000) as u64)
+    }
+}
+
+/// Failure to reload a shader, with validation diagnostics when available
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShaderReloadError {
+    pub message: String,
+    pub diagnostics: Vec<WgslDiagnostic>,
+}
+
+/// A file-backed cache entry as written to disk
+///
+/// Restoring one skips preprocessing as long as the shader and every file it
+/// includes still match the recorded stamps.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShaderSnapshot {
+    pub source: ShaderSource,
+    /// FNV-1a of `source.code`; stable across processes, unlike the in-memory hash
+    pub code_hash: u64,
+    pub(crate) stamp: Option<FileStamp>,
+    pub(crate) dependencies: Vec<(String, Option<FileStamp>)>,
+}
+
+impl ShaderSnapshot {
+    /// Snapshot of a shader that did not come from a file (no change detection)
+    pub fn from_source(source: ShaderSource) -> Self {
+        Self {
+            code_hash: crate::pipeline::canonical::fnv1a_64(source.code.as_bytes()),
+            source,
+            stamp: None,
+            dependencies: Vec::new(),
+        }
+    }
 }
 
 /// Cached shader entry
@@ -28,51 +116,118 @@ struct CachedShader {
     source: ShaderSource,
     hash: u64,
     compiled_at: u64,
+    stamp: FileStamp,
+    /// Included files with the stamp seen at load
+    dependencies: Vec<(String, Option<FileStamp>)>,
+}
+
+impl CachedShader {
+    fn dependencies_changed(&self) -> bool {
+        self.dependencies
+            .iter()
+            .any(|(path, stamp)| FileStamp::of(path) != *stamp)
+    }
 }
 
 /// Shader cache for hot-reload
 pub struct ShaderCache {
     shaders: HashMap<String, CachedShader>,
+    /// Compiled variants by (source hash, canonical variant key)
+    variants: HashMap<(u64, String), CompiledVariant>,
+    preprocessor: WgslPreprocessor,
 }
 
 impl ShaderCache {
     pub fn new() -> Self {
         Self {
             shaders: HashMap::new(),
+            variants: HashMap::new(),
+            preprocessor: WgslPreprocessor::new(),
         }
     }
 
+    /// Add a directory searched by `#include`
+    pub fn add_include_path(&mut self, path: String) {
+        self.preprocessor.add_include_path(path);
+        self.clear();
+    }
+
+    /// Define a preprocessor macro for every shader loaded by this cache
+    pub fn define(&mut self, name: String, value: String) {
+        self.preprocessor.define(&name, &value);
+        self.clear();
+    }
+
     /// Load shader from file, reload if changed
     pub fn load(&mut self, file_path: String) -> Result<ShaderSource, String> {
-        // Check if file changed
-        let metadata = std::fs::metadata(&file_path)
-            .map_err(|e| format!("Failed to read shader file: {}", e))?;
-
-        let modified = metadata
-            .modified()
-            .unwrap()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let stamp = FileStamp::of(&file_path)
+            .ok_or_else(|| format!("Failed to read shader file: {}", file_path))?;
 
         // Check cache
         if let Some(cached) = self.shaders.get(&file_path) {
-            if cached.source.last_modified == modified {
+            if cached.stamp == stamp && !cached.dependencies_changed() {
                 return Ok(cached.source.clone());
             }
         }
 
-        // Load and cache
-        let code = std::fs::read_to_string(&file_path)
-            .map_err(|e| format!("Failed to read shader: {}", e))?;
+        // Load, expand includes and cache
+        let preprocessed = self
+            .preprocessor
+            .process_file(&file_path)
+            .map_err(|e| e.to_string())?;
+
+        Ok(self.insert(file_path, preprocessed, stamp))
+    }
+
+    /// Reload a shader from disk and validate it before replacing the cached copy
+    ///
+    /// On failure the previously cached (last known good) version stays in the cache
+    /// and keeps being returned by `load` until the files change again.
+    pub fn reload_validated(&mut self, file_path: &str) -> Result<ShaderSource, ShaderReloadError> {
+        let stamp = FileStamp::of(file_path).ok_or_else(|| ShaderReloadError {
+            message: format!("Failed to read shader file: {}", file_path),
+            diagnostics: Vec::new(),
+        })?;
+
+        let preprocessed = self
+            .preprocessor
+            .process_file(file_path)
+            .map_err(|e| ShaderReloadError {
+                message: e.to_string(),
+                diagnostics: Vec::new(),
+            })?;
+
+        if let Err(e) = preprocessed.validate() {
+            if let Some(cached) = self.shaders.get_mut(file_path) {
+                cached.stamp = stamp;
+                cached.dependencies = dependency_stamps(&preprocessed);
+            }
+            return Err(ShaderReloadError {
+                message: e.to_string(),
+                diagnostics: e.diagnostics,
+            });
+        }
+
+        Ok(self.insert(file_path.to_string(), preprocessed, stamp))
+    }
+
+    fn insert(
+        &mut self,
+        file_path: String,
+        preprocessed: PreprocessedShader,
+        stamp: FileStamp,
+    ) -> ShaderSource {
+        let dependencies = dependency_stamps(&preprocessed);
 
         let stage = detect_shader_stage(&file_path);
         let source = ShaderSource {
-            code: code.clone(),
+            code: preprocessed.code,
             stage,
             entry_point: "main".to_string(),
             file_path: Some(file_path.clone()),
-            last_modified: modified,
+            last_modified: stamp.modified_secs(),
+            last_modified_ms: stamp.modified_millis(),
+            dependencies: preprocessed.dependencies,
         };
 
         let hash = Self::hash_source(&source.code);
@@ -81,11 +236,13 @@ impl ShaderCache {
             CachedShader {
                 source: source.clone(),
                 hash,
-                compiled_at: modified,
+                compiled_at: stamp.modified_secs(),
+                stamp,
+                dependencies,
             },
         );
 
-        Ok(source)
+        source
     }
 
     /// Load shader from string with custom stage and entry point
@@ -94,45 +251,201 @@ impl ShaderCache {
         code: String,
         stage: ShaderStage,
         entry_point: String,
-    ) -> ShaderSource {
-        ShaderSource {
-            code,
+    ) -> Result<ShaderSource, String> {
+        let preprocessed = self
+            .preprocessor
+            .process_str(&code, "<string>")
+            .map_err(|e| e.to_string())?;
+
+        Ok(ShaderSource {
+            code: preprocessed.code,
             stage,
             entry_point,
             file_path: None,
             last_modified: 0,
+            last_modified_ms: 0,
+            dependencies: preprocessed.dependencies,
+        })
+    }
+
+    /// Translate a GLSL compute shader to WGSL and load it as a compute shader.
+    /// Fails when the import left constructs untranslated or the WGSL does not validate.
+    pub fn load_from_glsl(
+        &mut self,
+        code: String,
+        options: &GlslImportOptions,
+    ) -> Result<ShaderSource, String> {
+        let import = import_glsl_compute(&code, options).map_err(|e| e.to_string())?;
+
+        if let Some(construct) = import.unsupported.first() {
+            return Err(format!(
+                "{}:{}: unsupported GLSL {}: {}",
+                construct.file, construct.line, construct.construct, construct.message
+            ));
+        }
+        if !import.valid {
+            let message = import
+                .diagnostics
+                .first()
+                .map_or("generated WGSL is invalid", |d| d.message.as_str());
+            return Err(format!("GLSL import produced invalid WGSL: {}", message));
         }
+
+        self.load_from_string(import.wgsl, ShaderStage::Compute, import.entry_point)
     }
 
-    /// Check if shader file has changed
+    /// Compile (or fetch from cache) one variant of a shader that declares `#pragma feature`s
+    pub fn load_variant(&mut self, code: &str, key: &VariantKey) -> Result<CompiledVariant, VariantError> {
+        let source_hash = Self::hash_source(code);
+        let canonical = canonical_variant_key(&variant_space(code)?, key)?;
+
+        if let Some(variant) = self.variants.get(&(source_hash, canonical.clone())) {
+            return Ok(variant.clone());
+        }
+
+        let variant = compile_variant(&self.preprocessor, code, source_hash, key)?;
+        self.variants.insert((source_hash, canonical), variant.clone());
+        Ok(variant)
+    }
+
+    /// Canonical keys of the cached variants of a shader, sorted
+    pub fn list_variants(&self, code: &str) -> Vec<String> {
+        let source_hash = Self::hash_source(code);
+        let mut keys: Vec<String> = self
+            .variants
+            .keys()
+            .filter(|(hash, _)| *hash == source_hash)
+            .map(|(_, key)| key.clone())
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    /// Compile the variants a workload will use so the first dispatch does not pay for it
+    pub fn prewarm_variants(&mut self, code: &str, keys: &[VariantKey]) -> VariantPrewarmReport {
+        let source_hash = Self::hash_source(code);
+        let space = variant_space(code);
+        let mut report = VariantPrewarmReport::default();
+
+        for key in keys {
+            let canonical = match &space {
+                Ok(space) => canonical_variant_key(space, key),
+                Err(e) => Err(e.clone()),
+            };
+            let canonical = match canonical {
+                Ok(canonical) => canonical,
+                Err(e) => {
+                    report.failed.push(e);
+                    continue;
+                }
+            };
+            if self.variants.contains_key(&(source_hash, canonical.clone())) {
+                if !report.already_cached.contains(&canonical) {
+                    report.already_cached.push(canonical);
+                }
+                continue;
+            }
+            match self.load_variant(code, key) {
+                Ok(variant) => report.compiled.push(variant.key),
+                Err(e) => report.failed.push(e),
+            }
+        }
+
+        report
+    }
+
+    /// Check if shader file (or any file it includes) has changed
     pub fn has_changed(&self, file_path: &str) -> bool {
-        let Ok(metadata) = std::fs::metadata(file_path) else {
+        let Some(cached) = self.shaders.get(file_path) else {
             return false;
         };
 
-        let modified = metadata
-            .modified()
-            .unwrap()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        match FileStamp::of(file_path) {
+            Some(stamp) => stamp != cached.stamp || cached.dependencies_changed(),
+            // Deleted or unreadable since it was cached
+            None => true,
+        }
+    }
 
-        if let Some(cached) = self.shaders.get(file_path) {
-            return cached.source.last_modified != modified;
+    /// Cached shaders that are `path` or include it
+    pub fn dependents(&self, path: &str) -> Vec<String> {
+        let target = canonical_path(path);
+        let mut dependents: Vec<String> = self
+            .shaders
+            .iter()
+            .filter(|(key, cached)| {
+                canonical_path(key) == target
+                    || cached.dependencies.iter().any(|(dep, _)| *dep == target)
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+        dependents.sort();
+        dependents
+    }
+
+    /// Drop `path` and every cached shader that includes it, returning the removed shaders
+    pub fn invalidate(&mut self, path: &str) -> Vec<String> {
+        let dependents = self.dependents(path);
+        for key in &dependents {
+            self.shaders.remove(key);
         }
+        dependents
+    }
 
-        false
+    /// File-backed shaders currently cached, sorted by path
+    pub fn snapshot(&self) -> Vec<ShaderSnapshot> {
+        let mut snapshots: Vec<ShaderSnapshot> = self
+            .shaders
+            .values()
+            .map(|cached| ShaderSnapshot {
+                stamp: Some(cached.stamp),
+                dependencies: cached.dependencies.clone(),
+                ..ShaderSnapshot::from_source(cached.source.clone())
+            })
+            .collect();
+        snapshots.sort_by(|a, b| a.source.file_path.cmp(&b.source.file_path));
+        snapshots
+    }
+
+    /// Re-populate the cache from snapshots whose files are unchanged, returning how many were restored
+    ///
+    /// Stale snapshots, and ones for paths already cached, are skipped; they load normally on first use.
+    pub fn restore(&mut self, snapshots: &[ShaderSnapshot]) -> usize {
+        let mut restored = 0;
+        for snapshot in snapshots {
+            let (Some(path), Some(stamp)) = (&snapshot.source.file_path, snapshot.stamp) else {
+                continue;
+            };
+            if self.shaders.contains_key(path) || FileStamp::of(path) != Some(stamp) {
+                continue;
+            }
+            let cached = CachedShader {
+                source: snapshot.source.clone(),
+                hash: Self::hash_source(&snapshot.source.code),
+                compiled_at: stamp.modified_secs(),
+                stamp,
+                dependencies: snapshot.dependencies.clone(),
+            };
+            if cached.dependencies_changed() {
+                continue;
+            }
+            self.shaders.insert(path.clone(), cached);
+            restored += 1;
+        }
+        restored
     }
 
     /// Clear shader cache
     pub fn clear(&mut self) {
         self.shaders.clear();
+        self.variants.clear();
     }
 
     /// Get cache statistics
     pub fn stats(&self) -> ShaderCacheStats {
         ShaderCacheStats {
             cached_shaders: self.shaders.len() as u32,
+            cached_variants: self.variants.len() as u32,
         }
     }
 
@@ -145,6 +458,20 @@ impl ShaderCache {
     }
 }
 
+fn dependency_stamps(preprocessed: &PreprocessedShader) -> Vec<(String, Option<FileStamp>)> {
+    preprocessed
+        .dependencies
+        .iter()
+        .map(|path| (path.clone(), FileStamp::of(path)))
+        .collect()
+}
+
+pub(crate) fn canonical_path(path: &str) -> String {
+    std::fs::canonicalize(path)
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| path.to_string())
+}
+
 impl Default for ShaderCache {
     fn default() -> Self {
         Self::new()
@@ -155,6 +482,7 @@ impl Default for ShaderCache {
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct ShaderCacheStats {
     pub cached_shaders: u32,
+    pub cached_variants: u32,
 }
 
 /// Detect shader stage from file extension
@@ -179,6 +507,20 @@ lazy_static! {
     static ref NEXT_CACHE_ID: Mutex<u64> = Mutex::new(1);
 }
 
+/// Run a closure against a registered shader cache
+pub(crate) fn with_shader_cache<R>(
+    cache_handle: u64,
+    f: impl FnOnce(&mut ShaderCache) -> R,
+) -> Result<R, String> {
+    let mut caches = SHADER_CACHES.lock().unwrap();
+
+    if let Some(cache) = caches.get_mut(&cache_handle) {
+        Ok(f(cache))
+    } else {
+        Err("Invalid shader cache handle".to_string())
+    }
+}
+
 /// Create a new shader cache
 pub fn shader_cache_create() -> u64 {
     let mut caches = SHADER_CACHES.lock().unwrap();
@@ -214,12 +556,103 @@ pub fn shader_cache_load_from_string(
     let mut caches = SHADER_CACHES.lock().unwrap();
 
     if let Some(cache) = caches.get_mut(&cache_handle) {
-        Ok(cache.load_from_string(code, stage, entry_point))
+        cache.load_from_string(code, stage, entry_point)
+    } else {
+        Err("Invalid shader cache handle".to_string())
+    }
+}
+
+/// Load a GLSL compute shader, translated to WGSL
+pub fn shader_cache_load_glsl(
+    cache_handle: u64,
+    code: String,
+    options: &GlslImportOptions,
+) -> Result<ShaderSource, String> {
+    let mut caches = SHADER_CACHES.lock().unwrap();
+
+    if let Some(cache) = caches.get_mut(&cache_handle) {
+        cache.load_from_glsl(code, options)
+    } else {
+        Err("Invalid shader cache handle".to_string())
+    }
+}
+
+/// Compile or fetch a shader variant
+pub fn shader_cache_load_variant(
+    cache_handle: u64,
+    code: &str,
+    key: &VariantKey,
+) -> Result<CompiledVariant, String> {
+    let mut caches = SHADER_CACHES.lock().unwrap();
+
+    if let Some(cache) = caches.get_mut(&cache_handle) {
+        cache.load_variant(code, key).map_err(|e| e.to_string())
+    } else {
+        Err("Invalid shader cache handle".to_string())
+    }
+}
+
+/// List cached variant keys of a shader
+pub fn shader_cache_list_variants(cache_handle: u64, code: &str) -> Vec<String> {
+    let caches = SHADER_CACHES.lock().unwrap();
+
+    if let Some(cache) = caches.get(&cache_handle) {
+        cache.list_variants(code)
+    } else {
+        Vec::new()
+    }
+}
+
+/// Compile a set of variants ahead of use
+pub fn shader_cache_prewarm_variants(
+    cache_handle: u64,
+    code: &str,
+    keys: &[VariantKey],
+) -> Result<VariantPrewarmReport, String> {
+    let mut caches = SHADER_CACHES.lock().unwrap();
+
+    if let Some(cache) = caches.get_mut(&cache_handle) {
+        Ok(cache.prewarm_variants(code, keys))
     } else {
         Err("Invalid shader cache handle".to_string())
     }
 }
 
+/// Add an include search directory to a shader cache
+pub fn shader_cache_add_include_path(cache_handle: u64, path: String) -> Result<(), String> {
+    let mut caches = SHADER_CACHES.lock().unwrap();
+
+    if let Some(cache) = caches.get_mut(&cache_handle) {
+        cache.add_include_path(path);
+        Ok(())
+    } else {
+        Err("Invalid shader cache handle".to_string())
+    }
+}
+
+/// Define a preprocessor macro on a shader cache
+pub fn shader_cache_define(cache_handle: u64, name: String, value: String) -> Result<(), String> {
+    let mut caches = SHADER_CACHES.lock().unwrap();
+
+    if let Some(cache) = caches.get_mut(&cache_handle) {
+        cache.define(name, value);
+        Ok(())
+    } else {
+        Err("Invalid shader cache handle".to_string())
+    }
+}
+
+/// Invalidate a file and every cached shader that includes it
+pub fn shader_cache_invalidate(cache_handle: u64, path: String) -> Vec<String> {
+    let mut caches = SHADER_CACHES.lock().unwrap();
+
+    if let Some(cache) = caches.get_mut(&cache_handle) {
+        cache.invalidate(&path)
+    } else {
+        Vec::new()
+    }
+}
+
 /// Check if shader file has changed
 pub fn shader_cache_has_changed(cache_handle: u64, file_path: String) -> bool {
     let caches = SHADER_CACHES.lock().unwrap();
@@ -249,6 +682,7 @@ pub fn shader_cache_stats(cache_handle: u64) -> ShaderCacheStats {
     } else {
         ShaderCacheStats {
             cached_shaders: 0,
+            cached_variants: 0,
         }
     }
 }
@@ -285,7 +719,7 @@ mod tests {
             "@compute @workgroup_size(64) fn main() {}".to_string(),
             ShaderStage::Compute,
             "main".to_string(),
-        );
+        ).unwrap();
 
         assert_eq!(source.stage, ShaderStage::Compute);
         assert_eq!(source.entry_point, "main");
@@ -316,6 +750,9 @@ mod tests {
         assert_eq!(source.stage, ShaderStage::Compute);
         assert!(source.code.contains("@compute"));
         assert_eq!(source.file_path, Some(test_shader.to_string()));
+        // `last_modified` stays in seconds, milliseconds live in their own field
+        assert!(source.last_modified > 0);
+        assert_eq!(source.last_modified_ms / 1000, source.last_modified);
 
         // Load again (should use cache)
         let result2 = cache.load(test_shader.to_string());
@@ -325,6 +762,30 @@ mod tests {
         fs::remove_file(test_shader).unwrap();
     }
 
+    #[test]
+    fn test_shader_cache_include_invalidation() {
+        let dir = std::env::temp_dir().join("webgpu_x_cache_include_test");
+        fs::create_dir_all(&dir).unwrap();
+        let common = dir.join("common.wgsl");
+        let main = dir.join("main.wgsl");
+        fs::write(&common, "const SCALE = 2.0;").unwrap();
+        fs::write(&main, "#include \"common.wgsl\"\n@compute @workgroup_size(WG) fn main() {}").unwrap();
+
+        let mut cache = ShaderCache::new();
+        cache.define("WG".to_string(), "64".to_string());
+        let main_path = main.to_str().unwrap().to_string();
+        let source = cache.load(main_path.clone()).unwrap();
+        assert!(source.code.contains("const SCALE"));
+        assert!(source.code.contains("@workgroup_size(64)"));
+        assert_eq!(source.dependencies.len(), 1);
+
+        assert_eq!(cache.dependents(common.to_str().unwrap()), vec![main_path.clone()]);
+        assert_eq!(cache.invalidate(common.to_str().unwrap()), vec![main_path]);
+        assert_eq!(cache.stats().cached_shaders, 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_shader_cache_has_changed() {
         // Create a temporary shader file
@@ -352,4 +813,36 @@ mod tests {
         // Clean up
         fs::remove_file(test_shader).unwrap();
     }
+
+    #[test]
+    fn test_shader_cache_variants() {
+        let code = "#pragma feature WG 64 128\n#pragma feature SCALE\noverride k: f32 = 1.0;\n\
+            @group(0) @binding(0) var<storage, read_write> d: array<f32>;\n\
+            @compute @workgroup_size(WG) fn main(@builtin(global_invocation_id) id: vec3<u32>) {\n\
+            #ifdef SCALE\nd[id.x] *= k;\n#endif\n}";
+        let mut cache = ShaderCache::new();
+
+        let report = cache.prewarm_variants(
+            code,
+            &[
+                VariantKey::new(),
+                VariantKey::new().with_feature("WG", "64"),
+                VariantKey::new().with_feature("WG", "128").enable("SCALE"),
+                VariantKey::new().with_feature("WG", "96"),
+            ],
+        );
+        assert_eq!(report.compiled, vec!["WG=64", "SCALE,WG=128"]);
+        assert_eq!(report.already_cached, vec!["WG=64"]);
+        assert_eq!(report.failed.len(), 1);
+
+        let variant = cache
+            .load_variant(code, &VariantKey::new().with_feature("WG", "128").with_feature("SCALE", "on"))
+            .unwrap();
+        assert!(variant.code.contains("@workgroup_size(128)"));
+        assert_eq!(cache.list_variants(code), vec!["SCALE,WG=128", "WG=64"]);
+        assert_eq!(cache.stats().cached_variants, 2);
+
+        cache.clear();
+        assert!(cache.list_variants(code).is_empty());
+    }
 }
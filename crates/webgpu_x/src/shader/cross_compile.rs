@@ -0,0 +1,483 @@
+//! WGSL cross-compilation
+//!
+//! Converts validated WGSL into other shading languages with naga's backends:
+//! - SPIR-V binary (inspect with `spirv-dis`)
+//! - GLSL ES 3.10 and GLSL 4.50 core (one entry point per output)
+//! - HLSL (shader model 5.0 - 6.7)
+//! - MSL
+//!
+//! Override constants are resolved before translation since none of the text
+//! backends can express them.
+
+use super::reflection::{validate_wgsl, ValidatedShader, WgslError};
+use naga::valid::{Capabilities, ValidationFlags, Validator};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Output shading language
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShaderTarget {
+    SpirV,
+    GlslEs310,
+    Glsl450,
+    Hlsl,
+    Msl,
+}
+
+impl ShaderTarget {
+    /// Conventional file extension for the output
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            ShaderTarget::SpirV => "spv",
+            ShaderTarget::GlslEs310 | ShaderTarget::Glsl450 => "glsl",
+            ShaderTarget::Hlsl => "hlsl",
+            ShaderTarget::Msl => "metal",
+        }
+    }
+}
+
+impl fmt::Display for ShaderTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ShaderTarget::SpirV => "SPIR-V",
+            ShaderTarget::GlslEs310 => "GLSL ES 3.10",
+            ShaderTarget::Glsl450 => "GLSL 4.50",
+            ShaderTarget::Hlsl => "HLSL",
+            ShaderTarget::Msl => "MSL",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Per-target translation options
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CrossCompileOptions {
+    pub target: ShaderTarget,
+    /// Translate only this entry point (required for GLSL when the module has several)
+    pub entry_point: Option<String>,
+    /// Values for `override` constants, by name or numeric id
+    pub overrides: HashMap<String, f64>,
+    /// Insert bounds checks matching WebGPU robust buffer access
+    pub bounds_checks: bool,
+    /// Flip Y / remap depth from WebGPU conventions to the target API
+    pub adjust_coordinate_space: bool,
+    /// SPIR-V (major, minor) version
+    pub spirv_version: (u8, u8),
+    /// Emit OpName/OpSource debug info into SPIR-V
+    pub spirv_debug: bool,
+    /// HLSL shader model such as "5_1" or "6_0"
+    pub hlsl_shader_model: String,
+    /// MSL (major, minor) version
+    pub msl_version: (u8, u8),
+}
+
+impl Default for CrossCompileOptions {
+    fn default() -> Self {
+        Self {
+            target: ShaderTarget::SpirV,
+            entry_point: None,
+            overrides: HashMap::new(),
+            bounds_checks: false,
+            adjust_coordinate_space: true,
+            spirv_version: (1, 0),
+            spirv_debug: false,
+            hlsl_shader_model: "5_1".to_string(),
+            msl_version: (1, 2),
+        }
+    }
+}
+
+impl CrossCompileOptions {
+    pub fn new(target: ShaderTarget) -> Self {
+        Self {
+            target,
+            ..Default::default()
+        }
+    }
+}
+
+/// Translated shader
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossCompiledShader {
+    pub target: ShaderTarget,
+    /// Source text for GLSL, HLSL and MSL (empty for SPIR-V)
+    pub code: String,
+    /// SPIR-V words (empty for text targets)
+    pub spirv: Vec<u32>,
+    /// Entry point names as they appear in the output (backends may rename reserved words)
+    pub entry_points: Vec<String>,
+}
+
+impl CrossCompiledShader {
+    /// Output as bytes: little-endian SPIR-V words or UTF-8 text
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self.target {
+            ShaderTarget::SpirV => self.spirv.iter().flat_map(|w| w.to_le_bytes()).collect(),
+            _ => self.code.as_bytes().to_vec(),
+        }
+    }
+
+    /// Write the output to a file (binary for SPIR-V)
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        std::fs::write(path, self.to_bytes()).map_err(|e| format!("Failed to write '{}': {}", path, e))
+    }
+}
+
+/// Why a cross-compilation failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CrossCompileErrorKind {
+    /// The WGSL did not parse or validate
+    InvalidWgsl,
+    /// The requested entry point is missing or ambiguous
+    EntryPoint,
+    /// Override constants could not be resolved
+    Overrides,
+    /// Invalid translation options
+    Options,
+    /// The shader uses a feature the target cannot express
+    Unsupported,
+}
+
+/// Cross-compilation failure
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossCompileError {
+    pub kind: CrossCompileErrorKind,
+    pub target: ShaderTarget,
+    pub message: String,
+    /// Source diagnostics when the input WGSL was invalid
+    pub wgsl: Option<WgslError>,
+}
+
+impl fmt::Display for CrossCompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.wgsl {
+            Some(e) => write!(f, "{}", e),
+            None => write!(f, "{} translation failed: {}", self.target, self.message),
+        }
+    }
+}
+
+impl std::error::Error for CrossCompileError {}
+
+/// Validate WGSL and translate it to another shading language
+pub fn cross_compile_wgsl(
+    source: &str,
+    options: &CrossCompileOptions,
+) -> Result<CrossCompiledShader, CrossCompileError> {
+    let shader = validate_wgsl(source).map_err(|e| CrossCompileError {
+        kind: CrossCompileErrorKind::InvalidWgsl,
+        target: options.target,
+        message: e.message.clone(),
+        wgsl: Some(e),
+    })?;
+    cross_compile_module(&shader, options)
+}
+
+/// Translate an already validated module
+pub fn cross_compile_module(
+    shader: &ValidatedShader,
+    options: &CrossCompileOptions,
+) -> Result<CrossCompiledShader, CrossCompileError> {
+    let target = options.target;
+    let fail = |kind, message: String| CrossCompileError {
+        kind,
+        target,
+        message,
+        wgsl: None,
+    };
+
+    let (module, info) = naga::back::pipeline_constants::process_overrides(
+        &shader.module,
+        &shader.info,
+        &options.overrides,
+    )
+    .map_err(|e| fail(CrossCompileErrorKind::Overrides, e.to_string()))?;
+
+    // Narrow the module to one entry point and revalidate so unused stages cannot fail translation
+    let selected;
+    let (module, info) = match &options.entry_point {
+        Some(name) => {
+            let mut narrowed = module.into_owned();
+            narrowed.entry_points.retain(|ep| ep.name == *name);
+            if narrowed.entry_points.is_empty() {
+                return Err(fail(
+                    CrossCompileErrorKind::EntryPoint,
+                    format!("Entry point '{}' not found", name),
+                ));
+            }
+            let narrowed_info = Validator::new(ValidationFlags::all(), Capabilities::all())
+                .validate(&narrowed)
+                .map_err(|e| fail(CrossCompileErrorKind::EntryPoint, e.as_inner().to_string()))?;
+            selected = (narrowed, narrowed_info);
+            (&selected.0, &selected.1)
+        }
+        None => (&*module, &*info),
+    };
+
+    let policies = bounds_check_policies(options.bounds_checks);
+
+    match target {
+        ShaderTarget::SpirV => {
+            let mut flags = naga::back::spv::WriterFlags::LABEL_VARYINGS
+                | naga::back::spv::WriterFlags::CLAMP_FRAG_DEPTH;
+            if options.adjust_coordinate_space {
+                flags |= naga::back::spv::WriterFlags::ADJUST_COORDINATE_SPACE;
+            }
+            if options.spirv_debug {
+                flags |= naga::back::spv::WriterFlags::DEBUG;
+            }
+            let spv_options = naga::back::spv::Options {
+                lang_version: options.spirv_version,
+                flags,
+                bounds_check_policies: policies,
+                ..Default::default()
+            };
+            let words = naga::back::spv::write_vec(module, info, &spv_options, None)
+                .map_err(|e| fail(CrossCompileErrorKind::Unsupported, e.to_string()))?;
+
+            Ok(CrossCompiledShader {
+                target,
+                code: String::new(),
+                spirv: words,
+                entry_points: module.entry_points.iter().map(|ep| ep.name.clone()).collect(),
+            })
+        }
+        ShaderTarget::GlslEs310 | ShaderTarget::Glsl450 => {
+            let entry = match module.entry_points.as_slice() {
+                [entry] => entry,
+                [] => return Err(fail(CrossCompileErrorKind::EntryPoint, "Module has no entry points".to_string())),
+                many => {
+                    return Err(fail(
+                        CrossCompileErrorKind::EntryPoint,
+                        format!(
+                            "GLSL output holds one entry point; set entry_point to one of: {}",
+                            many.iter().map(|ep| ep.name.as_str()).collect::<Vec<_>>().join(", ")
+                        ),
+                    ))
+                }
+            };
+
+            let version = match target {
+                ShaderTarget::GlslEs310 => naga::back::glsl::Version::new_gles(310),
+                _ => naga::back::glsl::Version::Desktop(450),
+            };
+            let mut writer_flags = naga::back::glsl::WriterFlags::empty();
+            if options.adjust_coordinate_space {
+                writer_flags |= naga::back::glsl::WriterFlags::ADJUST_COORDINATE_SPACE;
+            }
+            let glsl_options = naga::back::glsl::Options {
+                version,
+                writer_flags,
+                ..Default::default()
+            };
+            let pipeline_options = naga::back::glsl::PipelineOptions {
+                shader_stage: entry.stage,
+                entry_point: entry.name.clone(),
+                multiview: None,
+            };
+
+            let mut code = String::new();
+            let mut writer = naga::back::glsl::Writer::new(
+                &mut code,
+                module,
+                info,
+                &glsl_options,
+                &pipeline_options,
+                policies,
+            )
+            .map_err(|e| fail(CrossCompileErrorKind::Unsupported, glsl_error_message(e)))?;
+            writer
+                .write()
+                .map_err(|e| fail(CrossCompileErrorKind::Unsupported, glsl_error_message(e)))?;
+
+            Ok(CrossCompiledShader {
+                target,
+                code,
+                spirv: Vec::new(),
+                entry_points: vec![entry.name.clone()],
+            })
+        }
+        ShaderTarget::Hlsl => {
+            let hlsl_options = naga::back::hlsl::Options {
+                shader_model: parse_shader_model(&options.hlsl_shader_model).ok_or_else(|| {
+                    fail(
+                        CrossCompileErrorKind::Options,
+                        format!("Unknown HLSL shader model '{}'", options.hlsl_shader_model),
+                    )
+                })?,
+                ..Default::default()
+            };
+
+            let mut code = String::new();
+            let reflection = naga::back::hlsl::Writer::new(&mut code, &hlsl_options)
+                .write(module, info, None)
+                .map_err(|e| fail(CrossCompileErrorKind::Unsupported, e.to_string()))?;
+
+            Ok(CrossCompiledShader {
+                target,
+                code,
+                spirv: Vec::new(),
+                entry_points: collect_entry_names(reflection.entry_point_names, |e| e.to_string())
+                    .map_err(|m| fail(CrossCompileErrorKind::Unsupported, m))?,
+            })
+        }
+        ShaderTarget::Msl => {
+            let msl_options = naga::back::msl::Options {
+                lang_version: options.msl_version,
+                bounds_check_policies: policies,
+                ..Default::default()
+            };
+            let (code, translation) = naga::back::msl::write_string(
+                module,
+                info,
+                &msl_options,
+                &naga::back::msl::PipelineOptions::default(),
+            )
+            .map_err(|e| fail(CrossCompileErrorKind::Unsupported, e.to_string()))?;
+
+            Ok(CrossCompiledShader {
+                target,
+                code,
+                spirv: Vec::new(),
+                entry_points: collect_entry_names(translation.entry_point_names, |e| e.to_string())
+                    .map_err(|m| fail(CrossCompileErrorKind::Unsupported, m))?,
+            })
+        }
+    }
+}
+
+fn bounds_check_policies(enabled: bool) -> naga::proc::BoundsCheckPolicies {
+    if !enabled {
+        return naga::proc::BoundsCheckPolicies::default();
+    }
+    let policy = naga::proc::BoundsCheckPolicy::ReadZeroSkipWrite;
+    naga::proc::BoundsCheckPolicies {
+        index: policy,
+        buffer: policy,
+        image_load: policy,
+        binding_array: policy,
+        ..Default::default()
+    }
+}
+
+fn parse_shader_model(model: &str) -> Option<naga::back::hlsl::ShaderModel> {
+    use naga::back::hlsl::ShaderModel;
+    let model = match model.trim_start_matches("sm").replace('.', "_").as_str() {
+        "5_0" => ShaderModel::V5_0,
+        "5_1" => ShaderModel::V5_1,
+        "6_0" => ShaderModel::V6_0,
+        "6_1" => ShaderModel::V6_1,
+        "6_2" => ShaderModel::V6_2,
+        "6_3" => ShaderModel::V6_3,
+        "6_4" => ShaderModel::V6_4,
+        "6_5" => ShaderModel::V6_5,
+        "6_6" => ShaderModel::V6_6,
+        "6_7" => ShaderModel::V6_7,
+        _ => return None,
+    };
+    Some(model)
+}
+
+fn glsl_error_message(error: naga::back::glsl::Error) -> String {
+    match error {
+        naga::back::glsl::Error::MissingFeatures(features) => {
+            format!("the selected GLSL version cannot express {:?}", features)
+        }
+        other => other.to_string(),
+    }
+}
+
+fn collect_entry_names<E>(
+    names: Vec<Result<String, E>>,
+    describe: impl Fn(&E) -> String,
+) -> Result<Vec<String>, String> {
+    names
+        .into_iter()
+        .map(|name| name.map_err(|e| describe(&e)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMPUTE: &str = r#"
+override scale: f32 = 2.0;
+@group(0) @binding(0) var<storage, read_write> data: array<f32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    data[id.x] = data[id.x] * scale;
+}
+"#;
+
+    const RENDER: &str = r#"
+@vertex
+fn vs(@builtin(vertex_index) i: u32) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(f32(i), 0.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0);
+}
+"#;
+
+    #[test]
+    fn test_spirv_output() {
+        let out = cross_compile_wgsl(COMPUTE, &CrossCompileOptions::new(ShaderTarget::SpirV)).unwrap();
+        assert_eq!(out.spirv[0], 0x0723_0203);
+        assert_eq!(&out.to_bytes()[..4], &[0x03, 0x02, 0x23, 0x07]);
+    }
+
+    #[test]
+    fn test_text_targets() {
+        for target in [ShaderTarget::GlslEs310, ShaderTarget::Glsl450, ShaderTarget::Hlsl, ShaderTarget::Msl] {
+            let out = cross_compile_wgsl(COMPUTE, &CrossCompileOptions::new(target))
+                .unwrap_or_else(|e| panic!("{}: {}", target, e));
+            assert!(!out.code.is_empty(), "{} produced no code", target);
+        }
+
+        let glsl = cross_compile_wgsl(COMPUTE, &CrossCompileOptions::new(ShaderTarget::Glsl450)).unwrap();
+        assert!(glsl.code.starts_with("#version 450"));
+    }
+
+    #[test]
+    fn test_overrides_are_applied() {
+        let mut options = CrossCompileOptions::new(ShaderTarget::Glsl450);
+        options.overrides.insert("scale".to_string(), 4.0);
+        let out = cross_compile_wgsl(COMPUTE, &options).unwrap();
+        assert!(out.code.contains("4.0"));
+    }
+
+    #[test]
+    fn test_glsl_requires_single_entry_point() {
+        let err = cross_compile_wgsl(RENDER, &CrossCompileOptions::new(ShaderTarget::GlslEs310)).unwrap_err();
+        assert_eq!(err.kind, CrossCompileErrorKind::EntryPoint);
+        assert!(err.message.contains("vs, fs"));
+
+        let mut options = CrossCompileOptions::new(ShaderTarget::GlslEs310);
+        options.entry_point = Some("fs".to_string());
+        let out = cross_compile_wgsl(RENDER, &options).unwrap();
+        assert_eq!(out.entry_points, vec!["fs".to_string()]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = cross_compile_wgsl("fn broken( {", &CrossCompileOptions::new(ShaderTarget::Msl)).unwrap_err();
+        assert_eq!(err.kind, CrossCompileErrorKind::InvalidWgsl);
+        assert!(err.wgsl.is_some());
+
+        let mut options = CrossCompileOptions::new(ShaderTarget::Hlsl);
+        options.hlsl_shader_model = "4_0".to_string();
+        let err = cross_compile_wgsl(COMPUTE, &options).unwrap_err();
+        assert_eq!(err.kind, CrossCompileErrorKind::Options);
+
+        let mut options = CrossCompileOptions::new(ShaderTarget::SpirV);
+        options.entry_point = Some("missing".to_string());
+        assert_eq!(cross_compile_wgsl(COMPUTE, &options).unwrap_err().kind, CrossCompileErrorKind::EntryPoint);
+    }
+}
@@ -0,0 +1,698 @@
+//! WGSL formatter
+//!
+//! Prints a module in one canonical style so generated shaders can be checked in as
+//! golden files and diffed:
+//! - Configurable indent width, one statement and one struct member per line
+//! - Struct members always end with a comma, the optional `;` after a struct is dropped
+//! - Binary operators, `=`, `->`, `,` and `:` are spaced; templates, calls and unary
+//!   operators are not
+//! - Argument and parameter lists that would exceed the line width are split one per line
+//! - Comments are kept, trailing comments stay on their line, runs of blank lines become one
+//!
+//! Formatting works on tokens. `format_wgsl` checks that the output has the same tokens as
+//! the input (up to optional punctuation) and, when validating, that it parses into the
+//! same module, so a formatter bug surfaces as an error instead of a changed shader.
+
+use super::minify::{lex, Token, TokenKind};
+use super::reflection::{validate_wgsl, ValidatedShader, WgslError, WgslErrorKind};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Where entry point and function attributes are placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttributePlacement {
+    /// `@compute @workgroup_size(64)` on its own line above `fn`
+    OwnLine,
+    /// `@compute @workgroup_size(64) fn main() {`
+    SameLine,
+}
+
+/// Formatter settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormatOptions {
+    pub indent_width: u32,
+    pub attribute_placement: AttributePlacement,
+    /// Argument and parameter lists that would run past this column are split
+    pub max_width: u32,
+    /// Validate the input and the formatted output with naga
+    pub validate: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent_width: 4,
+            attribute_placement: AttributePlacement::OwnLine,
+            max_width: 100,
+            validate: true,
+        }
+    }
+}
+
+const BINARY_OPERATORS: [&str; 30] = [
+    "+", "-", "*", "/", "%", "&", "|", "^", "&&", "||", "==", "!=", "<", ">", "<=", ">=", "<<",
+    ">>", "=", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "<<=", ">>=", "->",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Group {
+    Block,
+    Struct,
+    Paren { broken: bool, attribute: bool },
+    Bracket,
+    Template,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Pending {
+    None,
+    Space,
+    Newline,
+    BlankLine,
+}
+
+/// Indices of `<` and `>`/`>>` tokens that delimit template lists
+///
+/// Follows the WGSL template list discovery rules: an identifier followed by `<` starts a
+/// template list if a matching `>` appears before a token that cannot be inside one.
+fn template_delimiters(tokens: &[Token]) -> (HashSet<usize>, HashSet<usize>) {
+    let (mut opens, mut closes) = (HashSet::new(), HashSet::new());
+
+    for i in 0..tokens.len().saturating_sub(1) {
+        if tokens[i].kind != TokenKind::Ident || tokens[i + 1].text != "<" {
+            continue;
+        }
+        let (mut depth, mut nesting) = (0usize, 0usize);
+        let mut j = i + 2;
+        while j < tokens.len() {
+            let token = &tokens[j];
+            match token.text.as_str() {
+                "<" if tokens[j - 1].kind == TokenKind::Ident => depth += 1,
+                ">" | ">>" if nesting == 0 => {
+                    let closing = if token.text == ">>" { 2 } else { 1 };
+                    if depth < closing {
+                        opens.insert(i + 1);
+                        closes.insert(j);
+                        break;
+                    }
+                    depth -= closing;
+                }
+                "(" | "[" => nesting += 1,
+                ")" | "]" if nesting > 0 => nesting -= 1,
+                ")" | "]" | ";" | "{" | "}" | ":" | "=" | "&&" | "||" | "<" | ">=" | "<=" => break,
+                _ => {}
+            }
+            j += 1;
+        }
+    }
+
+    (opens, closes)
+}
+
+fn end_line(token: &Token) -> u32 {
+    token.line + token.text.matches('\n').count() as u32
+}
+
+fn is_word(token: &Token) -> bool {
+    matches!(token.kind, TokenKind::Ident | TokenKind::Number) || token.text == "_"
+}
+
+struct Printer<'a> {
+    tokens: &'a [Token],
+    options: &'a FormatOptions,
+    template_opens: HashSet<usize>,
+    template_closes: HashSet<usize>,
+    out: String,
+    column: usize,
+    indent: usize,
+    groups: Vec<Group>,
+    pending: Pending,
+    /// Last printed token, comments included
+    last: Option<usize>,
+}
+
+impl<'a> Printer<'a> {
+    fn new(tokens: &'a [Token], options: &'a FormatOptions) -> Self {
+        let (template_opens, template_closes) = template_delimiters(tokens);
+        Self {
+            tokens,
+            options,
+            template_opens,
+            template_closes,
+            out: String::new(),
+            column: 0,
+            indent: 0,
+            groups: Vec::new(),
+            pending: Pending::None,
+            last: None,
+        }
+    }
+
+    fn prev_sig(&self, i: usize) -> Option<usize> {
+        (0..i).rev().find(|&j| self.tokens[j].kind != TokenKind::Comment)
+    }
+
+    fn next_sig(&self, i: usize) -> Option<usize> {
+        (i + 1..self.tokens.len()).find(|&j| self.tokens[j].kind != TokenKind::Comment)
+    }
+
+    fn text(&self, i: Option<usize>) -> &str {
+        i.map_or("", |i| self.tokens[i].text.as_str())
+    }
+
+    fn is_template(&self, i: usize) -> bool {
+        self.template_opens.contains(&i) || self.template_closes.contains(&i)
+    }
+
+    fn is_unary(&self, i: usize) -> bool {
+        if !matches!(self.tokens[i].text.as_str(), "-" | "!" | "~" | "&" | "*") {
+            return false;
+        }
+        match self.prev_sig(i) {
+            None => true,
+            Some(p) => {
+                let prev = &self.tokens[p];
+                match prev.kind {
+                    TokenKind::Ident => matches!(prev.text.as_str(), "return" | "case"),
+                    TokenKind::Punct => {
+                        !matches!(prev.text.as_str(), ")" | "]" | "_" | "++" | "--")
+                            && !self.template_closes.contains(&p)
+                    }
+                    _ => false,
+                }
+            }
+        }
+    }
+
+    fn is_binary(&self, i: usize) -> bool {
+        BINARY_OPERATORS.contains(&self.tokens[i].text.as_str()) && !self.is_unary(i) && !self.is_template(i)
+    }
+
+    /// Whether a space separates significant tokens `a` and `b` on one line
+    fn space_between(&self, a: Option<usize>, b: usize) -> bool {
+        let Some(a) = a else { return false };
+        let (ta, tb) = (&self.tokens[a], &self.tokens[b]);
+
+        if tb.kind == TokenKind::Comment || ta.kind == TokenKind::Comment {
+            return true;
+        }
+        if matches!(ta.text.as_str(), "@" | "(" | "[" | ".") {
+            return false;
+        }
+        if tb.text == "@" {
+            return true;
+        }
+        if matches!(tb.text.as_str(), ")" | "]" | "," | ";" | "." | ":" | "++" | "--") || self.is_unary(a) {
+            return false;
+        }
+        if self.is_template(b) || self.template_opens.contains(&a) {
+            return false;
+        }
+        if self.template_closes.contains(&a) {
+            return !matches!(tb.text.as_str(), "(" | "[");
+        }
+        if tb.text == "(" || tb.text == "[" {
+            let keyword = ta.kind == TokenKind::Ident
+                && matches!(ta.text.as_str(), "if" | "for" | "while" | "switch" | "return" | "case")
+                && tb.text == "(";
+            return keyword || self.is_binary(a) || matches!(ta.text.as_str(), "," | ":" | ";");
+        }
+        if self.is_binary(a) || self.is_binary(b) {
+            return true;
+        }
+        if matches!(ta.text.as_str(), "," | ":" | ";") {
+            return true;
+        }
+        if matches!(ta.text.as_str(), ")" | "]") {
+            return is_word(tb);
+        }
+        is_word(ta) && is_word(tb)
+    }
+
+    fn write(&mut self, text: &str) {
+        self.out.push_str(text);
+        match text.rfind('\n') {
+            Some(pos) => self.column = text.len() - pos - 1,
+            None => self.column += text.len(),
+        }
+    }
+
+    fn emit(&mut self, i: usize, space: bool) {
+        if self.pending >= Pending::Newline && !self.out.is_empty() {
+            self.write(if self.pending == Pending::BlankLine { "\n\n" } else { "\n" });
+            let indent = " ".repeat(self.indent * self.options.indent_width as usize);
+            self.write(&indent);
+        } else if (self.pending == Pending::Space || space) && self.column > self.indent * self.options.indent_width as usize {
+            self.write(" ");
+        }
+        self.pending = Pending::None;
+        let text = self.tokens[i].text.clone();
+        self.write(&text);
+        self.last = Some(i);
+    }
+
+    fn newline(&mut self, pending: Pending) {
+        self.pending = self.pending.max(pending);
+    }
+
+    fn matching(&self, open: usize) -> usize {
+        let mut depth = 0;
+        for j in open..self.tokens.len() {
+            match self.tokens[j].text.as_str() {
+                "(" => depth += 1,
+                ")" => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return j;
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.tokens.len() - 1
+    }
+
+    /// Width of `tokens[start..end]` printed on one line
+    fn flat_width(&self, start: usize, end: usize) -> usize {
+        let mut width = 0;
+        let mut prev = self.prev_sig(start);
+        for j in start..end {
+            if self.tokens[j].kind == TokenKind::Comment {
+                continue;
+            }
+            width += self.tokens[j].text.len() + usize::from(self.space_between(prev, j));
+            prev = Some(j);
+        }
+        width
+    }
+
+    /// Split the parenthesized list at `open` one item per line?
+    fn should_break(&self, open: usize) -> bool {
+        let close = self.matching(open);
+        let (mut nesting, mut commas) = (0usize, 0usize);
+        for j in open + 1..close {
+            let token = &self.tokens[j];
+            if token.kind == TokenKind::Comment && token.text.starts_with("//") {
+                return true;
+            }
+            match token.text.as_str() {
+                "(" | "[" => nesting += 1,
+                ")" | "]" => nesting -= 1,
+                "," if nesting == 0 && !self.inside_template(open, j) => commas += 1,
+                ";" => return false,
+                _ => {}
+            }
+        }
+        if commas == 0 {
+            return false;
+        }
+
+        // The rest of the line after the list, up to `{`, `;` or the enclosing list's end
+        let mut end = close + 1;
+        let mut nesting = 0usize;
+        while end < self.tokens.len() {
+            match self.tokens[end].text.as_str() {
+                "{" | ";" | "}" => break,
+                "(" | "[" => nesting += 1,
+                ")" | "]" | "," if nesting == 0 => break,
+                ")" | "]" => nesting -= 1,
+                _ => {}
+            }
+            end += 1;
+        }
+        let tail = match self.text(Some(end.min(self.tokens.len() - 1))) {
+            "{" => 2,
+            _ => 1,
+        };
+        self.column + self.flat_width(open + 1, end) + tail > self.options.max_width as usize
+    }
+
+    fn inside_template(&self, from: usize, at: usize) -> bool {
+        let mut depth = 0i32;
+        for j in from..at {
+            if self.template_opens.contains(&j) {
+                depth += 1;
+            } else if self.template_closes.contains(&j) {
+                depth -= if self.tokens[j].text == ">>" { 2 } else { 1 };
+            }
+        }
+        depth > 0
+    }
+
+    /// Keyword of the declaration starting at `i`, skipping its attributes
+    fn declaration_keyword(&self, mut i: usize) -> &str {
+        while self.tokens.get(i).is_some_and(|t| t.text == "@") {
+            i += 2;
+            if self.tokens.get(i).is_some_and(|t| t.text == "(") {
+                i = self.matching(i) + 1;
+            }
+        }
+        self.tokens.get(i).map_or("", |t| t.text.as_str())
+    }
+
+    /// Called after an attribute is complete; `after` is its last token
+    fn end_attribute(&mut self, after: usize) {
+        if self.options.attribute_placement != AttributePlacement::OwnLine {
+            return;
+        }
+        if let Some(next) = self.next_sig(after) {
+            if self.tokens[next].text == "fn" {
+                self.newline(Pending::Newline);
+            }
+        }
+    }
+
+    fn print_comment(&mut self, i: usize) {
+        let token = &self.tokens[i];
+        let trailing = self.last.is_some_and(|l| end_line(&self.tokens[l]) == token.line);
+        let line_comment = token.text.starts_with("//");
+
+        if trailing {
+            let text = format!(" {}", token.text);
+            self.write(&text);
+            self.last = Some(i);
+        } else {
+            if self.groups.is_empty() && matches!(self.text(self.last), ";" | "}") {
+                if let Some(next) = self.next_sig(i) {
+                    if matches!(self.declaration_keyword(next), "fn" | "struct") {
+                        self.newline(Pending::BlankLine);
+                    }
+                }
+            }
+            self.source_gap(i);
+            self.newline(Pending::Newline);
+            self.emit(i, false);
+        }
+
+        let next_on_later_line = self.tokens.get(i + 1).is_some_and(|n| n.line > end_line(&self.tokens[i]));
+        if line_comment || next_on_later_line {
+            self.newline(Pending::Newline);
+        }
+    }
+
+    /// Keep a single blank line where the source had one or more
+    fn source_gap(&mut self, i: usize) {
+        if let Some(last) = self.last {
+            if self.pending >= Pending::Newline
+                && self.tokens[last].text != "{"
+                && self.tokens[i].text != "}"
+                && self.tokens[i].line > end_line(&self.tokens[last]) + 1
+            {
+                self.pending = Pending::BlankLine;
+            }
+        }
+    }
+
+    /// Finish a `}`: `} else`, dropped struct `;`, blank line after top-level items
+    fn after_close(&mut self, i: usize, closed: Option<Group>) -> usize {
+        let next = self.next_sig(i);
+        match self.text(next) {
+            "else" => self.pending = Pending::Space,
+            ";" if closed == Some(Group::Struct) => {
+                self.newline(if self.groups.is_empty() { Pending::BlankLine } else { Pending::Newline });
+                return next.unwrap_or(i);
+            }
+            _ if self.groups.is_empty() => self.newline(Pending::BlankLine),
+            _ => self.newline(Pending::Newline),
+        }
+        i
+    }
+
+    fn print(mut self) -> String {
+        let mut i = 0;
+        while i < self.tokens.len() {
+            if self.tokens[i].kind == TokenKind::Comment {
+                self.print_comment(i);
+                i += 1;
+                continue;
+            }
+
+            self.source_gap(i);
+            let prev = self.prev_sig(i);
+            let text = self.tokens[i].text.clone();
+
+            if self.groups.is_empty() && matches!(self.text(self.last), ";") && matches!(self.declaration_keyword(i), "fn" | "struct") {
+                self.newline(Pending::BlankLine);
+            }
+
+            match text.as_str() {
+                "{" => {
+                    let is_struct = prev.and_then(|p| self.prev_sig(p)).is_some_and(|p| self.tokens[p].text == "struct");
+                    self.emit(i, true);
+                    if self.next_sig(i) == Some(i + 1) && self.tokens[i + 1].text == "}" {
+                        self.emit(i + 1, false);
+                        i = self.after_close(i + 1, Some(if is_struct { Group::Struct } else { Group::Block }));
+                    } else {
+                        self.groups.push(if is_struct { Group::Struct } else { Group::Block });
+                        self.indent += 1;
+                        self.newline(Pending::Newline);
+                    }
+                }
+                "}" => {
+                    let closed = self.groups.pop();
+                    self.indent = self.indent.saturating_sub(1);
+                    self.newline(Pending::Newline);
+                    self.emit(i, false);
+                    i = self.after_close(i, closed);
+                }
+                "(" => {
+                    let attribute = prev.and_then(|p| self.prev_sig(p)).is_some_and(|p| self.tokens[p].text == "@");
+                    let space = self.space_between(prev, i);
+                    self.emit(i, space);
+                    let broken = !attribute && self.should_break(i);
+                    self.groups.push(Group::Paren { broken, attribute });
+                    if broken {
+                        self.indent += 1;
+                        self.newline(Pending::Newline);
+                    }
+                }
+                ")" => {
+                    let group = self.groups.pop();
+                    if let Some(Group::Paren { broken: true, .. }) = group {
+                        if self.text(prev) != "," {
+                            self.write(",");
+                        }
+                        self.indent = self.indent.saturating_sub(1);
+                        self.newline(Pending::Newline);
+                    }
+                    self.emit(i, false);
+                    if let Some(Group::Paren { attribute: true, .. }) = group {
+                        self.end_attribute(i);
+                    }
+                }
+                "," => match self.groups.last() {
+                    Some(Group::Paren { broken: true, .. }) | Some(Group::Struct) => {
+                        self.emit(i, false);
+                        self.newline(Pending::Newline);
+                    }
+                    Some(Group::Paren { broken: false, .. }) if self.text(self.next_sig(i)) == ")" => {}
+                    _ => self.emit(i, false),
+                },
+                ";" => {
+                    self.emit(i, false);
+                    if !matches!(self.groups.last(), Some(Group::Paren { .. })) {
+                        self.newline(Pending::Newline);
+                    }
+                }
+                "[" => {
+                    let space = self.space_between(prev, i);
+                    self.emit(i, space);
+                    self.groups.push(Group::Bracket);
+                }
+                "]" => {
+                    self.groups.pop();
+                    self.emit(i, false);
+                }
+                _ => {
+                    let space = self.space_between(prev, i);
+                    self.emit(i, space);
+                    if self.template_opens.contains(&i) {
+                        self.groups.push(Group::Template);
+                    } else if self.template_closes.contains(&i) {
+                        for _ in 0..if text == ">>" { 2 } else { 1 } {
+                            if self.groups.last() == Some(&Group::Template) {
+                                self.groups.pop();
+                            }
+                        }
+                    } else if self.text(prev) == "@" && self.text(self.next_sig(i)) != "(" {
+                        self.end_attribute(i);
+                    }
+                }
+            }
+
+            // Struct members always end with a comma
+            if self.groups.last() == Some(&Group::Struct)
+                && !matches!(self.text(Some(i)), "," | "{")
+                && self.text(self.next_sig(i)) == "}"
+            {
+                self.write(",");
+            }
+
+            i += 1;
+        }
+
+        let mut out = self.out.trim_end().to_string();
+        out.push('\n');
+        out
+    }
+}
+
+/// Format WGSL in the canonical style
+pub fn format_wgsl(source: &str, options: &FormatOptions) -> Result<String, WgslError> {
+    let original = if options.validate {
+        Some(validate_wgsl(source)?)
+    } else {
+        None
+    };
+
+    let tokens = lex(source, true)?;
+    let formatted = Printer::new(&tokens, options).print();
+
+    if significant_tokens(source)? != significant_tokens(&formatted)? {
+        return Err(formatting_changed("the token stream differs"));
+    }
+    if let Some(original) = original {
+        let reparsed = validate_wgsl(&formatted).map_err(|e| WgslError {
+            message: format!("Formatted output failed validation: {}", e.message),
+            ..e
+        })?;
+        // naga's WGSL backend ignores spans, so equal printouts mean equal modules
+        // (it cannot print overrides; the token comparison covers those shaders)
+        if original.module.overrides.is_empty() {
+            let print = |shader: &ValidatedShader| {
+                naga::back::wgsl::write_string(&shader.module, &shader.info, naga::back::wgsl::WriterFlags::empty())
+                    .map_err(|e| formatting_changed(&e.to_string()))
+            };
+            if print(&original)? != print(&reparsed)? {
+                return Err(formatting_changed("the parsed module differs"));
+            }
+        }
+    }
+
+    Ok(formatted)
+}
+
+/// Tokens that carry meaning; comments, trailing commas and the `;` after a struct are optional
+fn significant_tokens(source: &str) -> Result<Vec<String>, WgslError> {
+    let tokens = lex(source, false)?;
+    let mut significant: Vec<String> = Vec::with_capacity(tokens.len());
+    for (i, token) in tokens.iter().enumerate() {
+        let next = tokens.get(i + 1).map(|t| t.text.as_str());
+        let optional = (token.text == "," && matches!(next, Some(")") | Some("}")))
+            || (token.text == ";" && significant.last().map(String::as_str) == Some("}"));
+        if !optional {
+            significant.push(token.text.clone());
+        }
+    }
+    Ok(significant)
+}
+
+fn formatting_changed(reason: &str) -> WgslError {
+    WgslError {
+        kind: WgslErrorKind::Parse,
+        message: format!("Formatting would change the shader: {}", reason),
+        diagnostics: Vec::new(),
+        rendered: String::new(),
+    }
+}
+
+/// Check that WGSL is already in canonical form, e.g. for golden files
+pub fn is_wgsl_formatted(source: &str, options: &FormatOptions) -> Result<bool, WgslError> {
+    Ok(format_wgsl(source, options)? == source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::templates::{generate_kernel, KernelOperation};
+
+    #[test]
+    fn test_format_canonical_style() {
+        let source = "struct Params{scale:f32,count:u32};\n@group(0)@binding(0) var<uniform> params:Params;\n@group(0) @binding(1) var<storage,read_write> data:array<f32>;\n@compute @workgroup_size(64) fn main(@builtin(global_invocation_id) id:vec3<u32>){\nif(id.x>=params.count){return;}else{data[id.x]=-data[id.x]*params.scale;}\nfor(var i=0u;i<2u;i++){data[i]+=1.0;}\n}";
+        let formatted = format_wgsl(source, &FormatOptions::default()).unwrap();
+        assert_eq!(
+            formatted,
+            "struct Params {
+    scale: f32,
+    count: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read_write> data: array<f32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.count) {
+        return;
+    } else {
+        data[id.x] = -data[id.x] * params.scale;
+    }
+    for (var i = 0u; i < 2u; i++) {
+        data[i] += 1.0;
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_equivalence_check() {
+        let tokens = |s: &str| significant_tokens(s).unwrap();
+        assert_eq!(tokens("struct A { x: f32 };"), tokens("struct A {\n    x: f32,\n}"));
+        assert_eq!(tokens("f(a, b,)"), tokens("f(a, b) // call"));
+        assert_ne!(tokens("x = a - -b;"), tokens("x = a--b;"));
+    }
+
+    #[test]
+    fn test_comments_round_trip() {
+        let source = "// Header\n\n/* block */\nconst A: f32 = 1.0; // trailing\n\n\n\nconst B: f32 = A * 2.0;\n";
+        let formatted = format_wgsl(source, &FormatOptions::default()).unwrap();
+        assert_eq!(formatted, "// Header\n\n/* block */\nconst A: f32 = 1.0; // trailing\n\nconst B: f32 = A * 2.0;\n");
+    }
+
+    #[test]
+    fn test_options() {
+        let source = "@compute @workgroup_size(1) fn main() { let x = max(vec2<f32>(1.0, 2.0), vec2<f32>(3.0, 4.0)); _ = x; }";
+        let options = FormatOptions {
+            indent_width: 2,
+            attribute_placement: AttributePlacement::SameLine,
+            max_width: 40,
+            validate: true,
+        };
+        let formatted = format_wgsl(source, &options).unwrap();
+        assert!(formatted.starts_with("@compute @workgroup_size(1) fn main() {\n  let x = max(\n"));
+        assert!(formatted.contains("\n    vec2<f32>(1.0, 2.0),\n    vec2<f32>(3.0, 4.0),\n  );"));
+    }
+
+    #[test]
+    fn test_generated_kernels_are_stable() {
+        let options = FormatOptions::default();
+        for operation in [
+            KernelOperation::Add,
+            KernelOperation::MatrixMultiply,
+            KernelOperation::Softmax,
+            KernelOperation::Conv2D,
+            // ReduceSum is left out: its template names a variable `shared`, a reserved word
+        ] {
+            let source = generate_kernel(operation, (64, 1, 1));
+            validate_wgsl(&source).unwrap_or_else(|e| panic!("{:?} kernel is invalid: {}", operation, e.message));
+            let formatted = format_wgsl(&source, &options).unwrap();
+            assert!(is_wgsl_formatted(&formatted, &options).unwrap(), "{:?} is not idempotent", operation);
+        }
+    }
+
+    #[test]
+    fn test_template_discovery() {
+        let tokens = lex("let a = b < c; let d: array<vec2<f32>> = e; if (x < y && z > w) {}", false).unwrap();
+        let (opens, closes) = template_delimiters(&tokens);
+        let text = |set: &HashSet<usize>| {
+            let mut v: Vec<&str> = set.iter().map(|&i| tokens[i].text.as_str()).collect();
+            v.sort();
+            v
+        };
+        assert_eq!(opens.len(), 2);
+        assert_eq!(text(&closes), vec![">>"]);
+    }
+}
@@ -0,0 +1,699 @@
+//! GLSL compute shader import
+//!
+//! Translates GLSL 4.50 compute shaders (Vulkan style `layout(set, binding)` resources)
+//! into WGSL so existing GPGPU code can go through [`ShaderCache`](super::ShaderCache).
+//! Parsing and translation are naga's GLSL frontend and WGSL backend; this module adds:
+//! - `#include` and extra defines through [`WgslPreprocessor`], with errors mapped back
+//!   to the original file and line
+//! - remapping of `(set, binding)` to `@group/@binding`, and free bindings for resources
+//!   declared without one; remaps that land two resources on one binding are reported
+//! - a report of constructs naga cannot translate, and reflection of the generated module
+
+use super::preprocessor::{PreprocessOptions, PreprocessedShader, WgslPreprocessor};
+use super::reflection::{code_frame, diagnostic_for_span, reflect_wgsl, validate_wgsl, ShaderReflection, WgslDiagnostic};
+use naga::front::glsl::{ErrorKind, Frontend, Options};
+use naga::valid::{Capabilities, ValidationFlags, Validator};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// GLSL import settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GlslImportOptions {
+    /// Name of the generated entry point (`void main()` in GLSL)
+    pub entry_point: String,
+    /// Explicit `(set, binding)` to `@group/@binding` assignments
+    pub bindings: Vec<BindingRemap>,
+    /// `@group` for resources declared without a `binding`
+    pub default_group: u32,
+    pub preprocess: PreprocessOptions,
+}
+
+impl Default for GlslImportOptions {
+    fn default() -> Self {
+        Self {
+            entry_point: "main".to_string(),
+            bindings: Vec::new(),
+            default_group: 0,
+            preprocess: PreprocessOptions::default(),
+        }
+    }
+}
+
+/// Move GLSL `layout(set = set, binding = binding)` to `@group(group) @binding(new_binding)`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BindingRemap {
+    pub set: u32,
+    pub binding: u32,
+    pub group: u32,
+    pub new_binding: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportedResourceKind {
+    StorageBuffer,
+    UniformBuffer,
+    StorageTexture,
+    Texture,
+    Sampler,
+}
+
+/// Where a GLSL resource ended up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedBinding {
+    pub name: String,
+    pub kind: ImportedResourceKind,
+    /// `set`/`binding` as declared in GLSL (`set` defaults to 0)
+    pub set: Option<u32>,
+    pub binding: Option<u32>,
+    pub group: u32,
+    pub wgsl_binding: u32,
+}
+
+/// GLSL construct that has no WGSL translation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsupportedConstruct {
+    pub construct: String,
+    pub message: String,
+    pub file: String,
+    /// 1-based line in `file`
+    pub line: u32,
+}
+
+/// Result of importing a GLSL compute shader
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlslImport {
+    /// Empty when the module could not be translated
+    pub wgsl: String,
+    pub entry_point: String,
+    pub workgroup_size: [u32; 3],
+    pub bindings: Vec<ImportedBinding>,
+    pub unsupported: Vec<UnsupportedConstruct>,
+    /// Whether the generated WGSL passed naga validation
+    pub valid: bool,
+    pub diagnostics: Vec<WgslDiagnostic>,
+    pub reflection: Option<ShaderReflection>,
+}
+
+impl GlslImport {
+    /// Valid WGSL with nothing left untranslated
+    pub fn is_complete(&self) -> bool {
+        self.valid && self.unsupported.is_empty()
+    }
+}
+
+/// GLSL that could not be parsed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlslImportError {
+    pub message: String,
+    pub file: String,
+    pub line: u32,
+}
+
+impl fmt::Display for GlslImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.file, self.line, self.message)
+    }
+}
+
+impl std::error::Error for GlslImportError {}
+
+/// Extensions naga accepts without changing the translation
+const HARMLESS_EXTENSIONS: &[&str] = &[
+    "GL_GOOGLE_include_directive",
+    "GL_GOOGLE_cpp_style_line_directive",
+    "GL_ARB_separate_shader_objects",
+    "GL_ARB_shading_language_420pack",
+    "GL_ARB_compute_shader",
+    "GL_ARB_shader_storage_buffer_object",
+];
+
+/// GLSL after `#include` and macro expansion, ready for naga
+struct GlslSource {
+    /// `#version`/`#extension` lines followed by the preprocessed code
+    code: String,
+    /// Lines of `code` taken by the directives in front
+    header_lines: u32,
+    preprocessed: PreprocessedShader,
+    /// Ignored extensions as (name, original line)
+    ignored_extensions: Vec<(String, u32)>,
+}
+
+impl GlslSource {
+    /// Original file and line of a 1-based line in `code`
+    fn location(&self, line: u32) -> (String, u32) {
+        let line = line.saturating_sub(self.header_lines);
+        self.preprocessed
+            .map_line(line)
+            .map(|l| (l.file.clone(), l.line))
+            .unwrap_or_else(|| ("<glsl>".to_string(), line))
+    }
+
+    fn span_location(&self, span: naga::Span) -> (String, u32) {
+        if !span.is_defined() {
+            return ("<glsl>".to_string(), 0);
+        }
+        self.location(span.location(&self.code).line_number)
+    }
+}
+
+/// Expand `#include` and macros; `#version` and `#extension` are kept aside for naga since
+/// they must stay in front of everything else
+fn preprocess(source: &str, options: &GlslImportOptions) -> Result<GlslSource, GlslImportError> {
+    let mut header = String::new();
+    let mut header_lines = 0;
+    let mut ignored_extensions = Vec::new();
+    let mut stripped = String::with_capacity(source.len());
+    for (index, line) in source.lines().enumerate() {
+        let directive = line.trim_start().strip_prefix('#').map(str::trim_start);
+        match directive {
+            Some(d) if d.starts_with("version") || d.starts_with("extension") => {
+                if let Some(rest) = d.strip_prefix("extension") {
+                    let name = rest.split(':').next().unwrap_or("").trim().to_string();
+                    if !HARMLESS_EXTENSIONS.contains(&name.as_str()) {
+                        ignored_extensions.push((name, index as u32 + 1));
+                    }
+                }
+                header.push_str(line.trim_start());
+                header.push('\n');
+                header_lines += 1;
+            }
+            Some(d) if d.starts_with("line") => {}
+            _ => stripped.push_str(line),
+        }
+        stripped.push('\n');
+    }
+
+    let preprocessed = WgslPreprocessor::from_options(&options.preprocess)
+        .process_str(&stripped, "<glsl>")
+        .map_err(|e| GlslImportError {
+            message: e.message,
+            file: e.file,
+            line: e.line,
+        })?;
+    Ok(GlslSource {
+        code: header + &preprocessed.code,
+        header_lines,
+        preprocessed,
+        ignored_extensions,
+    })
+}
+
+/// naga frontend errors that mean "valid GLSL, but not translatable" rather than a syntax error
+fn unsupported_construct(kind: &ErrorKind) -> Option<String> {
+    match kind {
+        ErrorKind::NotImplemented(what) => Some(what.to_string()),
+        ErrorKind::InvalidVersion(_) | ErrorKind::InvalidProfile(_) => Some("version".to_string()),
+        ErrorKind::UnknownLayoutQualifier(qualifier) => Some(format!("layout({})", qualifier)),
+        ErrorKind::UnsupportedMatrixTypeInStd140 => Some("std140 matCx2".to_string()),
+        // naga has no GLSL atomic builtins yet
+        ErrorKind::SemanticError(message)
+            if message.starts_with("Unknown function 'atomic") || message.starts_with("Unknown function 'imageAtomic") =>
+        {
+            Some("atomics".to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Valid GLSL that needs something WebGPU lacks (`f64`, `i64`, ...)
+fn missing_capability(error: &naga::valid::ValidationError) -> Option<String> {
+    use naga::valid::{TypeError, ValidationError, WidthError};
+
+    match error {
+        ValidationError::Type { source, .. } => match source {
+            TypeError::MissingCapability(capability) => Some(format!("{:?}", capability).to_lowercase()),
+            TypeError::WidthError(WidthError::MissingCapability { name, .. }) => Some(name.to_string()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn resource_kind(module: &naga::Module, var: &naga::GlobalVariable) -> Option<ImportedResourceKind> {
+    use naga::{AddressSpace, ImageClass, TypeInner};
+
+    let mut ty = &module.types[var.ty].inner;
+    if let TypeInner::BindingArray { base, .. } = ty {
+        ty = &module.types[*base].inner;
+    }
+    Some(match (var.space, ty) {
+        (AddressSpace::Storage { .. }, _) => ImportedResourceKind::StorageBuffer,
+        (AddressSpace::Uniform, _) => ImportedResourceKind::UniformBuffer,
+        (AddressSpace::Handle, TypeInner::Sampler { .. }) => ImportedResourceKind::Sampler,
+        (
+            AddressSpace::Handle,
+            TypeInner::Image {
+                class: ImageClass::Storage { .. },
+                ..
+            },
+        ) => ImportedResourceKind::StorageTexture,
+        (AddressSpace::Handle, TypeInner::Image { .. }) => ImportedResourceKind::Texture,
+        _ => return None,
+    })
+}
+
+/// GLSL `barrier()` comes out of naga as every barrier kind, including the subgroup one
+/// WGSL cannot express; it only needs to synchronize the workgroup
+fn drop_subgroup_barriers(block: &mut naga::Block) {
+    use naga::Statement;
+
+    for statement in block.iter_mut() {
+        match statement {
+            Statement::Barrier(barrier) => barrier.remove(naga::Barrier::SUB_GROUP),
+            Statement::Block(inner) => drop_subgroup_barriers(inner),
+            Statement::If { accept, reject, .. } => {
+                drop_subgroup_barriers(accept);
+                drop_subgroup_barriers(reject);
+            }
+            Statement::Switch { cases, .. } => {
+                for case in cases.iter_mut() {
+                    drop_subgroup_barriers(&mut case.body);
+                }
+            }
+            Statement::Loop { body, continuing, .. } => {
+                drop_subgroup_barriers(body);
+                drop_subgroup_barriers(continuing);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Apply the remaps and give unbound resources the first free binding of `default_group`
+fn assign_bindings(module: &mut naga::Module, options: &GlslImportOptions) -> Vec<ImportedBinding> {
+    let resources: Vec<_> = module
+        .global_variables
+        .iter()
+        .filter_map(|(handle, var)| resource_kind(module, var).map(|kind| (handle, kind)))
+        .collect();
+
+    let place = |binding: &naga::ResourceBinding| -> (u32, u32) {
+        match options
+            .bindings
+            .iter()
+            .find(|r| r.set == binding.group && r.binding == binding.binding)
+        {
+            Some(remap) => (remap.group, remap.new_binding),
+            None => (binding.group, binding.binding),
+        }
+    };
+    let mut used: HashSet<(u32, u32)> = resources
+        .iter()
+        .filter_map(|(handle, _)| module.global_variables[*handle].binding.as_ref().map(place))
+        .collect();
+
+    let mut bindings = Vec::new();
+    for (handle, kind) in resources {
+        let name = {
+            let var = &module.global_variables[handle];
+            var.name
+                .clone()
+                .or_else(|| module.types[var.ty].name.clone())
+                .unwrap_or_default()
+        };
+        let var = &mut module.global_variables[handle];
+        let declared = var.binding.clone();
+        let (group, wgsl_binding) = match &declared {
+            Some(binding) => place(binding),
+            None => {
+                let group = options.default_group;
+                let binding = (0..).find(|b| !used.contains(&(group, *b))).unwrap_or(0);
+                used.insert((group, binding));
+                (group, binding)
+            }
+        };
+        var.binding = Some(naga::ResourceBinding {
+            group,
+            binding: wgsl_binding,
+        });
+        bindings.push(ImportedBinding {
+            name,
+            kind,
+            set: declared.as_ref().map(|b| b.group),
+            binding: declared.as_ref().map(|b| b.binding),
+            group,
+            wgsl_binding,
+        });
+    }
+    bindings
+}
+
+/// Resources that ended up on the same `@group/@binding` after remapping, reported at the later declaration
+fn binding_collisions(module: &naga::Module, glsl: &GlslSource) -> Vec<UnsupportedConstruct> {
+    let mut seen: HashMap<(u32, u32), String> = HashMap::new();
+    let mut collisions = Vec::new();
+    for (handle, var) in module.global_variables.iter() {
+        let Some(binding) = &var.binding else { continue };
+        let name = var
+            .name
+            .clone()
+            .or_else(|| module.types[var.ty].name.clone())
+            .unwrap_or_default();
+        match seen.get(&(binding.group, binding.binding)) {
+            Some(first) => {
+                let (file, line) = glsl.span_location(module.global_variables.get_span(handle));
+                collisions.push(UnsupportedConstruct {
+                    construct: "binding".to_string(),
+                    message: format!(
+                        "'{}' and '{}' both map to @group({}) @binding({})",
+                        first, name, binding.group, binding.binding
+                    ),
+                    file,
+                    line,
+                });
+            }
+            None => {
+                seen.insert((binding.group, binding.binding), name);
+            }
+        }
+    }
+    collisions
+}
+
+/// Convert a GLSL compute shader to WGSL
+pub fn import_glsl_compute(source: &str, options: &GlslImportOptions) -> Result<GlslImport, GlslImportError> {
+    let glsl = preprocess(source, options)?;
+    let mut unsupported: Vec<UnsupportedConstruct> = glsl
+        .ignored_extensions
+        .iter()
+        .map(|(name, line)| UnsupportedConstruct {
+            construct: "extension".to_string(),
+            message: format!("Extension '{}' is ignored", name),
+            file: "<glsl>".to_string(),
+            line: *line,
+        })
+        .collect();
+    let mut import = GlslImport {
+        wgsl: String::new(),
+        entry_point: options.entry_point.clone(),
+        workgroup_size: [1, 1, 1],
+        bindings: Vec::new(),
+        unsupported: Vec::new(),
+        valid: false,
+        diagnostics: Vec::new(),
+        reflection: None,
+    };
+
+    let mut module = match Frontend::default().parse(&Options::from(naga::ShaderStage::Compute), &glsl.code) {
+        Ok(module) => module,
+        Err(e) => {
+            for error in &e.errors {
+                let (file, line) = glsl.span_location(error.meta);
+                match unsupported_construct(&error.kind) {
+                    Some(construct) => unsupported.push(UnsupportedConstruct {
+                        construct,
+                        message: error.kind.to_string(),
+                        file,
+                        line,
+                    }),
+                    None => {
+                        return Err(GlslImportError {
+                            message: error.kind.to_string(),
+                            file,
+                            line,
+                        })
+                    }
+                }
+            }
+            import.unsupported = unsupported;
+            return Ok(import);
+        }
+    };
+
+    for (_, function) in module.functions.iter_mut() {
+        drop_subgroup_barriers(&mut function.body);
+    }
+    for ep in module.entry_points.iter_mut() {
+        drop_subgroup_barriers(&mut ep.function.body);
+        if ep.name == "main" {
+            ep.name = options.entry_point.clone();
+        }
+    }
+    if let Some(ep) = module.entry_points.first() {
+        import.workgroup_size = ep.workgroup_size;
+    }
+    import.bindings = assign_bindings(&mut module, options);
+    unsupported.extend(binding_collisions(&module, &glsl));
+
+    let info = match Validator::new(ValidationFlags::all(), Capabilities::default()).validate(&module) {
+        Ok(info) => info,
+        Err(e) => {
+            let mut message = e.as_inner().to_string();
+            let mut cause = std::error::Error::source(e.as_inner());
+            while let Some(inner) = cause {
+                message.push_str(": ");
+                message.push_str(&inner.to_string());
+                cause = inner.source();
+            }
+            let (file, line) = e
+                .spans()
+                .next()
+                .map(|(span, _)| glsl.span_location(*span))
+                .unwrap_or_else(|| ("<glsl>".to_string(), 0));
+            if let Some(construct) = missing_capability(e.as_inner()) {
+                unsupported.push(UnsupportedConstruct {
+                    construct,
+                    message: message.clone(),
+                    file: file.clone(),
+                    line,
+                });
+            }
+            import.diagnostics = e
+                .spans()
+                .map(|(span, label)| {
+                    let mut diagnostic = diagnostic_for_span(&glsl.code, *span, label);
+                    let (file, line) = glsl.span_location(*span);
+                    // Number the frame like the original rather than naga's input
+                    if diagnostic.line > 0 {
+                        let line_text = glsl.code.lines().nth(diagnostic.line as usize - 1).unwrap_or("");
+                        let underline = diagnostic.code_frame.matches('^').count();
+                        diagnostic.code_frame = code_frame(line_text, line, diagnostic.column, underline);
+                    }
+                    diagnostic.file = Some(file);
+                    diagnostic.line = line;
+                    diagnostic
+                })
+                .collect();
+            if import.diagnostics.is_empty() {
+                import.diagnostics.push(WgslDiagnostic {
+                    message,
+                    file: Some(file),
+                    line,
+                    column: 0,
+                    offset: 0,
+                    length: 0,
+                    code_frame: String::new(),
+                });
+            }
+            import.unsupported = unsupported;
+            return Ok(import);
+        }
+    };
+
+    import.unsupported = unsupported;
+    import.wgsl = naga::back::wgsl::write_string(&module, &info, naga::back::wgsl::WriterFlags::empty())
+        .map_err(|e| GlslImportError {
+            message: format!("WGSL generation failed: {}", e),
+            file: "<glsl>".to_string(),
+            line: 0,
+        })?;
+
+    // Check the output as ShaderCache will see it
+    match validate_wgsl(&import.wgsl) {
+        Ok(_) => {
+            import.valid = true;
+            import.reflection = reflect_wgsl(&import.wgsl).ok();
+        }
+        Err(e) => import.diagnostics = e.diagnostics,
+    }
+    Ok(import)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAXPY: &str = r#"
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+#define BLOCK 64
+
+layout(local_size_x = BLOCK, local_size_y = 1, local_size_z = 1) in;
+
+layout(std430, set = 0, binding = 0) readonly buffer InputX { float x[]; };
+layout(std430, set = 0, binding = 1) buffer InputY { float values[]; } y;
+layout(std140, set = 1, binding = 0) uniform Params { float alpha; int count; } params;
+
+shared float tile[BLOCK];
+
+float scale(float v, float s) {
+    v *= s;
+    return v;
+}
+
+void main() {
+    uint i = gl_GlobalInvocationID.x;
+    if (i >= x.length()) return;
+    tile[gl_LocalInvocationID.x] = x[i];
+    barrier();
+    y.values[i] = i < uint(params.count) ? scale(tile[gl_LocalInvocationID.x], params.alpha) + y.values[i] : 0.0;
+}
+"#;
+
+    #[test]
+    fn test_import_saxpy() {
+        let import = import_glsl_compute(SAXPY, &GlslImportOptions::default()).unwrap();
+        assert!(import.is_complete(), "{}\n{:?}\n{:?}", import.wgsl, import.unsupported, import.diagnostics);
+        assert_eq!(import.workgroup_size, [64, 1, 1]);
+        assert!(import.wgsl.contains("@compute @workgroup_size(64, 1, 1)"), "{}", import.wgsl);
+        assert!(import.wgsl.contains("var<workgroup> tile: array<f32, 64>;"), "{}", import.wgsl);
+        assert!(import.wgsl.contains("workgroupBarrier()"));
+
+        let names: Vec<&str> = import.bindings.iter().map(|b| b.name.as_str()).collect();
+        assert!(names.contains(&"params") && names.contains(&"y"), "{:?}", names);
+        let reflection = import.reflection.unwrap();
+        assert_eq!(reflection.entry_points.len(), 1);
+        assert_eq!(reflection.resources.len(), 3);
+    }
+
+    #[test]
+    fn test_binding_remap_and_entry_point() {
+        let options = GlslImportOptions {
+            entry_point: "saxpy".to_string(),
+            bindings: vec![BindingRemap {
+                set: 1,
+                binding: 0,
+                group: 0,
+                new_binding: 5,
+            }],
+            ..GlslImportOptions::default()
+        };
+        let import = import_glsl_compute(SAXPY, &options).unwrap();
+        assert!(import.valid, "{:?}", import.diagnostics);
+        let params = import.bindings.iter().find(|b| b.name == "params").unwrap();
+        assert_eq!((params.set, params.binding), (Some(1), Some(0)));
+        assert_eq!((params.group, params.wgsl_binding), (0, 5));
+        assert!(import.wgsl.contains("@group(0) @binding(5)"));
+        assert!(import.wgsl.contains("fn saxpy("));
+        assert_eq!(import.reflection.unwrap().entry_points[0].name, "saxpy");
+    }
+
+    #[test]
+    fn test_binding_remap_collisions_are_reported() {
+        let options = GlslImportOptions {
+            bindings: vec![BindingRemap {
+                set: 1,
+                binding: 0,
+                group: 0,
+                new_binding: 1,
+            }],
+            ..GlslImportOptions::default()
+        };
+        let import = import_glsl_compute(SAXPY, &options).unwrap();
+        assert!(!import.is_complete());
+        let collisions: Vec<_> = import.unsupported.iter().filter(|u| u.construct == "binding").collect();
+        assert_eq!(collisions.len(), 1, "{:?}", import.unsupported);
+        assert!(collisions[0].message.contains("@group(0) @binding(1)"), "{}", collisions[0].message);
+        assert!(collisions[0].message.contains("'params'"), "{}", collisions[0].message);
+    }
+
+    #[test]
+    fn test_loops_switch_and_out_params() {
+        let source = r#"
+#version 450
+layout(local_size_x = 8) in;
+layout(set = 0, binding = 0) buffer Counters { uint hits[]; };
+const uint LIMIT = 4u;
+
+void accumulate(inout uint total, uint value) { total += value; }
+
+void main() {
+    uint total = 0u;
+    for (uint i = 0u; i < LIMIT; ++i) {
+        accumulate(total, i * 2u);
+    }
+    uint k = 0u;
+    do { k++; } while (k < 3u);
+    switch (int(k)) {
+        case 0:
+        case 1: total = 0u; break;
+        default: break;
+    }
+    hits[gl_LocalInvocationIndex] = total;
+}
+"#;
+        let import = import_glsl_compute(source, &GlslImportOptions::default()).unwrap();
+        assert!(import.is_complete(), "{}\n{:?}\n{:?}", import.wgsl, import.unsupported, import.diagnostics);
+        assert_eq!(import.workgroup_size, [8, 1, 1]);
+        assert!(import.wgsl.contains("ptr<function, u32>"), "{}", import.wgsl);
+        assert!(import.wgsl.contains("switch"), "{}", import.wgsl);
+    }
+
+    #[test]
+    fn test_includes_and_defines() {
+        let mut options = GlslImportOptions::default();
+        options.preprocess.defines.insert("SIZE".to_string(), "32".to_string());
+        options
+            .preprocess
+            .files
+            .insert("common.glsl".to_string(), "float twice(float v) { return v * 2.0; }\n".to_string());
+        let source = "#version 450\n#include \"common.glsl\"\nlayout(local_size_x = SIZE) in;\nlayout(binding = 0) buffer Data { float d[]; };\nvoid main() { d[0] = twice(d[0]); }\n";
+        let import = import_glsl_compute(source, &options).unwrap();
+        assert!(import.is_complete(), "{}\n{:?}\n{:?}", import.wgsl, import.unsupported, import.diagnostics);
+        assert_eq!(import.workgroup_size, [32, 1, 1]);
+    }
+
+    #[test]
+    fn test_unbound_resources_get_free_bindings() {
+        let source = "#version 450\nlayout(local_size_x = 1) in;\nlayout(set = 2, binding = 0) buffer A { float a[]; };\nlayout(set = 2, binding = 1) buffer B { float b[]; };\nvoid main() { b[0] = a[0]; }\n";
+        let options = GlslImportOptions {
+            bindings: vec![BindingRemap {
+                set: 2,
+                binding: 1,
+                group: 0,
+                new_binding: 0,
+            }],
+            ..GlslImportOptions::default()
+        };
+        let import = import_glsl_compute(source, &options).unwrap();
+        assert!(import.valid, "{:?}", import.diagnostics);
+        let placed: Vec<(u32, u32)> = import.bindings.iter().map(|b| (b.group, b.wgsl_binding)).collect();
+        assert!(placed.contains(&(2, 0)) && placed.contains(&(0, 0)), "{:?}", placed);
+    }
+
+    #[test]
+    fn test_unsupported_constructs_are_reported() {
+        let source = "#version 450\n#extension GL_EXT_shader_atomic_float : enable\nlayout(local_size_x = 1) in;\nlayout(binding = 0) buffer Out { double o[]; };\nvoid main() {\n    o[0] = 1.0;\n}\n";
+        let import = import_glsl_compute(source, &GlslImportOptions::default()).unwrap();
+        assert!(!import.is_complete());
+        assert!(!import.valid);
+        let constructs: Vec<(&str, u32)> = import.unsupported.iter().map(|u| (u.construct.as_str(), u.line)).collect();
+        assert!(constructs.contains(&("extension", 2)), "{:?}", constructs);
+        assert!(constructs.contains(&("f64", 4)), "{:?}", constructs);
+        assert_eq!(import.diagnostics[0].line, 4);
+        assert!(import.diagnostics[0].code_frame.contains("\n4 | layout(binding = 0)"), "{}", import.diagnostics[0].code_frame);
+
+        let atomics = "#version 450\nlayout(local_size_x = 1) in;\nlayout(binding = 0) buffer C { uint c[]; };\nvoid main() {\n    atomicAdd(c[0], 1u);\n}\n";
+        let import = import_glsl_compute(atomics, &GlslImportOptions::default()).unwrap();
+        assert!(import.wgsl.is_empty());
+        assert_eq!(import.unsupported[0].construct, "atomics");
+        assert_eq!(import.unsupported[0].line, 5);
+    }
+
+    #[test]
+    fn test_parse_error_location() {
+        let err = import_glsl_compute("#version 450\nvoid main() {\n  float x = ;\n}\n", &GlslImportOptions::default()).unwrap_err();
+        assert_eq!((err.file.as_str(), err.line), ("<glsl>", 3));
+
+        let mut options = GlslImportOptions::default();
+        options
+            .preprocess
+            .files
+            .insert("broken.glsl".to_string(), "// helper\nfloat f( {\n".to_string());
+        let err = import_glsl_compute("#version 450\n#include \"broken.glsl\"\nvoid main() {}\n", &options).unwrap_err();
+        assert_eq!((err.file.as_str(), err.line), ("broken.glsl", 2));
+    }
+}
@@ -0,0 +1,929 @@
+//! WGSL memory layout calculator and buffer packer
+//!
+//! Computes size, alignment and member offsets of host-shareable WGSL types and
+//! checks them against the uniform and storage address space constraints:
+//! - `vec3<T>` is aligned like `vec4<T>` but only 12 bytes, so a following scalar fills its padding
+//! - Uniform arrays need a 16-byte element stride, uniform structs 16-byte alignment
+//! - A member after a struct member in a uniform buffer starts at a 16-byte boundary
+//!
+//! naga 22's WGSL front end does not parse `f16`, so declarations using it are laid out
+//! with `f64` standing in and the types narrowed afterwards; `@align`/`@size` cannot be
+//! carried over and are rejected in that case.
+//!
+//! `pack_wgsl_value` turns JSON into correctly padded bytes and `unpack_wgsl_value`
+//! reads buffer contents back into JSON.
+
+use super::reflection::{alignment_value, validate_wgsl_with_capabilities};
+use naga::proc::Layouter;
+use naga::valid::Capabilities;
+use naga::{ArraySize, ScalarKind, TypeInner};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt;
+
+const PROBE_NAME: &str = "webgpu_x_layout_probe_";
+
+/// Address space whose layout constraints apply
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BufferAddressSpace {
+    Uniform,
+    Storage,
+}
+
+/// Host-shareable scalar type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutScalar {
+    F32,
+    F16,
+    I32,
+    U32,
+}
+
+impl LayoutScalar {
+    pub fn size(&self) -> u32 {
+        match self {
+            LayoutScalar::F16 => 2,
+            _ => 4,
+        }
+    }
+}
+
+/// Shape of a laid-out type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LayoutKind {
+    Scalar {
+        scalar: LayoutScalar,
+    },
+    Atomic {
+        scalar: LayoutScalar,
+    },
+    Vector {
+        scalar: LayoutScalar,
+        components: u32,
+    },
+    Matrix {
+        scalar: LayoutScalar,
+        columns: u32,
+        rows: u32,
+        column_stride: u32,
+    },
+    Array {
+        element: Box<TypeLayout>,
+        /// None for runtime-sized arrays
+        count: Option<u32>,
+        stride: u32,
+    },
+    Struct {
+        fields: Vec<FieldLayout>,
+    },
+}
+
+/// Size and alignment of a WGSL type
+///
+/// For runtime-sized arrays `size` covers a single element.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeLayout {
+    pub type_name: String,
+    pub size: u32,
+    pub alignment: u32,
+    #[serde(flatten)]
+    pub kind: LayoutKind,
+}
+
+/// Placement of a struct member
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldLayout {
+    pub name: String,
+    pub offset: u32,
+    /// Implicit padding inserted before this member
+    pub padding_before: u32,
+    pub layout: TypeLayout,
+}
+
+/// A layout constraint violation or a hint about a surprising placement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayoutIssue {
+    /// Dotted member path, e.g. `lights[].color`
+    pub path: String,
+    pub message: String,
+}
+
+/// Layout of a type together with address space checks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayoutReport {
+    pub layout: TypeLayout,
+    pub address_space: BufferAddressSpace,
+    pub valid: bool,
+    pub errors: Vec<LayoutIssue>,
+    pub notes: Vec<LayoutIssue>,
+}
+
+/// Layout, packing or unpacking failure at a value path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayoutError {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+fn layout_error(path: &str, message: impl Into<String>) -> LayoutError {
+    LayoutError {
+        path: path.to_string(),
+        message: message.into(),
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Byte offsets of whole-word occurrences of `word`
+fn word_positions<'a>(source: &'a str, word: &'a str) -> impl Iterator<Item = usize> + 'a {
+    source.match_indices(word).map(|(at, _)| at).filter(move |&at| {
+        let before = source[..at].chars().next_back();
+        let after = source[at + word.len()..].chars().next();
+        !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+    })
+}
+
+/// Replace whole-word occurrences of `from` with `to`
+fn replace_word(source: &str, from: &str, to: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut last = 0;
+    for at in word_positions(source, from) {
+        out.push_str(&source[last..at]);
+        out.push_str(to);
+        last = at + from.len();
+    }
+    out.push_str(&source[last..]);
+    out
+}
+
+/// Turn the `f64` placeholders back into `f16`, recomputing array strides and struct
+/// member offsets; types come in dependency order, so bases are narrowed first
+fn narrow_f16_placeholders(module: &mut naga::Module) -> Result<(), LayoutError> {
+    let narrow = |scalar: naga::Scalar| {
+        if scalar == naga::Scalar::F64 {
+            naga::Scalar { kind: ScalarKind::Float, width: 2 }
+        } else {
+            scalar
+        }
+    };
+    let handles: Vec<_> = module.types.iter().map(|(handle, _)| handle).collect();
+    for handle in handles {
+        let mut layouter = Layouter::default();
+        layouter
+            .update(module.to_ctx())
+            .map_err(|e| layout_error("", format!("Failed to compute layout: {}", e)))?;
+
+        let ty = &module.types[handle];
+        let inner = match ty.inner.clone() {
+            TypeInner::Scalar(scalar) => TypeInner::Scalar(narrow(scalar)),
+            TypeInner::Vector { size, scalar } => TypeInner::Vector { size, scalar: narrow(scalar) },
+            TypeInner::Matrix { columns, rows, scalar } => TypeInner::Matrix {
+                columns,
+                rows,
+                scalar: narrow(scalar),
+            },
+            TypeInner::Array { base, size, .. } => TypeInner::Array {
+                base,
+                size,
+                stride: layouter[base].to_stride(),
+            },
+            TypeInner::Struct { mut members, .. } => {
+                let mut end = 0;
+                let mut alignment = naga::proc::Alignment::ONE;
+                for member in members.iter_mut() {
+                    let layout = layouter[member.ty];
+                    member.offset = layout.alignment.round_up(end);
+                    end = member.offset + layout.size;
+                    alignment = alignment.max(layout.alignment);
+                }
+                TypeInner::Struct {
+                    members,
+                    span: alignment.round_up(end),
+                }
+            }
+            other => other,
+        };
+        if inner != ty.inner {
+            let name = ty.name.clone();
+            module.types.replace(handle, naga::Type { name, inner });
+        }
+    }
+    Ok(())
+}
+
+/// Compute the layout of `type_name` (a type expression or a struct declared in `declarations`)
+pub fn wgsl_type_layout(declarations: &str, type_name: &str) -> Result<TypeLayout, LayoutError> {
+    let source = format!(
+        "{}\n@group(0) @binding(0) var<storage> {}: {};\n",
+        declarations, PROBE_NAME, type_name
+    );
+    let uses_f16 = word_positions(&source, "f16").next().is_some();
+    let source = if uses_f16 {
+        if word_positions(&source, "f64").next().is_some() {
+            return Err(layout_error("", "f16 and f64 cannot be used in the same layout query"));
+        }
+        let mut attributes = source.split('@').skip(1).map(str::trim_start);
+        if attributes.any(|a| a.starts_with("align") || a.starts_with("size")) {
+            return Err(layout_error("", "@align and @size are not supported together with f16"));
+        }
+        // The front end does not know `enable f16;`, and the placeholders need no extension
+        let source: String = source
+            .lines()
+            .filter(|line| line.trim() != "enable f16;")
+            .map(|line| format!("{}\n", line))
+            .collect();
+        replace_word(&source, "f16", "f64")
+    } else {
+        source
+    };
+    // Layouts of invalid types (e.g. a runtime-sized array inside an array) are meaningless
+    let capabilities = if uses_f16 { Capabilities::FLOAT64 } else { Capabilities::default() };
+    let mut module = validate_wgsl_with_capabilities(&source, capabilities)
+        .map_err(|e| layout_error("", e.message))?
+        .module;
+    if uses_f16 {
+        narrow_f16_placeholders(&mut module)?;
+    }
+
+    let probe = module
+        .global_variables
+        .iter()
+        .find(|(_, var)| var.name.as_deref() == Some(PROBE_NAME))
+        .map(|(_, var)| var.ty)
+        .ok_or_else(|| layout_error("", format!("Unknown type '{}'", type_name)))?;
+
+    let mut layouter = Layouter::default();
+    layouter
+        .update(module.to_ctx())
+        .map_err(|e| layout_error("", format!("Failed to compute layout: {}", e)))?;
+
+    convert_type(&module, &layouter, probe, "")
+}
+
+/// Compute the layout of a struct built from `shader::wgsl_struct` style fields (`"name: type"`)
+pub fn wgsl_struct_layout(name: &str, fields: &[String]) -> Result<TypeLayout, LayoutError> {
+    let declaration = super::wgsl_struct(name.to_string(), fields.to_vec());
+    wgsl_type_layout(&declaration, name)
+}
+
+/// Compute a layout and check it against an address space
+pub fn wgsl_layout_report(
+    declarations: &str,
+    type_name: &str,
+    address_space: BufferAddressSpace,
+) -> Result<LayoutReport, LayoutError> {
+    let layout = wgsl_type_layout(declarations, type_name)?;
+    let mut errors = Vec::new();
+    let mut notes = Vec::new();
+    check_layout(&layout, address_space, "", true, &mut errors, &mut notes);
+
+    Ok(LayoutReport {
+        layout,
+        address_space,
+        valid: errors.is_empty(),
+        errors,
+        notes,
+    })
+}
+
+fn convert_type(
+    module: &naga::Module,
+    layouter: &Layouter,
+    handle: naga::Handle<naga::Type>,
+    path: &str,
+) -> Result<TypeLayout, LayoutError> {
+    let ty = &module.types[handle];
+    let type_name = handle.to_wgsl(&module.to_ctx());
+    let size = layouter[handle].size;
+    let alignment = alignment_value(layouter[handle].alignment);
+
+    let kind = match ty.inner {
+        TypeInner::Scalar(scalar) => LayoutKind::Scalar {
+            scalar: convert_scalar(scalar, path)?,
+        },
+        TypeInner::Atomic(scalar) => LayoutKind::Atomic {
+            scalar: convert_scalar(scalar, path)?,
+        },
+        TypeInner::Vector { size, scalar } => LayoutKind::Vector {
+            scalar: convert_scalar(scalar, path)?,
+            components: size as u32,
+        },
+        TypeInner::Matrix {
+            columns,
+            rows,
+            scalar,
+        } => {
+            let scalar = convert_scalar(scalar, path)?;
+            // Each column is a vecR, padded to its alignment
+            let column_align = if rows as u32 == 2 { 2 } else { 4 } * scalar.size();
+            LayoutKind::Matrix {
+                scalar,
+                columns: columns as u32,
+                rows: rows as u32,
+                column_stride: (rows as u32 * scalar.size()).div_ceil(column_align) * column_align,
+            }
+        }
+        TypeInner::Array { base, size, stride } => LayoutKind::Array {
+            element: Box::new(convert_type(module, layouter, base, &format!("{}[]", path))?),
+            count: match size {
+                ArraySize::Constant(n) => Some(n.get()),
+                _ => None,
+            },
+            stride,
+        },
+        TypeInner::Struct { ref members, .. } => {
+            let mut fields = Vec::with_capacity(members.len());
+            let mut end = 0;
+            for member in members {
+                let name = member.name.clone().unwrap_or_default();
+                let member_path = join_path(path, &name);
+                let layout = convert_type(module, layouter, member.ty, &member_path)?;
+                fields.push(FieldLayout {
+                    padding_before: member.offset.saturating_sub(end),
+                    offset: member.offset,
+                    name,
+                    layout,
+                });
+                end = member.offset + layouter[member.ty].size;
+            }
+            LayoutKind::Struct { fields }
+        }
+        _ => {
+            return Err(layout_error(
+                path,
+                format!("Type '{}' cannot be stored in a buffer", type_name),
+            ))
+        }
+    };
+
+    Ok(TypeLayout {
+        type_name,
+        size,
+        alignment,
+        kind,
+    })
+}
+
+fn convert_scalar(scalar: naga::Scalar, path: &str) -> Result<LayoutScalar, LayoutError> {
+    match (scalar.kind, scalar.width) {
+        (ScalarKind::Float, 4) => Ok(LayoutScalar::F32),
+        (ScalarKind::Float, 2) => Ok(LayoutScalar::F16),
+        (ScalarKind::Sint, 4) => Ok(LayoutScalar::I32),
+        (ScalarKind::Uint, 4) => Ok(LayoutScalar::U32),
+        (ScalarKind::Bool, _) => Err(layout_error(
+            path,
+            "bool is not host-shareable; use u32 (0 or 1) in buffers",
+        )),
+        _ => Err(layout_error(
+            path,
+            format!("Scalar {:?}{} is not supported in buffers", scalar.kind, scalar.width * 8),
+        )),
+    }
+}
+
+fn join_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+fn check_layout(
+    layout: &TypeLayout,
+    space: BufferAddressSpace,
+    path: &str,
+    top_level: bool,
+    errors: &mut Vec<LayoutIssue>,
+    notes: &mut Vec<LayoutIssue>,
+) {
+    let issue = |message: String| LayoutIssue {
+        path: if path.is_empty() { "<root>".to_string() } else { path.to_string() },
+        message,
+    };
+
+    match &layout.kind {
+        LayoutKind::Array {
+            element,
+            count,
+            stride,
+        } => {
+            if count.is_none() {
+                if space == BufferAddressSpace::Uniform {
+                    errors.push(issue("runtime-sized arrays are not allowed in uniform buffers".to_string()));
+                } else if !top_level {
+                    errors.push(issue(
+                        "runtime-sized arrays may only be the last member of the buffer's top-level struct"
+                            .to_string(),
+                    ));
+                }
+            }
+            if space == BufferAddressSpace::Uniform && stride % 16 != 0 {
+                errors.push(issue(format!(
+                    "uniform array stride must be a multiple of 16 bytes, but {} has stride {}; \
+                     use a vec4 element or wrap the element in a struct with @size(16)",
+                    layout.type_name, stride
+                )));
+            }
+            check_layout(element, space, &format!("{}[]", path), false, errors, notes);
+        }
+        LayoutKind::Struct { fields } => {
+            let mut previous: Option<&FieldLayout> = None;
+            for (i, field) in fields.iter().enumerate() {
+                let field_path = join_path(path, &field.name);
+                let field_issue = |message: String| LayoutIssue {
+                    path: field_path.clone(),
+                    message,
+                };
+
+                if space == BufferAddressSpace::Uniform {
+                    let needs_16 = matches!(
+                        field.layout.kind,
+                        LayoutKind::Array { .. } | LayoutKind::Struct { .. }
+                    );
+                    if needs_16 && field.offset % 16 != 0 {
+                        errors.push(field_issue(format!(
+                            "{} in a uniform buffer must start at a multiple of 16 bytes, but offset is {}; add @align(16)",
+                            field.layout.type_name, field.offset
+                        )));
+                    }
+                    if let Some(prev) = previous {
+                        if matches!(prev.layout.kind, LayoutKind::Struct { .. }) {
+                            let min_offset = prev.offset + prev.layout.size.div_ceil(16) * 16;
+                            if field.offset < min_offset {
+                                errors.push(field_issue(format!(
+                                    "member after struct '{}' in a uniform buffer must start at offset {} or later, but offset is {}; add @align(16)",
+                                    prev.name, min_offset, field.offset
+                                )));
+                            }
+                        }
+                    }
+                }
+
+                if let Some(prev) = previous {
+                    if let LayoutKind::Vector { components: 3, .. } = prev.layout.kind {
+                        if field.offset < prev.offset + prev.layout.alignment {
+                            notes.push(field_issue(format!(
+                                "packed into the trailing 4 bytes of {} '{}' at offset {}",
+                                prev.layout.type_name, prev.name, field.offset
+                            )));
+                        }
+                    }
+                }
+                if field.padding_before > 0 {
+                    notes.push(field_issue(format!(
+                        "{} bytes of padding inserted before offset {}",
+                        field.padding_before, field.offset
+                    )));
+                }
+
+                let last = i + 1 == fields.len();
+                check_layout(&field.layout, space, &field_path, top_level && last, errors, notes);
+                previous = Some(field);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Byte length needed to hold `value` (resolves runtime-sized array lengths)
+fn packed_size(layout: &TypeLayout, value: &Value) -> u32 {
+    match (&layout.kind, value) {
+        (LayoutKind::Array { count: None, stride, .. }, Value::Array(items)) => {
+            (items.len() as u32 * stride).max(*stride)
+        }
+        (LayoutKind::Struct { fields }, Value::Object(map)) => match fields.last() {
+            Some(last) if matches!(last.layout.kind, LayoutKind::Array { count: None, .. }) => {
+                let tail = map.get(&last.name).map_or(last.layout.size, |v| packed_size(&last.layout, v));
+                (last.offset + tail).div_ceil(layout.alignment) * layout.alignment
+            }
+            _ => layout.size,
+        },
+        _ => layout.size,
+    }
+}
+
+/// Pack a JSON value into bytes following the type's layout; padding is zeroed
+///
+/// Vectors are JSON arrays, matrices arrays of columns (or a flat column-major array),
+/// structs JSON objects keyed by member name.
+pub fn pack_wgsl_value(layout: &TypeLayout, value: &Value) -> Result<Vec<u8>, LayoutError> {
+    let mut bytes = vec![0u8; packed_size(layout, value) as usize];
+    write_value(layout, value, &mut bytes, 0, "")?;
+    Ok(bytes)
+}
+
+fn write_value(
+    layout: &TypeLayout,
+    value: &Value,
+    bytes: &mut [u8],
+    offset: usize,
+    path: &str,
+) -> Result<(), LayoutError> {
+    match &layout.kind {
+        LayoutKind::Scalar { scalar } | LayoutKind::Atomic { scalar } => {
+            write_scalar(*scalar, value, bytes, offset, path)
+        }
+        LayoutKind::Vector { scalar, components } => {
+            let items = expect_array(value, Some(*components as usize), path)?;
+            for (i, item) in items.iter().enumerate() {
+                let item_offset = offset + i * scalar.size() as usize;
+                write_scalar(*scalar, item, bytes, item_offset, &format!("{}[{}]", path, i))?;
+            }
+            Ok(())
+        }
+        LayoutKind::Matrix {
+            scalar,
+            columns,
+            rows,
+            column_stride,
+        } => {
+            let (columns, rows) = (*columns as usize, *rows as usize);
+            let items = expect_array(value, None, path)?;
+            let flat: Vec<&Value> = if items.len() == columns * rows && items.iter().all(|v| !v.is_array()) {
+                items.iter().collect()
+            } else if items.len() == columns {
+                let mut flat = Vec::with_capacity(columns * rows);
+                for (c, column) in items.iter().enumerate() {
+                    flat.extend(expect_array(column, Some(rows), &format!("{}[{}]", path, c))?);
+                }
+                flat
+            } else {
+                return Err(layout_error(
+                    path,
+                    format!("expected {} columns or {} values for {}", columns, columns * rows, layout.type_name),
+                ));
+            };
+
+            for (i, item) in flat.into_iter().enumerate() {
+                let (c, r) = (i / rows, i % rows);
+                let item_offset = offset + c * *column_stride as usize + r * scalar.size() as usize;
+                write_scalar(*scalar, item, bytes, item_offset, &format!("{}[{}][{}]", path, c, r))?;
+            }
+            Ok(())
+        }
+        LayoutKind::Array { element, count, stride } => {
+            let items = expect_array(value, count.map(|n| n as usize), path)?;
+            for (i, item) in items.iter().enumerate() {
+                let item_offset = offset + i * *stride as usize;
+                write_value(element, item, bytes, item_offset, &format!("{}[{}]", path, i))?;
+            }
+            Ok(())
+        }
+        LayoutKind::Struct { fields } => {
+            let map = value
+                .as_object()
+                .ok_or_else(|| layout_error(path, format!("expected object for {}", layout.type_name)))?;
+            if let Some(unknown) = map.keys().find(|k| !fields.iter().any(|f| &f.name == *k)) {
+                return Err(layout_error(
+                    &join_path(path, unknown),
+                    format!("{} has no member '{}'", layout.type_name, unknown),
+                ));
+            }
+            for field in fields {
+                let field_path = join_path(path, &field.name);
+                let field_value = map
+                    .get(&field.name)
+                    .ok_or_else(|| layout_error(&field_path, "missing member"))?;
+                write_value(&field.layout, field_value, bytes, offset + field.offset as usize, &field_path)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn expect_array<'a>(value: &'a Value, len: Option<usize>, path: &str) -> Result<&'a Vec<Value>, LayoutError> {
+    let items = value
+        .as_array()
+        .ok_or_else(|| layout_error(path, "expected array"))?;
+    match len {
+        Some(len) if items.len() != len => Err(layout_error(
+            path,
+            format!("expected {} elements, got {}", len, items.len()),
+        )),
+        _ => Ok(items),
+    }
+}
+
+fn write_scalar(
+    scalar: LayoutScalar,
+    value: &Value,
+    bytes: &mut [u8],
+    offset: usize,
+    path: &str,
+) -> Result<(), LayoutError> {
+    let number = match value {
+        Value::Number(n) => n.clone(),
+        Value::Bool(b) => serde_json::Number::from(*b as u32),
+        _ => return Err(layout_error(path, "expected number")),
+    };
+
+    match scalar {
+        LayoutScalar::F32 => {
+            let v = number.as_f64().unwrap_or(0.0) as f32;
+            bytes[offset..offset + 4].copy_from_slice(&v.to_le_bytes());
+        }
+        LayoutScalar::F16 => {
+            let v = f32_to_f16_bits(number.as_f64().unwrap_or(0.0) as f32);
+            bytes[offset..offset + 2].copy_from_slice(&v.to_le_bytes());
+        }
+        LayoutScalar::I32 => {
+            let v = number
+                .as_i64()
+                .and_then(|v| i32::try_from(v).ok())
+                .ok_or_else(|| layout_error(path, format!("{} is not a valid i32", number)))?;
+            bytes[offset..offset + 4].copy_from_slice(&v.to_le_bytes());
+        }
+        LayoutScalar::U32 => {
+            let v = number
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or_else(|| layout_error(path, format!("{} is not a valid u32", number)))?;
+            bytes[offset..offset + 4].copy_from_slice(&v.to_le_bytes());
+        }
+    }
+    Ok(())
+}
+
+/// Read buffer bytes back into JSON following the type's layout
+///
+/// Runtime-sized arrays take as many elements as fit in `bytes`.
+pub fn unpack_wgsl_value(layout: &TypeLayout, bytes: &[u8]) -> Result<Value, LayoutError> {
+    read_value(layout, bytes, 0, "")
+}
+
+fn read_value(layout: &TypeLayout, bytes: &[u8], offset: usize, path: &str) -> Result<Value, LayoutError> {
+    match &layout.kind {
+        LayoutKind::Scalar { scalar } | LayoutKind::Atomic { scalar } => read_scalar(*scalar, bytes, offset, path),
+        LayoutKind::Vector { scalar, components } => (0..*components as usize)
+            .map(|i| read_scalar(*scalar, bytes, offset + i * scalar.size() as usize, path))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array),
+        LayoutKind::Matrix {
+            scalar,
+            columns,
+            rows,
+            column_stride,
+        } => (0..*columns as usize)
+            .map(|c| {
+                (0..*rows as usize)
+                    .map(|r| {
+                        let item_offset = offset + c * *column_stride as usize + r * scalar.size() as usize;
+                        read_scalar(*scalar, bytes, item_offset, path)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(Value::Array)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array),
+        LayoutKind::Array { element, count, stride } => {
+            let stride = *stride as usize;
+            let count = match count {
+                Some(n) => *n as usize,
+                None => bytes.len().saturating_sub(offset).checked_div(stride).unwrap_or(0),
+            };
+            (0..count)
+                .map(|i| read_value(element, bytes, offset + i * stride, &format!("{}[{}]", path, i)))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::Array)
+        }
+        LayoutKind::Struct { fields } => {
+            let mut map = Map::new();
+            for field in fields {
+                let field_path = join_path(path, &field.name);
+                let value = read_value(&field.layout, bytes, offset + field.offset as usize, &field_path)?;
+                map.insert(field.name.clone(), value);
+            }
+            Ok(Value::Object(map))
+        }
+    }
+}
+
+fn read_scalar(scalar: LayoutScalar, bytes: &[u8], offset: usize, path: &str) -> Result<Value, LayoutError> {
+    let size = scalar.size() as usize;
+    let raw = bytes
+        .get(offset..offset + size)
+        .ok_or_else(|| layout_error(path, format!("buffer too small: need {} bytes", offset + size)))?;
+
+    let value = match scalar {
+        LayoutScalar::F32 => {
+            let v = f32::from_le_bytes(raw.try_into().unwrap());
+            serde_json::Number::from_f64(v as f64).map_or(Value::Null, Value::Number)
+        }
+        LayoutScalar::F16 => {
+            let v = f16_bits_to_f32(u16::from_le_bytes(raw.try_into().unwrap()));
+            serde_json::Number::from_f64(v as f64).map_or(Value::Null, Value::Number)
+        }
+        LayoutScalar::I32 => Value::from(i32::from_le_bytes(raw.try_into().unwrap())),
+        LayoutScalar::U32 => Value::from(u32::from_le_bytes(raw.try_into().unwrap())),
+    };
+    Ok(value)
+}
+
+fn f32_to_f16_bits(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x007f_ffff;
+
+    if exponent == 0xff {
+        // Inf or NaN
+        return sign | 0x7c00 | if mantissa != 0 { 0x200 } else { 0 };
+    }
+
+    let half_exponent = exponent - 127 + 15;
+    if half_exponent >= 0x1f {
+        sign | 0x7c00
+    } else if half_exponent <= 0 {
+        if half_exponent < -10 {
+            return sign;
+        }
+        // Subnormal half
+        let mantissa = mantissa | 0x0080_0000;
+        let shift = (14 - half_exponent) as u32;
+        let rounded = (mantissa + (1 << (shift - 1))) >> shift;
+        sign | rounded as u16
+    } else {
+        let rounded = mantissa + 0x1000;
+        if rounded & 0x0080_0000 != 0 {
+            // Mantissa overflowed into the exponent
+            let half_exponent = half_exponent + 1;
+            if half_exponent >= 0x1f {
+                return sign | 0x7c00;
+            }
+            return sign | ((half_exponent as u16) << 10);
+        }
+        sign | ((half_exponent as u16) << 10) | (rounded >> 13) as u16
+    }
+}
+
+fn f16_bits_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f32;
+
+    match exponent {
+        0 => sign * mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0.0 => sign * f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const LIGHT: &str = r#"
+struct Light {
+    color: vec3<f32>,
+    intensity: f32,
+    direction: vec3<f32>,
+    transform: mat3x3<f32>,
+}
+"#;
+
+    fn field<'a>(layout: &'a TypeLayout, name: &str) -> &'a FieldLayout {
+        match &layout.kind {
+            LayoutKind::Struct { fields } => fields.iter().find(|f| f.name == name).unwrap(),
+            _ => panic!("not a struct"),
+        }
+    }
+
+    #[test]
+    fn test_struct_layout() {
+        let layout = wgsl_type_layout(LIGHT, "Light").unwrap();
+        assert_eq!(field(&layout, "color").offset, 0);
+        assert_eq!(field(&layout, "intensity").offset, 12);
+        assert_eq!(field(&layout, "direction").offset, 16);
+        assert_eq!(field(&layout, "transform").offset, 32);
+        assert_eq!(field(&layout, "transform").layout.size, 48);
+        assert_eq!(layout.size, 80);
+        assert_eq!(layout.alignment, 16);
+    }
+
+    #[test]
+    fn test_uniform_array_stride_error() {
+        let report = wgsl_layout_report("", "array<f32, 4>", BufferAddressSpace::Uniform).unwrap();
+        assert!(!report.valid);
+        assert!(report.errors[0].message.contains("multiple of 16"));
+
+        let report = wgsl_layout_report("", "array<f32, 4>", BufferAddressSpace::Storage).unwrap();
+        assert!(report.valid);
+    }
+
+    #[test]
+    fn test_invalid_types_are_rejected() {
+        let source = "struct Nested { rows: array<array<f32>> }";
+        let error = wgsl_type_layout(source, "Nested").unwrap_err();
+        assert!(!error.message.is_empty());
+        assert!(wgsl_type_layout("", "array<array<f32>, 2>").is_err());
+        assert!(wgsl_type_layout("struct Counter { n: atomic<u32> }", "Counter").is_ok());
+    }
+
+    #[test]
+    fn test_f16_layouts() {
+        let layout = wgsl_type_layout("", "vec3<f16>").unwrap();
+        assert_eq!((layout.size, layout.alignment), (6, 8));
+        assert_eq!(layout.type_name, "vec3<f16>");
+
+        let source = "enable f16;\nstruct Half { a: f16, b: vec3<f16>, c: f32, d: array<f16, 3> }";
+        let layout = wgsl_type_layout(source, "Half").unwrap();
+        let LayoutKind::Struct { fields } = &layout.kind else { panic!("not a struct") };
+        let offsets: Vec<u32> = fields.iter().map(|f| f.offset).collect();
+        assert_eq!(offsets, vec![0, 8, 16, 20]);
+        assert_eq!((layout.size, layout.alignment), (32, 8));
+        assert!(matches!(fields[0].layout.kind, LayoutKind::Scalar { scalar: LayoutScalar::F16 }));
+        assert!(matches!(fields[3].layout.kind, LayoutKind::Array { stride: 2, count: Some(3), .. }));
+
+        assert!(wgsl_type_layout("struct P { @align(16) a: f16 }", "P").is_err());
+    }
+
+    #[test]
+    fn test_uniform_member_after_struct() {
+        let source = "struct Inner { a: f32 }\nstruct Outer { inner: Inner, b: f32 }";
+        let report = wgsl_layout_report(source, "Outer", BufferAddressSpace::Uniform).unwrap();
+        assert!(!report.valid);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].path, "b");
+
+        let report = wgsl_layout_report(LIGHT, "Light", BufferAddressSpace::Uniform).unwrap();
+        assert!(report.valid);
+        assert!(report.notes.iter().any(|n| n.path == "intensity"));
+    }
+
+    #[test]
+    fn test_struct_layout_from_fields() {
+        let layout = wgsl_struct_layout(
+            "Params",
+            &["scale: f32".to_string(), "offset: vec2<f32>".to_string()],
+        )
+        .unwrap();
+        assert_eq!(field(&layout, "offset").offset, 8);
+        assert_eq!(field(&layout, "offset").padding_before, 4);
+        assert_eq!(layout.size, 16);
+    }
+
+    #[test]
+    fn test_pack_and_unpack_roundtrip() {
+        let layout = wgsl_type_layout(LIGHT, "Light").unwrap();
+        let value = json!({
+            "color": [1.0, 0.5, 0.25],
+            "intensity": 2.0,
+            "direction": [0.0, -1.0, 0.0],
+            "transform": [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        });
+        let bytes = pack_wgsl_value(&layout, &value).unwrap();
+        assert_eq!(bytes.len(), 80);
+        assert_eq!(&bytes[12..16], &2.0f32.to_le_bytes());
+        // Column 1 of the mat3x3 starts 16 bytes after column 0
+        assert_eq!(&bytes[52..56], &1.0f32.to_le_bytes());
+
+        assert_eq!(unpack_wgsl_value(&layout, &bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn test_runtime_array_packing() {
+        let source = "struct Particles { count: u32, items: array<vec2<f32>> }";
+        let layout = wgsl_type_layout(source, "Particles").unwrap();
+        let value = json!({ "count": 2, "items": [[1.0, 2.0], [3.0, 4.0]] });
+        let bytes = pack_wgsl_value(&layout, &value).unwrap();
+        assert_eq!(bytes.len(), 24);
+        assert_eq!(unpack_wgsl_value(&layout, &bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn test_pack_errors_report_path() {
+        let layout = wgsl_type_layout(LIGHT, "Light").unwrap();
+        let err = pack_wgsl_value(&layout, &json!({ "color": [1.0, 0.5] })).unwrap_err();
+        assert_eq!(err.path, "color");
+
+        assert!(wgsl_type_layout("", "bool").is_err());
+        assert!(wgsl_type_layout("", "NotAType").is_err());
+    }
+
+    #[test]
+    fn test_f16_conversion() {
+        for v in [0.0f32, 1.0, -2.5, 65504.0, 0.000061035156] {
+            assert_eq!(f16_bits_to_f32(f32_to_f16_bits(v)), v);
+        }
+    }
+}
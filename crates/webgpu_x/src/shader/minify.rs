@@ -0,0 +1,1040 @@
+//! WGSL minifier
+//!
+//! Works on the validated naga module and prints it back through naga's WGSL backend:
+//! - Drops functions no entry point reaches through the call graph, then the constants and
+//!   structs nothing left refers to
+//! - Renames parameters, locals, `let` bindings, temporaries and non-entry-point functions
+//!   through their handles, so scoping and shadowing never come into play
+//! - Inlines scalar constants at their uses; literal arithmetic is folded by naga's
+//!   constant evaluator while parsing
+//! - Removes comments and all whitespace that is not needed to separate tokens
+//! - Emits a Source Map v3 pointing identifiers back at their declarations
+//!
+//! Entry points, global variables, constants that survive and struct members keep their
+//! names so pipeline creation and reflection are unaffected. The output is validated again.
+//! Modules without entry points are libraries: nothing is removed and functions keep their
+//! names. Shaders with pipeline-overridable constants, which naga's WGSL backend cannot
+//! print, only get comments and whitespace removed ([`minify_wgsl_tokens`]).
+
+use super::reflection::{validate_wgsl, ValidatedShader, WgslError, WgslErrorKind};
+use naga::{Block, Expression, Function, Handle, Statement};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Minifier switches; everything is enabled by default
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MinifyOptions {
+    pub rename_locals: bool,
+    pub rename_functions: bool,
+    pub remove_unused: bool,
+    pub fold_constants: bool,
+    /// File name recorded in the source map
+    pub source_name: String,
+}
+
+impl Default for MinifyOptions {
+    fn default() -> Self {
+        Self {
+            rename_locals: true,
+            rename_functions: true,
+            remove_unused: true,
+            fold_constants: true,
+            source_name: "shader.wgsl".to_string(),
+        }
+    }
+}
+
+/// Minified shader with source map
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MinifiedShader {
+    pub code: String,
+    /// Source Map v3 JSON
+    pub source_map: String,
+    pub original_size: u32,
+    pub minified_size: u32,
+    /// Names of removed top-level declarations
+    pub removed: Vec<String>,
+    /// Original to new name of renamed functions
+    pub renamed_functions: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenKind {
+    Ident,
+    Number,
+    Punct,
+    /// Only produced by [`lex`] with `keep_comments`
+    Comment,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Token {
+    pub(crate) kind: TokenKind,
+    pub(crate) text: String,
+    /// 0-based position in the original source
+    pub(crate) line: u32,
+    pub(crate) column: u32,
+    /// Original identifier when renamed
+    pub(crate) original: Option<String>,
+}
+
+const PUNCTUATION: [&str; 47] = [
+    ">>=", "<<=", "->", "&&", "||", "==", "!=", "<=", ">=", "<<", ">>", "++", "--", "+=", "-=",
+    "*=", "/=", "%=", "&=", "|=", "^=", "@", "(", ")", "{", "}", "[", "]", "<", ">", ";", ":",
+    ",", ".", "=", "+", "-", "*", "/", "%", "&", "|", "^", "!", "~", "_", "?",
+];
+
+
+fn minify_error(message: String) -> WgslError {
+    WgslError {
+        kind: WgslErrorKind::Parse,
+        message,
+        diagnostics: Vec::new(),
+        rendered: String::new(),
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, WgslError> {
+    lex(source, false)
+}
+
+/// Split WGSL source into tokens, optionally keeping comments as [`TokenKind::Comment`]
+pub(crate) fn lex(source: &str, keep_comments: bool) -> Result<Vec<Token>, WgslError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let (mut i, mut line, mut column) = (0usize, 0u32, 0u32);
+
+    let advance = |i: &mut usize, line: &mut u32, column: &mut u32, chars: &[char]| {
+        if chars[*i] == '\n' {
+            *line += 1;
+            *column = 0;
+        } else {
+            *column += 1;
+        }
+        *i += 1;
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            advance(&mut i, &mut line, &mut column, &chars);
+            continue;
+        }
+
+        let (start_line, start_column, start) = (line, column, i);
+
+        if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                advance(&mut i, &mut line, &mut column, &chars);
+            }
+            if keep_comments {
+                tokens.push(Token {
+                    kind: TokenKind::Comment,
+                    text: chars[start..i].iter().collect::<String>().trim_end().to_string(),
+                    line: start_line,
+                    column: start_column,
+                    original: None,
+                });
+            }
+            continue;
+        }
+
+        if c == '/' && chars.get(i + 1) == Some(&'*') {
+            // Block comments nest in WGSL
+            let mut depth = 0;
+            while i < chars.len() {
+                if chars[i] == '/' && chars.get(i + 1) == Some(&'*') {
+                    depth += 1;
+                    advance(&mut i, &mut line, &mut column, &chars);
+                } else if chars[i] == '*' && chars.get(i + 1) == Some(&'/') {
+                    depth -= 1;
+                    advance(&mut i, &mut line, &mut column, &chars);
+                    if depth == 0 {
+                        advance(&mut i, &mut line, &mut column, &chars);
+                        break;
+                    }
+                }
+                advance(&mut i, &mut line, &mut column, &chars);
+            }
+            if keep_comments {
+                tokens.push(Token {
+                    kind: TokenKind::Comment,
+                    text: chars[start..i].iter().collect(),
+                    line: start_line,
+                    column: start_column,
+                    original: None,
+                });
+            }
+            continue;
+        }
+
+        let kind = if c.is_alphabetic() || (c == '_' && chars.get(i + 1).is_some_and(|n| n.is_alphanumeric() || *n == '_')) {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                advance(&mut i, &mut line, &mut column, &chars);
+            }
+            TokenKind::Ident
+        } else if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit())) {
+            let hex = c == '0' && matches!(chars.get(i + 1), Some('x') | Some('X'));
+            while i < chars.len() {
+                let ch = chars[i];
+                let exponent_sign = (ch == '+' || ch == '-')
+                    && i > start
+                    && matches!(chars[i - 1], 'e' | 'E' | 'p' | 'P')
+                    && !(hex && matches!(chars[i - 1], 'e' | 'E'));
+                if ch.is_ascii_alphanumeric() || ch == '.' || exponent_sign {
+                    advance(&mut i, &mut line, &mut column, &chars);
+                } else {
+                    break;
+                }
+            }
+            TokenKind::Number
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 3)].iter().collect();
+            let punct = PUNCTUATION
+                .iter()
+                .find(|p| rest.starts_with(**p))
+                .ok_or_else(|| minify_error(format!("Unexpected character '{}' at {}:{}", c, line + 1, column + 1)))?;
+            for _ in 0..punct.chars().count() {
+                advance(&mut i, &mut line, &mut column, &chars);
+            }
+            TokenKind::Punct
+        };
+
+        tokens.push(Token {
+            kind,
+            text: chars[start..i].iter().collect(),
+            line: start_line,
+            column: start_column,
+            original: None,
+        });
+    }
+
+    Ok(tokens)
+}
+
+
+/// Names naga's WGSL backend gives unnamed items; generated names must not collide with them
+const BACKEND_FALLBACK_NAMES: &[&str] = &["type", "member", "param", "local", "global", "function", "unnamed"];
+
+/// Hands out short names that are unique across the whole module.
+///
+/// Names are letters only: the WGSL backend appends `_` to names ending in a digit and
+/// suffixes repeated names, even when they live in different functions.
+struct NameGenerator {
+    taken: HashSet<String>,
+    next: usize,
+}
+
+impl NameGenerator {
+    fn next_name(&mut self) -> String {
+        const LETTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        loop {
+            let mut n = self.next;
+            self.next += 1;
+            let mut name = String::new();
+            name.push(LETTERS[n % LETTERS.len()] as char);
+            n /= LETTERS.len();
+            while n > 0 {
+                n -= 1;
+                name.push(LETTERS[n % LETTERS.len()] as char);
+                n /= LETTERS.len();
+            }
+            if !self.taken.contains(&name) && !naga::keywords::wgsl::RESERVED.contains(&name.as_str()) {
+                self.taken.insert(name.clone());
+                return name;
+            }
+        }
+    }
+}
+
+/// Declaration an output identifier comes from, for the source map
+struct Origin {
+    /// Name in the original source when the identifier was renamed
+    original: Option<String>,
+    span: naga::Span,
+}
+
+/// Call `visit` on every statement of `block`, nested blocks included
+fn visit_statements(block: &Block, visit: &mut impl FnMut(&Statement)) {
+    for statement in block.iter() {
+        visit(statement);
+        match statement {
+            Statement::Block(inner) => visit_statements(inner, visit),
+            Statement::If { accept, reject, .. } => {
+                visit_statements(accept, visit);
+                visit_statements(reject, visit);
+            }
+            Statement::Switch { cases, .. } => {
+                for case in cases {
+                    visit_statements(&case.body, visit);
+                }
+            }
+            Statement::Loop { body, continuing, .. } => {
+                visit_statements(body, visit);
+                visit_statements(continuing, visit);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn remap_calls(block: &mut Block, remap: &HashMap<Handle<Function>, Handle<Function>>) {
+    for statement in block.iter_mut() {
+        match statement {
+            Statement::Call { function, .. } => *function = remap[function],
+            Statement::Block(inner) => remap_calls(inner, remap),
+            Statement::If { accept, reject, .. } => {
+                remap_calls(accept, remap);
+                remap_calls(reject, remap);
+            }
+            Statement::Switch { cases, .. } => {
+                for case in cases.iter_mut() {
+                    remap_calls(&mut case.body, remap);
+                }
+            }
+            Statement::Loop { body, continuing, .. } => {
+                remap_calls(body, remap);
+                remap_calls(continuing, remap);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn all_functions(module: &naga::Module) -> impl Iterator<Item = &Function> {
+    module
+        .functions
+        .iter()
+        .map(|(_, function)| function)
+        .chain(module.entry_points.iter().map(|ep| &ep.function))
+}
+
+fn all_functions_mut(module: &mut naga::Module) -> impl Iterator<Item = &mut Function> {
+    module
+        .functions
+        .iter_mut()
+        .map(|(_, function)| function)
+        .chain(module.entry_points.iter_mut().map(|ep| &mut ep.function))
+}
+
+/// Functions reachable from any entry point through the call graph
+fn reachable_functions(module: &naga::Module) -> HashSet<Handle<Function>> {
+    let mut reachable = HashSet::new();
+    let mut pending: Vec<&Function> = module.entry_points.iter().map(|ep| &ep.function).collect();
+    while let Some(function) = pending.pop() {
+        visit_statements(&function.body, &mut |statement| {
+            if let Statement::Call { function: callee, .. } = *statement {
+                if reachable.insert(callee) {
+                    pending.push(&module.functions[callee]);
+                }
+            }
+        });
+    }
+    reachable
+}
+
+/// Replace uses of constants initialized with a single literal by that literal
+fn inline_scalar_constants(module: &mut naga::Module) {
+    let literals: HashMap<_, _> = module
+        .constants
+        .iter()
+        .filter_map(|(handle, constant)| match module.global_expressions[constant.init] {
+            Expression::Literal(literal) => Some((handle, literal)),
+            _ => None,
+        })
+        .collect();
+
+    for function in all_functions_mut(module) {
+        for (_, expression) in function.expressions.iter_mut() {
+            if let Expression::Constant(constant) = *expression {
+                if let Some(&literal) = literals.get(&constant) {
+                    *expression = Expression::Literal(literal);
+                }
+            }
+        }
+    }
+}
+
+/// Try dropping the name of one unused struct; `compact` treats named types as used, so a
+/// struct is unused when naga's own tracing removes it once it is unnamed
+fn remove_unused_struct(module: &naga::Module) -> Option<(String, naga::Module)> {
+    let struct_count = |module: &naga::Module| {
+        module
+            .types
+            .iter()
+            .filter(|(_, ty)| matches!(ty.inner, naga::TypeInner::Struct { .. }))
+            .count()
+    };
+    let before = struct_count(module);
+
+    for (handle, ty) in module.types.iter() {
+        let name = match (&ty.name, &ty.inner) {
+            // `__`-prefixed structs are predeclared result types such as `__modf_result_f32`
+            (Some(name), naga::TypeInner::Struct { .. }) if !name.starts_with("__") => name,
+            _ => continue,
+        };
+        let unnamed = naga::Type {
+            name: None,
+            inner: ty.inner.clone(),
+        };
+        if module.types.get(&unnamed).is_some() {
+            continue;
+        }
+
+        let mut trial = module.clone();
+        trial.types.replace(handle, unnamed);
+        naga::compact::compact(&mut trial);
+        if struct_count(&trial) < before {
+            return Some((name.clone(), trial));
+        }
+    }
+    None
+}
+
+/// Drop functions, constants and structs no entry point can reach, returning their names
+fn remove_unreachable(module: &mut naga::Module) -> Vec<String> {
+    let mut removed = Vec::new();
+    // Without entry points everything is reachable from whoever links the module
+    if module.entry_points.is_empty() {
+        return removed;
+    }
+
+    let reachable = reachable_functions(module);
+    let mut remap = HashMap::new();
+    let mut functions = std::mem::take(&mut module.functions);
+    for (handle, function, span) in functions.drain() {
+        if reachable.contains(&handle) {
+            remap.insert(handle, module.functions.append(function, span));
+        } else if let Some(name) = function.name {
+            removed.push(name);
+        }
+    }
+    for function in all_functions_mut(module) {
+        remap_calls(&mut function.body, &remap);
+        for (_, expression) in function.expressions.iter_mut() {
+            if let Expression::CallResult(callee) = expression {
+                *callee = remap[callee];
+            }
+        }
+    }
+
+    // Constant initializers see through other constants, so only function bodies refer to them
+    let used_constants: HashSet<_> = all_functions(module)
+        .flat_map(|function| function.expressions.iter())
+        .filter_map(|(_, expression)| match *expression {
+            Expression::Constant(constant) => Some(constant),
+            _ => None,
+        })
+        .collect();
+    for (handle, constant) in module.constants.iter_mut() {
+        if !used_constants.contains(&handle) {
+            // `compact` keeps named constants only
+            if let Some(name) = constant.name.take() {
+                removed.push(name);
+            }
+        }
+    }
+    naga::compact::compact(module);
+
+    while let Some((name, trial)) = remove_unused_struct(module) {
+        removed.push(name);
+        *module = trial;
+    }
+    removed
+}
+
+fn assign_short_name(
+    name: &mut String,
+    span: naga::Span,
+    generator: &mut NameGenerator,
+    origins: &mut HashMap<String, Origin>,
+) {
+    let short = generator.next_name();
+    let original = std::mem::replace(name, short.clone());
+    origins.insert(
+        short,
+        Origin {
+            original: Some(original),
+            span,
+        },
+    );
+}
+
+/// Rename parameters, locals and `let` bindings; parameters map to the function itself
+fn rename_function_locals(
+    function: &mut Function,
+    span: naga::Span,
+    generator: &mut NameGenerator,
+    origins: &mut HashMap<String, Origin>,
+) {
+    for argument in function.arguments.iter_mut() {
+        if let Some(name) = argument.name.as_mut() {
+            assign_short_name(name, span, generator, origins);
+        }
+    }
+    let locals: Vec<_> = function.local_variables.iter().map(|(handle, _)| handle).collect();
+    for handle in locals {
+        let span = function.local_variables.get_span(handle);
+        if let Some(name) = function.local_variables[handle].name.as_mut() {
+            assign_short_name(name, span, generator, origins);
+        }
+    }
+    for (&handle, name) in function.named_expressions.iter_mut() {
+        assign_short_name(name, function.expressions.get_span(handle), generator, origins);
+    }
+}
+
+/// Name the expressions the WGSL backend would otherwise spell `_e<index>`, using the same
+/// rule it applies when deciding what to bake into a `let`
+fn name_temporaries(
+    function: &mut Function,
+    info: &naga::valid::FunctionInfo,
+    generator: &mut NameGenerator,
+    origins: &mut HashMap<String, Origin>,
+) {
+    let mut baked = Vec::new();
+    visit_statements(&function.body, &mut |statement| {
+        if let Statement::Emit(range) = statement {
+            for handle in range.clone() {
+                let expression = &function.expressions[handle];
+                let forced = matches!(
+                    expression,
+                    Expression::ImageLoad { .. } | Expression::ImageQuery { .. } | Expression::ImageSample { .. }
+                );
+                if !function.named_expressions.contains_key(&handle)
+                    && (forced || info[handle].ref_count >= expression.bake_ref_count())
+                {
+                    baked.push(handle);
+                }
+            }
+        }
+    });
+
+    for handle in baked {
+        let name = generator.next_name();
+        origins.insert(
+            name.clone(),
+            Origin {
+                original: None,
+                span: function.expressions.get_span(handle),
+            },
+        );
+        function.named_expressions.insert(handle, name);
+    }
+}
+
+/// Identifiers the WGSL backend adjusts while printing (`pass1` becomes `pass1_`), mapped
+/// back to the module's names so the interface of the shader does not change
+fn backend_renames(module: &naga::Module) -> HashMap<String, String> {
+    use naga::proc::NameKey;
+
+    let mut names = naga::FastHashMap::default();
+    naga::proc::Namer::default().reset(module, naga::keywords::wgsl::RESERVED, &[], &[], &["__"], &mut names);
+
+    let mut renames: HashMap<String, Option<String>> = HashMap::new();
+    for (key, emitted) in names {
+        let original = match key {
+            NameKey::Constant(h) => module.constants[h].name.as_ref(),
+            NameKey::GlobalVariable(h) => module.global_variables[h].name.as_ref(),
+            NameKey::Type(h) => module.types[h].name.as_ref(),
+            NameKey::StructMember(h, index) => match module.types[h].inner {
+                naga::TypeInner::Struct { ref members, .. } => members[index as usize].name.as_ref(),
+                _ => None,
+            },
+            NameKey::Function(h) => module.functions[h].name.as_ref(),
+            NameKey::FunctionArgument(h, index) => module.functions[h].arguments[index as usize].name.as_ref(),
+            NameKey::FunctionLocal(h, local) => module.functions[h].local_variables[local].name.as_ref(),
+            NameKey::EntryPoint(index) => Some(&module.entry_points[index as usize].name),
+            NameKey::EntryPointArgument(index, argument) => module.entry_points[index as usize]
+                .function
+                .arguments[argument as usize]
+                .name
+                .as_ref(),
+            NameKey::EntryPointLocal(index, local) => {
+                module.entry_points[index as usize].function.local_variables[local].name.as_ref()
+            }
+        };
+        if let Some(original) = original.filter(|original| **original != emitted) {
+            // The same printed name standing for two different names is left alone
+            renames
+                .entry(emitted)
+                .and_modify(|existing| {
+                    if existing.as_ref() != Some(original) {
+                        *existing = None;
+                    }
+                })
+                .or_insert_with(|| Some(original.clone()));
+        }
+    }
+    renames
+        .into_iter()
+        .filter_map(|(emitted, original)| original.map(|original| (emitted, original)))
+        .collect()
+}
+
+/// Whether two tokens would lex differently if written without a space
+fn needs_space(prev: &Token, next: &Token) -> bool {
+    let word = |c: char| c.is_alphanumeric() || c == '_';
+    let (a, b) = match (prev.text.chars().last(), next.text.chars().next()) {
+        (Some(a), Some(b)) => (a, b),
+        _ => return false,
+    };
+    if word(a) && word(b) {
+        return true;
+    }
+    // `1.` followed by `.5` or `x` would merge into one number
+    if (prev.kind == TokenKind::Number && (word(b) || b == '.')) || (a == '.' && next.kind == TokenKind::Number) {
+        return true;
+    }
+    if prev.kind == TokenKind::Punct || next.kind == TokenKind::Punct {
+        let joined = format!("{}{}", prev.text, next.text);
+        return match tokenize(&joined) {
+            Ok(tokens) => tokens.len() != 2 || tokens[0].text != prev.text,
+            Err(_) => true,
+        };
+    }
+    false
+}
+
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn encode_vlq(value: i64, out: &mut String) {
+    let mut vlq = if value < 0 { ((-value) << 1) | 1 } else { value << 1 };
+    loop {
+        let mut digit = (vlq & 0x1f) as usize;
+        vlq >>= 5;
+        if vlq > 0 {
+            digit |= 0x20;
+        }
+        out.push(BASE64[digit] as char);
+        if vlq == 0 {
+            break;
+        }
+    }
+}
+
+fn build_source_map(tokens: &[Token], columns: &[u32], source_name: &str, source: &str) -> String {
+    let mut names: Vec<String> = Vec::new();
+    let mut mappings = String::new();
+    let (mut prev_column, mut prev_line, mut prev_source_column, mut prev_name) = (0i64, 0i64, 0i64, 0i64);
+
+    for (token, &column) in tokens.iter().zip(columns) {
+        if !mappings.is_empty() {
+            mappings.push(',');
+        }
+        encode_vlq(column as i64 - prev_column, &mut mappings);
+        encode_vlq(0, &mut mappings);
+        encode_vlq(token.line as i64 - prev_line, &mut mappings);
+        encode_vlq(token.column as i64 - prev_source_column, &mut mappings);
+        prev_column = column as i64;
+        prev_line = token.line as i64;
+        prev_source_column = token.column as i64;
+
+        if let Some(original) = &token.original {
+            let index = match names.iter().position(|n| n == original) {
+                Some(index) => index,
+                None => {
+                    names.push(original.clone());
+                    names.len() - 1
+                }
+            } as i64;
+            encode_vlq(index - prev_name, &mut mappings);
+            prev_name = index;
+        }
+    }
+
+    serde_json::json!({
+        "version": 3,
+        "sources": [source_name],
+        "sourcesContent": [source],
+        "names": names,
+        "mappings": mappings,
+    })
+    .to_string()
+}
+
+/// Remove comments and whitespace only; identifiers map to themselves in the source map
+pub fn minify_wgsl_tokens(source: &str, options: &MinifyOptions) -> Result<MinifiedShader, WgslError> {
+    let tokens = tokenize(source)?;
+    let mut code = String::with_capacity(source.len());
+    let mut mapped = Vec::new();
+    let mut columns = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if i > 0 && needs_space(&tokens[i - 1], token) {
+            code.push(' ');
+        }
+        if token.kind == TokenKind::Ident {
+            mapped.push(token.clone());
+            columns.push(code.len() as u32);
+        }
+        code.push_str(&token.text);
+    }
+
+    Ok(MinifiedShader {
+        source_map: build_source_map(&mapped, &columns, &options.source_name, source),
+        original_size: source.len() as u32,
+        minified_size: code.len() as u32,
+        code,
+        removed: Vec::new(),
+        renamed_functions: BTreeMap::new(),
+    })
+}
+
+/// Minify validated WGSL
+pub fn minify_wgsl(source: &str, options: &MinifyOptions) -> Result<MinifiedShader, WgslError> {
+    let ValidatedShader { mut module, .. } = validate_wgsl(source)?;
+    if !module.overrides.is_empty() {
+        return minify_wgsl_tokens(source, options);
+    }
+    let is_library = module.entry_points.is_empty();
+
+    if options.fold_constants {
+        inline_scalar_constants(&mut module);
+    }
+    let removed = if options.remove_unused {
+        remove_unreachable(&mut module)
+    } else {
+        Vec::new()
+    };
+
+    // Every identifier of the source is taken, so short names never capture an existing one
+    let mut generator = NameGenerator {
+        taken: tokenize(source)?
+            .into_iter()
+            .filter(|t| t.kind == TokenKind::Ident)
+            .map(|t| t.text)
+            .chain(BACKEND_FALLBACK_NAMES.iter().map(|name| name.to_string()))
+            .collect(),
+        next: 0,
+    };
+    let mut origins: HashMap<String, Origin> = HashMap::new();
+    let mut renamed_functions = BTreeMap::new();
+
+    let functions: Vec<_> = module.functions.iter().map(|(handle, _)| handle).collect();
+    for &handle in &functions {
+        let span = module.functions.get_span(handle);
+        let function = &mut module.functions[handle];
+        if options.rename_functions && !is_library {
+            if let Some(name) = function.name.as_mut() {
+                let original = name.clone();
+                assign_short_name(name, span, &mut generator, &mut origins);
+                renamed_functions.insert(original, name.clone());
+            }
+        }
+        if options.rename_locals {
+            rename_function_locals(function, span, &mut generator, &mut origins);
+        }
+    }
+    for ep in module.entry_points.iter_mut() {
+        // Entry points have no span of their own; use their first statement
+        let span = ep.function.body.span_iter().next().map(|(_, span)| *span).unwrap_or_default();
+        origins.insert(ep.name.clone(), Origin { original: None, span });
+        if options.rename_locals {
+            rename_function_locals(&mut ep.function, span, &mut generator, &mut origins);
+        }
+    }
+    for (handle, var) in module.global_variables.iter() {
+        if let Some(name) = &var.name {
+            let span = module.global_variables.get_span(handle);
+            origins.insert(name.clone(), Origin { original: None, span });
+        }
+    }
+    for (handle, constant) in module.constants.iter() {
+        if let Some(name) = &constant.name {
+            let span = module.constants.get_span(handle);
+            origins.insert(name.clone(), Origin { original: None, span });
+        }
+    }
+    for (handle, ty) in module.types.iter() {
+        if let Some(name) = &ty.name {
+            let span = module.types.get_span(handle);
+            origins.insert(name.clone(), Origin { original: None, span });
+        }
+    }
+
+    let info = naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::default())
+        .validate(&module)
+        .map_err(|e| WgslError {
+            kind: WgslErrorKind::Validation,
+            message: format!("Minified module failed validation: {}", e.as_inner()),
+            diagnostics: Vec::new(),
+            rendered: String::new(),
+        })?;
+    if options.rename_locals {
+        for &handle in &functions {
+            name_temporaries(&mut module.functions[handle], &info[handle], &mut generator, &mut origins);
+        }
+        for (index, ep) in module.entry_points.iter_mut().enumerate() {
+            name_temporaries(&mut ep.function, info.get_entry_point(index), &mut generator, &mut origins);
+        }
+    }
+
+    let printed = naga::back::wgsl::write_string(&module, &info, naga::back::wgsl::WriterFlags::empty())
+        .map_err(|e| minify_error(format!("Failed to print minified WGSL: {}", e)))?;
+    let renames = backend_renames(&module);
+    let mut tokens = tokenize(&printed)?;
+    for token in tokens.iter_mut().filter(|t| t.kind == TokenKind::Ident) {
+        if let Some(original) = renames.get(&token.text) {
+            token.text = original.clone();
+        }
+    }
+    // The backend ends every struct member with a comma
+    let trailing_commas: Vec<usize> = (1..tokens.len())
+        .filter(|&i| tokens[i].text == "}" && tokens[i - 1].text == ",")
+        .map(|i| i - 1)
+        .collect();
+    for i in trailing_commas.into_iter().rev() {
+        tokens.remove(i);
+    }
+
+    let mut code = String::with_capacity(printed.len());
+    let mut mapped = Vec::new();
+    let mut columns = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if i > 0 && needs_space(&tokens[i - 1], token) {
+            code.push(' ');
+        }
+        let member_access = i > 0 && tokens[i - 1].text == ".";
+        let origin = origins
+            .get(&token.text)
+            .filter(|o| token.kind == TokenKind::Ident && !member_access && o.span.is_defined());
+        if let Some(origin) = origin {
+            let location = origin.span.location(source);
+            mapped.push(Token {
+                kind: TokenKind::Ident,
+                text: token.text.clone(),
+                line: location.line_number - 1,
+                column: location.line_position - 1,
+                original: origin.original.clone(),
+            });
+            columns.push(code.len() as u32);
+        }
+        code.push_str(&token.text);
+    }
+
+    validate_wgsl(&code).map_err(|e| WgslError {
+        message: format!("Minified output failed validation: {}", e.message),
+        ..e
+    })?;
+
+    Ok(MinifiedShader {
+        source_map: build_source_map(&mapped, &columns, &options.source_name, source),
+        original_size: source.len() as u32,
+        minified_size: code.len() as u32,
+        code,
+        removed,
+        renamed_functions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHADER: &str = r#"
+/* Scales a buffer /* nested */ in place */
+struct Params { factor: f32, count: u32 }
+struct Unused { x: f32 }
+const UNUSED_CONST: f32 = 1.0;
+const HALF: f32 = 0.5;
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read_write> data: array<f32>;
+
+fn unused_helper() -> f32 { return 1.0; }
+
+fn scale_value(value: f32, factor: f32) -> f32 {
+    let doubled = value * (2.0 * 3.0);  // folded
+    return doubled * factor * HALF;
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let index = global_id.x;
+    if (index >= params.count) {
+        return;
+    }
+    for (var i: u32 = 0u; i < 1u + 1u; i++) {
+        data[index] = scale_value(data[index], params.factor);
+    }
+}
+"#;
+
+    #[test]
+    fn test_minify_renames_and_removes() {
+        let out = minify_wgsl(SHADER, &MinifyOptions::default()).unwrap();
+        assert!(out.minified_size < out.original_size * 2 / 3);
+        assert!(!out.code.contains("unused_helper"));
+        assert!(!out.code.contains("Unused"));
+        assert!(!out.code.contains("UNUSED_CONST"));
+        assert!(!out.code.contains("scale_value"));
+        assert!(!out.code.contains("doubled"));
+        assert!(!out.code.contains("global_id"));
+        // Interface names survive
+        assert!(out.code.contains("fn main("));
+        assert!(out.code.contains("params.count"));
+        assert!(out.code.contains("@builtin(global_invocation_id)"));
+        // HALF is inlined, so its declaration goes too
+        let removed: HashSet<&str> = out.removed.iter().map(String::as_str).collect();
+        assert_eq!(removed, HashSet::from(["unused_helper", "Unused", "UNUSED_CONST", "HALF"]));
+        assert!(out.renamed_functions.contains_key("scale_value"));
+    }
+
+    #[test]
+    fn test_reachability_follows_the_call_graph() {
+        let source = r#"
+struct OnlyForDead { x: f32 }
+const ONLY_FOR_DEAD: f32 = 2.0;
+fn leaf(x: f32) -> f32 { return x + 1.0; }
+fn middle(x: f32) -> f32 { return leaf(x) * 2.0; }
+fn dead(v: OnlyForDead) -> f32 { return middle(v.x) * ONLY_FOR_DEAD; }
+
+@group(0) @binding(0) var<storage, read_write> data: array<f32>;
+
+@compute @workgroup_size(1)
+fn main() {
+    data[0] = middle(data[0]);
+}
+"#;
+        let options = MinifyOptions {
+            fold_constants: false,
+            ..Default::default()
+        };
+        let out = minify_wgsl(source, &options).unwrap();
+        let removed: HashSet<&str> = out.removed.iter().map(String::as_str).collect();
+        assert_eq!(removed, HashSet::from(["dead", "OnlyForDead", "ONLY_FOR_DEAD"]));
+        // Both callees of main survive, under new names
+        assert_eq!(out.renamed_functions.len(), 2);
+        for name in out.renamed_functions.values() {
+            assert!(out.code.contains(&format!("fn {}(", name)));
+        }
+    }
+
+    #[test]
+    fn test_constant_folding() {
+        let out = minify_wgsl(SHADER, &MinifyOptions::default()).unwrap();
+        assert!(out.code.contains("6f"));
+        assert!(out.code.contains("0.5f"));
+        assert!(out.code.contains("2u"));
+
+        let options = MinifyOptions {
+            fold_constants: false,
+            ..Default::default()
+        };
+        let out = minify_wgsl(SHADER, &options).unwrap();
+        assert!(out.code.contains("HALF"));
+    }
+
+    #[test]
+    fn test_shadowing_is_preserved() {
+        let source = r#"
+@group(0) @binding(0) var<storage, read_write> out: array<f32>;
+
+@compute @workgroup_size(1)
+fn main() {
+    var total = 1.0;
+    {
+        let total = total + 1.0;
+        out[1] = total;
+    }
+    total = total * 2.0;
+    out[0] = total;
+}
+"#;
+        let out = minify_wgsl(source, &MinifyOptions::default()).unwrap();
+        assert!(!out.code.contains("total"));
+        let inner = validate_wgsl(&out.code).unwrap();
+        let locals = &inner.module.entry_points[0].function.local_variables;
+        assert_eq!(locals.len(), 1);
+    }
+
+    #[test]
+    fn test_backend_suffixes_are_undone() {
+        let source = r#"
+struct Pass1 { value1: f32 }
+@group(0) @binding(0) var<storage, read_write> buffer0: array<Pass1>;
+
+@compute @workgroup_size(1)
+fn main2() {
+    buffer0[0].value1 = 1.0;
+}
+"#;
+        let out = minify_wgsl(source, &MinifyOptions::default()).unwrap();
+        assert!(out.code.contains("fn main2("));
+        assert!(out.code.contains("struct Pass1{value1:f32}"));
+        assert!(out.code.contains("buffer0"));
+        assert!(!out.code.contains("buffer0_"));
+    }
+
+    #[test]
+    fn test_spacing_keeps_tokens_apart() {
+        let source = "@group(0) @binding(0) var<storage, read_write> o: array<i32>; @compute @workgroup_size(1) fn main() { var x = o[0]; x = x - -o[1]; o[2] = x; }";
+        let out = minify_wgsl(source, &MinifyOptions::default()).unwrap();
+        assert!(out.code.contains("- -") || out.code.contains("-(-"));
+    }
+
+    #[test]
+    fn test_source_map() {
+        let out = minify_wgsl(SHADER, &MinifyOptions::default()).unwrap();
+        let map: serde_json::Value = serde_json::from_str(&out.source_map).unwrap();
+        assert_eq!(map["version"], 3);
+        assert!(map["names"].as_array().unwrap().iter().any(|n| n == "scale_value"));
+        assert!(!map["mappings"].as_str().unwrap().is_empty());
+
+        let mut vlq = String::new();
+        encode_vlq(-17, &mut vlq);
+        assert_eq!(vlq, "jB");
+    }
+
+    #[test]
+    fn test_invalid_input_is_rejected() {
+        assert!(minify_wgsl("fn broken( {", &MinifyOptions::default()).is_err());
+        // The infallible entry point still strips comments and whitespace
+        assert_eq!(crate::shader::wgsl_minify("fn broken( { // oops".to_string()), "fn broken({");
+    }
+
+    #[test]
+    fn test_overrides_fall_back_to_token_minification() {
+        let source = r#"
+// Scale factor set per pipeline
+override scale: f32 = 1.0;
+@group(0) @binding(0) var<storage, read_write> data: array<f32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let value = data[id.x];
+    data[id.x] = value * scale;
+}
+"#;
+        let out = minify_wgsl(source, &MinifyOptions::default()).unwrap();
+        assert!(!out.code.contains("Scale factor"));
+        assert!(out.code.contains("override scale:f32=1.0;"));
+        assert!(out.code.contains("let value=data[id.x];"));
+        assert!(out.removed.is_empty());
+        validate_wgsl(&out.code).unwrap();
+    }
+
+    #[test]
+    fn test_modules_without_entry_points_keep_everything() {
+        let source = r#"
+const SCALE: f32 = 2.0;
+struct Pair { a: f32, b: f32 }
+fn helper(x: f32) -> f32 { return x * SCALE; }
+fn sum(pair: Pair) -> f32 {
+    let total = pair.a + pair.b;
+    return helper(total);
+}
+"#;
+        let out = minify_wgsl(source, &MinifyOptions::default()).unwrap();
+        assert!(out.removed.is_empty());
+        assert!(out.renamed_functions.is_empty());
+        assert!(out.code.contains("fn helper("));
+        assert!(out.code.contains("fn sum("));
+        assert!(out.code.contains("struct Pair"));
+        assert!(!out.code.contains("total"));
+        let module = validate_wgsl(&out.code).unwrap().module;
+        assert_eq!(module.functions.len(), 2);
+    }
+}
@@ -2,19 +2,79 @@ use deno_bindgen::deno_bindgen;
 use serde::{Deserialize, Serialize};
 
 pub mod compilation;
+pub mod cross_compile;
+pub mod format;
+pub mod glsl_import;
+pub mod layout;
+pub mod minify;
+pub mod preprocessor;
+pub mod reflection;
+pub mod stdlib;
+pub mod variants;
+pub mod watcher;
 
 // Re-export public types and functions from compilation
 pub use compilation::{
     shader_cache_create,
     shader_cache_load,
     shader_cache_load_from_string,
+    shader_cache_load_glsl,
+    shader_cache_load_variant,
+    shader_cache_list_variants,
+    shader_cache_prewarm_variants,
     shader_cache_has_changed,
+    shader_cache_add_include_path,
+    shader_cache_define,
+    shader_cache_invalidate,
     shader_cache_clear,
     shader_cache_stats,
     shader_cache_destroy,
     detect_shader_stage,
     ShaderSource,
     ShaderCacheStats,
+    ShaderReloadError,
+    ShaderSnapshot,
+};
+
+pub use cross_compile::{
+    cross_compile_module, cross_compile_wgsl, CrossCompileError, CrossCompileErrorKind,
+    CrossCompileOptions, CrossCompiledShader, ShaderTarget,
+};
+
+pub use format::{format_wgsl, is_wgsl_formatted, AttributePlacement, FormatOptions};
+
+pub use glsl_import::{
+    import_glsl_compute, BindingRemap, GlslImport, GlslImportError, GlslImportOptions,
+    ImportedBinding, ImportedResourceKind, UnsupportedConstruct,
+};
+
+pub use layout::{
+    pack_wgsl_value, unpack_wgsl_value, wgsl_layout_report, wgsl_struct_layout, wgsl_type_layout,
+    BufferAddressSpace, FieldLayout, LayoutError, LayoutIssue, LayoutKind, LayoutReport,
+    LayoutScalar, TypeLayout,
+};
+
+pub use minify::{minify_wgsl, minify_wgsl_tokens, MinifiedShader, MinifyOptions};
+
+pub use preprocessor::{PreprocessError, PreprocessOptions, PreprocessedShader, SourceLine, WgslPreprocessor};
+
+pub use stdlib::{stdlib_modules, stdlib_source, StdModuleInfo, STDLIB_VERSION};
+
+pub use variants::{
+    canonical_variant_key, compile_variant, enumerate_variants, variant_space, CompiledVariant,
+    VariantError, VariantErrorKind, VariantFeature, VariantKey, VariantPrewarmReport, VariantSpace,
+};
+
+pub use watcher::{
+    shader_watcher_create, shader_watcher_destroy, shader_watcher_poll, shader_watcher_unwatch,
+    shader_watcher_watch, ShaderChangeEvent, ShaderChangeKind, ShaderReloadResult, ShaderWatcher,
+};
+
+pub use reflection::{
+    parse_wgsl, reflect_module, reflect_wgsl, validate_wgsl, validate_wgsl_with_capabilities,
+    wgsl_validation_report, EntryPointReflection, OverrideReflection, ResourceReflection,
+    ShaderReflection, StructMemberReflection, StructReflection, ValidatedShader, WgslDiagnostic,
+    WgslError, WgslErrorKind, WgslValidationReport,
 };
 
 /// WGSL shader type
@@ -276,71 +336,14 @@ pub fn wgsl_function(
     result
 }
 
-/// Minify WGSL shader code (remove comments and excess whitespace)
+/// Minify WGSL shader code with the default [`MinifyOptions`]; source that does not
+/// validate still has its comments and whitespace removed
 pub fn wgsl_minify(shader_code: String) -> String {
-    let mut result = String::new();
-    let mut in_comment = false;
-    let mut in_string = false;
-
-    let chars: Vec<char> = shader_code.chars().collect();
-    let mut i = 0;
-
-    while i < chars.len() {
-        let c = chars[i];
-
-        // Handle string literals
-        if c == '"' && (i == 0 || chars[i - 1] != '\\') {
-            in_string = !in_string;
-            result.push(c);
-            i += 1;
-            continue;
-        }
-
-        if in_string {
-            result.push(c);
-            i += 1;
-            continue;
-        }
-
-        // Handle line comments
-        if !in_comment && i + 1 < chars.len() && c == '/' && chars[i + 1] == '/' {
-            // Skip until end of line
-            while i < chars.len() && chars[i] != '\n' {
-                i += 1;
-            }
-            continue;
-        }
-
-        // Handle block comments
-        if !in_comment && i + 1 < chars.len() && c == '/' && chars[i + 1] == '*' {
-            in_comment = true;
-            i += 2;
-            continue;
-        }
-
-        if in_comment {
-            if i + 1 < chars.len() && c == '*' && chars[i + 1] == '/' {
-                in_comment = false;
-                i += 2;
-            } else {
-                i += 1;
-            }
-            continue;
-        }
-
-        // Collapse whitespace
-        if c.is_whitespace() {
-            if !result.is_empty() && !result.chars().last().unwrap().is_whitespace() {
-                result.push(' ');
-            }
-        } else {
-            result.push(c);
-        }
-
-        i += 1;
-    }
-
-    result.trim().to_string()
+    let options = MinifyOptions::default();
+    minify_wgsl(&shader_code, &options)
+        .or_else(|_| minify_wgsl_tokens(&shader_code, &options))
+        .map(|minified| minified.code)
+        .unwrap_or(shader_code)
 }
 
 /// Count lines in shader code
@@ -0,0 +1,976 @@
+//! WGSL preprocessor
+//!
+//! Lets shaders share code before they reach naga or the driver:
+//! - `#include "file"` (relative to the including file, then include paths) and `#include <file>`
+//! - `#define NAME value`, `#define NAME(a, b) body`, `#undef NAME`
+//! - `#ifdef`, `#ifndef`, `#if`, `#elif`, `#else`, `#endif` with `defined(NAME)` and integer expressions
+//! - `#pragma once` and classic `#ifndef` include guards
+//! - `#import std::module` for the WGSL standard library (see [`super::stdlib`])
+//! - Line map from expanded output back to the original file and line
+
+use super::reflection::{validate_wgsl, ValidatedShader, WgslError};
+use super::stdlib::{self, StdImport};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+const MAX_INCLUDE_DEPTH: usize = 32;
+const MAX_EXPANSION_DEPTH: usize = 64;
+
+/// Origin of one line of preprocessed output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLine {
+    pub file: String,
+    /// 1-based line number in `file`
+    pub line: u32,
+}
+
+/// Preprocessed shader with line map and include dependencies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreprocessedShader {
+    pub code: String,
+    /// `line_map[n]` is the origin of output line `n + 1`
+    pub line_map: Vec<SourceLine>,
+    /// Canonical paths of every file that was included (excluding the root)
+    pub dependencies: Vec<String>,
+    /// Standard library declarations appended to `code`, as emitted names
+    #[serde(default)]
+    pub linked: Vec<String>,
+}
+
+impl PreprocessedShader {
+    /// Map a 1-based output line back to its original file and line
+    pub fn map_line(&self, output_line: u32) -> Option<&SourceLine> {
+        if output_line == 0 {
+            return None;
+        }
+        self.line_map.get(output_line as usize - 1)
+    }
+
+    /// Validate the expanded code, reporting errors at their original locations
+    pub fn validate(&self) -> Result<ValidatedShader, WgslError> {
+        validate_wgsl(&self.code).map_err(|mut e| {
+            for diagnostic in &mut e.diagnostics {
+                if let Some(origin) = self.map_line(diagnostic.line) {
+                    diagnostic.file = Some(origin.file.clone());
+                    diagnostic.line = origin.line;
+                }
+            }
+            e
+        })
+    }
+}
+
+/// Preprocessing failure
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreprocessError {
+    pub message: String,
+    pub file: String,
+    pub line: u32,
+}
+
+impl fmt::Display for PreprocessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.file, self.line, self.message)
+    }
+}
+
+impl std::error::Error for PreprocessError {}
+
+/// JSON-friendly preprocessor configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreprocessOptions {
+    pub include_paths: Vec<String>,
+    /// Macro name (optionally with parameters, e.g. `SQUARE(x)`) to body
+    pub defines: HashMap<String, String>,
+    /// In-memory files resolvable by `#include`
+    pub files: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+struct MacroDef {
+    params: Option<Vec<String>>,
+    body: String,
+}
+
+/// One level of `#if` nesting
+struct Conditional {
+    parent_active: bool,
+    active: bool,
+    taken: bool,
+    seen_else: bool,
+}
+
+/// Mutable state for a single preprocessing run
+struct RunState {
+    defines: HashMap<String, MacroDef>,
+    once: HashSet<String>,
+    stack: Vec<String>,
+    code: String,
+    line_map: Vec<SourceLine>,
+    dependencies: Vec<String>,
+    imports: Vec<StdImport>,
+}
+
+/// WGSL preprocessor configuration
+#[derive(Debug, Clone, Default)]
+pub struct WgslPreprocessor {
+    include_paths: Vec<PathBuf>,
+    defines: HashMap<String, MacroDef>,
+    virtual_files: HashMap<String, String>,
+}
+
+impl WgslPreprocessor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create preprocessor from options
+    pub fn from_options(options: &PreprocessOptions) -> Self {
+        let mut preprocessor = Self::new();
+        for path in &options.include_paths {
+            preprocessor.add_include_path(path);
+        }
+        for (name, value) in &options.defines {
+            preprocessor.define(name, value);
+        }
+        for (name, code) in &options.files {
+            preprocessor.add_virtual_file(name, code);
+        }
+        preprocessor
+    }
+
+    /// Add a directory searched by `#include`
+    pub fn add_include_path(&mut self, path: impl Into<PathBuf>) {
+        let path = path.into();
+        if !self.include_paths.contains(&path) {
+            self.include_paths.push(path);
+        }
+    }
+
+    /// Define a macro; `name` may carry parameters, e.g. `SQUARE(x)`
+    pub fn define(&mut self, name: &str, value: &str) {
+        let (name, params) = parse_macro_name(name);
+        self.defines.insert(
+            name,
+            MacroDef {
+                params,
+                body: value.trim().to_string(),
+            },
+        );
+    }
+
+    /// Remove a macro definition
+    pub fn undefine(&mut self, name: &str) {
+        self.defines.remove(name);
+    }
+
+    /// Register in-memory source that `#include` resolves by name before touching the filesystem
+    pub fn add_virtual_file(&mut self, name: &str, code: &str) {
+        self.virtual_files.insert(name.to_string(), code.to_string());
+    }
+
+    /// Preprocess a shader file
+    pub fn process_file(&self, path: &str) -> Result<PreprocessedShader, PreprocessError> {
+        let code = std::fs::read_to_string(path).map_err(|e| PreprocessError {
+            message: format!("Failed to read shader: {}", e),
+            file: path.to_string(),
+            line: 0,
+        })?;
+        let id = canonical_id(Path::new(path));
+        let dir = Path::new(path).parent().map(Path::to_path_buf);
+        self.run(&code, &id, dir)
+    }
+
+    /// Preprocess shader source; `name` is used in the line map and error messages
+    pub fn process_str(&self, code: &str, name: &str) -> Result<PreprocessedShader, PreprocessError> {
+        self.run(code, name, None)
+    }
+
+    fn run(
+        &self,
+        code: &str,
+        name: &str,
+        dir: Option<PathBuf>,
+    ) -> Result<PreprocessedShader, PreprocessError> {
+        let mut state = RunState {
+            defines: self.defines.clone(),
+            once: HashSet::new(),
+            stack: Vec::new(),
+            code: String::new(),
+            line_map: Vec::new(),
+            dependencies: Vec::new(),
+            imports: Vec::new(),
+        };
+        self.process_source(&mut state, code, name, dir.as_deref())?;
+
+        let mut linked = Vec::new();
+        if !state.imports.is_empty() {
+            let result = stdlib::link(&state.code, &state.imports).map_err(|e| {
+                let origin = e
+                    .line
+                    .and_then(|line| line.checked_sub(1).and_then(|line| state.line_map.get(line as usize)))
+                    .cloned()
+                    .unwrap_or(SourceLine { file: name.to_string(), line: 0 });
+                error(&origin.file, origin.line, &e.message)
+            })?;
+            state.code = result.code;
+            state.line_map.extend(result.line_map);
+            linked = result.linked;
+        }
+
+        Ok(PreprocessedShader {
+            code: state.code,
+            line_map: state.line_map,
+            dependencies: state.dependencies,
+            linked,
+        })
+    }
+
+    fn process_source(
+        &self,
+        state: &mut RunState,
+        code: &str,
+        file: &str,
+        dir: Option<&Path>,
+    ) -> Result<(), PreprocessError> {
+        if state.stack.len() >= MAX_INCLUDE_DEPTH {
+            return Err(error(file, 0, "Include depth limit exceeded"));
+        }
+        state.stack.push(file.to_string());
+
+        let mut conditionals: Vec<Conditional> = Vec::new();
+        let lines: Vec<&str> = code.lines().collect();
+        let mut index = 0;
+
+        while index < lines.len() {
+            let line_number = index as u32 + 1;
+            let mut line = lines[index].to_string();
+            index += 1;
+
+            let active = conditionals.last().is_none_or(|c| c.active);
+            let trimmed = line.trim_start();
+
+            if let Some(directive) = trimmed.strip_prefix('#') {
+                // Backslash continues a directive on the next line
+                let mut directive = directive.to_string();
+                while directive.ends_with('\\') && index < lines.len() {
+                    directive.pop();
+                    directive.push(' ');
+                    directive.push_str(lines[index].trim());
+                    index += 1;
+                }
+
+                let directive = directive.trim();
+                let (name, rest) = match directive.find(|c: char| c.is_whitespace()) {
+                    Some(pos) => (&directive[..pos], directive[pos..].trim()),
+                    None => (directive, ""),
+                };
+
+                match name {
+                    "ifdef" | "ifndef" => {
+                        let defined = state.defines.contains_key(rest);
+                        let cond = active && (defined == (name == "ifdef"));
+                        conditionals.push(Conditional {
+                            parent_active: active,
+                            active: cond,
+                            taken: cond,
+                            seen_else: false,
+                        });
+                    }
+                    "if" => {
+                        let cond = active && self.evaluate(state, rest, file, line_number)? != 0;
+                        conditionals.push(Conditional {
+                            parent_active: active,
+                            active: cond,
+                            taken: cond,
+                            seen_else: false,
+                        });
+                    }
+                    "elif" => {
+                        let top = conditionals
+                            .last()
+                            .ok_or_else(|| error(file, line_number, "#elif without #if"))?;
+                        if top.seen_else {
+                            return Err(error(file, line_number, "#elif after #else"));
+                        }
+                        let eligible = top.parent_active && !top.taken;
+                        let cond = eligible && self.evaluate(state, rest, file, line_number)? != 0;
+                        let top = conditionals.last_mut().unwrap();
+                        top.active = cond;
+                        top.taken |= cond;
+                    }
+                    "else" => {
+                        let top = conditionals
+                            .last_mut()
+                            .ok_or_else(|| error(file, line_number, "#else without #if"))?;
+                        if top.seen_else {
+                            return Err(error(file, line_number, "Duplicate #else"));
+                        }
+                        top.seen_else = true;
+                        top.active = top.parent_active && !top.taken;
+                        top.taken = true;
+                    }
+                    "endif" => {
+                        if conditionals.pop().is_none() {
+                            return Err(error(file, line_number, "#endif without #if"));
+                        }
+                    }
+                    _ if !active => {}
+                    "define" => {
+                        let (macro_name, params, body) = parse_define(rest)
+                            .ok_or_else(|| error(file, line_number, "Malformed #define"))?;
+                        state.defines.insert(macro_name, MacroDef { params, body });
+                    }
+                    "undef" => {
+                        state.defines.remove(rest);
+                    }
+                    "include" => {
+                        self.include(state, rest, file, dir, line_number)?;
+                    }
+                    "import" => {
+                        let import = stdlib::parse_import(rest)
+                            .map_err(|message| error(file, line_number, &message))?;
+                        state.imports.push(import);
+                    }
+                    "pragma" => {
+                        if rest == "once" {
+                            state.once.insert(file.to_string());
+                        }
+                    }
+                    "error" => {
+                        return Err(error(file, line_number, &format!("#error {}", rest)));
+                    }
+                    _ => {
+                        return Err(error(
+                            file,
+                            line_number,
+                            &format!("Unknown preprocessor directive '#{}'", name),
+                        ));
+                    }
+                }
+                continue;
+            }
+
+            if !active {
+                continue;
+            }
+
+            if !state.defines.is_empty() {
+                line = expand_macros(&line, &state.defines, &mut Vec::new())
+                    .map_err(|m| error(file, line_number, &m))?;
+            }
+            state.code.push_str(&line);
+            state.code.push('\n');
+            state.line_map.push(SourceLine {
+                file: file.to_string(),
+                line: line_number,
+            });
+        }
+
+        if !conditionals.is_empty() {
+            return Err(error(file, lines.len() as u32, "Unterminated #if block"));
+        }
+
+        state.stack.pop();
+        Ok(())
+    }
+
+    fn include(
+        &self,
+        state: &mut RunState,
+        spec: &str,
+        file: &str,
+        dir: Option<&Path>,
+        line: u32,
+    ) -> Result<(), PreprocessError> {
+        let (target, quoted) = if let Some(inner) = spec.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+            (inner, true)
+        } else if let Some(inner) = spec.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+            (inner, false)
+        } else {
+            return Err(error(file, line, "#include expects \"file\" or <file>"));
+        };
+
+        let (id, code, include_dir) = if let Some(code) = self.virtual_files.get(target) {
+            (target.to_string(), code.clone(), None)
+        } else {
+            let path = self
+                .resolve_include(target, if quoted { dir } else { None })
+                .ok_or_else(|| error(file, line, &format!("Include '{}' not found", target)))?;
+            let code = std::fs::read_to_string(&path).map_err(|e| {
+                error(file, line, &format!("Failed to read include '{}': {}", target, e))
+            })?;
+            let id = canonical_id(&path);
+            if !state.dependencies.contains(&id) {
+                state.dependencies.push(id.clone());
+            }
+            (id, code, path.parent().map(Path::to_path_buf))
+        };
+
+        if state.once.contains(&id) {
+            return Ok(());
+        }
+        if state.stack.contains(&id) {
+            return Err(error(file, line, &format!("Circular include of '{}'", target)));
+        }
+
+        self.process_source(state, &code, &id, include_dir.as_deref())
+    }
+
+    fn resolve_include(&self, target: &str, dir: Option<&Path>) -> Option<PathBuf> {
+        dir.into_iter()
+            .chain(self.include_paths.iter().map(PathBuf::as_path))
+            .map(|base| base.join(target))
+            .find(|candidate| candidate.is_file())
+    }
+
+    fn evaluate(
+        &self,
+        state: &RunState,
+        expression: &str,
+        file: &str,
+        line: u32,
+    ) -> Result<i64, PreprocessError> {
+        let resolved = resolve_defined(expression, &state.defines);
+        let expanded = expand_macros(&resolved, &state.defines, &mut Vec::new())
+            .map_err(|m| error(file, line, &m))?;
+        let tokens = tokenize_expression(&expanded).map_err(|m| error(file, line, &m))?;
+        let mut parser = ExpressionParser { tokens, pos: 0 };
+        let value = parser.parse_or().map_err(|m| error(file, line, &m))?;
+        if parser.pos != parser.tokens.len() {
+            return Err(error(file, line, &format!("Unexpected tokens in #if '{}'", expression)));
+        }
+        Ok(value)
+    }
+}
+
+fn error(file: &str, line: u32, message: &str) -> PreprocessError {
+    PreprocessError {
+        message: message.to_string(),
+        file: file.to_string(),
+        line,
+    }
+}
+
+fn canonical_id(path: &Path) -> String {
+    std::fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Split `NAME(a, b)` into name and parameter list
+fn parse_macro_name(spec: &str) -> (String, Option<Vec<String>>) {
+    match spec.find('(') {
+        Some(open) => {
+            let params = spec[open + 1..]
+                .trim_end_matches(')')
+                .split(',')
+                .map(|p| p.trim().to_string())
+                .filter(|p| !p.is_empty())
+                .collect();
+            (spec[..open].trim().to_string(), Some(params))
+        }
+        None => (spec.trim().to_string(), None),
+    }
+}
+
+/// Parse the text after `#define`
+fn parse_define(rest: &str) -> Option<(String, Option<Vec<String>>, String)> {
+    let name_end = rest.find(|c: char| !is_ident_char(c)).unwrap_or(rest.len());
+    let name = &rest[..name_end];
+    if name.is_empty() || !name.starts_with(is_ident_start) {
+        return None;
+    }
+
+    let after = &rest[name_end..];
+    // Function-like only when '(' immediately follows the name
+    if let Some(params_part) = after.strip_prefix('(') {
+        let close = params_part.find(')')?;
+        let params = params_part[..close]
+            .split(',')
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+            .collect();
+        let body = params_part[close + 1..].trim().to_string();
+        Some((name.to_string(), Some(params), body))
+    } else {
+        Some((name.to_string(), None, after.trim().to_string()))
+    }
+}
+
+/// Replace `defined(NAME)` and `defined NAME` with 1 or 0
+fn resolve_defined(expression: &str, defines: &HashMap<String, MacroDef>) -> String {
+    let mut result = String::new();
+    let mut rest = expression;
+
+    while let Some(pos) = rest.find("defined") {
+        let before_ok = rest[..pos].chars().last().is_none_or(|c| !is_ident_char(c));
+        let after = &rest[pos + "defined".len()..];
+        let after_ok = after.chars().next().is_none_or(|c| !is_ident_char(c));
+        if !(before_ok && after_ok) {
+            result.push_str(&rest[..pos + "defined".len()]);
+            rest = after;
+            continue;
+        }
+
+        result.push_str(&rest[..pos]);
+        let trimmed = after.trim_start();
+        let (inner, remainder) = if let Some(paren) = trimmed.strip_prefix('(') {
+            match paren.find(')') {
+                Some(close) => (paren[..close].trim(), &paren[close + 1..]),
+                None => (paren.trim(), ""),
+            }
+        } else {
+            let end = trimmed.find(|c: char| !is_ident_char(c)).unwrap_or(trimmed.len());
+            (&trimmed[..end], &trimmed[end..])
+        };
+        result.push_str(if defines.contains_key(inner) { "1" } else { "0" });
+        rest = remainder;
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Expand macros in a line of code, skipping `//` comments
+fn expand_macros(
+    line: &str,
+    defines: &HashMap<String, MacroDef>,
+    active: &mut Vec<String>,
+) -> Result<String, String> {
+    if active.len() > MAX_EXPANSION_DEPTH {
+        return Err("Macro expansion too deep".to_string());
+    }
+
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::with_capacity(line.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c == '/' && chars.get(i + 1) == Some(&'/') {
+            out.extend(&chars[i..]);
+            break;
+        }
+
+        if c.is_ascii_digit() {
+            // Numbers such as 1e5 or 0x1fu must not be treated as identifiers
+            while i < chars.len() && (is_ident_char(chars[i]) || chars[i] == '.') {
+                out.push(chars[i]);
+                i += 1;
+            }
+            continue;
+        }
+
+        if !is_ident_start(c) {
+            out.push(c);
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < chars.len() && is_ident_char(chars[i]) {
+            i += 1;
+        }
+        let ident: String = chars[start..i].iter().collect();
+
+        let def = match defines.get(&ident) {
+            Some(def) if !active.contains(&ident) => def,
+            _ => {
+                out.push_str(&ident);
+                continue;
+            }
+        };
+
+        let replacement = match &def.params {
+            None => def.body.clone(),
+            Some(params) => {
+                let mut j = i;
+                while j < chars.len() && chars[j].is_whitespace() {
+                    j += 1;
+                }
+                if chars.get(j) != Some(&'(') {
+                    out.push_str(&ident);
+                    continue;
+                }
+                let (args, end) = collect_args(&chars, j)
+                    .ok_or_else(|| format!("Unterminated arguments for macro '{}'", ident))?;
+                if args.len() != params.len() && !(params.is_empty() && args.len() == 1 && args[0].is_empty()) {
+                    return Err(format!(
+                        "Macro '{}' expects {} arguments, got {}",
+                        ident,
+                        params.len(),
+                        args.len()
+                    ));
+                }
+                i = end;
+                let expanded_args = args
+                    .iter()
+                    .map(|a| expand_macros(a, defines, active))
+                    .collect::<Result<Vec<_>, _>>()?;
+                substitute_params(&def.body, params, &expanded_args)
+            }
+        };
+
+        active.push(ident);
+        let expanded = expand_macros(&replacement, defines, active)?;
+        active.pop();
+        out.push_str(&expanded);
+    }
+
+    Ok(out)
+}
+
+/// Collect comma-separated macro arguments starting at an opening parenthesis
+fn collect_args(chars: &[char], open: usize) -> Option<(Vec<String>, usize)> {
+    let mut depth = 0;
+    let mut args = Vec::new();
+    let mut current = String::new();
+
+    for (k, &c) in chars.iter().enumerate().skip(open) {
+        match c {
+            '(' => {
+                depth += 1;
+                if depth > 1 {
+                    current.push(c);
+                }
+            }
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    args.push(current.trim().to_string());
+                    return Some((args, k + 1));
+                }
+                current.push(c);
+            }
+            ',' if depth == 1 => {
+                args.push(current.trim().to_string());
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+
+    None
+}
+
+fn substitute_params(body: &str, params: &[String], args: &[String]) -> String {
+    let chars: Vec<char> = body.chars().collect();
+    let mut out = String::with_capacity(body.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        if is_ident_start(chars[i]) {
+            let start = i;
+            while i < chars.len() && is_ident_char(chars[i]) {
+                i += 1;
+            }
+            let ident: String = chars[start..i].iter().collect();
+            match params.iter().position(|p| *p == ident) {
+                Some(index) => out.push_str(args.get(index).map(String::as_str).unwrap_or("")),
+                None => out.push_str(&ident),
+            }
+        } else {
+            out.push(chars[i]);
+            i += 1;
+        }
+    }
+
+    out
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ExprToken {
+    Number(i64),
+    Op(&'static str),
+}
+
+fn tokenize_expression(expression: &str) -> Result<Vec<ExprToken>, String> {
+    const OPERATORS: [&str; 19] = [
+        "||", "&&", "==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "%", "!", "(", ")",
+        "&", "|", "^",
+    ];
+
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    'outer: while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_alphanumeric() {
+                i += 1;
+            }
+            let literal: String = chars[start..i].iter().collect();
+            let digits = literal.trim_end_matches(['u', 'i']);
+            let value = if let Some(hex) = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+                i64::from_str_radix(hex, 16)
+            } else {
+                digits.parse()
+            }
+            .map_err(|_| format!("Invalid number '{}' in #if", literal))?;
+            tokens.push(ExprToken::Number(value));
+            continue;
+        }
+
+        if is_ident_start(c) {
+            // Identifiers left after macro expansion evaluate to 0, as in C
+            while i < chars.len() && is_ident_char(chars[i]) {
+                i += 1;
+            }
+            tokens.push(ExprToken::Number(0));
+            continue;
+        }
+
+        for op in OPERATORS {
+            if chars[i..].iter().take(op.len()).copied().eq(op.chars()) {
+                tokens.push(ExprToken::Op(op));
+                i += op.len();
+                continue 'outer;
+            }
+        }
+
+        return Err(format!("Unexpected character '{}' in #if", c));
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent evaluator for `#if` expressions
+struct ExpressionParser {
+    tokens: Vec<ExprToken>,
+    pos: usize,
+}
+
+impl ExpressionParser {
+    fn eat(&mut self, op: &str) -> bool {
+        if matches!(self.tokens.get(self.pos), Some(ExprToken::Op(o)) if *o == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<i64, String> {
+        let mut value = self.parse_and()?;
+        while self.eat("||") {
+            let rhs = self.parse_and()?;
+            value = ((value != 0) || (rhs != 0)) as i64;
+        }
+        Ok(value)
+    }
+
+    fn parse_and(&mut self) -> Result<i64, String> {
+        let mut value = self.parse_bitwise()?;
+        while self.eat("&&") {
+            let rhs = self.parse_bitwise()?;
+            value = ((value != 0) && (rhs != 0)) as i64;
+        }
+        Ok(value)
+    }
+
+    fn parse_bitwise(&mut self) -> Result<i64, String> {
+        let mut value = self.parse_equality()?;
+        loop {
+            if self.eat("|") {
+                value |= self.parse_equality()?;
+            } else if self.eat("^") {
+                value ^= self.parse_equality()?;
+            } else if self.eat("&") {
+                value &= self.parse_equality()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn parse_equality(&mut self) -> Result<i64, String> {
+        let mut value = self.parse_relational()?;
+        loop {
+            if self.eat("==") {
+                value = (value == self.parse_relational()?) as i64;
+            } else if self.eat("!=") {
+                value = (value != self.parse_relational()?) as i64;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn parse_relational(&mut self) -> Result<i64, String> {
+        let mut value = self.parse_additive()?;
+        loop {
+            if self.eat("<=") {
+                value = (value <= self.parse_additive()?) as i64;
+            } else if self.eat(">=") {
+                value = (value >= self.parse_additive()?) as i64;
+            } else if self.eat("<") {
+                value = (value < self.parse_additive()?) as i64;
+            } else if self.eat(">") {
+                value = (value > self.parse_additive()?) as i64;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn parse_additive(&mut self) -> Result<i64, String> {
+        let mut value = self.parse_multiplicative()?;
+        loop {
+            if self.eat("+") {
+                value = value.wrapping_add(self.parse_multiplicative()?);
+            } else if self.eat("-") {
+                value = value.wrapping_sub(self.parse_multiplicative()?);
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<i64, String> {
+        let mut value = self.parse_unary()?;
+        loop {
+            if self.eat("*") {
+                value = value.wrapping_mul(self.parse_unary()?);
+            } else if self.eat("/") {
+                value = value
+                    .checked_div(self.parse_unary()?)
+                    .ok_or("Division by zero in #if")?;
+            } else if self.eat("%") {
+                value = value
+                    .checked_rem(self.parse_unary()?)
+                    .ok_or("Division by zero in #if")?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<i64, String> {
+        if self.eat("!") {
+            return Ok((self.parse_unary()? == 0) as i64);
+        }
+        if self.eat("-") {
+            return Ok(self.parse_unary()?.wrapping_neg());
+        }
+        if self.eat("(") {
+            let value = self.parse_or()?;
+            if !self.eat(")") {
+                return Err("Missing ')' in #if".to_string());
+            }
+            return Ok(value);
+        }
+        match self.tokens.get(self.pos) {
+            Some(ExprToken::Number(n)) => {
+                self.pos += 1;
+                Ok(*n)
+            }
+            _ => Err("Expected value in #if".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_define_and_function_macros() {
+        let mut pp = WgslPreprocessor::new();
+        pp.define("WG", "64");
+        let out = pp
+            .process_str(
+                "#define SQUARE(x) ((x) * (x))\n@compute @workgroup_size(WG)\nfn f() { let a = SQUARE(2.0); }",
+                "main.wgsl",
+            )
+            .unwrap();
+        assert!(out.code.contains("@workgroup_size(64)"));
+        assert!(out.code.contains("let a = ((2.0) * (2.0));"));
+        assert_eq!(out.map_line(1).unwrap().line, 2);
+    }
+
+    #[test]
+    fn test_conditionals() {
+        let mut pp = WgslPreprocessor::new();
+        pp.define("USE_BIAS", "1");
+        pp.define("LEVEL", "2");
+        let source = "#ifdef USE_BIAS\nbias\n#else\nno_bias\n#endif\n#if LEVEL > 1 && !defined(MISSING)\nhigh\n#elif LEVEL == 1\nlow\n#endif";
+        let out = pp.process_str(source, "main.wgsl").unwrap();
+        assert_eq!(out.code, "bias\nhigh\n");
+        assert_eq!(out.line_map[1].line, 7);
+
+        // Arithmetic wraps instead of overflowing
+        let out = pp.process_str("#if -(-9223372036854775807 - 1) < 0\nwrapped\n#endif", "main.wgsl").unwrap();
+        assert_eq!(out.code, "wrapped\n");
+    }
+
+    #[test]
+    fn test_virtual_include_with_pragma_once() {
+        let mut pp = WgslPreprocessor::new();
+        pp.add_virtual_file("common.wgsl", "#pragma once\nfn helper() -> f32 { return 1.0; }");
+        let out = pp
+            .process_str("#include <common.wgsl>\n#include \"common.wgsl\"\nfn main() {}", "main.wgsl")
+            .unwrap();
+        assert_eq!(out.code.matches("fn helper").count(), 1);
+        assert_eq!(out.line_map[0].file, "common.wgsl");
+        assert_eq!(out.line_map[1], SourceLine { file: "main.wgsl".to_string(), line: 3 });
+    }
+
+    #[test]
+    fn test_include_from_disk_tracks_dependencies() {
+        let dir = std::env::temp_dir().join("webgpu_x_pp_include_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("lib.wgsl"), "#ifndef LIB\n#define LIB\nconst PI = 3.14159;\n#endif").unwrap();
+        std::fs::write(dir.join("main.wgsl"), "#include \"lib.wgsl\"\n#include \"lib.wgsl\"\nfn main() {}").unwrap();
+
+        let pp = WgslPreprocessor::new();
+        let out = pp.process_file(dir.join("main.wgsl").to_str().unwrap()).unwrap();
+        assert_eq!(out.code.matches("const PI").count(), 1);
+        assert_eq!(out.dependencies.len(), 1);
+        assert!(out.dependencies[0].ends_with("lib.wgsl"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_errors_report_location() {
+        let pp = WgslPreprocessor::new();
+        let err = pp.process_str("fn a() {}\n#include \"missing.wgsl\"", "main.wgsl").unwrap_err();
+        assert_eq!(err.line, 2);
+
+        let err = pp.process_str("#ifdef X\n", "main.wgsl").unwrap_err();
+        assert!(err.message.contains("Unterminated"));
+    }
+
+    #[test]
+    fn test_validation_errors_map_to_original_line() {
+        let mut pp = WgslPreprocessor::new();
+        pp.add_virtual_file("bad.wgsl", "fn bad() -> f32 {\n    return 1u;\n}");
+        let out = pp.process_str("// header\n#include <bad.wgsl>\n", "main.wgsl").unwrap();
+        let err = out.validate().err().unwrap();
+        assert!(err
+            .diagnostics
+            .iter()
+            .any(|d| d.file.as_deref() == Some("bad.wgsl") && d.line == 2));
+    }
+}
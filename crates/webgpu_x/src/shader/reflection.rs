@@ -0,0 +1,503 @@
+//! WGSL parsing, validation, and reflection via naga
+//!
+//! Catches shader bugs before the driver sees them:
+//! - Parse and validate WGSL against the WebGPU core capabilities
+//! - Errors carry line/column spans and a code frame for each label
+//! - Reflection of entry points, resource bindings, struct layouts, and overrides
+
+use super::ShaderStage;
+use naga::proc::Layouter;
+use naga::valid::{Capabilities, ModuleInfo, ValidationFlags, Validator};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Stage of shader processing that failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WgslErrorKind {
+    Parse,
+    Validation,
+}
+
+/// A single labelled location inside the shader source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WgslDiagnostic {
+    pub message: String,
+    /// Original file when the source was preprocessed from several files
+    pub file: Option<String>,
+    /// 1-based line number (0 when the error has no location)
+    pub line: u32,
+    /// 1-based column in characters
+    pub column: u32,
+    /// 0-based byte offset into the source
+    pub offset: u32,
+    /// Length of the span in bytes
+    pub length: u32,
+    pub code_frame: String,
+}
+
+/// Parse or validation failure with source locations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WgslError {
+    pub kind: WgslErrorKind,
+    pub message: String,
+    pub diagnostics: Vec<WgslDiagnostic>,
+    /// Full human-readable report as rendered by naga
+    pub rendered: String,
+}
+
+impl fmt::Display for WgslError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.rendered.is_empty() {
+            write!(f, "WGSL {:?} error: {}", self.kind, self.message)
+        } else {
+            write!(f, "{}", self.rendered)
+        }
+    }
+}
+
+impl std::error::Error for WgslError {}
+
+/// Parsed and validated shader module
+pub struct ValidatedShader {
+    pub module: naga::Module,
+    pub info: ModuleInfo,
+}
+
+/// Validation outcome for FFI consumers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WgslValidationReport {
+    pub valid: bool,
+    pub error: Option<WgslError>,
+}
+
+/// Entry point information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryPointReflection {
+    pub name: String,
+    pub stage: ShaderStage,
+    /// Only present for compute entry points
+    pub workgroup_size: Option<[u32; 3]>,
+}
+
+/// A `@group/@binding` resource
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceReflection {
+    pub name: String,
+    pub group: u32,
+    pub binding: u32,
+    /// uniform, storage, texture, depth_texture, storage_texture, sampler,
+    /// comparison_sampler, or acceleration_structure
+    pub resource_type: String,
+    /// read, write, or read_write (empty for resources without access mode)
+    pub access: String,
+    pub type_name: String,
+    /// Size of the bound type for buffers (one element for runtime-sized arrays)
+    pub min_binding_size: Option<u64>,
+}
+
+/// Struct member layout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StructMemberReflection {
+    pub name: String,
+    pub type_name: String,
+    pub offset: u32,
+    pub size: u32,
+    pub alignment: u32,
+}
+
+/// Struct layout as computed by naga
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StructReflection {
+    pub name: String,
+    pub size: u32,
+    pub alignment: u32,
+    pub members: Vec<StructMemberReflection>,
+}
+
+/// Pipeline-overridable constant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverrideReflection {
+    pub name: String,
+    pub id: Option<u16>,
+    pub type_name: String,
+    /// Default value when the initializer is a literal
+    pub default_value: Option<f64>,
+    pub has_default: bool,
+}
+
+/// Complete reflection of a WGSL module
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShaderReflection {
+    pub entry_points: Vec<EntryPointReflection>,
+    pub resources: Vec<ResourceReflection>,
+    pub structs: Vec<StructReflection>,
+    pub overrides: Vec<OverrideReflection>,
+}
+
+/// Parse WGSL into a naga module
+pub fn parse_wgsl(source: &str) -> Result<naga::Module, WgslError> {
+    naga::front::wgsl::parse_str(source).map_err(|e| {
+        let diagnostics = e
+            .labels()
+            .map(|(span, label)| {
+                let label = if label.is_empty() { e.message() } else { label };
+                diagnostic_for_span(source, span, label)
+            })
+            .collect();
+
+        WgslError {
+            kind: WgslErrorKind::Parse,
+            message: e.message().to_string(),
+            diagnostics,
+            rendered: e.emit_to_string(source),
+        }
+    })
+}
+
+/// Parse and validate WGSL against WebGPU core capabilities
+pub fn validate_wgsl(source: &str) -> Result<ValidatedShader, WgslError> {
+    validate_wgsl_with_capabilities(source, Capabilities::default())
+}
+
+/// Parse and validate WGSL with explicit capabilities (e.g. `SHADER_FLOAT16`)
+pub fn validate_wgsl_with_capabilities(
+    source: &str,
+    capabilities: Capabilities,
+) -> Result<ValidatedShader, WgslError> {
+    let module = parse_wgsl(source)?;
+    let mut validator = Validator::new(ValidationFlags::all(), capabilities);
+
+    match validator.validate(&module) {
+        Ok(info) => Ok(ValidatedShader { module, info }),
+        Err(e) => {
+            let mut message = e.as_inner().to_string();
+            let mut cause = std::error::Error::source(e.as_inner());
+            while let Some(inner) = cause {
+                message.push_str(": ");
+                message.push_str(&inner.to_string());
+                cause = inner.source();
+            }
+
+            let diagnostics = e
+                .spans()
+                .map(|(span, label)| diagnostic_for_span(source, *span, label))
+                .collect();
+
+            Err(WgslError {
+                kind: WgslErrorKind::Validation,
+                message,
+                diagnostics,
+                rendered: e.emit_to_string(source),
+            })
+        }
+    }
+}
+
+/// Validate WGSL and summarize the outcome
+pub fn wgsl_validation_report(source: &str) -> WgslValidationReport {
+    match validate_wgsl(source) {
+        Ok(_) => WgslValidationReport {
+            valid: true,
+            error: None,
+        },
+        Err(e) => WgslValidationReport {
+            valid: false,
+            error: Some(e),
+        },
+    }
+}
+
+/// Validate WGSL and reflect its interface
+pub fn reflect_wgsl(source: &str) -> Result<ShaderReflection, WgslError> {
+    let shader = validate_wgsl(source)?;
+    reflect_module(&shader.module)
+}
+
+/// Reflect an already parsed module
+pub fn reflect_module(module: &naga::Module) -> Result<ShaderReflection, WgslError> {
+    let gctx = module.to_ctx();
+    let mut layouter = Layouter::default();
+    layouter.update(gctx).map_err(|e| WgslError {
+        kind: WgslErrorKind::Validation,
+        message: format!("Failed to compute type layouts: {}", e),
+        diagnostics: Vec::new(),
+        rendered: String::new(),
+    })?;
+
+    let entry_points = module
+        .entry_points
+        .iter()
+        .map(|ep| EntryPointReflection {
+            name: ep.name.clone(),
+            stage: convert_stage(ep.stage),
+            workgroup_size: match ep.stage {
+                naga::ShaderStage::Compute => Some(ep.workgroup_size),
+                _ => None,
+            },
+        })
+        .collect();
+
+    let mut resources: Vec<ResourceReflection> = module
+        .global_variables
+        .iter()
+        .filter_map(|(_, var)| {
+            let binding = var.binding.as_ref()?;
+            let (resource_type, access) = classify_resource(module, var);
+            let min_binding_size = match var.space {
+                naga::AddressSpace::Uniform | naga::AddressSpace::Storage { .. } => {
+                    Some(layouter[var.ty].size as u64)
+                }
+                _ => None,
+            };
+
+            Some(ResourceReflection {
+                name: var.name.clone().unwrap_or_default(),
+                group: binding.group,
+                binding: binding.binding,
+                resource_type,
+                access,
+                type_name: var.ty.to_wgsl(&gctx),
+                min_binding_size,
+            })
+        })
+        .collect();
+    resources.sort_by_key(|r| (r.group, r.binding));
+
+    let structs = module
+        .types
+        .iter()
+        .filter_map(|(handle, ty)| match ty.inner {
+            naga::TypeInner::Struct { ref members, span } => Some(StructReflection {
+                name: ty.name.clone().unwrap_or_default(),
+                size: span,
+                alignment: alignment_value(layouter[handle].alignment),
+                members: members
+                    .iter()
+                    .map(|member| StructMemberReflection {
+                        name: member.name.clone().unwrap_or_default(),
+                        type_name: member.ty.to_wgsl(&gctx),
+                        offset: member.offset,
+                        size: layouter[member.ty].size,
+                        alignment: alignment_value(layouter[member.ty].alignment),
+                    })
+                    .collect(),
+            }),
+            _ => None,
+        })
+        .collect();
+
+    let overrides = module
+        .overrides
+        .iter()
+        .map(|(_, o)| {
+            let default_value = o.init.and_then(|init| match module.global_expressions[init] {
+                naga::Expression::Literal(literal) => literal_value(literal),
+                _ => None,
+            });
+
+            OverrideReflection {
+                name: o.name.clone().unwrap_or_default(),
+                id: o.id,
+                type_name: o.ty.to_wgsl(&gctx),
+                default_value,
+                has_default: o.init.is_some(),
+            }
+        })
+        .collect();
+
+    Ok(ShaderReflection {
+        entry_points,
+        resources,
+        structs,
+        overrides,
+    })
+}
+
+pub(crate) fn convert_stage(stage: naga::ShaderStage) -> ShaderStage {
+    match stage {
+        naga::ShaderStage::Vertex => ShaderStage::Vertex,
+        naga::ShaderStage::Fragment => ShaderStage::Fragment,
+        naga::ShaderStage::Compute => ShaderStage::Compute,
+    }
+}
+
+fn classify_resource(module: &naga::Module, var: &naga::GlobalVariable) -> (String, String) {
+    use naga::{AddressSpace, ImageClass, StorageAccess, TypeInner};
+
+    let access_name = |access: StorageAccess| {
+        if access.contains(StorageAccess::LOAD | StorageAccess::STORE) {
+            "read_write"
+        } else if access.contains(StorageAccess::STORE) {
+            "write"
+        } else {
+            "read"
+        }
+    };
+
+    let (kind, access) = match var.space {
+        AddressSpace::Uniform => ("uniform", ""),
+        AddressSpace::Storage { access } => ("storage", access_name(access)),
+        _ => {
+            let mut inner = &module.types[var.ty].inner;
+            if let TypeInner::BindingArray { base, .. } = *inner {
+                inner = &module.types[base].inner;
+            }
+            match *inner {
+                TypeInner::Image { class, .. } => match class {
+                    ImageClass::Sampled { .. } => ("texture", ""),
+                    ImageClass::Depth { .. } => ("depth_texture", ""),
+                    ImageClass::Storage { access, .. } => ("storage_texture", access_name(access)),
+                },
+                TypeInner::Sampler { comparison: true } => ("comparison_sampler", ""),
+                TypeInner::Sampler { comparison: false } => ("sampler", ""),
+                TypeInner::AccelerationStructure => ("acceleration_structure", ""),
+                _ => ("handle", ""),
+            }
+        }
+    };
+
+    (kind.to_string(), access.to_string())
+}
+
+pub(crate) fn alignment_value(alignment: naga::proc::Alignment) -> u32 {
+    alignment.round_up(1)
+}
+
+fn literal_value(literal: naga::Literal) -> Option<f64> {
+    match literal {
+        naga::Literal::F64(v) | naga::Literal::AbstractFloat(v) => Some(v),
+        naga::Literal::F32(v) => Some(v as f64),
+        naga::Literal::U32(v) => Some(v as f64),
+        naga::Literal::I32(v) => Some(v as f64),
+        naga::Literal::U64(v) => Some(v as f64),
+        naga::Literal::I64(v) | naga::Literal::AbstractInt(v) => Some(v as f64),
+        naga::Literal::Bool(v) => Some(if v { 1.0 } else { 0.0 }),
+    }
+}
+
+pub(crate) fn diagnostic_for_span(source: &str, span: naga::Span, message: &str) -> WgslDiagnostic {
+    if !span.is_defined() {
+        return WgslDiagnostic {
+            message: message.to_string(),
+            file: None,
+            line: 0,
+            column: 0,
+            offset: 0,
+            length: 0,
+            code_frame: String::new(),
+        };
+    }
+
+    let location = span.location(source);
+    let line_text = source
+        .lines()
+        .nth(location.line_number.saturating_sub(1) as usize)
+        .unwrap_or("");
+
+    // naga reports byte columns; convert to characters for display
+    let byte_column = (location.line_position.saturating_sub(1) as usize).min(line_text.len());
+    let prefix = line_text.get(..byte_column).unwrap_or(line_text);
+    let column = prefix.chars().count() as u32 + 1;
+    let span_end = (byte_column + location.length as usize).min(line_text.len());
+    let underline = line_text
+        .get(byte_column..span_end)
+        .map(|s| s.chars().count())
+        .unwrap_or(0)
+        .max(1);
+
+    WgslDiagnostic {
+        message: message.to_string(),
+        file: None,
+        line: location.line_number,
+        column,
+        offset: location.offset,
+        length: location.length,
+        code_frame: code_frame(line_text, location.line_number, column, underline),
+    }
+}
+
+/// Render a single source line with a caret underline
+pub(crate) fn code_frame(line_text: &str, line: u32, column: u32, underline: usize) -> String {
+    let gutter = line.to_string().len();
+    format!(
+        "{:>gutter$} |\n{} | {}\n{:>gutter$} | {}{}",
+        "",
+        line,
+        line_text,
+        "",
+        " ".repeat(column.saturating_sub(1) as usize),
+        "^".repeat(underline),
+        gutter = gutter
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHADER: &str = r#"
+struct Params {
+    scale: vec3<f32>,
+    bias: f32,
+}
+
+override block_size: u32 = 64u;
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> input: array<f32>;
+@group(0) @binding(2) var<storage, read_write> output: array<f32>;
+@group(1) @binding(0) var tex: texture_2d<f32>;
+@group(1) @binding(1) var samp: sampler;
+
+@compute @workgroup_size(64, 1, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    output[id.x] = input[id.x] * params.scale.x + params.bias + f32(block_size);
+}
+"#;
+
+    #[test]
+    fn test_reflect_entry_points_and_resources() {
+        let reflection = reflect_wgsl(SHADER).unwrap();
+
+        assert_eq!(reflection.entry_points.len(), 1);
+        assert_eq!(reflection.entry_points[0].stage, ShaderStage::Compute);
+        assert_eq!(reflection.entry_points[0].workgroup_size, Some([64, 1, 1]));
+
+        assert_eq!(reflection.resources.len(), 5);
+        assert_eq!(reflection.resources[0].resource_type, "uniform");
+        assert_eq!(reflection.resources[0].min_binding_size, Some(16));
+        assert_eq!(reflection.resources[1].access, "read");
+        assert_eq!(reflection.resources[2].access, "read_write");
+        assert_eq!(reflection.resources[3].resource_type, "texture");
+        assert_eq!(reflection.resources[4].resource_type, "sampler");
+    }
+
+    #[test]
+    fn test_reflect_struct_layout_and_overrides() {
+        let reflection = reflect_wgsl(SHADER).unwrap();
+
+        let params = &reflection.structs[0];
+        assert_eq!(params.name, "Params");
+        assert_eq!(params.size, 16);
+        assert_eq!(params.members[1].offset, 12);
+
+        assert_eq!(reflection.overrides[0].name, "block_size");
+        assert_eq!(reflection.overrides[0].default_value, Some(64.0));
+    }
+
+    #[test]
+    fn test_parse_error_has_location() {
+        let err = validate_wgsl("fn main() {\n    let x = ;\n}").err().unwrap();
+        assert_eq!(err.kind, WgslErrorKind::Parse);
+        assert_eq!(err.diagnostics[0].line, 2);
+        assert!(err.diagnostics[0].code_frame.contains("let x = ;"));
+    }
+
+    #[test]
+    fn test_validation_error_has_location() {
+        let err = validate_wgsl("fn f() -> f32 {\n    return 1u;\n}").err().unwrap();
+        assert_eq!(err.kind, WgslErrorKind::Validation);
+        assert!(err.diagnostics.iter().any(|d| d.line == 2));
+        assert!(!err.rendered.is_empty());
+    }
+}
@@ -0,0 +1,657 @@
+//! WGSL standard library
+//!
+//! Versioned WGSL modules shipped with webgpu_x and linked by the preprocessor:
+//! - `#import std::color` makes `color::luminance(c)` available (`as name` renames the prefix)
+//! - `#import std::random::{pcg, rand_f32}` makes the listed functions available unqualified
+//! - `#import std::color@1` (or `@1.0`) fails unless the module is compatible with that version
+//!
+//! Only the declarations a shader references (and what they reference in turn) are
+//! appended to the output. Library names are emitted as `std_<module>_<name>`, so they
+//! cannot collide with each other or with the shader's own declarations.
+
+use super::minify::{lex, TokenKind};
+use super::preprocessor::SourceLine;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+/// Version of the library as a whole
+pub const STDLIB_VERSION: &str = "1.0.0";
+
+struct StdModule {
+    name: &'static str,
+    version: (u32, u32),
+    source: &'static str,
+}
+
+const MODULES: &[StdModule] = &[
+    StdModule {
+        name: "color",
+        version: (1, 0),
+        source: include_str!("stdlib/color.wgsl"),
+    },
+    StdModule {
+        name: "math",
+        version: (1, 0),
+        source: include_str!("stdlib/math.wgsl"),
+    },
+    StdModule {
+        name: "matrix",
+        version: (1, 0),
+        source: include_str!("stdlib/matrix.wgsl"),
+    },
+    StdModule {
+        name: "nn",
+        version: (1, 0),
+        source: include_str!("stdlib/nn.wgsl"),
+    },
+    StdModule {
+        name: "noise",
+        version: (1, 0),
+        source: include_str!("stdlib/noise.wgsl"),
+    },
+    StdModule {
+        name: "pack",
+        version: (1, 0),
+        source: include_str!("stdlib/pack.wgsl"),
+    },
+    StdModule {
+        name: "random",
+        version: (1, 0),
+        source: include_str!("stdlib/random.wgsl"),
+    },
+];
+
+/// Public description of a library module
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StdModuleInfo {
+    pub name: String,
+    pub version: String,
+    /// Functions and constants, in declaration order
+    pub exports: Vec<String>,
+}
+
+/// All library modules
+pub fn stdlib_modules() -> Vec<StdModuleInfo> {
+    MODULES
+        .iter()
+        .map(|module| StdModuleInfo {
+            name: module.name.to_string(),
+            version: format!("{}.{}", module.version.0, module.version.1),
+            exports: declarations(module).into_iter().map(|d| d.name).collect(),
+        })
+        .collect()
+}
+
+/// WGSL source of a library module
+pub fn stdlib_source(module: &str) -> Option<&'static str> {
+    find_module(module).map(|m| m.source)
+}
+
+fn find_module(name: &str) -> Option<&'static StdModule> {
+    MODULES.iter().find(|m| m.name == name)
+}
+
+fn mangle(module: &str, name: &str) -> String {
+    format!("std_{}_{}", module, name)
+}
+
+/// Linking failure; `line` is a 1-based line of the preprocessed code when known
+#[derive(Debug, Clone)]
+pub(crate) struct StdlibError {
+    pub(crate) message: String,
+    pub(crate) line: Option<u32>,
+}
+
+impl fmt::Display for StdlibError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// One `#import` directive
+#[derive(Debug, Clone)]
+pub(crate) struct StdImport {
+    module: &'static str,
+    /// Prefix for qualified calls
+    alias: String,
+    /// Functions imported unqualified
+    names: Vec<String>,
+}
+
+/// Parse the text after `#import`
+pub(crate) fn parse_import(spec: &str) -> Result<StdImport, String> {
+    let path = spec
+        .trim()
+        .strip_prefix("std::")
+        .ok_or_else(|| format!("Only std:: modules can be imported, got '{}'", spec.trim()))?;
+
+    let (path, names) = match path.find("::{") {
+        Some(pos) => {
+            let list = path[pos + 3..]
+                .strip_suffix('}')
+                .ok_or_else(|| format!("Unterminated import list in '{}'", spec.trim()))?;
+            let names: Vec<String> = list
+                .split(',')
+                .map(str::trim)
+                .filter(|n| !n.is_empty())
+                .map(str::to_string)
+                .collect();
+            (&path[..pos], Some(names))
+        }
+        None => (path, None),
+    };
+
+    let (path, alias) = match path.split_once(" as ") {
+        Some((path, alias)) if names.is_none() => (path.trim(), Some(alias.trim().to_string())),
+        Some(_) => return Err("'as' cannot be combined with an import list".to_string()),
+        None => (path.trim(), None),
+    };
+
+    let (name, requested) = match path.split_once('@') {
+        Some((name, version)) => (name, Some(version)),
+        None => (path, None),
+    };
+    let module = find_module(name).ok_or_else(|| {
+        let known: Vec<&str> = MODULES.iter().map(|m| m.name).collect();
+        format!("Unknown module 'std::{}' (available: {})", name, known.join(", "))
+    })?;
+
+    if let Some(requested) = requested {
+        let mut parts = requested.split('.');
+        let major: Option<u32> = parts.next().and_then(|p| p.parse().ok());
+        let minor: Option<u32> = match parts.next() {
+            Some(p) => Some(p.parse().map_err(|_| format!("Invalid version '{}'", requested))?),
+            None => None,
+        };
+        let Some(major) = major else {
+            return Err(format!("Invalid version '{}'", requested));
+        };
+        if major != module.version.0 || minor.is_some_and(|m| m > module.version.1) {
+            return Err(format!(
+                "std::{} is version {}.{}, which is not compatible with {}",
+                module.name, module.version.0, module.version.1, requested
+            ));
+        }
+    }
+
+    if let Some(names) = &names {
+        let exports: Vec<String> = declarations(module).into_iter().map(|d| d.name).collect();
+        if let Some(missing) = names.iter().find(|n| !exports.contains(n)) {
+            return Err(format!("std::{} has no function '{}'", module.name, missing));
+        }
+    }
+
+    Ok(StdImport {
+        module: module.name,
+        alias: alias.unwrap_or_else(|| module.name.to_string()),
+        names: names.unwrap_or_default(),
+    })
+}
+
+/// A top-level declaration of a library module
+struct Declaration {
+    name: String,
+    /// Text including the comment directly above it
+    text: String,
+    /// 1-based line of the first line of `text`
+    line: u32,
+}
+
+/// Split a module into declarations
+///
+/// Library sources keep every declaration at column 0; functions and structs end with a
+/// `}` line at column 0, constants and aliases with a line ending in `;`.
+fn declarations(module: &StdModule) -> Vec<Declaration> {
+    let mut result = Vec::new();
+    let mut comment: Vec<&str> = Vec::new();
+    let mut lines = module.source.lines().enumerate();
+
+    while let Some((index, line)) = lines.next() {
+        if line.starts_with("//") {
+            comment.push(line);
+            continue;
+        }
+        let keyword = ["fn ", "const ", "struct ", "alias "]
+            .into_iter()
+            .find(|k| line.starts_with(k));
+        let Some(keyword) = keyword else {
+            comment.clear();
+            continue;
+        };
+
+        let name: String = line[keyword.len()..]
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_')
+            .collect();
+        let mut text: Vec<&str> = std::mem::take(&mut comment);
+        let start = (index + 1 - text.len()) as u32;
+        text.push(line);
+        let block = matches!(keyword, "fn " | "struct ");
+        if block && line != "}" {
+            for (_, next) in lines.by_ref() {
+                text.push(next);
+                if next == "}" {
+                    break;
+                }
+            }
+        } else if !block && !line.trim_end().ends_with(';') {
+            for (_, next) in lines.by_ref() {
+                text.push(next);
+                if next.trim_end().ends_with(';') {
+                    break;
+                }
+            }
+        }
+
+        result.push(Declaration {
+            name,
+            text: text.join("\n"),
+            line: start,
+        });
+    }
+    result
+}
+
+/// Rewrite library references in `code`, collecting the mangled names used
+///
+/// `qualified` maps a prefix to a module, `unqualified` maps bare names to mangled ones.
+/// Comments, member accesses (`.name`), member and parameter names, and numeric
+/// literals are left alone.
+fn rewrite(
+    code: &str,
+    qualified: &HashMap<String, &'static str>,
+    unqualified: &HashMap<String, String>,
+    referenced: &mut BTreeSet<String>,
+) -> Result<String, StdlibError> {
+    let chars: Vec<char> = code.chars().collect();
+    let mut out = String::with_capacity(code.len());
+    let mut line = 1u32;
+    let mut i = 0;
+
+    let is_ident_start = |c: char| c.is_alphabetic() || c == '_';
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                out.push(chars[i]);
+                i += 1;
+            }
+            continue;
+        }
+        if c == '/' && chars.get(i + 1) == Some(&'*') {
+            let mut depth = 0;
+            while i < chars.len() {
+                if chars[i] == '/' && chars.get(i + 1) == Some(&'*') {
+                    depth += 1;
+                    out.push_str("/*");
+                    i += 2;
+                } else if chars[i] == '*' && chars.get(i + 1) == Some(&'/') {
+                    depth -= 1;
+                    out.push_str("*/");
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    if chars[i] == '\n' {
+                        line += 1;
+                    }
+                    out.push(chars[i]);
+                    i += 1;
+                }
+            }
+            continue;
+        }
+        if c.is_ascii_digit() {
+            while i < chars.len() && (is_ident(chars[i]) || chars[i] == '.') {
+                out.push(chars[i]);
+                i += 1;
+            }
+            continue;
+        }
+        if !is_ident_start(c) {
+            if c == '\n' {
+                line += 1;
+            }
+            out.push(c);
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < chars.len() && is_ident(chars[i]) {
+            i += 1;
+        }
+        let word: String = chars[start..i].iter().collect();
+        let previous = chars[..start].iter().rev().find(|c| !c.is_whitespace()).copied();
+        let member = previous == Some('.');
+
+        // prefix::name
+        if chars.get(i) == Some(&':') && chars.get(i + 1) == Some(&':') {
+            if let Some(module) = qualified.get(&word) {
+                let name_start = i + 2;
+                let mut end = name_start;
+                while end < chars.len() && is_ident(chars[end]) {
+                    end += 1;
+                }
+                let name: String = chars[name_start..end].iter().collect();
+                let exists = find_module(module)
+                    .is_some_and(|m| declarations(m).iter().any(|d| d.name == name));
+                if !exists {
+                    return Err(StdlibError {
+                        message: format!("std::{} has no function '{}'", module, name),
+                        line: Some(line),
+                    });
+                }
+                let mangled = mangle(module, &name);
+                out.push_str(&mangled);
+                referenced.insert(mangled);
+                i = end;
+                continue;
+            }
+        }
+
+        // Struct members and parameters named like an import: `name: type`
+        let mut next = i;
+        while next < chars.len() && chars[next].is_whitespace() {
+            next += 1;
+        }
+        let field = matches!(previous, Some('{' | '(' | ','))
+            && chars.get(next) == Some(&':')
+            && chars.get(next + 1) != Some(&':');
+
+        match unqualified.get(&word) {
+            Some(mangled) if !member && !field => {
+                out.push_str(mangled);
+                referenced.insert(mangled.clone());
+            }
+            _ => out.push_str(&word),
+        }
+    }
+
+    Ok(out)
+}
+
+/// Names declared at any scope by `fn`, `const`, `struct`, `alias`, `var`, `let` or `override`
+fn declared_names(code: &str) -> BTreeSet<String> {
+    let Ok(tokens) = lex(code, false) else {
+        return BTreeSet::new();
+    };
+    let mut names = BTreeSet::new();
+    let mut i = 0;
+    while i + 1 < tokens.len() {
+        if matches!(tokens[i].text.as_str(), "fn" | "const" | "struct" | "alias" | "let" | "override" | "var") {
+            // Skip `var<storage, read>`
+            let mut j = i + 1;
+            if tokens[j].text == "<" {
+                while j < tokens.len() && tokens[j].text != ">" {
+                    j += 1;
+                }
+                j += 1;
+            }
+            if let Some(token) = tokens.get(j).filter(|t| t.kind == TokenKind::Ident) {
+                names.insert(token.text.clone());
+            }
+        }
+        i += 1;
+    }
+    names
+}
+
+/// Shader code with the referenced library declarations appended
+pub(crate) struct LinkedCode {
+    pub(crate) code: String,
+    /// Origin of every appended line
+    pub(crate) line_map: Vec<SourceLine>,
+    /// Mangled names that were linked
+    pub(crate) linked: Vec<String>,
+}
+
+/// Resolve library references in preprocessed code and append what they need
+pub(crate) fn link(code: &str, imports: &[StdImport]) -> Result<LinkedCode, StdlibError> {
+    let mut qualified: HashMap<String, &'static str> = HashMap::new();
+    let mut unqualified: HashMap<String, String> = HashMap::new();
+    for import in imports {
+        if import.names.is_empty() {
+            if let Some(previous) = qualified.insert(import.alias.clone(), import.module) {
+                if previous != import.module {
+                    return Err(StdlibError {
+                        message: format!(
+                            "'{}' refers to both std::{} and std::{}",
+                            import.alias, previous, import.module
+                        ),
+                        line: None,
+                    });
+                }
+            }
+        }
+        for name in &import.names {
+            let mangled = mangle(import.module, name);
+            if let Some(previous) = unqualified.insert(name.clone(), mangled.clone()) {
+                if previous != mangled {
+                    return Err(StdlibError {
+                        message: format!("'{}' is imported from two modules", name),
+                        line: None,
+                    });
+                }
+            }
+        }
+    }
+
+    let declared = declared_names(code);
+    if let Some(clash) = unqualified.keys().find(|name| declared.contains(*name)) {
+        return Err(StdlibError {
+            message: format!(
+                "'{}' is imported from the standard library and also declared in the shader",
+                clash
+            ),
+            line: None,
+        });
+    }
+
+    let mut referenced = BTreeSet::new();
+    let user_code = rewrite(code, &qualified, &unqualified, &mut referenced)?;
+    if let Some(clash) = referenced.iter().find(|name| declared.contains(*name)) {
+        return Err(StdlibError {
+            message: format!("'{}' is reserved for the standard library", clash),
+            line: None,
+        });
+    }
+
+    // Transitively pull in what the referenced declarations use
+    let mut linked: BTreeSet<String> = BTreeSet::new();
+    let mut bodies: HashMap<String, (usize, usize, String, u32)> = HashMap::new();
+    let mut pending: Vec<String> = referenced.into_iter().collect();
+    while let Some(mangled) = pending.pop() {
+        if !linked.insert(mangled.clone()) {
+            continue;
+        }
+        let (module_index, module) = MODULES
+            .iter()
+            .enumerate()
+            .find(|(_, m)| mangled.starts_with(&format!("std_{}_", m.name)))
+            .expect("mangled names come from known modules");
+        let decls = declarations(module);
+        let (decl_index, decl) = decls
+            .iter()
+            .enumerate()
+            .find(|(_, d)| mangle(module.name, &d.name) == mangled)
+            .expect("mangled names come from module declarations");
+
+        // Inside a module, sibling declarations are referenced unqualified and other
+        // modules by their own name
+        let siblings: HashMap<String, String> = decls
+            .iter()
+            .map(|d| (d.name.clone(), mangle(module.name, &d.name)))
+            .collect();
+        let modules: HashMap<String, &'static str> = MODULES.iter().map(|m| (m.name.to_string(), m.name)).collect();
+        let mut uses = BTreeSet::new();
+        let text = rewrite(&decl.text, &modules, &siblings, &mut uses).map_err(|e| StdlibError {
+            message: format!("std::{}: {}", module.name, e.message),
+            line: None,
+        })?;
+        pending.extend(uses.into_iter().filter(|u| !linked.contains(u)));
+        bodies.insert(mangled, (module_index, decl_index, text, decl.line));
+    }
+
+    // Library declarations in module order, each module under a version comment
+    let mut ordered: Vec<(usize, usize, String, u32)> = bodies.into_values().collect();
+    ordered.sort_by_key(|(module, decl, _, _)| (*module, *decl));
+
+    let mut code = user_code;
+    if !code.is_empty() && !code.ends_with('\n') {
+        code.push('\n');
+    }
+    let mut line_map = Vec::new();
+    let mut current_module = None;
+    for (module_index, _, text, line) in &ordered {
+        let module = &MODULES[*module_index];
+        let file = format!("std::{}", module.name);
+        if current_module != Some(*module_index) {
+            current_module = Some(*module_index);
+            code.push_str(&format!("\n// std::{} {}.{}\n", module.name, module.version.0, module.version.1));
+            line_map.push(SourceLine { file: file.clone(), line: 0 });
+            line_map.push(SourceLine { file: file.clone(), line: 0 });
+        } else {
+            code.push('\n');
+            line_map.push(SourceLine { file: file.clone(), line: 0 });
+        }
+        for (offset, text_line) in text.lines().enumerate() {
+            code.push_str(text_line);
+            code.push('\n');
+            line_map.push(SourceLine {
+                file: file.clone(),
+                line: line + offset as u32,
+            });
+        }
+    }
+
+    let mut linked: Vec<String> = linked.into_iter().collect();
+    linked.sort();
+    Ok(LinkedCode { code, line_map, linked })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shader::preprocessor::WgslPreprocessor;
+    use crate::shader::reflection::validate_wgsl;
+
+    /// A compute shader calling every export of a module with zero-valued arguments
+    fn module_probe(module: &StdModule) -> String {
+        let mut body = String::new();
+        for decl in declarations(module) {
+            let header = decl.text.lines().find(|l| !l.starts_with("//")).unwrap();
+            if !header.starts_with("fn ") {
+                body.push_str(&format!("    _ = {}::{};\n", module.name, decl.name));
+                continue;
+            }
+            let params = &header[header.find('(').unwrap() + 1..header.find(')').unwrap()];
+            let mut depth = 0;
+            let params: Vec<&str> = params
+                .split(|c| {
+                    match c {
+                        '<' => depth += 1,
+                        '>' => depth -= 1,
+                        _ => {}
+                    }
+                    c == ',' && depth == 0
+                })
+                .collect();
+            let args: Vec<String> = params
+                .into_iter()
+                .filter(|p| !p.trim().is_empty())
+                .map(|p| {
+                    let ty = p.split_once(':').unwrap().1.trim();
+                    if ty.starts_with("ptr<") {
+                        "&state".to_string()
+                    } else {
+                        format!("{}()", ty)
+                    }
+                })
+                .collect();
+            body.push_str(&format!("    _ = {}::{}({});\n", module.name, decl.name, args.join(", ")));
+        }
+        format!(
+            "#import std::{}\n@compute @workgroup_size(1)\nfn main() {{\n    var state = 1u;\n{}}}\n",
+            module.name, body
+        )
+    }
+
+    #[test]
+    fn test_every_module_validates() {
+        let preprocessor = WgslPreprocessor::new();
+        for module in MODULES {
+            let out = preprocessor.process_str(&module_probe(module), "probe.wgsl").unwrap();
+            assert_eq!(out.code.lines().count(), out.line_map.len());
+            validate_wgsl(&out.code).unwrap_or_else(|e| panic!("std::{}: {}\n{}", module.name, e, out.code));
+        }
+    }
+
+    #[test]
+    fn test_links_only_referenced_declarations() {
+        let code = "#import std::noise\nfn main() -> f32 { return noise::fbm2(vec2<f32>(1.0), 4u); }\n";
+        let out = WgslPreprocessor::new().process_str(code, "main.wgsl").unwrap();
+        assert!(out.linked.contains(&"std_noise_fbm2".to_string()));
+        assert!(out.linked.contains(&"std_random_pcg".to_string()));
+        assert!(!out.linked.contains(&"std_random_wang_hash".to_string()));
+        assert!(!out.linked.contains(&"std_color_luminance".to_string()));
+        assert!(!out.code.contains("noise::"));
+        assert!(out.code.contains("// std::noise 1.0"));
+        validate_wgsl(&out.code).unwrap();
+
+        let fbm = out.code.lines().position(|l| l.starts_with("fn std_noise_fbm2")).unwrap();
+        assert_eq!(out.line_map[fbm].file, "std::noise");
+    }
+
+    #[test]
+    fn test_unqualified_imports_and_aliases() {
+        let code = "#import std::random::{pcg}\n#import std::color as c\n\
+                    fn main(v: vec3<f32>) -> f32 { let x = pcg(1u); return c::luminance(v) + f32(x); }\n";
+        let out = WgslPreprocessor::new().process_str(code, "main.wgsl").unwrap();
+        assert!(out.code.contains("std_random_pcg(1u)"));
+        assert!(out.code.contains("std_color_luminance(v)"));
+        validate_wgsl(&out.code).unwrap();
+
+        // Member accesses are not rewritten
+        let code = "#import std::random::{pcg}\nstruct S { pcg: u32 }\nfn f(s: S) -> u32 { return s.pcg; }\n";
+        let out = WgslPreprocessor::new().process_str(code, "main.wgsl").unwrap();
+        assert!(out.code.contains("s.pcg"));
+        assert!(out.linked.is_empty());
+    }
+
+    #[test]
+    fn test_import_errors() {
+        let preprocessor = WgslPreprocessor::new();
+
+        let err = preprocessor
+            .process_str("#import std::random::{pcg}\nfn pcg(x: u32) -> u32 { return x; }\n", "main.wgsl")
+            .unwrap_err();
+        assert!(err.message.contains("also declared"));
+
+        let err = preprocessor.process_str("\n#import std::color@2\n", "main.wgsl").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(err.message.contains("not compatible"));
+        assert!(preprocessor.process_str("#import std::color@1.0\n", "main.wgsl").is_ok());
+
+        let err = preprocessor.process_str("#import std::physics\n", "main.wgsl").unwrap_err();
+        assert!(err.message.contains("Unknown module"));
+
+        let err = preprocessor
+            .process_str("#import std::math\n\nfn f() -> f32 { return math::nope(1.0); }\n", "main.wgsl")
+            .unwrap_err();
+        assert_eq!((err.file.as_str(), err.line), ("main.wgsl", 3));
+    }
+
+    #[test]
+    fn test_module_listing() {
+        let modules = stdlib_modules();
+        let random = modules.iter().find(|m| m.name == "random").unwrap();
+        assert_eq!(random.version, "1.0");
+        assert!(random.exports.contains(&"rand_f32".to_string()));
+        assert!(stdlib_source("matrix").unwrap().contains("fn inverse4"));
+    }
+}
@@ -0,0 +1,38 @@
+// std::math - safe arithmetic and small numeric helpers
+
+const EPSILON: f32 = 1e-7;
+
+// a / b, or 0 when b is zero
+fn safe_div(a: f32, b: f32) -> f32 {
+    return select(a / b, 0.0, b == 0.0);
+}
+
+// a / b, or `fallback` when |b| is below EPSILON
+fn safe_div_or(a: f32, b: f32, fallback: f32) -> f32 {
+    return select(a / b, fallback, abs(b) < EPSILON);
+}
+
+// Unit vector, or zero for a (near) zero vector
+fn safe_normalize(v: vec3<f32>) -> vec3<f32> {
+    let len = length(v);
+    return select(v / len, vec3<f32>(0.0), len < EPSILON);
+}
+
+fn safe_sqrt(x: f32) -> f32 {
+    return sqrt(max(x, 0.0));
+}
+
+// Number of `b`-sized groups needed to cover `a` items
+fn ceil_div(a: u32, b: u32) -> u32 {
+    return (a + b - 1u) / b;
+}
+
+// Map x from [in_min, in_max] to [out_min, out_max]
+fn remap(x: f32, in_min: f32, in_max: f32, out_min: f32, out_max: f32) -> f32 {
+    return out_min + (x - in_min) * safe_div(out_max - out_min, in_max - in_min);
+}
+
+// Equality with a tolerance relative to the larger magnitude (absolute below 1)
+fn approx_eq(a: f32, b: f32, tolerance: f32) -> bool {
+    return abs(a - b) <= tolerance * max(1.0, max(abs(a), abs(b)));
+}
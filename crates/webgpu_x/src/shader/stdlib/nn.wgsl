@@ -0,0 +1,45 @@
+// std::nn - activations and normalization terms used by the compute templates
+
+// Epsilon added to the variance by layer and batch normalization
+const NORM_EPSILON: f32 = 1e-5;
+
+fn relu(x: f32) -> f32 {
+    return max(0.0, x);
+}
+
+fn leaky_relu(x: f32, slope: f32) -> f32 {
+    return select(x * slope, x, x >= 0.0);
+}
+
+// Logistic sigmoid that does not overflow for large |x|
+fn sigmoid(x: f32) -> f32 {
+    let e = exp(-abs(x));
+    return select(e / (1.0 + e), 1.0 / (1.0 + e), x >= 0.0);
+}
+
+fn silu(x: f32) -> f32 {
+    return x * sigmoid(x);
+}
+
+// GELU, tanh approximation
+fn gelu(x: f32) -> f32 {
+    return 0.5 * x * (1.0 + tanh(0.7978845608 * (x + 0.044715 * x * x * x)));
+}
+
+fn softplus(x: f32) -> f32 {
+    return max(x, 0.0) + log(1.0 + exp(-abs(x)));
+}
+
+fn elu(x: f32, alpha: f32) -> f32 {
+    return select(alpha * (exp(x) - 1.0), x, x > 0.0);
+}
+
+// Softmax numerator, shifted by the row maximum for stability
+fn softmax_term(x: f32, row_max: f32) -> f32 {
+    return exp(x - row_max);
+}
+
+// (x - mean) / sqrt(variance + NORM_EPSILON)
+fn normalize_value(x: f32, mean: f32, variance: f32) -> f32 {
+    return (x - mean) * inverseSqrt(variance + NORM_EPSILON);
+}
@@ -0,0 +1,48 @@
+// std::noise - 2D value, gradient and fractal noise
+
+// Random value in [0, 1) at an integer lattice point
+fn lattice(i: vec2<f32>) -> f32 {
+    return random::to_unit_f32(random::hash2(bitcast<vec2<u32>>(vec2<i32>(i))));
+}
+
+fn lattice_gradient(i: vec2<f32>) -> vec2<f32> {
+    let angle = lattice(i) * 6.2831853;
+    return vec2<f32>(cos(angle), sin(angle));
+}
+
+// Smoothly interpolated lattice values in [0, 1)
+fn value2(p: vec2<f32>) -> f32 {
+    let i = floor(p);
+    let f = fract(p);
+    let u = f * f * (3.0 - 2.0 * f);
+    let a = lattice(i);
+    let b = lattice(i + vec2<f32>(1.0, 0.0));
+    let c = lattice(i + vec2<f32>(0.0, 1.0));
+    let d = lattice(i + vec2<f32>(1.0, 1.0));
+    return mix(mix(a, b, u.x), mix(c, d, u.x), u.y);
+}
+
+// Perlin-style gradient noise in about [-1, 1]
+fn gradient2(p: vec2<f32>) -> f32 {
+    let i = floor(p);
+    let f = fract(p);
+    let u = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+    let a = dot(lattice_gradient(i), f);
+    let b = dot(lattice_gradient(i + vec2<f32>(1.0, 0.0)), f - vec2<f32>(1.0, 0.0));
+    let c = dot(lattice_gradient(i + vec2<f32>(0.0, 1.0)), f - vec2<f32>(0.0, 1.0));
+    let d = dot(lattice_gradient(i + vec2<f32>(1.0, 1.0)), f - vec2<f32>(1.0, 1.0));
+    return mix(mix(a, b, u.x), mix(c, d, u.x), u.y) * 1.4142135;
+}
+
+// Fractional Brownian motion: octaves of gradient noise, each at half the amplitude
+fn fbm2(p: vec2<f32>, octaves: u32) -> f32 {
+    var sum = 0.0;
+    var amplitude = 0.5;
+    var q = p;
+    for (var o = 0u; o < octaves; o++) {
+        sum += amplitude * gradient2(q);
+        q = q * 2.0 + vec2<f32>(17.0, 31.0);
+        amplitude *= 0.5;
+    }
+    return sum;
+}
@@ -0,0 +1,41 @@
+// std::pack - bit packing helpers
+
+// Octahedral encoding of a unit vector into [-1, 1]^2
+fn encode_octahedral(n: vec3<f32>) -> vec2<f32> {
+    let p = n.xy / (abs(n.x) + abs(n.y) + abs(n.z));
+    if (n.z >= 0.0) {
+        return p;
+    }
+    return (1.0 - abs(p.yx)) * select(vec2<f32>(-1.0), vec2<f32>(1.0), p >= vec2<f32>(0.0));
+}
+
+fn decode_octahedral(e: vec2<f32>) -> vec3<f32> {
+    var n = vec3<f32>(e, 1.0 - abs(e.x) - abs(e.y));
+    let t = max(-n.z, 0.0);
+    n.x += select(t, -t, n.x >= 0.0);
+    n.y += select(t, -t, n.y >= 0.0);
+    return normalize(n);
+}
+
+// Four bytes (clamped to 255) into one u32, x in the low byte
+fn pack_u8x4(v: vec4<u32>) -> u32 {
+    let c = min(v, vec4<u32>(255u));
+    return c.x | (c.y << 8u) | (c.z << 16u) | (c.w << 24u);
+}
+
+fn unpack_u8x4(p: u32) -> vec4<u32> {
+    return (vec4<u32>(p) >> vec4<u32>(0u, 8u, 16u, 24u)) & vec4<u32>(255u);
+}
+
+fn pack_half2(a: f32, b: f32) -> u32 {
+    return pack2x16float(vec2<f32>(a, b));
+}
+
+fn unpack_half2(p: u32) -> vec2<f32> {
+    return unpack2x16float(p);
+}
+
+// RGBA8 unorm color, clamped to [0, 1] first
+fn pack_color(c: vec4<f32>) -> u32 {
+    return pack4x8unorm(clamp(c, vec4<f32>(0.0), vec4<f32>(1.0)));
+}
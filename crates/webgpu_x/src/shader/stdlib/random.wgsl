@@ -0,0 +1,50 @@
+// std::random - integer hashes and PCG random numbers
+
+// PCG hash (Jarzynski and Olano, "Hash Functions for GPU Rendering", 2020)
+fn pcg(v: u32) -> u32 {
+    let state = v * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+// Three hashes from three inputs in one pass
+fn pcg3d(v: vec3<u32>) -> vec3<u32> {
+    var w = v * 1664525u + 1013904223u;
+    w.x += w.y * w.z;
+    w.y += w.z * w.x;
+    w.z += w.x * w.y;
+    w ^= w >> vec3<u32>(16u);
+    w.x += w.y * w.z;
+    w.y += w.z * w.x;
+    w.z += w.x * w.y;
+    return w;
+}
+
+fn wang_hash(v: u32) -> u32 {
+    var x = (v ^ 61u) ^ (v >> 16u);
+    x *= 9u;
+    x ^= x >> 4u;
+    x *= 0x27d4eb2du;
+    x ^= x >> 15u;
+    return x;
+}
+
+// Mix `v` into an existing hash
+fn hash_combine(seed: u32, v: u32) -> u32 {
+    return seed ^ (pcg(v) + 0x9e3779b9u + (seed << 6u) + (seed >> 2u));
+}
+
+fn hash2(v: vec2<u32>) -> u32 {
+    return pcg(v.x ^ pcg(v.y));
+}
+
+// Top 24 bits of a hash as a float in [0, 1)
+fn to_unit_f32(x: u32) -> f32 {
+    return f32(x >> 8u) * (1.0 / 16777216.0);
+}
+
+// Advance a per-invocation state and return a float in [0, 1)
+fn rand_f32(state: ptr<function, u32>) -> f32 {
+    *state = pcg(*state);
+    return to_unit_f32(*state);
+}
@@ -0,0 +1,485 @@
+//! Shader variants
+//!
+//! One WGSL source, many specialised kernels. A shader declares its variant space:
+//! - `#pragma feature NAME`: an on/off feature, enabled as `#define NAME 1`
+//! - `#pragma feature NAME a b c`: one of the listed values (the first is the default),
+//!   defined as `#define NAME value` plus `#define NAME_value 1` for `#ifdef` tests
+//!   (a `.` in the value becomes `_` there, so `1.5` gives `NAME_1_5`)
+//! - `override` constants, set per variant and passed as pipeline constants, so changing
+//!   them never changes the WGSL text
+//!
+//! A [`VariantKey`] picks feature values and override values. [`ShaderCache`](super::ShaderCache)
+//! compiles a variant the first time it is requested and caches it by
+//! (source hash, canonical variant key); keys that only restate defaults share an entry.
+
+use super::preprocessor::WgslPreprocessor;
+use super::reflection::{reflect_module, ShaderReflection, WgslDiagnostic};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// A feature declared with `#pragma feature`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VariantFeature {
+    pub name: String,
+    /// Allowed values, first is the default; empty for on/off features
+    pub values: Vec<String>,
+}
+
+impl VariantFeature {
+    pub fn is_toggle(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// Features a shader can be specialised on
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VariantSpace {
+    pub features: Vec<VariantFeature>,
+}
+
+impl VariantSpace {
+    /// Number of feature combinations (override values not counted)
+    pub fn combinations(&self) -> u64 {
+        self.features
+            .iter()
+            .map(|f| if f.is_toggle() { 2 } else { f.values.len() as u64 })
+            .product()
+    }
+
+    fn feature(&self, name: &str) -> Option<&VariantFeature> {
+        self.features.iter().find(|f| f.name == name)
+    }
+}
+
+/// Selection of feature values and override constants
+///
+/// On/off features accept `on`/`off` (also `true`/`false`, `1`/`0`); missing features
+/// take their default (off, or the first listed value).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VariantKey {
+    pub features: BTreeMap<String, String>,
+    pub overrides: BTreeMap<String, f64>,
+}
+
+impl VariantKey {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable an on/off feature
+    pub fn enable(mut self, feature: &str) -> Self {
+        self.features.insert(feature.to_string(), "on".to_string());
+        self
+    }
+
+    /// Pick a value for a multi-valued feature
+    pub fn with_feature(mut self, feature: &str, value: &str) -> Self {
+        self.features.insert(feature.to_string(), value.to_string());
+        self
+    }
+
+    pub fn with_override(mut self, name: &str, value: f64) -> Self {
+        self.overrides.insert(name.to_string(), value);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VariantErrorKind {
+    /// Malformed `#pragma feature`
+    Declaration,
+    UnknownFeature,
+    InvalidFeatureValue,
+    Preprocess,
+    Validation,
+    /// Unknown override or a value naga rejects
+    Overrides,
+}
+
+/// Failure to compile a variant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariantError {
+    pub kind: VariantErrorKind,
+    /// Canonical key of the failing variant (empty if the key itself is invalid)
+    pub key: String,
+    pub message: String,
+    pub diagnostics: Vec<WgslDiagnostic>,
+}
+
+impl VariantError {
+    fn new(kind: VariantErrorKind, key: &str, message: String) -> Self {
+        Self {
+            kind,
+            key: key.to_string(),
+            message,
+            diagnostics: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for VariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.key.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "variant [{}]: {}", self.key, self.message)
+        }
+    }
+}
+
+impl std::error::Error for VariantError {}
+
+/// A variant ready for pipeline creation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompiledVariant {
+    /// Canonical variant key, e.g. `BIAS,WORKGROUP_SIZE=128|alpha=2`
+    pub key: String,
+    pub source_hash: u64,
+    /// Preprocessed WGSL for this feature combination
+    pub code: String,
+    /// Preprocessor defines the feature values expanded to
+    pub defines: BTreeMap<String, String>,
+    /// Values to pass as pipeline-overridable constants
+    pub overrides: BTreeMap<String, f64>,
+    pub reflection: ShaderReflection,
+}
+
+/// Outcome of compiling the variants a workload will use ahead of time
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VariantPrewarmReport {
+    pub compiled: Vec<String>,
+    pub already_cached: Vec<String>,
+    pub failed: Vec<VariantError>,
+}
+
+/// `#ifdef` name for one value of a multi-value feature
+fn value_define(name: &str, value: &str) -> String {
+    format!("{}_{}", name, value.replace('.', "_"))
+}
+
+/// Read `#pragma feature` declarations
+pub fn variant_space(source: &str) -> Result<VariantSpace, VariantError> {
+    let mut space = VariantSpace::default();
+    for (index, line) in source.lines().enumerate() {
+        let Some(rest) = line.trim().strip_prefix('#') else { continue };
+        let mut words = rest.split_whitespace();
+        if words.next() != Some("pragma") || words.next() != Some("feature") {
+            continue;
+        }
+        let fail = |message: String| {
+            VariantError::new(
+                VariantErrorKind::Declaration,
+                "",
+                format!("line {}: {}", index + 1, message),
+            )
+        };
+
+        let name = words
+            .next()
+            .ok_or_else(|| fail("'#pragma feature' needs a feature name".to_string()))?;
+        if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(fail(format!("Invalid feature name '{}'", name)));
+        }
+        if space.feature(name).is_some() {
+            return Err(fail(format!("Feature '{}' declared twice", name)));
+        }
+        let values: Vec<String> = words.map(str::to_string).collect();
+        if let Some(value) = values.iter().find(|v| !v.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')) {
+            return Err(fail(format!("Invalid value '{}' for feature '{}'", value, name)));
+        }
+        for (i, value) in values.iter().enumerate() {
+            if let Some(other) = values[..i].iter().find(|other| value_define(name, other) == value_define(name, value)) {
+                return Err(fail(format!(
+                    "Values '{}' and '{}' of feature '{}' both define {}",
+                    other,
+                    value,
+                    name,
+                    value_define(name, value)
+                )));
+            }
+        }
+        space.features.push(VariantFeature {
+            name: name.to_string(),
+            values,
+        });
+    }
+    Ok(space)
+}
+
+fn parse_toggle(value: &str) -> Option<bool> {
+    match value {
+        "on" | "true" | "1" => Some(true),
+        "off" | "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Feature values with defaults filled in, in declaration order
+fn resolve_features(space: &VariantSpace, key: &VariantKey) -> Result<Vec<(String, String)>, VariantError> {
+    if let Some(unknown) = key.features.keys().find(|name| space.feature(name).is_none()) {
+        return Err(VariantError::new(
+            VariantErrorKind::UnknownFeature,
+            "",
+            format!("Unknown feature '{}'", unknown),
+        ));
+    }
+
+    space
+        .features
+        .iter()
+        .map(|feature| {
+            let requested = key.features.get(&feature.name);
+            let value = if feature.is_toggle() {
+                let enabled = match requested {
+                    Some(value) => parse_toggle(value).ok_or_else(|| {
+                        VariantError::new(
+                            VariantErrorKind::InvalidFeatureValue,
+                            "",
+                            format!("Feature '{}' is on/off, got '{}'", feature.name, value),
+                        )
+                    })?,
+                    None => false,
+                };
+                if enabled { "on" } else { "off" }.to_string()
+            } else {
+                match requested {
+                    Some(value) if feature.values.contains(value) => value.clone(),
+                    Some(value) => {
+                        return Err(VariantError::new(
+                            VariantErrorKind::InvalidFeatureValue,
+                            "",
+                            format!(
+                                "Feature '{}' has no value '{}' (expected one of: {})",
+                                feature.name,
+                                value,
+                                feature.values.join(", ")
+                            ),
+                        ))
+                    }
+                    None => feature.values[0].clone(),
+                }
+            };
+            Ok((feature.name.clone(), value))
+        })
+        .collect()
+}
+
+/// Canonical key: enabled toggles and `NAME=value` sorted by name, then `|` and overrides
+///
+/// Keys that differ only in spelling (`true` vs `on`, omitted vs explicit defaults) are equal.
+pub fn canonical_variant_key(space: &VariantSpace, key: &VariantKey) -> Result<String, VariantError> {
+    let mut features: Vec<String> = resolve_features(space, key)?
+        .into_iter()
+        .filter_map(|(name, value)| match value.as_str() {
+            "on" if space.feature(&name).is_some_and(VariantFeature::is_toggle) => Some(name),
+            "off" if space.feature(&name).is_some_and(VariantFeature::is_toggle) => None,
+            _ => Some(format!("{}={}", name, value)),
+        })
+        .collect();
+    features.sort();
+
+    let overrides: Vec<String> = key
+        .overrides
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect();
+
+    Ok(if overrides.is_empty() {
+        features.join(",")
+    } else {
+        format!("{}|{}", features.join(","), overrides.join(","))
+    })
+}
+
+/// Every feature combination, without overrides
+pub fn enumerate_variants(space: &VariantSpace) -> Vec<VariantKey> {
+    let mut keys = vec![VariantKey::default()];
+    for feature in &space.features {
+        let values: Vec<String> = if feature.is_toggle() {
+            vec!["off".to_string(), "on".to_string()]
+        } else {
+            feature.values.clone()
+        };
+        keys = keys
+            .into_iter()
+            .flat_map(|key| {
+                values
+                    .iter()
+                    .map(move |value| key.clone().with_feature(&feature.name, value))
+            })
+            .collect();
+    }
+    keys
+}
+
+/// Preprocess, validate and check the overrides of one variant
+pub fn compile_variant(
+    preprocessor: &WgslPreprocessor,
+    source: &str,
+    source_hash: u64,
+    key: &VariantKey,
+) -> Result<CompiledVariant, VariantError> {
+    let space = variant_space(source)?;
+    let canonical = canonical_variant_key(&space, key)?;
+    let fail = |kind, message: String| VariantError::new(kind, &canonical, message);
+
+    let mut defines = BTreeMap::new();
+    for (name, value) in resolve_features(&space, key)? {
+        match value.as_str() {
+            "off" if space.feature(&name).is_some_and(VariantFeature::is_toggle) => {}
+            "on" if space.feature(&name).is_some_and(VariantFeature::is_toggle) => {
+                defines.insert(name, "1".to_string());
+            }
+            _ => {
+                defines.insert(value_define(&name, &value), "1".to_string());
+                defines.insert(name, value);
+            }
+        }
+    }
+
+    let mut preprocessor = preprocessor.clone();
+    for (name, value) in &defines {
+        preprocessor.define(name, value);
+    }
+    let preprocessed = preprocessor
+        .process_str(source, "<variant>")
+        .map_err(|e| fail(VariantErrorKind::Preprocess, e.to_string()))?;
+
+    let shader = preprocessed.validate().map_err(|e| VariantError {
+        kind: VariantErrorKind::Validation,
+        key: canonical.clone(),
+        message: e.message,
+        diagnostics: e.diagnostics,
+    })?;
+    let reflection = reflect_module(&shader.module).map_err(|e| VariantError {
+        kind: VariantErrorKind::Validation,
+        key: canonical.clone(),
+        message: e.message,
+        diagnostics: e.diagnostics,
+    })?;
+
+    if let Some(unknown) = key
+        .overrides
+        .keys()
+        .find(|name| !reflection.overrides.iter().any(|o| o.name == **name))
+    {
+        return Err(fail(
+            VariantErrorKind::Overrides,
+            format!("Shader has no override constant '{}'", unknown),
+        ));
+    }
+    let constants: HashMap<String, f64> = key.overrides.iter().map(|(k, v)| (k.clone(), *v)).collect();
+    naga::back::pipeline_constants::process_overrides(&shader.module, &shader.info, &constants)
+        .map_err(|e| fail(VariantErrorKind::Overrides, e.to_string()))?;
+
+    Ok(CompiledVariant {
+        key: canonical,
+        source_hash,
+        code: preprocessed.code,
+        defines,
+        overrides: key.overrides.clone(),
+        reflection,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHADER: &str = r#"
+#pragma feature WORKGROUP_SIZE 64 128 256
+#pragma feature BIAS
+#pragma feature DTYPE f32 i32
+
+alias T = DTYPE;
+override alpha: f32 = 1.0;
+@group(0) @binding(0) var<storage, read_write> data: array<T>;
+
+@compute @workgroup_size(WORKGROUP_SIZE)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    var v = f32(data[id.x]) * alpha;
+#ifdef BIAS
+    v += 1.0;
+#endif
+#ifdef DTYPE_i32
+    v = floor(v);
+#endif
+    data[id.x] = T(v);
+}
+"#;
+
+    #[test]
+    fn test_variant_space() {
+        let space = variant_space(SHADER).unwrap();
+        assert_eq!(space.features.len(), 3);
+        assert!(space.features[1].is_toggle());
+        assert_eq!(space.combinations(), 12);
+        assert_eq!(enumerate_variants(&space).len(), 12);
+
+        assert!(variant_space("#pragma feature SCALE 1.5 1_5").is_err());
+        assert!(variant_space("#pragma feature SCALE 1 1 ").is_err());
+    }
+
+    #[test]
+    fn test_canonical_key_ignores_defaults_and_spelling() {
+        let space = variant_space(SHADER).unwrap();
+        let a = canonical_variant_key(&space, &VariantKey::new().enable("BIAS")).unwrap();
+        let b = canonical_variant_key(
+            &space,
+            &VariantKey::new()
+                .with_feature("BIAS", "true")
+                .with_feature("WORKGROUP_SIZE", "64"),
+        )
+        .unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a, "BIAS,DTYPE=f32,WORKGROUP_SIZE=64");
+
+        let with_override = canonical_variant_key(&space, &VariantKey::new().with_override("alpha", 2.0)).unwrap();
+        assert_eq!(with_override, "DTYPE=f32,WORKGROUP_SIZE=64|alpha=2");
+    }
+
+    #[test]
+    fn test_compile_variant() {
+        let preprocessor = WgslPreprocessor::new();
+        let key = VariantKey::new()
+            .with_feature("WORKGROUP_SIZE", "256")
+            .with_feature("DTYPE", "i32")
+            .enable("BIAS")
+            .with_override("alpha", 0.5);
+        let variant = compile_variant(&preprocessor, SHADER, 7, &key).unwrap();
+        assert_eq!(variant.reflection.entry_points[0].workgroup_size, Some([256, 1, 1]));
+        assert!(variant.code.contains("v += 1.0;"));
+        assert!(variant.code.contains("floor(v)"));
+        assert_eq!(variant.defines.get("DTYPE_i32").map(String::as_str), Some("1"));
+        assert_eq!(variant.overrides.get("alpha"), Some(&0.5));
+
+        let source = "#pragma feature SCALE 1.0 1.5\n\
+            @group(0) @binding(0) var<storage, read_write> data: array<f32>;\n\
+            @compute @workgroup_size(1) fn main() {\n\
+            #ifdef SCALE_1_5\n\
+            data[0] = SCALE;\n\
+            #endif\n\
+            }\n";
+        let key = VariantKey::new().with_feature("SCALE", "1.5");
+        let variant = compile_variant(&preprocessor, source, 8, &key).unwrap();
+        assert_eq!(variant.defines.get("SCALE_1_5").map(String::as_str), Some("1"));
+        assert!(variant.code.contains("data[0] = 1.5;"));
+    }
+
+    #[test]
+    fn test_invalid_keys() {
+        let preprocessor = WgslPreprocessor::new();
+        let err = compile_variant(&preprocessor, SHADER, 0, &VariantKey::new().enable("MASK")).unwrap_err();
+        assert_eq!(err.kind, VariantErrorKind::UnknownFeature);
+
+        let key = VariantKey::new().with_feature("WORKGROUP_SIZE", "32");
+        let err = compile_variant(&preprocessor, SHADER, 0, &key).unwrap_err();
+        assert_eq!(err.kind, VariantErrorKind::InvalidFeatureValue);
+
+        let key = VariantKey::new().with_override("beta", 1.0);
+        let err = compile_variant(&preprocessor, SHADER, 0, &key).unwrap_err();
+        assert_eq!(err.kind, VariantErrorKind::Overrides);
+    }
+}
@@ -0,0 +1,585 @@
+//! Event-driven shader hot reload
+//!
+//! Watches shader directories and turns filesystem changes into reload events:
+//! - inotify on Linux, periodic directory scans elsewhere (or if inotify is unavailable)
+//! - Rapid successive saves of one file are debounced into a single event
+//! - Every cached shader that is, or includes, the changed file is re-validated
+//!   before the cache hands it out again; invalid edits keep the last good version
+//! - Events queue up until polled, so Deno can drain them from its own loop
+
+use super::compilation::{canonical_path, FileStamp, ShaderCache, ShaderReloadError};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use lazy_static::lazy_static;
+
+/// Default debounce window for editor saves
+pub const DEFAULT_DEBOUNCE_MS: u64 = 50;
+
+/// Maximum queued events; the oldest are dropped beyond this
+const MAX_QUEUED_EVENTS: usize = 1024;
+
+/// Kind of filesystem change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShaderChangeKind {
+    Created,
+    Modified,
+    Removed,
+}
+
+/// Outcome of re-validating one cached shader
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShaderReloadResult {
+    pub path: String,
+    pub valid: bool,
+    pub error: Option<ShaderReloadError>,
+}
+
+/// A debounced change to a watched file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShaderChangeEvent {
+    pub path: String,
+    pub kind: ShaderChangeKind,
+    /// Milliseconds since the Unix epoch when the event was emitted
+    pub timestamp: u64,
+    /// Cached shaders affected by the change (the file itself and its includers)
+    pub reloaded: Vec<ShaderReloadResult>,
+}
+
+enum WatchBackend {
+    #[cfg(target_os = "linux")]
+    Inotify(inotify::Inotify),
+    Polling(HashMap<PathBuf, Option<FileStamp>>),
+}
+
+struct WatchedDirectory {
+    path: PathBuf,
+    recursive: bool,
+}
+
+/// Directory watcher feeding a shader cache
+pub struct ShaderWatcher {
+    backend: WatchBackend,
+    directories: Vec<WatchedDirectory>,
+    debounce: Duration,
+    pending: HashMap<PathBuf, (ShaderChangeKind, Instant)>,
+    events: VecDeque<ShaderChangeEvent>,
+}
+
+impl ShaderWatcher {
+    /// Create a watcher, preferring inotify when available
+    pub fn new(debounce_ms: u64) -> Self {
+        #[cfg(target_os = "linux")]
+        let backend = match inotify::Inotify::new() {
+            Some(inotify) => WatchBackend::Inotify(inotify),
+            None => WatchBackend::Polling(HashMap::new()),
+        };
+        #[cfg(not(target_os = "linux"))]
+        let backend = WatchBackend::Polling(HashMap::new());
+
+        Self::with_backend(backend, debounce_ms)
+    }
+
+    /// Create a watcher that scans directories on every poll
+    pub fn new_polling(debounce_ms: u64) -> Self {
+        Self::with_backend(WatchBackend::Polling(HashMap::new()), debounce_ms)
+    }
+
+    fn with_backend(backend: WatchBackend, debounce_ms: u64) -> Self {
+        Self {
+            backend,
+            directories: Vec::new(),
+            debounce: Duration::from_millis(debounce_ms),
+            pending: HashMap::new(),
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Whether the watcher uses kernel notifications rather than scanning
+    pub fn is_event_driven(&self) -> bool {
+        !matches!(self.backend, WatchBackend::Polling(_))
+    }
+
+    /// Start watching a directory of shaders
+    pub fn watch_directory(&mut self, path: &str, recursive: bool) -> Result<(), String> {
+        let dir = std::fs::canonicalize(path)
+            .map_err(|e| format!("Failed to watch '{}': {}", path, e))?;
+        if !dir.is_dir() {
+            return Err(format!("'{}' is not a directory", path));
+        }
+        if self.directories.iter().any(|d| d.path == dir) {
+            return Ok(());
+        }
+
+        let mut dirs = vec![dir.clone()];
+        if recursive {
+            collect_subdirectories(&dir, &mut dirs);
+        }
+
+        match &mut self.backend {
+            #[cfg(target_os = "linux")]
+            WatchBackend::Inotify(inotify) => {
+                for d in &dirs {
+                    inotify.add_watch(d)?;
+                }
+            }
+            WatchBackend::Polling(snapshot) => {
+                for d in &dirs {
+                    scan_directory(d, false, snapshot);
+                }
+            }
+        }
+
+        self.directories.push(WatchedDirectory { path: dir, recursive });
+        Ok(())
+    }
+
+    /// Stop watching a directory
+    pub fn unwatch_directory(&mut self, path: &str) {
+        let dir = PathBuf::from(canonical_path(path));
+        self.directories.retain(|d| d.path != dir);
+
+        match &mut self.backend {
+            #[cfg(target_os = "linux")]
+            WatchBackend::Inotify(inotify) => inotify.remove_watches_under(&dir),
+            WatchBackend::Polling(snapshot) => snapshot.retain(|p, _| !p.starts_with(&dir)),
+        }
+        self.pending.retain(|p, _| !p.starts_with(&dir));
+    }
+
+    /// Watched directories
+    pub fn directories(&self) -> Vec<String> {
+        self.directories
+            .iter()
+            .map(|d| d.path.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    /// Collect filesystem changes, re-validate affected shaders and queue events
+    ///
+    /// Returns the number of events waiting in the queue.
+    pub fn poll(&mut self, cache: &mut ShaderCache) -> usize {
+        let now = Instant::now();
+        for (path, kind) in self.read_changes() {
+            // Keep the first kind unless the file ended up removed
+            let entry = self.pending.entry(path).or_insert((kind, now));
+            if kind == ShaderChangeKind::Removed || entry.0 == ShaderChangeKind::Removed {
+                entry.0 = kind;
+            }
+            entry.1 = now;
+        }
+
+        let ready: Vec<(PathBuf, ShaderChangeKind)> = self
+            .pending
+            .iter()
+            .filter(|(_, (_, last))| now.duration_since(*last) >= self.debounce)
+            .map(|(path, (kind, _))| (path.clone(), *kind))
+            .collect();
+
+        for (path, kind) in ready {
+            self.pending.remove(&path);
+            let event = self.reload(cache, &path, kind);
+            if self.events.len() == MAX_QUEUED_EVENTS {
+                self.events.pop_front();
+            }
+            self.events.push_back(event);
+        }
+
+        self.events.len()
+    }
+
+    /// Take all queued events
+    pub fn drain_events(&mut self) -> Vec<ShaderChangeEvent> {
+        self.events.drain(..).collect()
+    }
+
+    /// Number of changes still inside their debounce window
+    pub fn pending_changes(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn reload(&self, cache: &mut ShaderCache, path: &Path, kind: ShaderChangeKind) -> ShaderChangeEvent {
+        let path_str = path.to_string_lossy().into_owned();
+        let reloaded = cache
+            .dependents(&path_str)
+            .into_iter()
+            .map(|shader| match cache.reload_validated(&shader) {
+                Ok(_) => ShaderReloadResult {
+                    path: shader,
+                    valid: true,
+                    error: None,
+                },
+                Err(e) => ShaderReloadResult {
+                    path: shader,
+                    valid: false,
+                    error: Some(e),
+                },
+            })
+            .collect();
+
+        ShaderChangeEvent {
+            path: path_str,
+            kind,
+            timestamp: unix_millis(),
+            reloaded,
+        }
+    }
+
+    fn read_changes(&mut self) -> Vec<(PathBuf, ShaderChangeKind)> {
+        match &mut self.backend {
+            #[cfg(target_os = "linux")]
+            WatchBackend::Inotify(inotify) => {
+                let recursive = |dir: &Path| {
+                    self.directories
+                        .iter()
+                        .any(|d| d.recursive && dir.starts_with(&d.path))
+                };
+                inotify
+                    .read_events(&recursive)
+                    .into_iter()
+                    .filter(|(path, _)| !is_ignored(path))
+                    .collect()
+            }
+            WatchBackend::Polling(snapshot) => {
+                let mut current = HashMap::new();
+                for d in &self.directories {
+                    scan_directory(&d.path, d.recursive, &mut current);
+                }
+
+                let mut changes = Vec::new();
+                for (path, stamp) in &current {
+                    match snapshot.get(path) {
+                        None => changes.push((path.clone(), ShaderChangeKind::Created)),
+                        Some(old) if old != stamp => {
+                            changes.push((path.clone(), ShaderChangeKind::Modified))
+                        }
+                        _ => {}
+                    }
+                }
+                for path in snapshot.keys() {
+                    if !current.contains_key(path) {
+                        changes.push((path.clone(), ShaderChangeKind::Removed));
+                    }
+                }
+
+                *snapshot = current;
+                changes.retain(|(path, _)| !is_ignored(path));
+                changes
+            }
+        }
+    }
+}
+
+/// Editor swap/backup files never affect shaders
+fn is_ignored(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    name.starts_with('.') || name.ends_with('~') || name.ends_with(".swp") || name.ends_with(".tmp")
+}
+
+fn collect_subdirectories(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            out.push(path.clone());
+            collect_subdirectories(&path, out);
+        }
+    }
+}
+
+fn scan_directory(dir: &Path, recursive: bool, out: &mut HashMap<PathBuf, Option<FileStamp>>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if recursive {
+                scan_directory(&path, recursive, out);
+            }
+        } else {
+            let stamp = FileStamp::of(&path.to_string_lossy());
+            out.insert(path, stamp);
+        }
+    }
+}
+
+fn unix_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(target_os = "linux")]
+mod inotify {
+    use super::ShaderChangeKind;
+    use std::collections::HashMap;
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::{Path, PathBuf};
+
+    const WATCH_MASK: u32 = libc::IN_CLOSE_WRITE
+        | libc::IN_MODIFY
+        | libc::IN_CREATE
+        | libc::IN_DELETE
+        | libc::IN_MOVED_FROM
+        | libc::IN_MOVED_TO;
+
+    /// Minimal non-blocking inotify wrapper
+    pub(super) struct Inotify {
+        fd: i32,
+        watches: HashMap<i32, PathBuf>,
+    }
+
+    impl Inotify {
+        pub(super) fn new() -> Option<Self> {
+            let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+            if fd < 0 {
+                return None;
+            }
+            Some(Self {
+                fd,
+                watches: HashMap::new(),
+            })
+        }
+
+        pub(super) fn add_watch(&mut self, dir: &Path) -> Result<(), String> {
+            let c_path = CString::new(dir.as_os_str().as_bytes())
+                .map_err(|_| format!("Invalid path '{}'", dir.display()))?;
+            let wd = unsafe { libc::inotify_add_watch(self.fd, c_path.as_ptr(), WATCH_MASK) };
+            if wd < 0 {
+                return Err(format!(
+                    "inotify_add_watch failed for '{}': {}",
+                    dir.display(),
+                    std::io::Error::last_os_error()
+                ));
+            }
+            self.watches.insert(wd, dir.to_path_buf());
+            Ok(())
+        }
+
+        pub(super) fn remove_watches_under(&mut self, dir: &Path) {
+            let fd = self.fd;
+            self.watches.retain(|&wd, path| {
+                if path.starts_with(dir) {
+                    unsafe { libc::inotify_rm_watch(fd, wd) };
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+
+        /// Drain all queued kernel events without blocking
+        pub(super) fn read_events(
+            &mut self,
+            is_recursive: &dyn Fn(&Path) -> bool,
+        ) -> Vec<(PathBuf, ShaderChangeKind)> {
+            let mut changes = Vec::new();
+            let mut new_dirs = Vec::new();
+            let mut buffer = [0u8; 4096];
+            let header = std::mem::size_of::<libc::inotify_event>();
+
+            loop {
+                let read = unsafe {
+                    libc::read(self.fd, buffer.as_mut_ptr() as *mut libc::c_void, buffer.len())
+                };
+                if read <= 0 {
+                    break;
+                }
+
+                let mut offset = 0;
+                while offset + header <= read as usize {
+                    // The buffer is only byte-aligned, so copy the header out
+                    let event: libc::inotify_event = unsafe {
+                        std::ptr::read_unaligned(buffer.as_ptr().add(offset) as *const _)
+                    };
+                    let name_bytes = &buffer[offset + header..offset + header + event.len as usize];
+                    offset += header + event.len as usize;
+
+                    let Some(dir) = self.watches.get(&event.wd) else {
+                        continue;
+                    };
+                    let name_end = name_bytes.iter().position(|&b| b == 0).unwrap_or(name_bytes.len());
+                    if name_end == 0 {
+                        continue;
+                    }
+                    let path = dir.join(std::ffi::OsStr::from_bytes(&name_bytes[..name_end]));
+
+                    if event.mask & libc::IN_ISDIR != 0 {
+                        if event.mask & (libc::IN_CREATE | libc::IN_MOVED_TO) != 0 && is_recursive(dir) {
+                            new_dirs.push(path);
+                        }
+                        continue;
+                    }
+
+                    let kind = if event.mask & (libc::IN_DELETE | libc::IN_MOVED_FROM) != 0 {
+                        ShaderChangeKind::Removed
+                    } else if event.mask & (libc::IN_CREATE | libc::IN_MOVED_TO) != 0 {
+                        ShaderChangeKind::Created
+                    } else {
+                        ShaderChangeKind::Modified
+                    };
+                    changes.push((path, kind));
+                }
+            }
+
+            for dir in new_dirs {
+                let _ = self.add_watch(&dir);
+            }
+            changes
+        }
+    }
+
+    impl Drop for Inotify {
+        fn drop(&mut self) {
+            unsafe { libc::close(self.fd) };
+        }
+    }
+}
+
+// Global watcher registry; each watcher is bound to a shader cache handle
+lazy_static! {
+    static ref SHADER_WATCHERS: Mutex<HashMap<u64, (u64, ShaderWatcher)>> = Mutex::new(HashMap::new());
+    static ref NEXT_WATCHER_ID: Mutex<u64> = Mutex::new(1);
+}
+
+/// Create a watcher that reloads shaders in the given cache
+pub fn shader_watcher_create(cache_handle: u64, debounce_ms: u64) -> u64 {
+    let mut watchers = SHADER_WATCHERS.lock().unwrap();
+    let mut next_id = NEXT_WATCHER_ID.lock().unwrap();
+
+    let watcher_id = *next_id;
+    *next_id += 1;
+
+    watchers.insert(watcher_id, (cache_handle, ShaderWatcher::new(debounce_ms)));
+    watcher_id
+}
+
+/// Start watching a directory
+pub fn shader_watcher_watch(watcher_handle: u64, path: String, recursive: bool) -> Result<(), String> {
+    let mut watchers = SHADER_WATCHERS.lock().unwrap();
+
+    if let Some((_, watcher)) = watchers.get_mut(&watcher_handle) {
+        watcher.watch_directory(&path, recursive)
+    } else {
+        Err("Invalid shader watcher handle".to_string())
+    }
+}
+
+/// Stop watching a directory
+pub fn shader_watcher_unwatch(watcher_handle: u64, path: String) {
+    let mut watchers = SHADER_WATCHERS.lock().unwrap();
+
+    if let Some((_, watcher)) = watchers.get_mut(&watcher_handle) {
+        watcher.unwatch_directory(&path);
+    }
+}
+
+/// Process filesystem changes and return all queued events
+pub fn shader_watcher_poll(watcher_handle: u64) -> Result<Vec<ShaderChangeEvent>, String> {
+    let mut watchers = SHADER_WATCHERS.lock().unwrap();
+    let (cache_handle, watcher) = watchers
+        .get_mut(&watcher_handle)
+        .ok_or_else(|| "Invalid shader watcher handle".to_string())?;
+
+    super::compilation::with_shader_cache(*cache_handle, |cache| {
+        watcher.poll(cache);
+    })?;
+
+    Ok(watcher.drain_events())
+}
+
+/// Destroy a watcher
+pub fn shader_watcher_destroy(watcher_handle: u64) {
+    let mut watchers = SHADER_WATCHERS.lock().unwrap();
+    watchers.remove(&watcher_handle);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn poll_until_event(watcher: &mut ShaderWatcher, cache: &mut ShaderCache) -> Vec<ShaderChangeEvent> {
+        for _ in 0..100 {
+            if watcher.poll(cache) > 0 {
+                return watcher.drain_events();
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        Vec::new()
+    }
+
+    fn check_reload(mut watcher: ShaderWatcher, name: &str) {
+        let dir = temp_dir(name);
+        let common = dir.join("common.wgsl");
+        let main = dir.join("main.wgsl");
+        fs::write(&common, "const SCALE: f32 = 2.0;").unwrap();
+        fs::write(&main, "#include \"common.wgsl\"\n@compute @workgroup_size(1) fn main() { _ = SCALE; }").unwrap();
+
+        let mut cache = ShaderCache::new();
+        let main_path = main.to_string_lossy().into_owned();
+        cache.load(main_path.clone()).unwrap();
+        watcher.watch_directory(dir.to_str().unwrap(), false).unwrap();
+
+        // A broken include is reported against the includer and the good version is kept
+        fs::write(&common, "const SCALE: f32 = ;").unwrap();
+        let events = poll_until_event(&mut watcher, &mut cache);
+        let event = events.iter().find(|e| e.path.ends_with("common.wgsl")).unwrap();
+        assert_eq!(event.reloaded.len(), 1);
+        assert_eq!(event.reloaded[0].path, main_path);
+        assert!(!event.reloaded[0].valid);
+        assert!(cache.load(main_path.clone()).unwrap().code.contains("2.0"));
+
+        fs::write(&common, "const SCALE: f32 = 3.0;").unwrap();
+        let events = poll_until_event(&mut watcher, &mut cache);
+        let event = events.iter().find(|e| e.path.ends_with("common.wgsl")).unwrap();
+        assert!(event.reloaded[0].valid);
+        assert!(cache.load(main_path).unwrap().code.contains("3.0"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_watcher_reloads_dependents() {
+        check_reload(ShaderWatcher::new(0), "webgpu_x_watcher_test");
+    }
+
+    #[test]
+    fn test_polling_watcher_reloads_dependents() {
+        check_reload(ShaderWatcher::new_polling(0), "webgpu_x_watcher_polling_test");
+    }
+
+    #[test]
+    fn test_debounce_coalesces_saves() {
+        let dir = temp_dir("webgpu_x_watcher_debounce_test");
+        let mut cache = ShaderCache::new();
+        let mut watcher = ShaderWatcher::new_polling(60_000);
+        watcher.watch_directory(dir.to_str().unwrap(), false).unwrap();
+
+        fs::write(dir.join("a.wgsl"), "fn a() {}").unwrap();
+        assert_eq!(watcher.poll(&mut cache), 0);
+        assert_eq!(watcher.pending_changes(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_watch_missing_directory() {
+        let mut watcher = ShaderWatcher::new(0);
+        assert!(watcher.watch_directory("/nonexistent/webgpu_x/shaders", true).is_err());
+    }
+}
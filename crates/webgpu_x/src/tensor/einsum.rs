@@ -0,0 +1,534 @@
+//! Einsum front end
+//!
+//! Parses Einstein summation specs such as `"bij,bjk->bik"`, checks them against the
+//! operand shapes and generates an `f32` compute kernel:
+//! - `MatMul`: plain `ij,jk->ik` contractions reuse the matrix multiply template
+//! - `Reduction`: one operand summed down to fewer outputs than it has summed terms
+//!   (`ij->i`, `ij->`, `ii->`) gets one workgroup per output and a shared-memory tree
+//! - `LoopNest`: everything else gets one invocation per output element looping over
+//!   the contracted labels (batched contractions, outer products, permutations, diagonals)
+//!
+//! Without `->` the output is every label that appears exactly once, in alphabetical
+//! order. Repeated labels within one operand select its diagonal. Ellipsis is not supported.
+
+use super::storage::TensorShape;
+use crate::compute::templates::{generate_kernel, KernelOperation};
+use crate::compute::workgroup::{calculate_dispatch_size_1d, calculate_dispatch_size_2d, WorkgroupSize};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const WORKGROUP_SIZE: u32 = 64;
+const MATMUL_TILE: u32 = 16;
+const MAX_DISPATCH: u32 = 65535;
+
+/// Parsed einsum spec; labels are single ASCII letters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EinsumSpec {
+    pub inputs: Vec<Vec<char>>,
+    pub output: Vec<char>,
+}
+
+impl EinsumSpec {
+    /// Normalized equation with an explicit output, e.g. `ij,jk->ik`
+    pub fn equation(&self) -> String {
+        let inputs: Vec<String> = self.inputs.iter().map(|labels| labels.iter().collect()).collect();
+        format!("{}->{}", inputs.join(","), self.output.iter().collect::<String>())
+    }
+
+    /// Labels summed over (in the inputs but not the output), in order of first appearance
+    pub fn contracted(&self) -> Vec<char> {
+        let mut labels = Vec::new();
+        for &label in self.inputs.iter().flatten() {
+            if !self.output.contains(&label) && !labels.contains(&label) {
+                labels.push(label);
+            }
+        }
+        labels
+    }
+}
+
+/// Parse an einsum spec
+pub fn parse_einsum(spec: &str) -> Result<EinsumSpec, String> {
+    let spec: String = spec.chars().filter(|c| !c.is_whitespace()).collect();
+    if spec.contains("...") {
+        return Err("Ellipsis ('...') is not supported".to_string());
+    }
+
+    let (inputs, output) = match spec.split_once("->") {
+        Some((inputs, output)) => (inputs, Some(output)),
+        None => (spec.as_str(), None),
+    };
+
+    let parse_labels = |text: &str| -> Result<Vec<char>, String> {
+        match text.chars().find(|c| !c.is_ascii_alphabetic()) {
+            Some(c) => Err(format!("Invalid einsum label '{}'", c)),
+            None => Ok(text.chars().collect()),
+        }
+    };
+
+    let inputs = inputs
+        .split(',')
+        .map(parse_labels)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let output = match output {
+        Some(output) => {
+            let output = parse_labels(output)?;
+            for (i, label) in output.iter().enumerate() {
+                if output[..i].contains(label) {
+                    return Err(format!("Output label '{}' appears more than once", label));
+                }
+                if !inputs.iter().flatten().any(|l| l == label) {
+                    return Err(format!("Output label '{}' does not appear in any input", label));
+                }
+            }
+            output
+        }
+        None => {
+            let mut counts: BTreeMap<char, usize> = BTreeMap::new();
+            for &label in inputs.iter().flatten() {
+                *counts.entry(label).or_default() += 1;
+            }
+            counts
+                .into_iter()
+                .filter(|(_, count)| *count == 1)
+                .map(|(label, _)| label)
+                .collect()
+        }
+    };
+
+    Ok(EinsumSpec { inputs, output })
+}
+
+/// How an einsum is executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EinsumStrategy {
+    MatMul,
+    Reduction,
+    LoopNest,
+}
+
+/// One buffer of the generated kernel, all in `@group(0)`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EinsumBinding {
+    pub binding: u32,
+    /// Variable name in the WGSL
+    pub name: String,
+    /// Index of the operand bound here, `None` for the output and uniforms
+    pub operand: Option<usize>,
+    pub size_bytes: u64,
+}
+
+/// Kernel and launch parameters for an einsum
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EinsumPlan {
+    pub spec: EinsumSpec,
+    pub equation: String,
+    pub label_sizes: BTreeMap<String, u32>,
+    pub output_shape: TensorShape,
+    pub strategy: EinsumStrategy,
+    pub wgsl: String,
+    pub entry_point: String,
+    pub workgroup_size: WorkgroupSize,
+    /// Workgroup counts for `dispatch_workgroups`
+    pub dispatch: WorkgroupSize,
+    pub bindings: Vec<EinsumBinding>,
+    /// Contents of the uniform binding as `u32` words (empty when there is none)
+    pub uniform_data: Vec<u32>,
+    /// Products summed: output elements times the contracted extent
+    pub terms: u64,
+}
+
+fn element_count(dimensions: &[u32]) -> u64 {
+    dimensions.iter().map(|&d| d as u64).product()
+}
+
+/// Validate a spec against operand shapes and generate its kernel
+pub fn plan_einsum(spec: &str, shapes: &[TensorShape]) -> Result<EinsumPlan, String> {
+    let spec = parse_einsum(spec)?;
+    if spec.inputs.len() != shapes.len() {
+        return Err(format!(
+            "Einsum '{}' has {} operand(s) but {} shape(s) were given",
+            spec.equation(),
+            spec.inputs.len(),
+            shapes.len()
+        ));
+    }
+
+    let mut sizes: BTreeMap<char, (u32, usize)> = BTreeMap::new();
+    for (operand, (labels, shape)) in spec.inputs.iter().zip(shapes).enumerate() {
+        if labels.len() != shape.dimensions.len() {
+            return Err(format!(
+                "Operand {} has {} label(s) but rank {}",
+                operand,
+                labels.len(),
+                shape.dimensions.len()
+            ));
+        }
+        for (&label, &size) in labels.iter().zip(&shape.dimensions) {
+            if size == 0 {
+                return Err(format!("Operand {} has a zero-sized dimension", operand));
+            }
+            match sizes.get(&label) {
+                Some(&(existing, first)) if existing != size => {
+                    return Err(format!(
+                        "Label '{}' has size {} in operand {} but {} in operand {}",
+                        label, existing, first, size, operand
+                    ));
+                }
+                Some(_) => {}
+                None => {
+                    sizes.insert(label, (size, operand));
+                }
+            }
+        }
+        if element_count(&shape.dimensions) > u32::MAX as u64 {
+            return Err(format!("Operand {} has more than 2^32 elements", operand));
+        }
+    }
+    let size = |label: char| sizes[&label].0;
+
+    let output_dims: Vec<u32> = spec.output.iter().map(|&l| size(l)).collect();
+    let contracted = spec.contracted();
+    let output_elements = element_count(&output_dims);
+    let reduced_elements = contracted.iter().map(|&l| size(l) as u64).product::<u64>();
+    if output_elements > u32::MAX as u64 || reduced_elements > u32::MAX as u64 {
+        return Err("Einsum output or contraction has more than 2^32 elements".to_string());
+    }
+
+    let mut bindings: Vec<EinsumBinding> = shapes
+        .iter()
+        .enumerate()
+        .map(|(operand, shape)| EinsumBinding {
+            binding: operand as u32,
+            name: format!("operand{}", operand),
+            operand: Some(operand),
+            size_bytes: element_count(&shape.dimensions) * 4,
+        })
+        .collect();
+    bindings.push(EinsumBinding {
+        binding: shapes.len() as u32,
+        name: "output".to_string(),
+        operand: None,
+        size_bytes: output_elements * 4,
+    });
+
+    let generator = KernelGenerator {
+        spec: &spec,
+        shapes,
+        sizes: spec
+            .inputs
+            .iter()
+            .flatten()
+            .map(|&l| (l, size(l)))
+            .collect(),
+    };
+
+    let (strategy, wgsl, workgroup_size, dispatch, uniform_data) = if let Some((m, k, n)) =
+        matmul_dimensions(&spec, shapes).filter(|&(m, _, n)| {
+            n.div_ceil(MATMUL_TILE) <= MAX_DISPATCH && m.div_ceil(MATMUL_TILE) <= MAX_DISPATCH
+        }) {
+        // Template bindings: matrix_a, matrix_b, output, dims
+        for (binding, name) in bindings.iter_mut().zip(["matrix_a", "matrix_b", "output"]) {
+            binding.name = name.to_string();
+        }
+        bindings.push(EinsumBinding {
+            binding: 3,
+            name: "dims".to_string(),
+            operand: None,
+            size_bytes: 16,
+        });
+        let workgroup = WorkgroupSize {
+            x: MATMUL_TILE,
+            y: MATMUL_TILE,
+            z: 1,
+            total_invocations: MATMUL_TILE * MATMUL_TILE,
+        };
+        let dispatch = calculate_dispatch_size_2d(n, m, workgroup.clone());
+        let wgsl = generate_kernel(KernelOperation::MatrixMultiply, (MATMUL_TILE, MATMUL_TILE, 1));
+        (EinsumStrategy::MatMul, wgsl, workgroup, dispatch, vec![m, k, n, 0])
+    } else if spec.inputs.len() == 1
+        && !contracted.is_empty()
+        && output_elements < reduced_elements
+        && output_elements <= MAX_DISPATCH as u64
+    {
+        let groups = output_elements as u32;
+        (
+            EinsumStrategy::Reduction,
+            generator.reduction(&contracted, reduced_elements),
+            linear_workgroup(),
+            WorkgroupSize {
+                x: groups,
+                y: 1,
+                z: 1,
+                total_invocations: groups,
+            },
+            Vec::new(),
+        )
+    } else {
+        let groups = calculate_dispatch_size_1d(output_elements as u32, WORKGROUP_SIZE);
+        let y = groups.div_ceil(MAX_DISPATCH);
+        let x = groups.min(MAX_DISPATCH);
+        (
+            EinsumStrategy::LoopNest,
+            generator.loop_nest(&contracted, output_elements),
+            linear_workgroup(),
+            WorkgroupSize {
+                x,
+                y,
+                z: 1,
+                total_invocations: x * y,
+            },
+            Vec::new(),
+        )
+    };
+
+    Ok(EinsumPlan {
+        equation: spec.equation(),
+        label_sizes: sizes
+            .iter()
+            .map(|(label, (size, _))| (label.to_string(), *size))
+            .collect(),
+        output_shape: TensorShape::new(output_dims),
+        strategy,
+        wgsl,
+        entry_point: "main".to_string(),
+        workgroup_size,
+        dispatch,
+        bindings,
+        uniform_data,
+        terms: output_elements * reduced_elements,
+        spec,
+    })
+}
+
+fn linear_workgroup() -> WorkgroupSize {
+    WorkgroupSize {
+        x: WORKGROUP_SIZE,
+        y: 1,
+        z: 1,
+        total_invocations: WORKGROUP_SIZE,
+    }
+}
+
+/// `(M, K, N)` when the spec is a plain row-major `ij,jk->ik` product
+fn matmul_dimensions(spec: &EinsumSpec, shapes: &[TensorShape]) -> Option<(u32, u32, u32)> {
+    let [a, b] = spec.inputs.as_slice() else {
+        return None;
+    };
+    let (&[i, j], &[j2, k], &[oi, ok]) = (a.as_slice(), b.as_slice(), spec.output.as_slice()) else {
+        return None;
+    };
+    if j != j2 || oi != i || ok != k || i == j || j == k || i == k {
+        return None;
+    }
+    Some((shapes[0].dimensions[0], shapes[0].dimensions[1], shapes[1].dimensions[1]))
+}
+
+struct KernelGenerator<'a> {
+    spec: &'a EinsumSpec,
+    shapes: &'a [TensorShape],
+    sizes: BTreeMap<char, u32>,
+}
+
+impl KernelGenerator<'_> {
+    fn bindings(&self) -> String {
+        let mut wgsl = String::new();
+        for operand in 0..self.shapes.len() {
+            wgsl.push_str(&format!(
+                "@group(0) @binding({0}) var<storage, read> operand{0}: array<f32>;\n",
+                operand
+            ));
+        }
+        wgsl.push_str(&format!(
+            "@group(0) @binding({}) var<storage, read_write> output: array<f32>;\n\n",
+            self.shapes.len()
+        ));
+        wgsl
+    }
+
+    /// `let` statements recovering each label's index from the linear index `linear`
+    fn decode(&self, labels: &[char], linear: &str, indent: &str) -> String {
+        let mut code = String::new();
+        let mut stride = 1u64;
+        for &label in labels.iter().rev() {
+            let size = self.sizes[&label];
+            code.insert_str(
+                0,
+                &format!("{}let idx_{} = ({} / {}u) % {}u;\n", indent, label, linear, stride, size),
+            );
+            stride *= size as u64;
+        }
+        code
+    }
+
+    /// Product of all operand elements at the current label indices
+    fn product(&self) -> String {
+        self.spec
+            .inputs
+            .iter()
+            .zip(self.shapes)
+            .enumerate()
+            .map(|(operand, (labels, shape))| {
+                let terms: Vec<String> = labels
+                    .iter()
+                    .zip(shape.strides())
+                    .map(|(label, stride)| match stride {
+                        1 => format!("idx_{}", label),
+                        _ => format!("idx_{} * {}u", label, stride),
+                    })
+                    .collect();
+                let offset = if terms.is_empty() {
+                    "0u".to_string()
+                } else {
+                    terms.join(" + ")
+                };
+                format!("operand{}[{}]", operand, offset)
+            })
+            .collect::<Vec<_>>()
+            .join(" * ")
+    }
+
+    fn loop_nest(&self, contracted: &[char], output_elements: u64) -> String {
+        let mut wgsl = format!("// {}\n", self.spec.equation());
+        wgsl.push_str(&self.bindings());
+        wgsl.push_str(&format!(
+            "@compute @workgroup_size({0})\nfn main(\n    @builtin(global_invocation_id) global_id: vec3<u32>,\n    @builtin(num_workgroups) num_workgroups: vec3<u32>\n) {{\n    let index = global_id.x + global_id.y * num_workgroups.x * {0}u;\n",
+            WORKGROUP_SIZE
+        ));
+        wgsl.push_str(&format!(
+            "    if (index >= {}u) {{\n        return;\n    }}\n",
+            output_elements
+        ));
+        wgsl.push_str(&self.decode(&self.spec.output, "index", "    "));
+
+        if contracted.is_empty() {
+            wgsl.push_str(&format!("    output[index] = {};\n}}\n", self.product()));
+            return wgsl;
+        }
+
+        wgsl.push_str("    var sum = 0.0;\n");
+        let mut indent = "    ".to_string();
+        for &label in contracted {
+            wgsl.push_str(&format!(
+                "{0}for (var idx_{1} = 0u; idx_{1} < {2}u; idx_{1} = idx_{1} + 1u) {{\n",
+                indent, label, self.sizes[&label]
+            ));
+            indent.push_str("    ");
+        }
+        wgsl.push_str(&format!("{}sum = sum + {};\n", indent, self.product()));
+        for _ in contracted {
+            indent.truncate(indent.len() - 4);
+            wgsl.push_str(&format!("{}}}\n", indent));
+        }
+        wgsl.push_str("    output[index] = sum;\n}\n");
+        wgsl
+    }
+
+    fn reduction(&self, contracted: &[char], reduced_elements: u64) -> String {
+        let mut wgsl = format!("// {}\n", self.spec.equation());
+        wgsl.push_str(&self.bindings());
+        wgsl.push_str(&format!("var<workgroup> partial: array<f32, {}>;\n\n", WORKGROUP_SIZE));
+        wgsl.push_str(&format!(
+            "@compute @workgroup_size({})\nfn main(\n    @builtin(local_invocation_id) local_id: vec3<u32>,\n    @builtin(workgroup_id) workgroup_id: vec3<u32>\n) {{\n    let index = workgroup_id.x;\n",
+            WORKGROUP_SIZE
+        ));
+        wgsl.push_str(&self.decode(&self.spec.output, "index", "    "));
+        wgsl.push_str("    var sum = 0.0;\n");
+        wgsl.push_str(&format!(
+            "    for (var r = local_id.x; r < {}u; r = r + {}u) {{\n",
+            reduced_elements, WORKGROUP_SIZE
+        ));
+        wgsl.push_str(&self.decode(contracted, "r", "        "));
+        wgsl.push_str(&format!("        sum = sum + {};\n    }}\n", self.product()));
+        wgsl.push_str(&format!(
+            "    partial[local_id.x] = sum;\n    workgroupBarrier();\n\n    for (var stride = {}u; stride > 0u; stride = stride / 2u) {{\n        if (local_id.x < stride) {{\n            partial[local_id.x] = partial[local_id.x] + partial[local_id.x + stride];\n        }}\n        workgroupBarrier();\n    }}\n\n    if (local_id.x == 0u) {{\n        output[index] = partial[0];\n    }}\n}}\n",
+            WORKGROUP_SIZE / 2
+        ));
+        wgsl
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shader::validate_wgsl;
+
+    fn shapes(dims: &[&[u32]]) -> Vec<TensorShape> {
+        dims.iter().map(|d| TensorShape::new(d.to_vec())).collect()
+    }
+
+    #[test]
+    fn test_parse_einsum() {
+        let spec = parse_einsum("ij, jk").unwrap();
+        assert_eq!(spec.equation(), "ij,jk->ik");
+        assert_eq!(spec.contracted(), vec!['j']);
+
+        let spec = parse_einsum("ii->").unwrap();
+        assert_eq!(spec.equation(), "ii->");
+        assert_eq!(spec.contracted(), vec!['i']);
+
+        assert!(parse_einsum("ij->ii").unwrap_err().contains("more than once"));
+        assert!(parse_einsum("ij->k").unwrap_err().contains("does not appear"));
+        assert!(parse_einsum("...ij->ij").is_err());
+        assert!(parse_einsum("i1->i").is_err());
+    }
+
+    #[test]
+    fn test_shape_validation() {
+        let err = plan_einsum("ij,jk->ik", &shapes(&[&[2, 3], &[4, 5]])).unwrap_err();
+        assert!(err.contains("Label 'j'"));
+        let err = plan_einsum("ij,jk->ik", &shapes(&[&[2, 3, 1], &[3, 5]])).unwrap_err();
+        assert!(err.contains("rank 3"));
+        let err = plan_einsum("ij,jk->ik", &shapes(&[&[2, 3]])).unwrap_err();
+        assert!(err.contains("operand"));
+    }
+
+    #[test]
+    fn test_matmul_route() {
+        let plan = plan_einsum("mk,kn->mn", &shapes(&[&[40, 8], &[8, 20]])).unwrap();
+        assert_eq!(plan.strategy, EinsumStrategy::MatMul);
+        assert_eq!(plan.output_shape.dimensions, vec![40, 20]);
+        assert_eq!(plan.uniform_data, vec![40, 8, 20, 0]);
+        assert_eq!((plan.dispatch.x, plan.dispatch.y), (2, 3));
+        assert_eq!(plan.bindings[3].name, "dims");
+        validate_wgsl(&plan.wgsl).unwrap();
+    }
+
+    #[test]
+    fn test_reduction_route() {
+        let plan = plan_einsum("ij->i", &shapes(&[&[4, 1000]])).unwrap();
+        assert_eq!(plan.strategy, EinsumStrategy::Reduction);
+        assert_eq!(plan.dispatch.x, 4);
+        validate_wgsl(&plan.wgsl).unwrap_or_else(|e| panic!("{}\n{}", e, plan.wgsl));
+
+        let plan = plan_einsum("ii", &shapes(&[&[16, 16]])).unwrap();
+        assert_eq!(plan.strategy, EinsumStrategy::Reduction);
+        assert!(plan.output_shape.dimensions.is_empty());
+        assert!(plan.wgsl.contains("operand0[idx_i * 16u + idx_i]"));
+        validate_wgsl(&plan.wgsl).unwrap_or_else(|e| panic!("{}\n{}", e, plan.wgsl));
+    }
+
+    #[test]
+    fn test_loop_nest_route() {
+        let cases: &[(&str, &[&[u32]])] = &[
+            ("bij,bjk->bik", &[&[2, 3, 4], &[2, 4, 5]]),
+            ("i,j->ij", &[&[3], &[5]]),
+            ("ijk->kji", &[&[2, 3, 4]]),
+            ("bi,ij,bj->b", &[&[8, 3], &[3, 4], &[8, 4]]),
+            ("ij,kj->ik", &[&[2, 3], &[4, 3]]),
+        ];
+        for (spec, dims) in cases {
+            let plan = plan_einsum(spec, &shapes(dims)).unwrap();
+            assert_eq!(plan.strategy, EinsumStrategy::LoopNest, "{}", spec);
+            validate_wgsl(&plan.wgsl).unwrap_or_else(|e| panic!("{}: {}\n{}", spec, e, plan.wgsl));
+        }
+
+        let plan = plan_einsum("bij,bjk->bik", &shapes(&[&[2, 3, 4], &[2, 4, 5]])).unwrap();
+        assert_eq!(plan.output_shape.dimensions, vec![2, 3, 5]);
+        assert_eq!(plan.dispatch.x, 1);
+        assert_eq!(plan.terms, 2 * 3 * 5 * 4);
+        assert!(plan.wgsl.contains("operand1[idx_b * 20u + idx_j * 5u + idx_k]"));
+    }
+}
@@ -1,3 +1,9 @@
 pub mod storage;
+pub mod planner;
+pub mod einsum;
 
 pub use storage::{TensorAccess, TensorDType, TensorMeta, TensorShape};
+pub use planner::{
+    plan_tensor_memory, PlannerStrategy, TensorLiveRange, TensorMemoryPlan, TensorMemoryPlanner,
+};
+pub use einsum::{parse_einsum, plan_einsum, EinsumBinding, EinsumPlan, EinsumSpec, EinsumStrategy};
@@ -0,0 +1,287 @@
+//! Static tensor memory planner
+//!
+//! For a fixed model every tensor's size and lifetime is known ahead of time, so
+//! all tensors can be packed into one arena buffer instead of one buffer each:
+//! - Tensors whose live ranges do not overlap may share bytes
+//! - Greedy-by-size placement (largest first, best-fitting gap)
+//! - Offsets respect the device's minimum storage/uniform offset alignment
+//! - Arena size is rounded to a power of two so it can be carved from a buddy allocator
+
+use super::storage::{TensorAccess, TensorDType, TensorMeta, TensorShape};
+use crate::gpu::limits::DeviceLimits;
+use serde::{Deserialize, Serialize};
+
+/// Placement strategy for the planner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlannerStrategy {
+    /// Place largest tensors first into the best-fitting free gap
+    GreedyBySize,
+    /// Give every tensor its own region (no sharing), useful to rule out aliasing bugs
+    Sequential,
+}
+
+/// A tensor to be placed in the arena
+///
+/// `first_use` and `last_use` are inclusive operator indices in execution order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TensorLiveRange {
+    pub dimensions: Vec<u32>,
+    pub dtype: TensorDType,
+    pub access: TensorAccess,
+    pub first_use: u32,
+    pub last_use: u32,
+}
+
+impl TensorLiveRange {
+    /// Get size in bytes
+    pub fn size_bytes(&self) -> u64 {
+        TensorShape::new(self.dimensions.clone()).total_elements() * self.dtype.size_bytes()
+    }
+
+    fn overlaps(&self, other: &TensorLiveRange) -> bool {
+        self.first_use <= other.last_use && other.first_use <= self.last_use
+    }
+}
+
+/// Result of planning: tensors in input order with `offset` filled in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TensorMemoryPlan {
+    pub tensors: Vec<TensorMeta>,
+    /// Highest byte used by any tensor
+    pub peak_bytes: u64,
+    /// Power-of-two arena size suitable for `buddy_allocator_create`
+    pub arena_size: u64,
+    /// Bytes needed if every tensor had its own buffer
+    pub unshared_bytes: u64,
+}
+
+/// Memory planner configured with device alignment requirements
+pub struct TensorMemoryPlanner {
+    storage_alignment: u64,
+    uniform_alignment: u64,
+}
+
+impl TensorMemoryPlanner {
+    pub fn new(storage_alignment: u32, uniform_alignment: u32) -> Self {
+        Self {
+            storage_alignment: storage_alignment.max(1) as u64,
+            uniform_alignment: uniform_alignment.max(1) as u64,
+        }
+    }
+
+    /// Create planner using alignment limits of a device
+    pub fn from_limits(limits: &DeviceLimits) -> Self {
+        Self::new(
+            limits.min_storage_buffer_offset_alignment,
+            limits.min_uniform_buffer_offset_alignment,
+        )
+    }
+
+    fn alignment_for(&self, access: TensorAccess) -> u64 {
+        match access {
+            TensorAccess::Uniform => self.uniform_alignment,
+            _ => self.storage_alignment,
+        }
+    }
+
+    /// Plan offsets for all tensors inside one arena
+    pub fn plan(
+        &self,
+        tensors: &[TensorLiveRange],
+        strategy: PlannerStrategy,
+        arena_handle: u64,
+    ) -> Result<TensorMemoryPlan, String> {
+        for (i, tensor) in tensors.iter().enumerate() {
+            if tensor.last_use < tensor.first_use {
+                return Err(format!(
+                    "Tensor {} has last_use {} before first_use {}",
+                    i, tensor.last_use, tensor.first_use
+                ));
+            }
+        }
+
+        let offsets = match strategy {
+            PlannerStrategy::GreedyBySize => self.place_greedy_by_size(tensors),
+            PlannerStrategy::Sequential => self.place_sequential(tensors),
+        };
+
+        let peak_bytes = tensors
+            .iter()
+            .zip(&offsets)
+            .map(|(t, &offset)| offset + t.size_bytes())
+            .max()
+            .unwrap_or(0);
+
+        let min_block = self.storage_alignment.max(self.uniform_alignment);
+        let arena_size = peak_bytes.max(min_block).next_power_of_two();
+
+        let planned = tensors
+            .iter()
+            .zip(&offsets)
+            .map(|(t, &offset)| {
+                let mut meta =
+                    TensorMeta::new(arena_handle, t.dimensions.clone(), t.dtype, t.access);
+                meta.offset = offset;
+                meta
+            })
+            .collect();
+
+        Ok(TensorMemoryPlan {
+            tensors: planned,
+            peak_bytes,
+            arena_size,
+            unshared_bytes: tensors.iter().map(|t| t.size_bytes()).sum(),
+        })
+    }
+
+    fn place_sequential(&self, tensors: &[TensorLiveRange]) -> Vec<u64> {
+        let mut cursor = 0u64;
+        tensors
+            .iter()
+            .map(|t| {
+                let offset = align_up(cursor, self.alignment_for(t.access));
+                cursor = offset + t.size_bytes();
+                offset
+            })
+            .collect()
+    }
+
+    fn place_greedy_by_size(&self, tensors: &[TensorLiveRange]) -> Vec<u64> {
+        // Largest first; ties broken by earliest use so the result is deterministic
+        let mut order: Vec<usize> = (0..tensors.len()).collect();
+        order.sort_by(|&a, &b| {
+            tensors[b]
+                .size_bytes()
+                .cmp(&tensors[a].size_bytes())
+                .then(tensors[a].first_use.cmp(&tensors[b].first_use))
+                .then(a.cmp(&b))
+        });
+
+        let mut offsets = vec![0u64; tensors.len()];
+        let mut placed: Vec<usize> = Vec::new();
+
+        for index in order {
+            let tensor = &tensors[index];
+            let size = tensor.size_bytes();
+            let alignment = self.alignment_for(tensor.access);
+
+            // Regions already taken by tensors alive at the same time, sorted by offset
+            let mut conflicts: Vec<(u64, u64)> = placed
+                .iter()
+                .filter(|&&other| tensors[other].overlaps(tensor))
+                .map(|&other| (offsets[other], offsets[other] + tensors[other].size_bytes()))
+                .collect();
+            conflicts.sort();
+
+            // Best fit: smallest gap between live regions that holds the tensor
+            let mut best: Option<(u64, u64)> = None; // (gap size, offset)
+            let mut cursor = 0u64;
+            for (start, end) in conflicts {
+                let candidate = align_up(cursor, alignment);
+                if candidate + size <= start {
+                    let gap = start - candidate;
+                    if best.is_none_or(|(best_gap, _)| gap < best_gap) {
+                        best = Some((gap, candidate));
+                    }
+                }
+                cursor = cursor.max(end);
+            }
+
+            offsets[index] = match best {
+                Some((_, offset)) => offset,
+                None => align_up(cursor, alignment),
+            };
+            placed.push(index);
+        }
+
+        offsets
+    }
+}
+
+/// Plan tensor offsets using the alignment limits of a device
+pub fn plan_tensor_memory(
+    tensors: &[TensorLiveRange],
+    strategy: PlannerStrategy,
+    arena_handle: u64,
+    limits: &DeviceLimits,
+) -> Result<TensorMemoryPlan, String> {
+    TensorMemoryPlanner::from_limits(limits).plan(tensors, strategy, arena_handle)
+}
+
+fn align_up(value: u64, alignment: u64) -> u64 {
+    value.div_ceil(alignment) * alignment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(elements: u32, first_use: u32, last_use: u32) -> TensorLiveRange {
+        TensorLiveRange {
+            dimensions: vec![elements],
+            dtype: TensorDType::Float32,
+            access: TensorAccess::ReadWrite,
+            first_use,
+            last_use,
+        }
+    }
+
+    #[test]
+    fn test_disjoint_lifetimes_share_memory() {
+        let planner = TensorMemoryPlanner::new(256, 256);
+        let tensors = vec![range(256, 0, 1), range(256, 2, 3)];
+        let plan = planner.plan(&tensors, PlannerStrategy::GreedyBySize, 7).unwrap();
+
+        assert_eq!(plan.tensors[0].offset, 0);
+        assert_eq!(plan.tensors[1].offset, 0);
+        assert_eq!(plan.tensors[1].buffer_handle, 7);
+        assert_eq!(plan.peak_bytes, 1024);
+        assert_eq!(plan.unshared_bytes, 2048);
+    }
+
+    #[test]
+    fn test_overlapping_lifetimes_do_not_alias() {
+        let planner = TensorMemoryPlanner::new(256, 256);
+        let tensors = vec![range(100, 0, 2), range(300, 1, 3), range(50, 2, 4)];
+        let plan = planner.plan(&tensors, PlannerStrategy::GreedyBySize, 0).unwrap();
+
+        for (i, a) in plan.tensors.iter().enumerate() {
+            assert_eq!(a.offset % 256, 0);
+            for (j, b) in plan.tensors.iter().enumerate().skip(i + 1) {
+                if tensors[i].overlaps(&tensors[j]) {
+                    let disjoint = a.offset + a.size_bytes() <= b.offset
+                        || b.offset + b.size_bytes() <= a.offset;
+                    assert!(disjoint, "tensors {} and {} alias", i, j);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_gap_is_reused() {
+        // Short-lived tensors share the start of the arena; the long-lived one goes after them
+        let planner = TensorMemoryPlanner::new(4, 4);
+        let tensors = vec![range(16, 0, 0), range(8, 1, 1), range(4, 0, 3)];
+        let plan = planner.plan(&tensors, PlannerStrategy::GreedyBySize, 0).unwrap();
+
+        assert_eq!(plan.tensors[0].offset, 0);
+        assert_eq!(plan.tensors[2].offset, 64);
+        assert_eq!(plan.tensors[1].offset, 0);
+        assert_eq!(plan.peak_bytes, 80);
+        assert_eq!(plan.arena_size, 128);
+    }
+
+    #[test]
+    fn test_sequential_strategy() {
+        let planner = TensorMemoryPlanner::new(256, 256);
+        let tensors = vec![range(10, 0, 0), range(10, 1, 1)];
+        let plan = planner.plan(&tensors, PlannerStrategy::Sequential, 0).unwrap();
+        assert_eq!(plan.tensors[1].offset, 256);
+    }
+
+    #[test]
+    fn test_invalid_live_range() {
+        let planner = TensorMemoryPlanner::new(256, 256);
+        assert!(planner.plan(&[range(4, 3, 1)], PlannerStrategy::GreedyBySize, 0).is_err());
+    }
+}
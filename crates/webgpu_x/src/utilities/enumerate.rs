@@ -0,0 +1,247 @@
+//! Adapter enumeration
+//!
+//! Walks every adapter wgpu can see (Vulkan, Metal, DX12 and GL) and registers each one
+//! in the `find` registry with its real limits and features, so the `find_*` queries work
+//! without manual `register_gpu_device` calls. A GPU exposed through several backends is
+//! registered once per backend, which is what `find_gpus_by_backend` expects.
+//!
+//! Software rasterizers (llvmpipe, lavapipe, SwiftShader, WARP) are registered with
+//! device type "cpu" even when the driver reports them as "other".
+//!
+//! Each adapter carries a [`GPUInfo`] filled from the PCI ID database: architecture,
+//! CUDA compute capability or ROCm gfx target, and the tuning values the vendor helpers
+//! derive from them.
+//!
+//! wgpu does not report VRAM, so it is taken from DRM sysfs where the kernel driver
+//! exposes it (amdgpu); elsewhere the memory size stays unknown.
+
+use super::find::{clear_gpu_registry, insert_registered_gpu, next_gpu_index, RegisteredGPU};
+use crate::gpu::detection::GPUInfo;
+use crate::gpu::limits::DeviceLimits;
+use crate::os::{discover_system_gpus, LinuxGpuDevice};
+use serde::{Deserialize, Serialize};
+
+/// Name fragments of known software adapters (matched case-insensitively)
+const SOFTWARE_ADAPTER_NAMES: &[&str] = &[
+    "llvmpipe",
+    "lavapipe",
+    "softpipe",
+    "swiftshader",
+    "microsoft basic render driver",
+    "warp",
+];
+
+/// One adapter found by wgpu
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnumeratedAdapter {
+    /// Registry index (assigned on registration)
+    pub index: u32,
+    pub name: String,
+    pub vendor_id: u32,
+    pub device_id: u32,
+    /// "discrete", "integrated", "virtual", "cpu" or "other"
+    pub device_type: String,
+    /// wgpu backend name: "vulkan", "metal", "dx12" or "gl"
+    pub backend: String,
+    pub driver: String,
+    pub driver_info: String,
+    /// True for CPU rasterizers, whatever device type the driver reports
+    pub is_software: bool,
+    pub limits: DeviceLimits,
+    /// Supported features in WebGPU naming, e.g. "shader-f16", "timestamp-query"
+    pub features: Vec<String>,
+    /// VRAM in bytes from DRM sysfs; None when the OS does not report it
+    #[serde(default)]
+    pub memory_size: Option<u64>,
+    /// Architecture and tuning facts from the PCI ID database
+    pub gpu_info: GPUInfo,
+}
+
+/// Whether an adapter name belongs to a software rasterizer
+pub fn is_software_adapter(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SOFTWARE_ADAPTER_NAMES.iter().any(|software| name.contains(software))
+}
+
+/// Registry device type for an adapter
+fn device_type_name(device_type: wgpu::DeviceType, name: &str) -> &'static str {
+    if is_software_adapter(name) {
+        return "cpu";
+    }
+    match device_type {
+        wgpu::DeviceType::DiscreteGpu => "discrete",
+        wgpu::DeviceType::IntegratedGpu => "integrated",
+        wgpu::DeviceType::VirtualGpu => "virtual",
+        wgpu::DeviceType::Cpu => "cpu",
+        wgpu::DeviceType::Other => "other",
+    }
+}
+
+/// Feature flags as kebab-case names (`SHADER_F16` becomes "shader-f16")
+pub fn feature_names(features: wgpu::Features) -> Vec<String> {
+    features
+        .iter_names()
+        .map(|(name, _)| name.to_lowercase().replace('_', "-"))
+        .collect()
+}
+
+pub(super) fn describe_adapter(adapter: &wgpu::Adapter) -> EnumeratedAdapter {
+    let info = adapter.get_info();
+    EnumeratedAdapter {
+        index: 0,
+        gpu_info: GPUInfo::from_adapter_info(&info),
+        device_type: device_type_name(info.device_type, &info.name).to_string(),
+        is_software: is_software_adapter(&info.name) || info.device_type == wgpu::DeviceType::Cpu,
+        name: info.name,
+        vendor_id: info.vendor,
+        device_id: info.device,
+        backend: info.backend.to_str().to_string(),
+        driver: info.driver,
+        driver_info: info.driver_info,
+        limits: DeviceLimits::from(&adapter.limits()),
+        features: feature_names(adapter.features()),
+        memory_size: None,
+    }
+}
+
+/// Fill in VRAM from sysfs devices with the same PCI IDs. Identical cards that report
+/// different sizes cannot be told apart, so those stay unknown.
+pub(super) fn fill_memory_sizes(adapters: &mut [EnumeratedAdapter], devices: &[LinuxGpuDevice]) {
+    for adapter in adapters.iter_mut().filter(|a| !a.is_software) {
+        let mut sizes = devices
+            .iter()
+            .filter(|d| d.vendor_id == adapter.vendor_id && d.device_id == adapter.device_id)
+            .map(|d| d.vram_total);
+        if let Some(Some(first)) = sizes.next() {
+            if sizes.all(|size| size == Some(first)) {
+                adapter.memory_size = Some(first);
+            }
+        }
+    }
+}
+
+/// All adapters visible on every backend, without touching the registry
+pub fn enumerate_adapters() -> Vec<EnumeratedAdapter> {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::all(),
+        ..Default::default()
+    });
+    let mut adapters: Vec<EnumeratedAdapter> = instance
+        .enumerate_adapters(wgpu::Backends::all())
+        .iter()
+        .map(describe_adapter)
+        .collect();
+    fill_memory_sizes(&mut adapters, &discover_system_gpus());
+    adapters
+}
+
+/// Register adapters, numbering them after existing entries or from 0 when `replace`
+/// clears the registry first. Returns the adapters with their assigned indices.
+pub fn register_adapters(adapters: Vec<EnumeratedAdapter>, replace: bool) -> Vec<EnumeratedAdapter> {
+    if replace {
+        clear_gpu_registry();
+    }
+    let first = next_gpu_index();
+    adapters
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut adapter)| {
+            adapter.index = first + i as u32;
+            insert_registered_gpu(RegisteredGPU {
+                index: adapter.index,
+                vendor_id: adapter.vendor_id,
+                device_id: adapter.device_id,
+                name: adapter.name.clone(),
+                device_type: adapter.device_type.clone(),
+                backend: adapter.backend.clone(),
+                // 0 is "unknown" in the registry
+                memory_size: adapter.memory_size.unwrap_or(0),
+                limits: Some(adapter.limits.clone()),
+                features: adapter.features.clone(),
+            });
+            adapter
+        })
+        .collect()
+}
+
+/// Enumerate every adapter and register it in the GPU registry
+pub fn enumerate_and_register_gpus(replace: bool) -> Vec<EnumeratedAdapter> {
+    register_adapters(enumerate_adapters(), replace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::find::{find_compute_capable_gpus, find_gpus_by_backend, get_gpu_info};
+
+    fn adapter(name: &str, device_type: wgpu::DeviceType, backend: &str) -> EnumeratedAdapter {
+        EnumeratedAdapter {
+            index: 0,
+            name: name.to_string(),
+            vendor_id: 0x10005,
+            device_id: 0,
+            device_type: device_type_name(device_type, name).to_string(),
+            backend: backend.to_string(),
+            driver: String::new(),
+            driver_info: String::new(),
+            is_software: is_software_adapter(name),
+            limits: DeviceLimits::default(),
+            features: feature_names(wgpu::Features::SHADER_F16 | wgpu::Features::TIMESTAMP_QUERY),
+            memory_size: None,
+            gpu_info: GPUInfo::from_pci_ids(0x10005, 0, backend, ""),
+        }
+    }
+
+    #[test]
+    fn test_software_adapters_are_cpu() {
+        assert_eq!(device_type_name(wgpu::DeviceType::Other, "llvmpipe (LLVM 17.0.6, 256 bits)"), "cpu");
+        assert_eq!(device_type_name(wgpu::DeviceType::Cpu, "lavapipe"), "cpu");
+        assert_eq!(device_type_name(wgpu::DeviceType::DiscreteGpu, "NVIDIA GeForce RTX 4090"), "discrete");
+        assert_eq!(device_type_name(wgpu::DeviceType::Other, "Some GPU"), "other");
+        assert!(is_software_adapter("Microsoft Basic Render Driver"));
+    }
+
+    #[test]
+    fn test_register_adapters() {
+        let registered = register_adapters(
+            vec![
+                adapter("AMD Radeon RX 7900 XTX", wgpu::DeviceType::DiscreteGpu, "vulkan"),
+                adapter("llvmpipe (LLVM 17.0.6, 256 bits)", wgpu::DeviceType::Other, "gl"),
+            ],
+            true,
+        );
+        assert_eq!(registered.iter().map(|a| a.index).collect::<Vec<_>>(), vec![0, 1]);
+
+        let info = get_gpu_info(1);
+        assert_eq!(info.device_type, "cpu");
+        assert_eq!(info.features, vec!["timestamp-query", "shader-f16"]);
+        assert_eq!(info.limits, Some(DeviceLimits::default()));
+        assert_eq!(find_gpus_by_backend("GL".to_string()), vec![1]);
+        assert_eq!(find_compute_capable_gpus(), vec![0]);
+    }
+
+    #[test]
+    fn test_memory_sizes_from_sysfs() {
+        let device = |device_id: u32, vram_total: Option<u64>| LinuxGpuDevice {
+            vendor_id: 0x10005,
+            device_id,
+            vram_total,
+            ..Default::default()
+        };
+        let mut adapters = vec![
+            adapter("AMD Radeon RX 7900 XTX", wgpu::DeviceType::DiscreteGpu, "vulkan"),
+            adapter("AMD Radeon RX 7900 XTX", wgpu::DeviceType::DiscreteGpu, "gl"),
+            adapter("Other GPU", wgpu::DeviceType::DiscreteGpu, "vulkan"),
+            adapter("Twin GPU", wgpu::DeviceType::DiscreteGpu, "vulkan"),
+            adapter("llvmpipe (LLVM 17.0.6, 256 bits)", wgpu::DeviceType::Other, "vulkan"),
+        ];
+        adapters[2].device_id = 1;
+        adapters[3].device_id = 2;
+        fill_memory_sizes(
+            &mut adapters,
+            &[device(0, Some(24 << 30)), device(1, None), device(2, Some(8 << 30)), device(2, Some(16 << 30))],
+        );
+        let sizes: Vec<_> = adapters.iter().map(|a| a.memory_size).collect();
+        assert_eq!(sizes, vec![Some(24 << 30), Some(24 << 30), None, None, None]);
+    }
+}
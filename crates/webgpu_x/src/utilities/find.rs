@@ -1,20 +1,25 @@
 use deno_bindgen::deno_bindgen;
 use serde::{Deserialize, Serialize};
 use crate::gpu::detection::GPUVendor;
+use crate::gpu::limits::DeviceLimits;
 use lazy_static::lazy_static;
 use parking_lot::Mutex;
 use std::collections::HashMap;
 
 /// GPU device registry entry
 #[derive(Debug, Clone)]
-struct RegisteredGPU {
-    index: u32,
-    vendor_id: u32,
-    device_id: u32,
-    name: String,
-    device_type: String, // "discrete", "integrated", "virtual", "cpu"
-    backend: String,
-    memory_size: u64,
+pub(super) struct RegisteredGPU {
+    pub(super) index: u32,
+    pub(super) vendor_id: u32,
+    pub(super) device_id: u32,
+    pub(super) name: String,
+    pub(super) device_type: String, // "discrete", "integrated", "virtual", "cpu"
+    pub(super) backend: String,
+    pub(super) memory_size: u64,
+    /// Limits reported by the adapter (None for manual registrations)
+    pub(super) limits: Option<DeviceLimits>,
+    /// Supported features in WebGPU naming, e.g. "shader-f16"
+    pub(super) features: Vec<String>,
 }
 
 /// Global GPU registry
@@ -32,6 +37,10 @@ pub struct FoundGPUDevice {
     pub name: String,
     pub memory_size: u64,
     pub is_discrete: bool,
+    pub device_type: String,
+    pub backend: String,
+    pub limits: Option<DeviceLimits>,
+    pub features: Vec<String>,
 }
 
 /// Register a GPU device
@@ -53,9 +62,28 @@ pub fn register_gpu_device(
         device_type,
         backend,
         memory_size,
+        limits: None,
+        features: Vec::new(),
     });
 }
 
+/// Insert a fully described entry, replacing any entry at the same index
+pub(super) fn insert_registered_gpu(gpu: RegisteredGPU) {
+    GPU_REGISTRY.lock().insert(gpu.index, gpu);
+}
+
+/// Snapshot of every registered GPU, ordered by index
+pub(super) fn registered_gpus() -> Vec<RegisteredGPU> {
+    let mut gpus: Vec<RegisteredGPU> = GPU_REGISTRY.lock().values().cloned().collect();
+    gpus.sort_by_key(|gpu| gpu.index);
+    gpus
+}
+
+/// First index above every registered GPU
+pub(super) fn next_gpu_index() -> u32 {
+    GPU_REGISTRY.lock().keys().max().map_or(0, |max| max + 1)
+}
+
 /// Clear GPU registry
 pub fn clear_gpu_registry() {
     GPU_REGISTRY.lock().clear();
@@ -317,6 +345,10 @@ pub fn get_gpu_info(index: u32) -> FoundGPUDevice {
             name: gpu.name.clone(),
             memory_size: gpu.memory_size,
             is_discrete: gpu.device_type == "discrete",
+            device_type: gpu.device_type.clone(),
+            backend: gpu.backend.clone(),
+            limits: gpu.limits.clone(),
+            features: gpu.features.clone(),
         }
     } else {
         FoundGPUDevice {
@@ -327,6 +359,10 @@ pub fn get_gpu_info(index: u32) -> FoundGPUDevice {
             name: "Unknown".to_string(),
             memory_size: 0,
             is_discrete: false,
+            device_type: String::new(),
+            backend: String::new(),
+            limits: None,
+            features: Vec::new(),
         }
     }
 }
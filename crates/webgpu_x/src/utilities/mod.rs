@@ -1,5 +1,8 @@
 pub mod detect;
+pub mod enumerate;
 pub mod find;
+pub mod report;
+pub mod select;
 pub mod serialize;
 
 pub use detect::{
@@ -9,6 +12,11 @@ pub use detect::{
     SystemGPUDetection,
 };
 
+pub use enumerate::{
+    enumerate_adapters, enumerate_and_register_gpus, feature_names, is_software_adapter,
+    register_adapters, EnumeratedAdapter,
+};
+
 pub use find::{
     clear_gpu_registry, find_compute_capable_gpus, find_discrete_gpus, find_gpu_with_min_memory,
     find_gpus_by_backend, find_gpus_by_vendor, find_highest_compute_gpu,
@@ -18,6 +26,18 @@ pub use find::{
     gpu_exists, register_gpu_device, FoundGPUDevice,
 };
 
+pub use report::{
+    generate_capability_report, parse_mesa_version, redact_driver_info, system_report, AdapterReport,
+    CapabilityReport, DriverReport, ReportOptions, SystemReport, TextureFormatReport,
+    CAPABILITY_REPORT_VERSION,
+};
+
+pub use select::{
+    list_selection_presets, parse_selection_policy, rank_gpus, selection_preset, CriterionScore,
+    GpuCandidate, GpuSelection, SelectionPolicy, SelectionWeights, DEFAULT_SELECTION_PRESET,
+    SELECTION_PRESETS,
+};
+
 pub use serialize::{
     deserialize_buffer_descriptor, json_get_field, json_merge, json_minify, json_pretty_print,
     json_validate, serialize_bind_group_layout_entry, serialize_blend_state,
@@ -0,0 +1,608 @@
+//! GPU capability reports
+//!
+//! Collects everything needed to triage a rendering bug in one document: the platform
+//! from `detect`, every adapter wgpu can see with its features, full limits and
+//! texture format capabilities, the Vulkan/GL driver (with the Mesa version when it is a
+//! Mesa driver) and, on Linux, the PCI devices and kernel drivers from sysfs.
+//!
+//! The JSON form carries a `version` that is bumped whenever fields change meaning or
+//! are removed. [`CapabilityReport::to_markdown`] renders the same data for pasting into
+//! an issue.
+//!
+//! Redaction removes machine-specific identifiers: PCI bus addresses, subsystem (board)
+//! IDs, device node paths, NUMA placement and the build details of driver strings
+//! (parenthesized notes, `+build` and git suffixes). GPU model, vendor, driver versions
+//! and capabilities are kept since they are the point.
+
+use super::detect::{
+    detect_architecture, detect_cpu_thread_count, detect_endianness, detect_os,
+    detect_pointer_size, detect_preferred_backend, detect_vector_instructions, DetectedBackend,
+};
+use super::enumerate::{describe_adapter, fill_memory_sizes, EnumeratedAdapter};
+use crate::os::linux_drm::{discover_system_gpus, LinuxGpuDevice};
+use serde::{Deserialize, Serialize};
+use wgpu::{AstcBlock, AstcChannel, TextureFormat};
+
+/// JSON report format version
+pub const CAPABILITY_REPORT_VERSION: u32 = 1;
+
+/// Placeholder for redacted strings
+pub const REDACTED: &str = "[redacted]";
+
+/// Driver names (matched case-insensitively) of Mesa drivers that may not say so in
+/// their driver info
+const MESA_DRIVER_NAMES: &[&str] = &[
+    "radv", "anv", "hasvk", "nvk", "turnip", "venus", "v3dv", "panvk", "dozen", "llvmpipe",
+    "lavapipe", "mesa",
+];
+
+/// Formats probed for each adapter, by WebGPU name
+const PROBED_FORMATS: &[(&str, TextureFormat)] = &[
+    ("r8unorm", TextureFormat::R8Unorm),
+    ("r8snorm", TextureFormat::R8Snorm),
+    ("r8uint", TextureFormat::R8Uint),
+    ("r8sint", TextureFormat::R8Sint),
+    ("r16uint", TextureFormat::R16Uint),
+    ("r16sint", TextureFormat::R16Sint),
+    ("r16float", TextureFormat::R16Float),
+    ("rg8unorm", TextureFormat::Rg8Unorm),
+    ("rg8snorm", TextureFormat::Rg8Snorm),
+    ("rg8uint", TextureFormat::Rg8Uint),
+    ("rg8sint", TextureFormat::Rg8Sint),
+    ("r32uint", TextureFormat::R32Uint),
+    ("r32sint", TextureFormat::R32Sint),
+    ("r32float", TextureFormat::R32Float),
+    ("rg16uint", TextureFormat::Rg16Uint),
+    ("rg16sint", TextureFormat::Rg16Sint),
+    ("rg16float", TextureFormat::Rg16Float),
+    ("rgba8unorm", TextureFormat::Rgba8Unorm),
+    ("rgba8unorm-srgb", TextureFormat::Rgba8UnormSrgb),
+    ("rgba8snorm", TextureFormat::Rgba8Snorm),
+    ("rgba8uint", TextureFormat::Rgba8Uint),
+    ("rgba8sint", TextureFormat::Rgba8Sint),
+    ("bgra8unorm", TextureFormat::Bgra8Unorm),
+    ("bgra8unorm-srgb", TextureFormat::Bgra8UnormSrgb),
+    ("rgb9e5ufloat", TextureFormat::Rgb9e5Ufloat),
+    ("rgb10a2uint", TextureFormat::Rgb10a2Uint),
+    ("rgb10a2unorm", TextureFormat::Rgb10a2Unorm),
+    ("rg11b10ufloat", TextureFormat::Rg11b10Float),
+    ("rg32uint", TextureFormat::Rg32Uint),
+    ("rg32sint", TextureFormat::Rg32Sint),
+    ("rg32float", TextureFormat::Rg32Float),
+    ("rgba16uint", TextureFormat::Rgba16Uint),
+    ("rgba16sint", TextureFormat::Rgba16Sint),
+    ("rgba16float", TextureFormat::Rgba16Float),
+    ("rgba32uint", TextureFormat::Rgba32Uint),
+    ("rgba32sint", TextureFormat::Rgba32Sint),
+    ("rgba32float", TextureFormat::Rgba32Float),
+    ("stencil8", TextureFormat::Stencil8),
+    ("depth16unorm", TextureFormat::Depth16Unorm),
+    ("depth24plus", TextureFormat::Depth24Plus),
+    ("depth24plus-stencil8", TextureFormat::Depth24PlusStencil8),
+    ("depth32float", TextureFormat::Depth32Float),
+    ("depth32float-stencil8", TextureFormat::Depth32FloatStencil8),
+    ("bc1-rgba-unorm", TextureFormat::Bc1RgbaUnorm),
+    ("bc3-rgba-unorm", TextureFormat::Bc3RgbaUnorm),
+    ("bc4-r-unorm", TextureFormat::Bc4RUnorm),
+    ("bc5-rg-unorm", TextureFormat::Bc5RgUnorm),
+    ("bc6h-rgb-ufloat", TextureFormat::Bc6hRgbUfloat),
+    ("bc7-rgba-unorm", TextureFormat::Bc7RgbaUnorm),
+    ("etc2-rgb8unorm", TextureFormat::Etc2Rgb8Unorm),
+    ("etc2-rgba8unorm", TextureFormat::Etc2Rgba8Unorm),
+    ("eac-r11unorm", TextureFormat::EacR11Unorm),
+    (
+        "astc-4x4-unorm",
+        TextureFormat::Astc {
+            block: AstcBlock::B4x4,
+            channel: AstcChannel::Unorm,
+        },
+    ),
+];
+
+/// What to include in a report
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReportOptions {
+    /// Strip machine-specific identifiers
+    pub redact: bool,
+}
+
+/// Platform the report was taken on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SystemReport {
+    pub os: String,
+    pub architecture: String,
+    pub endianness: String,
+    /// Bytes
+    pub pointer_size: u32,
+    pub cpu_threads: u32,
+    pub vector_instructions: Vec<String>,
+    pub preferred_backend: DetectedBackend,
+}
+
+/// Driver facts derived from the adapter's driver strings and sysfs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DriverReport {
+    pub is_mesa: bool,
+    /// e.g. "24.0.5-1ubuntu1"
+    pub mesa_version: Option<String>,
+    /// Kernel driver bound to the matching PCI device ("amdgpu", "i915", "nvidia", ...)
+    pub kernel_driver: Option<String>,
+}
+
+/// What one texture format supports on an adapter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextureFormatReport {
+    /// WebGPU name, e.g. "rgba8unorm-srgb"
+    pub format: String,
+    /// "copy-src", "copy-dst", "texture-binding", "storage-binding", "render-attachment";
+    /// empty when the format is unsupported
+    pub usages: Vec<String>,
+    /// "filterable", "blendable", "multisample-x4", "storage-read-write", ...
+    pub flags: Vec<String>,
+}
+
+/// One adapter with everything it reports
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdapterReport {
+    pub adapter: EnumeratedAdapter,
+    pub driver: DriverReport,
+    pub texture_formats: Vec<TextureFormatReport>,
+}
+
+/// Full capability report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapabilityReport {
+    /// [`CAPABILITY_REPORT_VERSION`] at the time of writing
+    pub version: u32,
+    /// "webgpu_x <crate version>"
+    pub generator: String,
+    pub redacted: bool,
+    pub system: SystemReport,
+    pub adapters: Vec<AdapterReport>,
+    /// GPUs from Linux sysfs; empty on other platforms
+    pub pci_devices: Vec<LinuxGpuDevice>,
+}
+
+/// Version in a Mesa driver string, e.g. "4.6 (Core Profile) Mesa 24.0.5-1ubuntu1"
+pub fn parse_mesa_version(driver_info: &str) -> Option<String> {
+    let mut words = driver_info.split_whitespace();
+    words.find(|word| word.eq_ignore_ascii_case("mesa"))?;
+    let version = words
+        .next()?
+        .trim_end_matches(|c: char| !c.is_ascii_alphanumeric());
+    version
+        .starts_with(|c: char| c.is_ascii_digit())
+        .then(|| version.to_string())
+}
+
+/// Driver string with build details removed: "Mesa 24.0.5-1ubuntu1 (git-5a8e4f2)" becomes
+/// "Mesa 24.0.5-1ubuntu1", "1.3.275+git20240101.abc" becomes "1.3.275", and plain versions
+/// such as NVIDIA's "550.54.14" are kept as they are
+pub fn redact_driver_info(driver_info: &str) -> String {
+    let mut unparenthesized = String::with_capacity(driver_info.len());
+    let mut depth = 0usize;
+    for c in driver_info.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => unparenthesized.push(c),
+            _ => {}
+        }
+    }
+    unparenthesized
+        .split_whitespace()
+        .filter_map(|word| {
+            let word = word.split('+').next().unwrap_or_default();
+            // A git suffix starts the word or follows a separator ("-git5a8e4f2", "~git")
+            let lower = word.to_ascii_lowercase();
+            let git = lower
+                .match_indices("git")
+                .map(|(at, _)| at)
+                .find(|&at| at == 0 || !lower.as_bytes()[at - 1].is_ascii_alphanumeric());
+            let word = &word[..git.unwrap_or(word.len())];
+            let word = word.trim_end_matches(|c: char| !c.is_ascii_alphanumeric());
+            (!word.is_empty()).then_some(word)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn driver_report(adapter: &EnumeratedAdapter, pci_devices: &[LinuxGpuDevice]) -> DriverReport {
+    let mesa_version = parse_mesa_version(&adapter.driver_info);
+    let driver = adapter.driver.to_lowercase();
+    DriverReport {
+        is_mesa: mesa_version.is_some() || MESA_DRIVER_NAMES.iter().any(|name| driver.contains(name)),
+        mesa_version,
+        kernel_driver: pci_devices
+            .iter()
+            .find(|pci| pci.vendor_id == adapter.vendor_id && pci.device_id == adapter.device_id)
+            .and_then(|pci| pci.driver.clone()),
+    }
+}
+
+fn kebab(name: &str) -> String {
+    name.to_lowercase().replace('_', "-")
+}
+
+fn texture_format_reports(adapter: &wgpu::Adapter) -> Vec<TextureFormatReport> {
+    PROBED_FORMATS
+        .iter()
+        .map(|(name, format)| {
+            let features = adapter.get_texture_format_features(*format);
+            TextureFormatReport {
+                format: name.to_string(),
+                usages: features.allowed_usages.iter_names().map(|(n, _)| kebab(n)).collect(),
+                flags: features.flags.iter_names().map(|(n, _)| kebab(n)).collect(),
+            }
+        })
+        .collect()
+}
+
+/// Platform facts from `detect`
+pub fn system_report() -> SystemReport {
+    SystemReport {
+        os: detect_os(),
+        architecture: detect_architecture(),
+        endianness: detect_endianness(),
+        pointer_size: detect_pointer_size(),
+        cpu_threads: detect_cpu_thread_count(),
+        vector_instructions: detect_vector_instructions(),
+        preferred_backend: detect_preferred_backend(),
+    }
+}
+
+/// Report on this machine; does not touch the GPU registry
+pub fn generate_capability_report(options: &ReportOptions) -> CapabilityReport {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::all(),
+        ..Default::default()
+    });
+    let pci_devices = discover_system_gpus();
+    let adapters = instance
+        .enumerate_adapters(wgpu::Backends::all())
+        .iter()
+        .enumerate()
+        .map(|(index, adapter)| {
+            let mut described = describe_adapter(adapter);
+            described.index = index as u32;
+            fill_memory_sizes(std::slice::from_mut(&mut described), &pci_devices);
+            AdapterReport {
+                driver: driver_report(&described, &pci_devices),
+                adapter: described,
+                texture_formats: texture_format_reports(adapter),
+            }
+        })
+        .collect();
+
+    let mut report = CapabilityReport::new(system_report(), adapters, pci_devices);
+    if options.redact {
+        report.redact();
+    }
+    report
+}
+
+/// Escape a value for a Markdown table cell
+fn cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+fn join_or_dash(values: &[String]) -> String {
+    if values.is_empty() {
+        "-".to_string()
+    } else {
+        values.join(", ")
+    }
+}
+
+impl CapabilityReport {
+    pub fn new(system: SystemReport, adapters: Vec<AdapterReport>, pci_devices: Vec<LinuxGpuDevice>) -> Self {
+        Self {
+            version: CAPABILITY_REPORT_VERSION,
+            generator: format!("webgpu_x {}", env!("CARGO_PKG_VERSION")),
+            redacted: false,
+            system,
+            adapters,
+            pci_devices,
+        }
+    }
+
+    /// Strip machine-specific identifiers in place
+    pub fn redact(&mut self) {
+        for report in &mut self.adapters {
+            report.adapter.driver_info = redact_driver_info(&report.adapter.driver_info);
+            report.adapter.gpu_info.driver_version = redact_driver_info(&report.adapter.gpu_info.driver_version);
+        }
+        for pci in &mut self.pci_devices {
+            if !pci.pci_address.is_empty() {
+                pci.pci_address = REDACTED.to_string();
+            }
+            pci.subsystem_vendor_id = 0;
+            pci.subsystem_device_id = 0;
+            pci.card_node = None;
+            pci.render_node = None;
+            pci.numa_node = None;
+        }
+        self.redacted = true;
+    }
+
+    /// Human-readable summary for bug reports
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        let mut line = |text: String| {
+            md.push_str(&text);
+            md.push('\n');
+        };
+
+        line("# GPU capability report".to_string());
+        line(String::new());
+        line(format!(
+            "Report format v{}, generated by {}{}.",
+            self.version,
+            self.generator,
+            if self.redacted { "; identifying fields redacted" } else { "" }
+        ));
+        line(String::new());
+
+        let system = &self.system;
+        line("## System".to_string());
+        line(String::new());
+        line("| Property | Value |".to_string());
+        line("|---|---|".to_string());
+        line(format!("| OS | {} |", system.os));
+        line(format!("| Architecture | {} ({}-endian, {}-bit) |", system.architecture, system.endianness, system.pointer_size * 8));
+        line(format!("| CPU threads | {} |", system.cpu_threads));
+        line(format!("| Vector instructions | {} |", join_or_dash(&system.vector_instructions)));
+        line(format!("| Preferred backend | {:?} |", system.preferred_backend));
+        line(String::new());
+
+        if self.adapters.is_empty() {
+            line("No adapters found.".to_string());
+            line(String::new());
+        }
+        for report in &self.adapters {
+            let adapter = &report.adapter;
+            line(format!("## Adapter {}: {}", adapter.index, adapter.name));
+            line(String::new());
+            line("| Property | Value |".to_string());
+            line("|---|---|".to_string());
+            line(format!("| Backend | {} |", adapter.backend));
+            line(format!("| Device type | {} |", adapter.device_type));
+            line(format!("| Vendor / device ID | 0x{:04x} / 0x{:04x} |", adapter.vendor_id, adapter.device_id));
+            let gpu = &adapter.gpu_info;
+            if let Some(architecture) = &gpu.architecture {
+                line(format!("| Architecture | {} |", cell(architecture)));
+            }
+            if let Some((major, minor)) = gpu.cuda_compute_capability {
+                line(format!("| CUDA compute capability | {}.{} |", major, minor));
+            }
+            if let Some(target) = &gpu.gfx_target {
+                line(format!("| gfx target | {} |", cell(target)));
+            }
+            if let Some(size) = gpu.subgroup_size {
+                line(format!("| Subgroup size | {} |", size));
+            }
+            if let Some(size) = gpu.optimal_workgroup_size {
+                line(format!("| Optimal workgroup size | {} |", size));
+            }
+            if let Some(bytes) = adapter.memory_size {
+                line(format!("| VRAM | {} MiB |", bytes / (1024 * 1024)));
+            }
+            line(format!("| Driver | {} |", cell(&adapter.driver)));
+            line(format!("| Driver info | {} |", cell(&adapter.driver_info)));
+            if let Some(version) = &report.driver.mesa_version {
+                line(format!("| Mesa | {} |", version));
+            } else if report.driver.is_mesa {
+                line("| Mesa | yes |".to_string());
+            }
+            if let Some(kernel_driver) = &report.driver.kernel_driver {
+                line(format!("| Kernel driver | {} |", kernel_driver));
+            }
+            line(format!("| Software rasterizer | {} |", if adapter.is_software { "yes" } else { "no" }));
+            line(String::new());
+
+            line(format!("### Features ({})", adapter.features.len()));
+            line(String::new());
+            let features: Vec<String> = adapter.features.iter().map(|f| format!("`{}`", f)).collect();
+            line(join_or_dash(&features));
+            line(String::new());
+
+            line("### Limits".to_string());
+            line(String::new());
+            line("| Limit | Value |".to_string());
+            line("|---|---|".to_string());
+            for (name, value) in adapter.limits.to_map() {
+                line(format!("| {} | {} |", name, value));
+            }
+            line(String::new());
+
+            let (supported, unsupported): (Vec<_>, Vec<_>) =
+                report.texture_formats.iter().partition(|f| !f.usages.is_empty());
+            line(format!(
+                "### Texture formats ({} of {} supported)",
+                supported.len(),
+                report.texture_formats.len()
+            ));
+            line(String::new());
+            if !supported.is_empty() {
+                line("| Format | Usages | Capabilities |".to_string());
+                line("|---|---|---|".to_string());
+                for format in &supported {
+                    line(format!("| {} | {} | {} |", format.format, join_or_dash(&format.usages), join_or_dash(&format.flags)));
+                }
+                line(String::new());
+            }
+            if !unsupported.is_empty() {
+                let names: Vec<String> = unsupported.iter().map(|f| f.format.clone()).collect();
+                line(format!("Unsupported: {}", names.join(", ")));
+                line(String::new());
+            }
+        }
+
+        if !self.pci_devices.is_empty() {
+            line("## PCI devices".to_string());
+            line(String::new());
+            line("| Address | Vendor:Device | Kernel driver | VRAM | Boot VGA |".to_string());
+            line("|---|---|---|---|---|".to_string());
+            for pci in &self.pci_devices {
+                line(format!(
+                    "| {} | {:04x}:{:04x} | {} | {} | {} |",
+                    if pci.pci_address.is_empty() { "-" } else { &pci.pci_address },
+                    pci.vendor_id,
+                    pci.device_id,
+                    pci.driver.as_deref().unwrap_or("-"),
+                    pci.vram_total
+                        .map(|bytes| format!("{} MiB", bytes / (1024 * 1024)))
+                        .unwrap_or_else(|| "-".to_string()),
+                    if pci.boot_vga { "yes" } else { "no" }
+                ));
+            }
+            line(String::new());
+        }
+
+        md
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpu::detection::GPUInfo;
+    use crate::gpu::limits::DeviceLimits;
+
+    fn sample_report() -> CapabilityReport {
+        let adapter = EnumeratedAdapter {
+            index: 0,
+            name: "AMD Radeon RX 7900 XTX (RADV NAVI31)".to_string(),
+            vendor_id: 0x1002,
+            device_id: 0x744C,
+            device_type: "discrete".to_string(),
+            backend: "vulkan".to_string(),
+            driver: "radv".to_string(),
+            driver_info: "Mesa 24.0.5-1ubuntu1 (git-5a8e4f2)".to_string(),
+            is_software: false,
+            limits: DeviceLimits::default(),
+            features: vec!["shader-f16".to_string(), "timestamp-query".to_string()],
+            memory_size: Some(24 * 1024 * 1024 * 1024),
+            gpu_info: GPUInfo::from_pci_ids(0x1002, 0x744C, "vulkan", "Mesa 24.0.5-1ubuntu1 (git-5a8e4f2)"),
+        };
+        let pci = LinuxGpuDevice {
+            pci_address: "0000:03:00.0".to_string(),
+            vendor_id: 0x1002,
+            device_id: 0x744C,
+            subsystem_vendor_id: 0x1DA2,
+            subsystem_device_id: 0xE471,
+            class_code: 0x030000,
+            driver: Some("amdgpu".to_string()),
+            vram_total: Some(24 * 1024 * 1024 * 1024),
+            card_node: Some("/dev/dri/card1".to_string()),
+            render_node: Some("/dev/dri/renderD128".to_string()),
+            boot_vga: true,
+            numa_node: Some(0),
+            ..Default::default()
+        };
+        let formats = vec![
+            TextureFormatReport {
+                format: "rgba8unorm".to_string(),
+                usages: vec!["copy-src".to_string(), "render-attachment".to_string()],
+                flags: vec!["filterable".to_string(), "blendable".to_string()],
+            },
+            TextureFormatReport {
+                format: "astc-4x4-unorm".to_string(),
+                usages: Vec::new(),
+                flags: Vec::new(),
+            },
+        ];
+        let pci_devices = vec![pci];
+        let adapters = vec![AdapterReport {
+            driver: driver_report(&adapter, &pci_devices),
+            adapter,
+            texture_formats: formats,
+        }];
+        CapabilityReport::new(system_report(), adapters, pci_devices)
+    }
+
+    #[test]
+    fn test_mesa_version() {
+        assert_eq!(parse_mesa_version("Mesa 24.0.5-1ubuntu1").as_deref(), Some("24.0.5-1ubuntu1"));
+        assert_eq!(
+            parse_mesa_version("4.6 (Core Profile) Mesa 23.3.6 (git-abc)").as_deref(),
+            Some("23.3.6")
+        );
+        assert_eq!(parse_mesa_version("550.54.14"), None);
+        assert_eq!(parse_mesa_version("Mesa"), None);
+
+        let report = sample_report();
+        let driver = &report.adapters[0].driver;
+        assert!(driver.is_mesa);
+        assert_eq!(driver.mesa_version.as_deref(), Some("24.0.5-1ubuntu1"));
+        assert_eq!(driver.kernel_driver.as_deref(), Some("amdgpu"));
+    }
+
+    #[test]
+    fn test_json_is_versioned() {
+        let json = serde_json::to_value(sample_report()).unwrap();
+        assert_eq!(json["version"], CAPABILITY_REPORT_VERSION);
+        assert_eq!(json["redacted"], false);
+        assert_eq!(json["adapters"][0]["adapter"]["limits"]["maxBindGroups"], 4);
+        assert_eq!(json["adapters"][0]["texture_formats"][0]["format"], "rgba8unorm");
+        assert_eq!(json["adapters"][0]["adapter"]["gpu_info"]["gfx_target"], "gfx1100");
+        assert_eq!(json["pci_devices"][0]["pci_address"], "0000:03:00.0");
+    }
+
+    #[test]
+    fn test_redaction() {
+        let mut report = sample_report();
+        report.redact();
+        assert!(report.redacted);
+        let adapter = &report.adapters[0];
+        assert_eq!(adapter.adapter.driver_info, "Mesa 24.0.5-1ubuntu1");
+        assert_eq!(adapter.adapter.gpu_info.driver_version, "Mesa 24.0.5-1ubuntu1");
+        assert_eq!(adapter.adapter.name, "AMD Radeon RX 7900 XTX (RADV NAVI31)");
+        assert_eq!(adapter.driver.mesa_version.as_deref(), Some("24.0.5-1ubuntu1"));
+
+        let pci = &report.pci_devices[0];
+        assert_eq!(pci.pci_address, REDACTED);
+        assert_eq!((pci.subsystem_vendor_id, pci.subsystem_device_id), (0, 0));
+        assert_eq!(pci.card_node, None);
+        assert_eq!(pci.numa_node, None);
+        assert_eq!(pci.driver.as_deref(), Some("amdgpu"));
+
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(!json.contains("0000:03:00.0"));
+        assert!(!json.contains("renderD128"));
+        assert!(!json.contains("git-5a8e4f2"));
+
+        assert_eq!(redact_driver_info("550.54.14"), "550.54.14");
+        assert_eq!(redact_driver_info("4.6.0 NVIDIA 550.54.14"), "4.6.0 NVIDIA 550.54.14");
+        assert_eq!(redact_driver_info("1.3.275+git20240101.abcdef"), "1.3.275");
+        assert_eq!(redact_driver_info("Mesa 24.1.0-devel-git5a8e4f2"), "Mesa 24.1.0-devel");
+        assert_eq!(redact_driver_info("4.6 (Core Profile) Mesa 23.3.6 (git-abc)"), "4.6 Mesa 23.3.6");
+        assert_eq!(redact_driver_info("Digital 1.0"), "Digital 1.0");
+        assert_eq!(redact_driver_info(""), "");
+    }
+
+    #[test]
+    fn test_markdown() {
+        let md = sample_report().to_markdown();
+        assert!(md.starts_with("# GPU capability report\n"));
+        assert!(md.contains("## Adapter 0: AMD Radeon RX 7900 XTX (RADV NAVI31)"));
+        assert!(md.contains("| Mesa | 24.0.5-1ubuntu1 |"));
+        assert!(md.contains("| Kernel driver | amdgpu |"));
+        assert!(md.contains("| Architecture | RDNA3 |"));
+        assert!(md.contains("| Subgroup size | 32 |"));
+        assert!(md.contains("| Optimal workgroup size | 256 |"));
+        assert!(md.contains("`shader-f16`, `timestamp-query`"));
+        assert!(md.contains("| maxBindGroups | 4 |"));
+        assert!(md.contains("### Texture formats (1 of 2 supported)"));
+        assert!(md.contains("| rgba8unorm | copy-src, render-attachment | filterable, blendable |"));
+        assert!(md.contains("Unsupported: astc-4x4-unorm"));
+        assert!(md.contains("| 0000:03:00.0 | 1002:744c | amdgpu | 24576 MiB | yes |"));
+
+        let mut redacted = sample_report();
+        redacted.redact();
+        let md = redacted.to_markdown();
+        assert!(md.contains("identifying fields redacted"));
+        assert!(!md.contains("0000:03:00.0"));
+    }
+}
@@ -0,0 +1,502 @@
+//! Explainable GPU selection
+//!
+//! Scores every registered GPU against a [`SelectionPolicy`] and returns all candidates
+//! ranked, each with a per-criterion breakdown, instead of a bare index. A policy has:
+//! - weights for the scored criteria: device type, memory, vendor, backend and power
+//! - per-value scores (0.0 to 1.0) for device types, vendors and backends
+//! - hard requirements: features, minimum limits and memory; GPUs that miss one are
+//!   still listed with the reasons, but ranked last and never selected
+//!
+//! A memory size of 0 means the GPU did not report one. Such GPUs are not scored on
+//! memory and not rejected by `min_memory_bytes`; the breakdown says "unknown".
+//!
+//! Built-in presets mirror the workloads of `find_optimal_gpu_for_workload`. User JSON
+//! starts from a preset and overrides what it lists:
+//! `{"preset": "ml", "weights": {"memory": 3.0}, "vendor_scores": {"amd": 1.0},
+//!   "required_features": ["shader-f16"], "exclude": ["llvmpipe"]}`
+
+use super::find::{registered_gpus, RegisteredGPU};
+use crate::gpu::detection::detect_gpu_vendor_enum;
+use crate::gpu::limits::DeviceLimits;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Preset used when a policy names none
+pub const DEFAULT_SELECTION_PRESET: &str = "balanced";
+
+/// Built-in presets
+pub const SELECTION_PRESETS: &[&str] = &["balanced", "compute", "graphics", "ml", "power", "memory"];
+
+/// Relative importance of each scored criterion
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelectionWeights {
+    pub device_type: f64,
+    pub memory: f64,
+    pub vendor: f64,
+    pub backend: f64,
+    pub power: f64,
+}
+
+/// How the GPU should be chosen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelectionPolicy {
+    /// Preset this policy started from
+    pub preset: String,
+    pub weights: SelectionWeights,
+    /// Keyed by registry device type: "discrete", "integrated", "virtual", "cpu"
+    pub device_type_scores: BTreeMap<String, f64>,
+    /// Keyed by vendor: "nvidia", "amd", "intel", "apple", "qualcomm", "arm", "unknown"
+    pub vendor_scores: BTreeMap<String, f64>,
+    /// Keyed by backend: "vulkan", "metal", "dx12", "gl"
+    pub backend_scores: BTreeMap<String, f64>,
+    /// "high-performance", "low-power" or "none"
+    pub power_preference: String,
+    /// Features every candidate must support, e.g. "shader-f16"
+    pub required_features: Vec<String>,
+    /// Limits every candidate must meet, by WebGPU name; `min*` limits are upper bounds
+    pub min_limits: BTreeMap<String, u64>,
+    /// GPUs with unknown memory (0) are not held to this
+    pub min_memory_bytes: u64,
+    /// Case-insensitive name fragments of GPUs never to select
+    pub exclude: Vec<String>,
+}
+
+/// JSON form of a policy; everything but `preset` overrides the preset
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PolicySource {
+    preset: Option<String>,
+    weights: BTreeMap<String, f64>,
+    device_type_scores: BTreeMap<String, f64>,
+    vendor_scores: BTreeMap<String, f64>,
+    backend_scores: BTreeMap<String, f64>,
+    power_preference: Option<String>,
+    required_features: Vec<String>,
+    min_limits: BTreeMap<String, u64>,
+    min_memory_bytes: Option<u64>,
+    exclude: Vec<String>,
+}
+
+/// One criterion's contribution to a candidate's score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CriterionScore {
+    pub criterion: String,
+    pub weight: f64,
+    /// 0.0 to 1.0
+    pub score: f64,
+    /// weight * score
+    pub weighted: f64,
+    pub reason: String,
+}
+
+/// A registered GPU as the policy sees it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GpuCandidate {
+    pub index: u32,
+    pub name: String,
+    pub vendor: String,
+    pub device_type: String,
+    pub backend: String,
+    /// False when a requirement is not met; see `rejections`
+    pub eligible: bool,
+    pub rejections: Vec<String>,
+    /// Sum of `breakdown[].weighted`
+    pub total_score: f64,
+    pub breakdown: Vec<CriterionScore>,
+}
+
+/// Ranked candidates, best first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GpuSelection {
+    pub policy: SelectionPolicy,
+    /// Index of the best eligible GPU
+    pub selected: Option<u32>,
+    pub candidates: Vec<GpuCandidate>,
+}
+
+fn scores(entries: &[(&str, f64)]) -> BTreeMap<String, f64> {
+    entries.iter().map(|(key, score)| (key.to_string(), *score)).collect()
+}
+
+/// A built-in preset by name
+pub fn selection_preset(name: &str) -> Option<SelectionPolicy> {
+    let (weights, vendors, power) = match name {
+        "balanced" => (
+            [2.0, 1.0, 0.5, 0.5, 0.5],
+            [("nvidia", 1.0), ("amd", 0.9), ("apple", 0.9), ("intel", 0.6)],
+            "high-performance",
+        ),
+        // Discrete NVIDIA > discrete AMD > Apple > Intel, as find_highest_compute_gpu
+        "compute" => (
+            [2.0, 1.0, 1.0, 0.5, 0.5],
+            [("nvidia", 1.0), ("amd", 0.8), ("apple", 0.6), ("intel", 0.4)],
+            "high-performance",
+        ),
+        "graphics" => (
+            [3.0, 0.5, 0.5, 0.5, 0.5],
+            [("nvidia", 1.0), ("amd", 1.0), ("apple", 0.9), ("intel", 0.6)],
+            "high-performance",
+        ),
+        // Discrete NVIDIA (tensor cores) > AMD (matrix cores) > Apple > Intel, then memory,
+        // as find_ml_optimized_gpu; the vendor score is the only proxy for matrix hardware
+        "ml" => (
+            [2.0, 1.0, 2.0, 0.5, 0.5],
+            [("nvidia", 1.0), ("amd", 0.6), ("apple", 0.5), ("intel", 0.3)],
+            "high-performance",
+        ),
+        "power" => (
+            [0.5, 0.25, 0.25, 0.25, 3.0],
+            [("nvidia", 0.5), ("amd", 0.5), ("apple", 1.0), ("intel", 0.8)],
+            "low-power",
+        ),
+        "memory" => (
+            [0.5, 3.0, 0.25, 0.25, 0.25],
+            [("nvidia", 1.0), ("amd", 1.0), ("apple", 1.0), ("intel", 1.0)],
+            "none",
+        ),
+        _ => return None,
+    };
+    let [device_type, memory, vendor, backend, power_weight] = weights;
+    let mut vendor_scores = scores(&vendors);
+    for other in ["qualcomm", "arm", "unknown"] {
+        vendor_scores.insert(other.to_string(), 0.2);
+    }
+    Some(SelectionPolicy {
+        preset: name.to_string(),
+        weights: SelectionWeights {
+            device_type,
+            memory,
+            vendor,
+            backend,
+            power: power_weight,
+        },
+        device_type_scores: scores(&[("discrete", 1.0), ("integrated", 0.5), ("virtual", 0.3), ("cpu", 0.0)]),
+        vendor_scores,
+        // Native APIs ahead of GL
+        backend_scores: scores(&[("vulkan", 1.0), ("metal", 1.0), ("dx12", 1.0), ("gl", 0.3)]),
+        power_preference: power.to_string(),
+        required_features: Vec::new(),
+        min_limits: BTreeMap::new(),
+        min_memory_bytes: 0,
+        exclude: Vec::new(),
+    })
+}
+
+/// Every built-in preset
+pub fn list_selection_presets() -> Vec<SelectionPolicy> {
+    SELECTION_PRESETS.iter().filter_map(|name| selection_preset(name)).collect()
+}
+
+/// Parse a user policy, starting from its `preset` (default "balanced")
+pub fn parse_selection_policy(policy_json: &str) -> Result<SelectionPolicy, String> {
+    let source: PolicySource = if policy_json.trim().is_empty() {
+        PolicySource::default()
+    } else {
+        serde_json::from_str(policy_json).map_err(|e| format!("Invalid selection policy: {}", e))?
+    };
+    let preset = source.preset.as_deref().unwrap_or(DEFAULT_SELECTION_PRESET);
+    let mut policy = selection_preset(preset).ok_or_else(|| format!("Unknown selection preset '{}'", preset))?;
+
+    for (criterion, weight) in source.weights {
+        let slot = match criterion.as_str() {
+            "device_type" => &mut policy.weights.device_type,
+            "memory" => &mut policy.weights.memory,
+            "vendor" => &mut policy.weights.vendor,
+            "backend" => &mut policy.weights.backend,
+            "power" => &mut policy.weights.power,
+            _ => return Err(format!("Unknown selection criterion '{}'", criterion)),
+        };
+        if !weight.is_finite() || weight < 0.0 {
+            return Err(format!("Weight for '{}' must be a non-negative number", criterion));
+        }
+        *slot = weight;
+    }
+    policy.device_type_scores.extend(source.device_type_scores);
+    policy.vendor_scores.extend(source.vendor_scores);
+    policy.backend_scores.extend(source.backend_scores);
+    if let Some(power) = source.power_preference {
+        if !matches!(power.as_str(), "high-performance" | "low-power" | "none") {
+            return Err(format!("Unknown power preference '{}'", power));
+        }
+        policy.power_preference = power;
+    }
+
+    let known_limits = DeviceLimits::default().to_map();
+    if let Some(unknown) = source.min_limits.keys().find(|name| !known_limits.contains_key(*name)) {
+        return Err(format!("Unknown limit '{}'", unknown));
+    }
+    policy.required_features.extend(source.required_features);
+    policy.min_limits.extend(source.min_limits);
+    if let Some(bytes) = source.min_memory_bytes {
+        policy.min_memory_bytes = bytes;
+    }
+    policy.exclude.extend(source.exclude);
+    Ok(policy)
+}
+
+fn vendor_key(vendor_id: u32) -> String {
+    format!("{:?}", detect_gpu_vendor_enum(vendor_id)).to_lowercase()
+}
+
+fn format_gib(bytes: u64) -> String {
+    format!("{:.1} GiB", bytes as f64 / (1024.0 * 1024.0 * 1024.0))
+}
+
+/// Why `gpu` cannot be selected under `policy`
+fn rejections(gpu: &RegisteredGPU, policy: &SelectionPolicy) -> Vec<String> {
+    let mut reasons = Vec::new();
+    let name = gpu.name.to_lowercase();
+    for fragment in &policy.exclude {
+        if name.contains(&fragment.to_lowercase()) {
+            reasons.push(format!("excluded by '{}'", fragment));
+        }
+    }
+    if gpu.memory_size != 0 && gpu.memory_size < policy.min_memory_bytes {
+        reasons.push(format!(
+            "memory {} is below the required {}",
+            format_gib(gpu.memory_size),
+            format_gib(policy.min_memory_bytes)
+        ));
+    }
+    for feature in &policy.required_features {
+        if !gpu.features.contains(feature) {
+            reasons.push(format!("missing feature '{}'", feature));
+        }
+    }
+    if !policy.min_limits.is_empty() {
+        match &gpu.limits {
+            None => reasons.push("limits not reported; register through adapter enumeration".to_string()),
+            Some(limits) => {
+                let actual = limits.to_map();
+                for (limit, &required) in &policy.min_limits {
+                    let value = actual.get(limit).copied().unwrap_or(0);
+                    let met = if DeviceLimits::lower_is_better(limit) {
+                        value <= required
+                    } else {
+                        value >= required
+                    };
+                    if !met {
+                        reasons.push(format!("{} is {}, requires {}", limit, value, required));
+                    }
+                }
+            }
+        }
+    }
+    reasons
+}
+
+fn criterion(criterion: &str, weight: f64, score: f64, reason: String) -> CriterionScore {
+    let score = score.clamp(0.0, 1.0);
+    CriterionScore {
+        criterion: criterion.to_string(),
+        weight,
+        score,
+        weighted: weight * score,
+        reason,
+    }
+}
+
+fn score_gpu(gpu: &RegisteredGPU, policy: &SelectionPolicy, max_memory: u64) -> Vec<CriterionScore> {
+    let weights = &policy.weights;
+    let vendor = vendor_key(gpu.vendor_id);
+    let backend = gpu.backend.to_lowercase();
+
+    let memory = if gpu.memory_size == 0 {
+        criterion("memory", 0.0, 0.0, "memory size unknown; not scored".to_string())
+    } else {
+        criterion(
+            "memory",
+            weights.memory,
+            gpu.memory_size as f64 / max_memory as f64,
+            format!("{} of the largest {}", format_gib(gpu.memory_size), format_gib(max_memory)),
+        )
+    };
+
+    let power_score = match (policy.power_preference.as_str(), gpu.device_type.as_str()) {
+        ("low-power", "integrated") => 1.0,
+        ("low-power", "cpu" | "virtual") => 0.5,
+        ("low-power", _) => 0.1,
+        ("high-performance", "discrete") => 1.0,
+        ("high-performance", "integrated") => 0.4,
+        ("high-performance", _) => 0.1,
+        _ => 0.0,
+    };
+
+    vec![
+        criterion(
+            "device_type",
+            weights.device_type,
+            policy.device_type_scores.get(&gpu.device_type).copied().unwrap_or(0.0),
+            format!("{} device", gpu.device_type),
+        ),
+        memory,
+        criterion(
+            "vendor",
+            weights.vendor,
+            policy.vendor_scores.get(&vendor).copied().unwrap_or(0.0),
+            format!("vendor {}", vendor),
+        ),
+        criterion(
+            "backend",
+            weights.backend,
+            policy.backend_scores.get(&backend).copied().unwrap_or(0.0),
+            format!("backend {}", backend),
+        ),
+        criterion(
+            "power",
+            weights.power,
+            power_score,
+            format!("{} preference on a {} device", policy.power_preference, gpu.device_type),
+        ),
+    ]
+}
+
+/// Rank `gpus` under `policy`
+pub(super) fn rank_candidates(gpus: &[RegisteredGPU], policy: &SelectionPolicy) -> GpuSelection {
+    let max_memory = gpus.iter().map(|gpu| gpu.memory_size).max().unwrap_or(0);
+    let mut candidates: Vec<GpuCandidate> = gpus
+        .iter()
+        .map(|gpu| {
+            let breakdown = score_gpu(gpu, policy, max_memory);
+            let rejections = rejections(gpu, policy);
+            GpuCandidate {
+                index: gpu.index,
+                name: gpu.name.clone(),
+                vendor: vendor_key(gpu.vendor_id),
+                device_type: gpu.device_type.clone(),
+                backend: gpu.backend.clone(),
+                eligible: rejections.is_empty(),
+                rejections,
+                total_score: breakdown.iter().map(|c| c.weighted).sum(),
+                breakdown,
+            }
+        })
+        .collect();
+
+    // Eligible first, then by score; ties keep registry order
+    candidates.sort_by(|a, b| {
+        b.eligible
+            .cmp(&a.eligible)
+            .then(b.total_score.total_cmp(&a.total_score))
+            .then(a.index.cmp(&b.index))
+    });
+
+    GpuSelection {
+        policy: policy.clone(),
+        selected: candidates.first().filter(|c| c.eligible).map(|c| c.index),
+        candidates,
+    }
+}
+
+/// Rank every registered GPU under `policy`
+pub fn rank_gpus(policy: &SelectionPolicy) -> GpuSelection {
+    rank_candidates(&registered_gpus(), policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gpu(index: u32, vendor_id: u32, name: &str, device_type: &str, memory_gib: u64) -> RegisteredGPU {
+        RegisteredGPU {
+            index,
+            vendor_id,
+            device_id: 0,
+            name: name.to_string(),
+            device_type: device_type.to_string(),
+            backend: "vulkan".to_string(),
+            memory_size: memory_gib * 1024 * 1024 * 1024,
+            limits: Some(DeviceLimits::default()),
+            features: vec!["timestamp-query".to_string()],
+        }
+    }
+
+    fn machine() -> Vec<RegisteredGPU> {
+        vec![
+            gpu(0, 0x8086, "Intel Iris Xe Graphics", "integrated", 0),
+            gpu(1, 0x1002, "AMD Radeon RX 7900 XTX", "discrete", 24),
+            gpu(2, 0x10DE, "NVIDIA GeForce RTX 4070", "discrete", 12),
+        ]
+    }
+
+    #[test]
+    fn test_presets_rank_differently() {
+        let ml = rank_candidates(&machine(), &selection_preset("ml").unwrap());
+        assert_eq!(ml.selected, Some(2));
+        let memory = rank_candidates(&machine(), &selection_preset("memory").unwrap());
+        assert_eq!(memory.selected, Some(1));
+        let power = rank_candidates(&machine(), &selection_preset("power").unwrap());
+        assert_eq!(power.selected, Some(0));
+
+        let best = &ml.candidates[0];
+        assert_eq!(best.breakdown.len(), 5);
+        let total: f64 = best.breakdown.iter().map(|c| c.weighted).sum();
+        assert!((best.total_score - total).abs() < 1e-9);
+        let memory_score = best.breakdown.iter().find(|c| c.criterion == "memory").unwrap();
+        assert!((memory_score.score - 0.5).abs() < 1e-9);
+        assert_eq!(memory_score.reason, "12.0 GiB of the largest 24.0 GiB");
+    }
+
+    #[test]
+    fn test_user_policy_overrides_preset() {
+        let policy = parse_selection_policy(
+            r#"{"preset": "ml", "vendor_scores": {"amd": 1.0}, "weights": {"memory": 3}}"#,
+        )
+        .unwrap();
+        assert_eq!(policy.weights.memory, 3.0);
+        assert_eq!(policy.weights.vendor, 2.0);
+        assert_eq!(rank_candidates(&machine(), &policy).selected, Some(1));
+
+        assert_eq!(parse_selection_policy("").unwrap().preset, DEFAULT_SELECTION_PRESET);
+        assert!(parse_selection_policy(r#"{"preset": "nope"}"#).is_err());
+        assert!(parse_selection_policy(r#"{"weights": {"speed": 1}}"#).is_err());
+        assert!(parse_selection_policy(r#"{"min_limits": {"maxWarpSize": 1}}"#).is_err());
+    }
+
+    #[test]
+    fn test_requirements_reject_with_reasons() {
+        let mut gpus = machine();
+        gpus[1].limits = None;
+        let policy = parse_selection_policy(
+            r#"{"preset": "compute", "required_features": ["timestamp-query"],
+                "min_limits": {"maxComputeWorkgroupStorageSize": 32768}, "exclude": ["intel"]}"#,
+        )
+        .unwrap();
+        gpus[2].limits.as_mut().unwrap().max_compute_workgroup_storage_size = 49152;
+        gpus[2].features.clear();
+
+        let selection = rank_candidates(&gpus, &policy);
+        assert_eq!(selection.selected, None);
+        assert!(selection.candidates.iter().all(|c| !c.eligible));
+        let nvidia = selection.candidates.iter().find(|c| c.index == 2).unwrap();
+        assert_eq!(nvidia.rejections, vec!["missing feature 'timestamp-query'"]);
+        let amd = selection.candidates.iter().find(|c| c.index == 1).unwrap();
+        assert!(amd.rejections[0].starts_with("limits not reported"));
+        let intel = selection.candidates.iter().find(|c| c.index == 0).unwrap();
+        assert!(intel.rejections.contains(&"excluded by 'intel'".to_string()));
+
+        gpus[2].features.push("timestamp-query".to_string());
+        assert_eq!(rank_candidates(&gpus, &policy).selected, Some(2));
+    }
+
+    #[test]
+    fn test_unknown_memory_is_not_scored() {
+        let mut gpus = machine();
+        gpus[1].memory_size = 0;
+        let policy = parse_selection_policy(r#"{"preset": "memory", "min_memory_bytes": 8589934592}"#).unwrap();
+        let selection = rank_candidates(&gpus, &policy);
+        assert!(selection.candidates.iter().all(|c| c.eligible));
+
+        let amd = selection.candidates.iter().find(|c| c.index == 1).unwrap();
+        let memory = amd.breakdown.iter().find(|c| c.criterion == "memory").unwrap();
+        assert_eq!((memory.weight, memory.weighted), (0.0, 0.0));
+        assert_eq!(memory.reason, "memory size unknown; not scored");
+        let nvidia = selection.candidates.iter().find(|c| c.index == 2).unwrap();
+        let memory = nvidia.breakdown.iter().find(|c| c.criterion == "memory").unwrap();
+        assert_eq!(memory.score, 1.0);
+
+        gpus[2].memory_size = 4 * 1024 * 1024 * 1024;
+        let nvidia = rank_candidates(&gpus, &policy).candidates.into_iter().find(|c| c.index == 2).unwrap();
+        assert_eq!(nvidia.rejections, vec!["memory 4.0 GiB is below the required 8.0 GiB"]);
+    }
+}
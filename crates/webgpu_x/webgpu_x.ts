@@ -175,8 +175,10 @@ export interface ShaderSource {
   entry_point: string;
   /** Optional file path if loaded from file */
   file_path?: string | null;
-  /** Last modification time (Unix timestamp) */
+  /** Last modification time (Unix timestamp in seconds) */
   last_modified: number;
+  /** Last modification time in milliseconds since the Unix epoch */
+  last_modified_ms?: number;
 }
 
 /**